@@ -0,0 +1,148 @@
+# English diagnostic messages
+#
+# Every message has an id and a value, optionally followed by attributes
+# for the help text and labels of the diagnostic. Placeables like `{ $found }`
+# are replaced with the arguments passed by the error.
+
+label-here = here
+label-annotated = annotated here
+expected-one-of = one of { $list }
+
+## Lexing
+
+lex-unexpected-eof = Unexpected end-of-file
+lex-unexpected-symbol = Unexpected Symbol: found { $found }, expected { $expected }
+lex-unterminated-string = Unterminated String
+    .help = add a closing `"` to end the string
+    .label = string starts here
+lex-unterminated-character = Unterminated Character
+    .help = add a closing `'` to end the character
+    .label = character starts here
+lex-invalid-boolean = Invalid Boolean: { $found }
+    .help = valid boolean literals are `#t`, `#true`, `#f`, and `#false`
+lex-invalid-escape = Invalid Escape Sequence: { $found }
+lex-invalid-number = Invalid Number: { $found }
+    .help-hexadecimal = `0x` numbers can only contain the digits 0-9 and a-f
+    .help-octal = `0o` numbers can only contain the digits 0-7
+    .help-binary = `0b` numbers can only contain the digits 0 and 1
+    .help-decimal = decimal numbers can only contain the digits 0-9
+    .help-non-decimal-float = this number appears to be a float, however floats can only be created using decimal notation
+    .help-range = integers have to be between -9223372036854775808 and 9223372036854775807
+    .help-zero-denominator = the denominator of a rational can't be zero
+lex-unknown-symbol = Unknown Symbol: { $found }
+
+## Parsing
+
+parse-unexpected-eof = Unexpected end-of-file
+parse-unmatched-close-paren = Unmatched closing parenthesis
+    .help = remove this `)`, or add a `(` before it
+parse-unclosed-paren = Unclosed `(`
+    .help = add a `)` to close the list
+    .label = unclosed `(` opened here
+    .label-close = expected `)` here
+parse-unexpected-token = Unexpected Token: found `{ $found }`, expected { $expected }
+parse-invalid-expression = Invalid Expression: found `{ $found }`, expected { $expected }
+parse-not-callable = This expression is not callable
+    .help = the first element of a form is called with the others as its arguments, so it has to evaluate to a function. To write a list of values, quote it instead: { $quoted }
+    .label = this is { $article } { $kind }, not a function
+parse-invalid-annotation = Invalid Annotation Type: found `{ $found }`, expected one of `:type`, `:doc`
+parse-invalid-datum = Invalid Datum: found `{ $found }`, expected one of `Identifier`, `Boolean`, `Integer`, `Rational`, `Float`, `Character`, `String`, `Atom`, `(`
+parse-invalid-pattern = Invalid Pattern: found `{ $found }`, expected one of `Identifier`, `Boolean`, `Integer`, `Rational`, `Float`, `Character`, `String`, `Atom`, `(`
+parse-invalid-typespec = Invalid Type Specification: found `{ $found }`, expected one of `Identifier`, `Bottom`, `Tuple`, `List`, `Function`, `Sum`, `Product`
+parse-invalid-algebraic-type = Invalid Algebraic Type: found `{ $found }`, expected one of `Sum`, `Product`
+parse-invalid-formals = Invalid Formals: found `{ $found }`, expected one of `Identifier`, `(`
+parse-multiple = Found { $count } errors while parsing
+
+## Macro expansion
+
+expand-wrong-argument-count = Macro `{ $name }` takes { $expected } arguments, got { $found }
+expand-not-an-identifier = Macro `{ $name }` uses `{ $formal }` as a name, but it is not an identifier
+    .label = passed here
+expand-misplaced-rest = Rest parameter `{ $rest }` can only be used where any number of expressions is allowed
+    .help = use it as an operand of a call, or in the body of a `seq`, `let`, `fn`, `lambda` or `match`
+expand-recursion-limit = Expanding macro `{ $name }` did not finish after { $limit } nested expansions
+    .label = expanded here
+
+## Type checking
+
+type-mismatch = `{ $name }` is annotated as `{ $expected }`, but is defined as `{ $found }`
+    .label = defined here
+type-argument-mismatch = Argument { $position } of `{ $name }` is annotated as `{ $expected }`, but is given `{ $found }`
+    .label = passed here
+type-return-mismatch = `{ $name }` is annotated to return `{ $expected }`, but returns `{ $found }`
+    .label = returned here
+type-wrong-argument-count = `{ $name }` is annotated to take { $expected } arguments, but is called with { $found }
+    .label = called here
+type-not-callable = `{ $name }` is annotated as `{ $spec }`, which can't be called
+    .label = called here
+type-multiple = Found { $count } errors while checking types
+
+## Evaluation
+
+eval-unknown-identifier = Could not find value for `{ $id }` in this scope
+eval-constant-redefinition = Cannot redefine constant `{ $name }`
+    .help = constants can only be shadowed by definitions in a nested body, like that of a `fn`, `lambda` or `seq`
+    .label = redefined here
+    .label-defined = defined as a constant here
+eval-not-a-function = This expression is not callable
+    .help = the first element of a form is called with the others as its arguments, so it has to evaluate to a function, closure or primitive
+    .label = the operator of this call is a value of type `{ $name }`, not a function
+eval-wrong-argument-count = `{ $callee }` takes { $expected } arguments, got { $found }
+eval-capability-denied = `{ $callee }` requires the `{ $capability }` capability, which this program was not granted
+eval-timeout = Evaluation did not finish within its time limit of { $timeout }
+    .label = aborted here
+eval-quota-exceeded = Evaluation exceeded its quota of { $quota } calls and values
+    .label = aborted here
+eval-pattern-mismatch = Value does not match pattern, expected { $expected } found { $found }
+    .label = this pattern
+eval-no-match = No pattern matches the value `{ $value }`
+    .label = this match
+eval-no-matching-clause = No clause of `{ $name }` matches the arguments `{ $arguments }`
+    .label = this call
+eval-missing-implementation = Type `{ $type_name }` does not implement protocol `{ $protocol }` needed by `{ $method }`
+eval-invalid-implementation = Implementation of protocol `{ $protocol }` has the wrong amount of methods, expected { $expected } found { $found }
+eval-unknown-field = Type `{ $type_name }` has no field `{ $field }`
+eval-missing-field = Missing field `{ $field }` when constructing `{ $type_name }`
+eval-duplicate-field = Field `{ $field }` is given more than once when constructing `{ $type_name }`
+    .label = given again here
+    .first = first given here
+eval-missing-field-value = Field `{ $field }` has no value when constructing `{ $type_name }`
+eval-invalid-regex = Invalid regular expression: { $message }
+eval-invalid-date-format = Invalid date format `{ $format }`
+eval-invalid-environment-variable = Can't set environment variable `{ $name }`, { $reason }
+eval-wrong-type = Wrong type, expected `{ $expected }` found `{ $found }`
+eval-output-failed = Could not write output: { $message }
+eval-raised = { $payload }
+    .label = raised here
+eval-include-not-found = Could not find included file `{ $file }`
+    .help = searched in { $searched }
+    .label = included here
+eval-include-cycle = `{ $file }` is already being included
+    .help = include chain: { $chain }
+    .label = included again here
+eval-include-failed = Failed to include `{ $file }`
+    .label = included here
+eval-unwrap-err = Called `unwrap` on an `err` value: { $payload }
+eval-arithmetic-overflow = The result of this arithmetic is too large to be represented
+    .help = integers without the `bigint` feature, and the numerators and denominators of rationals, have to be between -9223372036854775808 and 9223372036854775807
+    .label = this overflowed
+eval-division-by-zero = Division by zero
+    .label = this divisor is zero
+eval-unhashable = Values of type `{ $type_name }` can't be compared or hashed
+    .help = only data can be sorted or used as a key, functions, iterators and resources like sockets can't
+eval-index-out-of-bounds = Index { $index } is out of bounds for a length of { $len }
+    .label = indexed here
+eval-invalid-range = The start of a range must not exceed its end, but { $start } is after { $end }
+    .label = this range
+eval-value-too-deep = Value is nested more than { $limit } levels deep
+    .label = this value
+eval-value-too-large = Value contains more than { $limit } values
+    .label = this value
+eval-string-too-long = String would be longer than { $limit } bytes
+    .label = built here
+
+## Message catalogs
+
+catalog-invalid-entry = Invalid catalog entry on line { $line }: `{ $text }`
+    .help = messages are written as `id = value`, and their attributes as `.name = value` on the following, indented lines
+catalog-orphan-attribute = Attribute on line { $line } does not belong to a message
@@ -2,25 +2,30 @@
 
 #![allow(dead_code)]
 
-use miette::SourceSpan;
+use num_rational::Rational64;
+use static_assertions::assert_impl_all;
 
-use crate::{Token, TokenType};
+use crate::{Span, Token, TokenType};
 
 /// A single ream program
 #[derive(Clone, Debug)]
 pub struct Program<'s>(pub Vec<Expression<'s>>);
 
+// Parsed programs are shared between threads, eg. when included files are
+// parsed in parallel
+assert_impl_all!(Program<'static>: Send, Sync);
+
 /// A single expression
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum Expression<'s> {
 	TypeAlias {
-		span:   SourceSpan,
+		span:   Span,
 		target: Identifier<'s>,
 		spec:   TypeSpec<'s>,
 	},
 	AlgebraicTypeDefintion {
-		span:   SourceSpan,
+		span:   Span,
 		target: Identifier<'s>,
 		spec:   TypeSpec<'s>,
 	},
@@ -28,40 +33,148 @@ pub enum Expression<'s> {
 	Literal(Literal<'s>),
 	Identifier(Identifier<'s>),
 	VariableDefinition {
-		span:   SourceSpan,
+		span:   Span,
+		target: Pattern<'s>,
+		value:  Box<Expression<'s>>,
+	},
+	/// A binding that can't be redefined in the scope it's made in, written
+	/// as `(const <identifier> <expression>)`
+	ConstantDefinition {
+		span:   Span,
 		target: Identifier<'s>,
 		value:  Box<Expression<'s>>,
 	},
 	FunctionDefinition {
-		span:    SourceSpan,
+		span:    Span,
 		target:  Identifier<'s>,
-		formals: Vec<Identifier<'s>>,
+		formals: Vec<Pattern<'s>>,
 		body:    Vec<Expression<'s>>,
 	},
+	/// One clause of a function dispatching on its arguments, written as
+	/// `(fn (<identifier> <pattern>*) <expression>+)`
+	///
+	/// Clauses defined one after the other in the same scope make up a single
+	/// function, which evaluates the body of the first clause whose patterns
+	/// match its arguments
+	FunctionClause {
+		span:     Span,
+		target:   Identifier<'s>,
+		patterns: Vec<Pattern<'s>>,
+		body:     Vec<Expression<'s>>,
+	},
 	ClosureDefintion {
-		span:    SourceSpan,
-		formals: Vec<Identifier<'s>>,
+		span:    Span,
+		formals: Vec<Pattern<'s>>,
 		body:    Vec<Expression<'s>>,
 	},
 	Sequence {
-		span: SourceSpan,
+		span: Span,
 		seq:  Vec<Expression<'s>>,
 	},
+	/// A body evaluated in a scope of its own holding the given bindings,
+	/// written as `(let (<binding>*) <expression>+)`
+	Let {
+		span:     Span,
+		bindings: Vec<LetBinding<'s>>,
+		body:     Vec<Expression<'s>>,
+	},
+	/// A vector holding the values of its elements, written as
+	/// `#(<expression>*)`
+	Vector {
+		span:     Span,
+		elements: Vec<Expression<'s>>,
+	},
 	ProcedureCall {
-		span:     SourceSpan,
+		span:     Span,
 		operator: Box<Expression<'s>>,
 		operands: Vec<Expression<'s>>,
 	},
 	Conditional {
-		span:       SourceSpan,
+		span:       Span,
 		test:       Box<Expression<'s>>,
 		consequent: Box<Expression<'s>>,
 		alternate:  Option<Box<Expression<'s>>>,
 	},
 	Inclusion {
-		span:  SourceSpan,
+		span:  Span,
 		files: Vec<&'s str>,
 	},
+	Match {
+		span:    Span,
+		value:   Box<Expression<'s>>,
+		clauses: Vec<MatchClause<'s>>,
+	},
+	/// Evaluates the body of the first clause listing a datum equal to the
+	/// value, or the default body if none do
+	Case {
+		span:    Span,
+		value:   Box<Expression<'s>>,
+		clauses: Vec<CaseClause<'s>>,
+		default: Option<Vec<Expression<'s>>>,
+	},
+	ProtocolDefinition {
+		span:    Span,
+		target:  Identifier<'s>,
+		methods: Vec<ProtocolMethod<'s>>,
+	},
+	ProtocolImplementation {
+		span:     Span,
+		protocol: Identifier<'s>,
+		target:   Identifier<'s>,
+		methods:  Vec<Expression<'s>>,
+	},
+	/// Checks that a value has a type at runtime, converting numbers to the
+	/// type if they have another numeric type, written as
+	/// `(the <typespec> <expression>)`
+	TypeAssertion {
+		span:  Span,
+		spec:  TypeSpec<'s>,
+		value: Box<Expression<'s>>,
+	},
+	/// Evaluates the value, measuring how long that took and how many values
+	/// it created
+	Time {
+		span:  Span,
+		value: Box<Expression<'s>>,
+	},
+	MacroDefinition {
+		span:    Span,
+		target:  Identifier<'s>,
+		formals: Vec<Identifier<'s>>,
+		rest:    Option<Identifier<'s>>,
+		body:    Box<Expression<'s>>,
+	},
+}
+
+impl<'s> Expression<'s> {
+	/// Get the region of source code wrapped by this expression
+	pub fn span(&self) -> Span {
+		match self {
+			Self::Annotation(a) => a.span(),
+			Self::Literal(l) => l.span(),
+			Self::Identifier(i) => i.span,
+			Self::TypeAlias { span, .. }
+			| Self::AlgebraicTypeDefintion { span, .. }
+			| Self::VariableDefinition { span, .. }
+			| Self::ConstantDefinition { span, .. }
+			| Self::FunctionDefinition { span, .. }
+			| Self::FunctionClause { span, .. }
+			| Self::ClosureDefintion { span, .. }
+			| Self::Sequence { span, .. }
+			| Self::Let { span, .. }
+			| Self::Vector { span, .. }
+			| Self::ProcedureCall { span, .. }
+			| Self::Conditional { span, .. }
+			| Self::Inclusion { span, .. }
+			| Self::Match { span, .. }
+			| Self::Case { span, .. }
+			| Self::ProtocolDefinition { span, .. }
+			| Self::ProtocolImplementation { span, .. }
+			| Self::TypeAssertion { span, .. }
+			| Self::Time { span, .. }
+			| Self::MacroDefinition { span, .. } => *span,
+		}
+	}
 }
 
 impl<'s> From<Identifier<'s>> for Expression<'s> {
@@ -80,7 +193,7 @@ impl<'s> From<Annotation<'s>> for Expression<'s> {
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug)]
 pub struct Identifier<'s> {
-	pub span: SourceSpan,
+	pub span: Span,
 	pub id:   &'s str,
 }
 
@@ -93,17 +206,117 @@ impl<'s> From<Token<'s>> for Identifier<'s> {
 	}
 }
 
+/// The signature of a single method of a protocol
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct ProtocolMethod<'s> {
+	pub span:    Span,
+	pub target:  Identifier<'s>,
+	pub formals: Vec<Identifier<'s>>,
+}
+
+/// A pattern values can be destructured with
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub enum Pattern<'s> {
+	/// Matches anything, binding it to the identifier
+	Identifier(Identifier<'s>),
+	/// Matches anything without binding it, written as `_`
+	Wildcard { span: Span },
+	/// Matches a value equal to a boolean, number, character or string
+	/// literal
+	///
+	/// Numbers only match numbers of the same representation, `1` doesn't
+	/// match `1.0`
+	Literal(Literal<'s>),
+	/// Matches a list, tuple or vector with exactly as many elements as there
+	/// are patterns, each of which matches its corresponding pattern
+	List { span: Span, patterns: Vec<Pattern<'s>> },
+	/// Matches a non-empty list whose first element matches the head pattern
+	/// and whose other elements, as a list, match the tail pattern, written
+	/// as `(cons <pattern> <pattern>)`
+	Cons { span: Span, head: Box<Pattern<'s>>, tail: Box<Pattern<'s>> },
+	/// Matches a value of a sum type with the given variant, written as
+	/// `<atom>` or `(<atom> <pattern>)`, results and options match as if
+	/// they had the variants `ok` and `err`, and `some` and `none`
+	///
+	/// The variant name is stored without its leading `:`, leaving out the
+	/// payload pattern only checks the variant and also matches the atom
+	/// itself
+	Variant { span: Span, variant: &'s str, payload: Option<Box<Pattern<'s>>> },
+}
+
+/// A single clause of a `match` expression
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct MatchClause<'s> {
+	pub span:    Span,
+	pub pattern: Pattern<'s>,
+	/// An expression that has to be truthy for the clause to be chosen,
+	/// written as `:when <expression>` after the pattern
+	///
+	/// It's evaluated with the bindings of the pattern in scope
+	pub guard:   Option<Expression<'s>>,
+	pub body:    Vec<Expression<'s>>,
+}
+
+/// A single clause of a `case` expression
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct CaseClause<'s> {
+	pub span: Span,
+	pub data: Vec<Datum<'s>>,
+	pub body: Vec<Expression<'s>>,
+}
+
+impl<'s> From<Token<'s>> for Pattern<'s> {
+	fn from(value: Token<'s>) -> Self {
+		match value.t {
+			TokenType::Identifier("_") => Self::Wildcard { span: value.span },
+			TokenType::Identifier(_) => Self::Identifier(value.into()),
+			_ => unreachable!(),
+		}
+	}
+}
+
+/// A single binding of a `let` expression, written as
+/// `(<pattern> <expression>)`
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct LetBinding<'s> {
+	pub span:   Span,
+	pub target: Pattern<'s>,
+	pub value:  Expression<'s>,
+}
+
 /// A literal value
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum Literal<'s> {
-	Quotation { span: SourceSpan, q: Datum<'s> },
-	Boolean { span: SourceSpan, b: bool },
-	Integer { span: SourceSpan, i: u64 },
-	Float { span: SourceSpan, f: f64 },
-	Character { span: SourceSpan, c: char },
-	String { span: SourceSpan, s: &'s str },
-	Atom { span: SourceSpan, a: &'s str },
+	Quotation { span: Span, q: Datum<'s> },
+	Boolean { span: Span, b: bool },
+	Integer { span: Span, i: i64 },
+	Rational { span: Span, r: Rational64 },
+	Float { span: Span, f: f64 },
+	Character { span: Span, c: char },
+	String { span: Span, s: &'s str },
+	Atom { span: Span, a: &'s str },
+}
+
+impl<'s> Literal<'s> {
+	/// Get the region of source code wrapped by this literal
+	pub fn span(&self) -> Span {
+		match self {
+			Self::Quotation { span, .. }
+			| Self::Boolean { span, .. }
+			| Self::Integer { span, .. }
+			| Self::Rational { span, .. }
+			| Self::Float { span, .. }
+			| Self::Character { span, .. }
+			| Self::String { span, .. }
+			| Self::Atom { span, .. } => *span,
+		}
+	}
 }
 
 impl<'s> Token<'s> {
@@ -118,6 +331,7 @@ impl<'s> From<Token<'s>> for Literal<'s> {
 		match value.t {
 			TokenType::Boolean(b) => Self::Boolean { span: value.span, b },
 			TokenType::Integer(i) => Self::Integer { span: value.span, i },
+			TokenType::Rational(r) => Self::Rational { span: value.span, r },
 			TokenType::Float(f) => Self::Float { span: value.span, f },
 			TokenType::Character(c) => Self::Character { span: value.span, c },
 			TokenType::String(s) => Self::String { span: value.span, s },
@@ -131,14 +345,84 @@ impl<'s> From<Token<'s>> for Literal<'s> {
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum Datum<'s> {
-	Identifier { span: SourceSpan, id: &'s str },
-	Boolean { span: SourceSpan, b: bool },
-	Integer { span: SourceSpan, i: u64 },
-	Float { span: SourceSpan, f: f64 },
-	Character { span: SourceSpan, c: char },
-	String { span: SourceSpan, s: &'s str },
-	Atom { span: SourceSpan, a: &'s str },
-	List { span: SourceSpan, l: ConsList<'s> },
+	Identifier {
+		span: Span,
+		id:   &'s str,
+	},
+	Boolean {
+		span: Span,
+		b:    bool,
+	},
+	Integer {
+		span: Span,
+		i:    i64,
+	},
+	Rational {
+		span: Span,
+		r:    Rational64,
+	},
+	Float {
+		span: Span,
+		f:    f64,
+	},
+	Character {
+		span: Span,
+		c:    char,
+	},
+	String {
+		span: Span,
+		s:    &'s str,
+	},
+	Atom {
+		span: Span,
+		a:    &'s str,
+	},
+	List {
+		span: Span,
+		l:    ConsList<'s>,
+	},
+	/// A vector written as `#(<datum>*)`
+	///
+	/// Vectors are mutable, so a quoted vector is never shared: every
+	/// evaluation of the datum creates a new vector, and mutating it doesn't
+	/// change what the datum evaluates to the next time. Values inserted with
+	/// an unquote aren't copied though
+	Vector {
+		span: Span,
+		data: Vec<Datum<'s>>,
+	},
+	/// An expression whose value is inserted into a quasiquoted datum,
+	/// written as `,<expression>`
+	Unquote {
+		span: Span,
+		e:    Box<Expression<'s>>,
+	},
+	/// An expression evaluating to a list whose elements are inserted into
+	/// the surrounding quasiquoted list, written as `,@<expression>`
+	UnquoteSplicing {
+		span: Span,
+		e:    Box<Expression<'s>>,
+	},
+}
+
+impl<'s> Datum<'s> {
+	/// Get the region of source code wrapped by this datum
+	pub fn span(&self) -> Span {
+		match self {
+			Self::Identifier { span, .. }
+			| Self::Boolean { span, .. }
+			| Self::Integer { span, .. }
+			| Self::Rational { span, .. }
+			| Self::Float { span, .. }
+			| Self::Character { span, .. }
+			| Self::String { span, .. }
+			| Self::Atom { span, .. }
+			| Self::List { span, .. }
+			| Self::Vector { span, .. }
+			| Self::Unquote { span, .. }
+			| Self::UnquoteSplicing { span, .. } => *span,
+		}
+	}
 }
 
 impl<'s> From<Token<'s>> for Datum<'s> {
@@ -147,6 +431,7 @@ impl<'s> From<Token<'s>> for Datum<'s> {
 			TokenType::Identifier(id) => Self::Identifier { span: value.span, id },
 			TokenType::Boolean(b) => Self::Boolean { span: value.span, b },
 			TokenType::Integer(i) => Self::Integer { span: value.span, i },
+			TokenType::Rational(r) => Self::Rational { span: value.span, r },
 			TokenType::Float(f) => Self::Float { span: value.span, f },
 			TokenType::Character(c) => Self::Character { span: value.span, c },
 			TokenType::String(s) => Self::String { span: value.span, s },
@@ -172,6 +457,14 @@ pub struct ConsCell<'s> {
 	tail: Option<Box<ConsCell<'s>>>,
 }
 
+impl<'s> ConsList<'s> {
+	/// Iterate over the elements of the list, in order
+	pub fn iter(&self) -> impl Iterator<Item = &Datum<'s>> {
+		std::iter::successors(self.head.as_deref(), |cell| cell.tail.as_deref())
+			.map(|cell| &cell.head)
+	}
+}
+
 impl<'s> From<Vec<Datum<'s>>> for ConsList<'s> {
 	fn from(value: Vec<Datum<'s>>) -> Self {
 		let iter = value.into_iter();
@@ -222,8 +515,17 @@ fn cons_to_vec_helper<'s>(mut collector: Vec<Datum<'s>>, list: ConsCell<'s>) ->
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum Annotation<'s> {
-	TypeAnnotation { span: SourceSpan, target: Identifier<'s>, spec: TypeSpec<'s> },
-	DocAnnotation { span: SourceSpan, target: Identifier<'s>, doc: &'s str },
+	TypeAnnotation { span: Span, target: Identifier<'s>, spec: TypeSpec<'s> },
+	DocAnnotation { span: Span, target: Identifier<'s>, doc: &'s str },
+}
+
+impl<'s> Annotation<'s> {
+	/// Get the region of source code wrapped by this annotation
+	pub fn span(&self) -> Span {
+		match self {
+			Self::TypeAnnotation { span, .. } | Self::DocAnnotation { span, .. } => *span,
+		}
+	}
 }
 
 /// A type specification
@@ -246,20 +548,396 @@ impl<'s> From<TypeConstructor<'s>> for TypeSpec<'s> {
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub enum TypeConstructor<'s> {
-	Bottom { span: SourceSpan },
-	Tuple { span: SourceSpan, fields: Vec<TypeSpec<'s>> },
-	List { span: SourceSpan, t: Box<TypeSpec<'s>> },
-	Vector { span: SourceSpan, t: Box<TypeSpec<'s>> },
-	Function { span: SourceSpan, arguments: Vec<TypeSpec<'s>>, values: Vec<TypeSpec<'s>> },
-	Sum { span: SourceSpan, fields: Vec<NamedTypeSpec<'s>> },
-	Product { span: SourceSpan, fields: Vec<NamedTypeSpec<'s>> },
+	Bottom { span: Span },
+	Tuple { span: Span, fields: Vec<TypeSpec<'s>> },
+	List { span: Span, t: Box<TypeSpec<'s>> },
+	Vector { span: Span, t: Box<TypeSpec<'s>> },
+	Function { span: Span, arguments: Vec<TypeSpec<'s>>, values: Vec<TypeSpec<'s>> },
+	Sum { span: Span, fields: Vec<NamedTypeSpec<'s>> },
+	Product { span: Span, fields: Vec<NamedTypeSpec<'s>> },
 }
 
 /// A named (labeled) type specification
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
 pub struct NamedTypeSpec<'s> {
-	span: SourceSpan,
-	name: Literal<'s>,
-	spec: Option<TypeSpec<'s>>,
+	pub span: Span,
+	pub name: Literal<'s>,
+	pub spec: Option<TypeSpec<'s>>,
+}
+
+/// Structural equality between AST nodes that ignores their spans
+pub trait SyntacticEq {
+	/// Check if two nodes have the same structure and contents, regardless
+	/// of where in the source code they appear
+	fn syntactic_eq(&self, other: &Self) -> bool;
+}
+
+impl<T: SyntacticEq> SyntacticEq for Vec<T> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.syntactic_eq(b))
+	}
+}
+
+impl<T: SyntacticEq> SyntacticEq for Option<T> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Some(a), Some(b)) => a.syntactic_eq(b),
+			(None, None) => true,
+			_ => false,
+		}
+	}
+}
+
+impl<T: SyntacticEq> SyntacticEq for Box<T> {
+	fn syntactic_eq(&self, other: &Self) -> bool { (**self).syntactic_eq(other) }
+}
+
+impl<'s> SyntacticEq for Program<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool { self.0.syntactic_eq(&other.0) }
+}
+
+impl<'s> SyntacticEq for Expression<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(
+				Self::TypeAlias { target: a_target, spec: a_spec, .. },
+				Self::TypeAlias { target: b_target, spec: b_spec, .. },
+			)
+			| (
+				Self::AlgebraicTypeDefintion { target: a_target, spec: a_spec, .. },
+				Self::AlgebraicTypeDefintion { target: b_target, spec: b_spec, .. },
+			) => a_target.syntactic_eq(b_target) && a_spec.syntactic_eq(b_spec),
+			(Self::Annotation(a), Self::Annotation(b)) => a.syntactic_eq(b),
+			(Self::Literal(a), Self::Literal(b)) => a.syntactic_eq(b),
+			(Self::Identifier(a), Self::Identifier(b)) => a.syntactic_eq(b),
+			(
+				Self::VariableDefinition { target: a_target, value: a_value, .. },
+				Self::VariableDefinition { target: b_target, value: b_value, .. },
+			) => a_target.syntactic_eq(b_target) && a_value.syntactic_eq(b_value),
+			(
+				Self::ConstantDefinition { target: a_target, value: a_value, .. },
+				Self::ConstantDefinition { target: b_target, value: b_value, .. },
+			) => a_target.syntactic_eq(b_target) && a_value.syntactic_eq(b_value),
+			(
+				Self::FunctionDefinition {
+					target: a_target, formals: a_formals, body: a_body, ..
+				},
+				Self::FunctionDefinition {
+					target: b_target, formals: b_formals, body: b_body, ..
+				},
+			) => {
+				a_target.syntactic_eq(b_target)
+					&& a_formals.syntactic_eq(b_formals)
+					&& a_body.syntactic_eq(b_body)
+			},
+			(
+				Self::FunctionClause {
+					target: a_target, patterns: a_patterns, body: a_body, ..
+				},
+				Self::FunctionClause {
+					target: b_target, patterns: b_patterns, body: b_body, ..
+				},
+			) => {
+				a_target.syntactic_eq(b_target)
+					&& a_patterns.syntactic_eq(b_patterns)
+					&& a_body.syntactic_eq(b_body)
+			},
+			(
+				Self::ClosureDefintion { formals: a_formals, body: a_body, .. },
+				Self::ClosureDefintion { formals: b_formals, body: b_body, .. },
+			) => a_formals.syntactic_eq(b_formals) && a_body.syntactic_eq(b_body),
+			(Self::Sequence { seq: a, .. }, Self::Sequence { seq: b, .. }) => a.syntactic_eq(b),
+			(
+				Self::Let { bindings: a_bindings, body: a_body, .. },
+				Self::Let { bindings: b_bindings, body: b_body, .. },
+			) => a_bindings.syntactic_eq(b_bindings) && a_body.syntactic_eq(b_body),
+			(Self::Vector { elements: a, .. }, Self::Vector { elements: b, .. }) => {
+				a.syntactic_eq(b)
+			},
+			(
+				Self::ProcedureCall { operator: a_operator, operands: a_operands, .. },
+				Self::ProcedureCall { operator: b_operator, operands: b_operands, .. },
+			) => a_operator.syntactic_eq(b_operator) && a_operands.syntactic_eq(b_operands),
+			(
+				Self::Conditional {
+					test: a_test,
+					consequent: a_consequent,
+					alternate: a_alternate,
+					..
+				},
+				Self::Conditional {
+					test: b_test,
+					consequent: b_consequent,
+					alternate: b_alternate,
+					..
+				},
+			) => {
+				a_test.syntactic_eq(b_test)
+					&& a_consequent.syntactic_eq(b_consequent)
+					&& a_alternate.syntactic_eq(b_alternate)
+			},
+			(Self::Inclusion { files: a, .. }, Self::Inclusion { files: b, .. }) => a == b,
+			(
+				Self::Match { value: a_value, clauses: a_clauses, .. },
+				Self::Match { value: b_value, clauses: b_clauses, .. },
+			) => a_value.syntactic_eq(b_value) && a_clauses.syntactic_eq(b_clauses),
+			(
+				Self::Case { value: a_value, clauses: a_clauses, default: a_default, .. },
+				Self::Case { value: b_value, clauses: b_clauses, default: b_default, .. },
+			) => {
+				a_value.syntactic_eq(b_value)
+					&& a_clauses.syntactic_eq(b_clauses)
+					&& a_default.syntactic_eq(b_default)
+			},
+			(
+				Self::ProtocolDefinition { target: a_target, methods: a_methods, .. },
+				Self::ProtocolDefinition { target: b_target, methods: b_methods, .. },
+			) => a_target.syntactic_eq(b_target) && a_methods.syntactic_eq(b_methods),
+			(
+				Self::ProtocolImplementation {
+					protocol: a_protocol,
+					target: a_target,
+					methods: a_methods,
+					..
+				},
+				Self::ProtocolImplementation {
+					protocol: b_protocol,
+					target: b_target,
+					methods: b_methods,
+					..
+				},
+			) => {
+				a_protocol.syntactic_eq(b_protocol)
+					&& a_target.syntactic_eq(b_target)
+					&& a_methods.syntactic_eq(b_methods)
+			},
+			(
+				Self::TypeAssertion { spec: a_spec, value: a_value, .. },
+				Self::TypeAssertion { spec: b_spec, value: b_value, .. },
+			) => a_spec.syntactic_eq(b_spec) && a_value.syntactic_eq(b_value),
+			(Self::Time { value: a, .. }, Self::Time { value: b, .. }) => a.syntactic_eq(b),
+			(
+				Self::MacroDefinition {
+					target: a_target,
+					formals: a_formals,
+					rest: a_rest,
+					body: a_body,
+					..
+				},
+				Self::MacroDefinition {
+					target: b_target,
+					formals: b_formals,
+					rest: b_rest,
+					body: b_body,
+					..
+				},
+			) => {
+				a_target.syntactic_eq(b_target)
+					&& a_formals.syntactic_eq(b_formals)
+					&& a_rest.syntactic_eq(b_rest)
+					&& a_body.syntactic_eq(b_body)
+			},
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for Identifier<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool { self.id == other.id }
+}
+
+impl<'s> SyntacticEq for ProtocolMethod<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.target.syntactic_eq(&other.target) && self.formals.syntactic_eq(&other.formals)
+	}
+}
+
+impl<'s> SyntacticEq for Pattern<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Identifier(a), Self::Identifier(b)) => a.syntactic_eq(b),
+			(Self::Wildcard { .. }, Self::Wildcard { .. }) => true,
+			(Self::Literal(a), Self::Literal(b)) => a.syntactic_eq(b),
+			(Self::List { patterns: a, .. }, Self::List { patterns: b, .. }) => a.syntactic_eq(b),
+			(
+				Self::Cons { head: a_head, tail: a_tail, .. },
+				Self::Cons { head: b_head, tail: b_tail, .. },
+			) => a_head.syntactic_eq(b_head) && a_tail.syntactic_eq(b_tail),
+			(
+				Self::Variant { variant: a_variant, payload: a_payload, .. },
+				Self::Variant { variant: b_variant, payload: b_payload, .. },
+			) => a_variant == b_variant && a_payload.syntactic_eq(b_payload),
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for MatchClause<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.pattern.syntactic_eq(&other.pattern)
+			&& self.guard.syntactic_eq(&other.guard)
+			&& self.body.syntactic_eq(&other.body)
+	}
+}
+
+impl<'s> SyntacticEq for CaseClause<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.data.syntactic_eq(&other.data) && self.body.syntactic_eq(&other.body)
+	}
+}
+
+impl<'s> SyntacticEq for LetBinding<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.target.syntactic_eq(&other.target) && self.value.syntactic_eq(&other.value)
+	}
+}
+
+impl<'s> SyntacticEq for Literal<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Quotation { q: a, .. }, Self::Quotation { q: b, .. }) => a.syntactic_eq(b),
+			(Self::Boolean { b: a, .. }, Self::Boolean { b, .. }) => a == b,
+			(Self::Integer { i: a, .. }, Self::Integer { i: b, .. }) => a == b,
+			(Self::Rational { r: a, .. }, Self::Rational { r: b, .. }) => a == b,
+			(Self::Float { f: a, .. }, Self::Float { f: b, .. }) => a == b,
+			(Self::Character { c: a, .. }, Self::Character { c: b, .. }) => a == b,
+			(Self::String { s: a, .. }, Self::String { s: b, .. }) => a == b,
+			(Self::Atom { a, .. }, Self::Atom { a: b, .. }) => a == b,
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for Datum<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Identifier { id: a, .. }, Self::Identifier { id: b, .. }) => a == b,
+			(Self::Boolean { b: a, .. }, Self::Boolean { b, .. }) => a == b,
+			(Self::Integer { i: a, .. }, Self::Integer { i: b, .. }) => a == b,
+			(Self::Rational { r: a, .. }, Self::Rational { r: b, .. }) => a == b,
+			(Self::Float { f: a, .. }, Self::Float { f: b, .. }) => a == b,
+			(Self::Character { c: a, .. }, Self::Character { c: b, .. }) => a == b,
+			(Self::String { s: a, .. }, Self::String { s: b, .. }) => a == b,
+			(Self::Atom { a, .. }, Self::Atom { a: b, .. }) => a == b,
+			(Self::List { l: a, .. }, Self::List { l: b, .. }) => a.syntactic_eq(b),
+			(Self::Vector { data: a, .. }, Self::Vector { data: b, .. }) => a.syntactic_eq(b),
+			(Self::Unquote { e: a, .. }, Self::Unquote { e: b, .. }) => a.syntactic_eq(b),
+			(Self::UnquoteSplicing { e: a, .. }, Self::UnquoteSplicing { e: b, .. }) => {
+				a.syntactic_eq(b)
+			},
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for ConsList<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool { self.head.syntactic_eq(&other.head) }
+}
+
+impl<'s> SyntacticEq for ConsCell<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.head.syntactic_eq(&other.head) && self.tail.syntactic_eq(&other.tail)
+	}
+}
+
+impl<'s> SyntacticEq for Annotation<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(
+				Self::TypeAnnotation { target: a_target, spec: a_spec, .. },
+				Self::TypeAnnotation { target: b_target, spec: b_spec, .. },
+			) => a_target.syntactic_eq(b_target) && a_spec.syntactic_eq(b_spec),
+			(
+				Self::DocAnnotation { target: a_target, doc: a_doc, .. },
+				Self::DocAnnotation { target: b_target, doc: b_doc, .. },
+			) => a_target.syntactic_eq(b_target) && a_doc == b_doc,
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for TypeSpec<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Identifier(a), Self::Identifier(b)) => a.syntactic_eq(b),
+			(Self::Constructor(a), Self::Constructor(b)) => a.syntactic_eq(b),
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for TypeConstructor<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Bottom { .. }, Self::Bottom { .. }) => true,
+			(Self::Tuple { fields: a, .. }, Self::Tuple { fields: b, .. }) => a.syntactic_eq(b),
+			(Self::List { t: a, .. }, Self::List { t: b, .. })
+			| (Self::Vector { t: a, .. }, Self::Vector { t: b, .. }) => a.syntactic_eq(b),
+			(
+				Self::Function { arguments: a_arguments, values: a_values, .. },
+				Self::Function { arguments: b_arguments, values: b_values, .. },
+			) => a_arguments.syntactic_eq(b_arguments) && a_values.syntactic_eq(b_values),
+			(Self::Sum { fields: a, .. }, Self::Sum { fields: b, .. })
+			| (Self::Product { fields: a, .. }, Self::Product { fields: b, .. }) => {
+				a.syntactic_eq(b)
+			},
+			_ => false,
+		}
+	}
+}
+
+impl<'s> SyntacticEq for NamedTypeSpec<'s> {
+	fn syntactic_eq(&self, other: &Self) -> bool {
+		self.name.syntactic_eq(&other.name) && self.spec.syntactic_eq(&other.spec)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::parse_str;
+
+	/// Parse a source containing a single expression
+	fn parse(source: &str) -> Expression<'_> { parse_str(source).unwrap().0.remove(0) }
+
+	#[test]
+	fn expressions_span_their_source() {
+		let source = "(let x (+ 1 2))\n";
+		let Expression::VariableDefinition { value, .. } = parse(source) else { unreachable!() };
+		let Expression::ProcedureCall { operands, .. } = &*value else { unreachable!() };
+
+		assert_eq!(&source[parse(source).span().range()], "(let x (+ 1 2))");
+		assert_eq!(&source[value.span().range()], "(+ 1 2)");
+		assert_eq!(&source[operands[1].span().range()], "2");
+	}
+
+	#[test]
+	fn literals_and_data_span_their_source() {
+		let source = "`(a #(1 ,x))\n";
+		let Expression::Literal(literal) = parse(source) else { unreachable!() };
+		let Literal::Quotation { q, .. } = &literal else { unreachable!() };
+
+		assert_eq!(&source[literal.span().range()], "`(a #(1 ,x))");
+		assert_eq!(&source[q.span().range()], "(a #(1 ,x))");
+	}
+
+	#[test]
+	fn syntactic_equality_ignores_spans() {
+		let a = parse_str("(fn f (x) (+ x 1))\n").unwrap();
+		let b = parse_str("(fn   f (x)\n\t(+ x   1))\n").unwrap();
+
+		assert!(a.syntactic_eq(&b));
+	}
+
+	#[test]
+	fn syntactic_equality_compares_contents() {
+		let a = parse("(fn f (x) (+ x 1))\n");
+
+		for source in ["(fn g (x) (+ x 1))\n", "(fn f (y) (+ y 1))\n", "(fn f (x) (+ x 2))\n"] {
+			assert!(!a.syntactic_eq(&parse(source)), "{source}");
+		}
+		assert!(!parse("`(1 2)\n").syntactic_eq(&parse("`(1 2 3)\n")));
+		assert!(!parse("1\n").syntactic_eq(&parse("1.0\n")));
+	}
 }
@@ -1,8 +1,15 @@
 //! Error definition
 
-use miette::{Diagnostic, SourceSpan};
+use std::fmt;
+use std::sync::Arc;
+
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+use static_assertions::assert_impl_all;
 use thiserror::Error;
 
+use crate::messages::message;
+use crate::{Span, Value};
+
 /// Any possible error
 #[allow(missing_docs)]
 #[derive(Debug, Diagnostic, Error)]
@@ -18,6 +25,80 @@ pub enum Error {
 	#[error(transparent)]
 	#[diagnostic(code(ream::parse_error))]
 	Parse(#[from] ParseError),
+
+	#[error(transparent)]
+	#[diagnostic(code(ream::eval_error))]
+	Eval(#[from] EvalError),
+}
+
+assert_impl_all!(Error: Send, Sync);
+
+/// The category an error falls into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+	/// An IO operation failed
+	Io,
+	/// The source code could not be lexed
+	Lex,
+	/// The source code could not be parsed
+	Parse,
+	/// An identifier could not be resolved
+	Name,
+	/// A value was called incorrectly
+	Call,
+	/// A value had the wrong type
+	Type,
+	/// The program was not granted a capability it needed
+	Capability,
+	/// The program exceeded one of its resource limits
+	Limit,
+	/// The program raised an error itself using `(error ...)`
+	User,
+	/// A file included by the program could not be found, read or run
+	Include,
+	/// A macro was used incorrectly
+	Macro,
+	/// The result of an arithmetic operation could not be represented
+	Arithmetic,
+}
+
+impl Error {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Io(_) => ErrorKind::Io,
+			Self::Lex(_) => ErrorKind::Lex,
+			Self::Parse(_) => ErrorKind::Parse,
+			Self::Eval(e) => e.kind(),
+		}
+	}
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			Self::Io(_) => None,
+			Self::Lex(e) => e.span(),
+			Self::Parse(e) => e.span(),
+			Self::Eval(e) => e.span(),
+		}
+	}
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+
+	/// Get the payload of an error raised using `(error ...)`, or of an
+	/// `err` value that was unwrapped
+	pub fn payload(&self) -> Option<&Value> {
+		match self {
+			Self::Eval(e) => e.payload(),
+			_ => None,
+		}
+	}
+}
+
+/// Get the span of the first label of a diagnostic
+fn primary_span<D: Diagnostic + ?Sized>(diagnostic: &D) -> Option<Span> {
+	diagnostic.labels()?.next().map(|label| Span::from(*label.inner()))
 }
 
 /// Any error related to lexing
@@ -25,55 +106,82 @@ pub enum Error {
 pub enum LexError {
 	/// Unexpected end-of-file
 	#[allow(missing_docs)]
-	#[error("Unexpected end-of-file")]
+	#[error("{}", message!("lex-unexpected-eof"))]
 	#[diagnostic(code(ream::lex_error::unexpected_eof))]
 	UnexpectedEof {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 	},
 
 	/// Expected one symbol, found another
 	#[allow(missing_docs)]
-	#[error("Unexpected Symbol: found {found:?}, expected {}", format_expected_symbols(expected))]
+	#[error(
+		"{}",
+		message!(
+			"lex-unexpected-symbol",
+			found = format!("{found:?}"),
+			expected = format_expected_symbols(expected),
+		)
+	)]
 	#[diagnostic(code(ream::lex_error::unexpected_symbol))]
 	UnexpectedSymbol {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found:    char,
 		expected: Vec<char>,
 	},
 
+	/// String literal without a closing quote
+	#[allow(missing_docs)]
+	#[error("{}", message!("lex-unterminated-string"))]
+	#[diagnostic(help("{}", message!("lex-unterminated-string.help")))]
+	#[diagnostic(code(ream::lex_error::unterminated_string))]
+	UnterminatedString {
+		#[label("{}", message!("lex-unterminated-string.label"))]
+		loc: Span,
+	},
+
+	/// Character literal without a closing quote
+	#[allow(missing_docs)]
+	#[error("{}", message!("lex-unterminated-character"))]
+	#[diagnostic(help("{}", message!("lex-unterminated-character.help")))]
+	#[diagnostic(code(ream::lex_error::unterminated_character))]
+	UnterminatedCharacter {
+		#[label("{}", message!("lex-unterminated-character.label"))]
+		loc: Span,
+	},
+
 	/// Invalid boolean
 	#[allow(missing_docs)]
-	#[error("Invalid Boolean: {found:?}")]
-	#[diagnostic(help("valid boolean literals are `#t`, `#true`, `#f`, and `#false`"))]
+	#[error("{}", message!("lex-invalid-boolean", found = format!("{found:?}")))]
+	#[diagnostic(help("{}", message!("lex-invalid-boolean.help")))]
 	#[diagnostic(code(ream::lex_error::invalid_boolean))]
 	InvalidBoolean {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: String,
 	},
 
 	/// Invalid escape sequence
 	#[allow(missing_docs)]
-	#[error("Invalid Escape Sequence: {found:?}")]
+	#[error("{}", message!("lex-invalid-escape", found = format!("{found:?}")))]
 	#[diagnostic(code(ream::lex_error::invalid_escape))]
 	InvalidEscape {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: String,
 	},
 
 	/// Invalid number
 	#[allow(missing_docs)]
-	#[error("Invalid Number: {found:?}")]
+	#[error("{}", message!("lex-invalid-number", found = format!("{found:?}")))]
 	#[diagnostic(code(ream::lex_error::invalid_number))]
 	InvalidNumber {
-		#[label = "here"]
-		loc:  SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc:  Span,
 		#[help]
 		help: Option<String>,
 
@@ -82,16 +190,27 @@ pub enum LexError {
 
 	/// Unknown symbol
 	#[allow(missing_docs)]
-	#[error("Unknown Symbol: {found:?}")]
+	#[error("{}", message!("lex-unknown-symbol", found = format!("{found:?}")))]
 	#[diagnostic(code(ream::lex_error::unexpected_symbol))]
 	UnknownSymbol {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: char,
 	},
 }
 
+impl LexError {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind { ErrorKind::Lex }
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> { primary_span(self) }
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+}
+
 /// Any error related to parsing
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum ParseError {
@@ -100,26 +219,55 @@ pub enum ParseError {
 	#[error("test {loc:?}")]
 	#[diagnostic(code(ream::test))]
 	Test {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 	},
 
 	/// Unexpected end-of-file
 	#[allow(missing_docs)]
-	#[error("Unexpected end-of-file")]
+	#[error("{}", message!("parse-unexpected-eof"))]
 	#[diagnostic(code(ream::parse_error::unexpected_eof))]
 	UnexpectedEof {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
+	},
+
+	/// A `)` without a matching `(`
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-unmatched-close-paren"))]
+	#[diagnostic(help("{}", message!("parse-unmatched-close-paren.help")))]
+	#[diagnostic(code(ream::parse_error::unmatched_close_paren))]
+	UnmatchedCloseParen {
+		#[label("{}", message!("label-here"))]
+		loc: Span,
+	},
+
+	/// A `(` was never closed
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-unclosed-paren"))]
+	#[diagnostic(help("{}", message!("parse-unclosed-paren.help")))]
+	#[diagnostic(code(ream::parse_error::unclosed_paren))]
+	UnclosedParen {
+		#[label("{}", message!("parse-unclosed-paren.label"))]
+		loc:   Span,
+		#[label("{}", message!("parse-unclosed-paren.label-close"))]
+		close: Span,
 	},
 
 	/// Expected one token, found another
 	#[allow(missing_docs)]
-	#[error("Unexpected Token: found `{found}`, expected {}", format_expected_tokens(expected))]
+	#[error(
+		"{}",
+		message!(
+			"parse-unexpected-token",
+			found = found,
+			expected = format_expected_tokens(expected),
+		)
+	)]
 	#[diagnostic(code(ream::parse_error::unexpected_token))]
 	UnexpectedToken {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found:    String,
 		expected: Vec<String>,
@@ -127,101 +275,838 @@ pub enum ParseError {
 
 	/// Invalid expression
 	#[allow(missing_docs)]
-	#[error("Invalid Expression: found `{found}`, expected {}", format_expected_tokens(expected))]
+	#[error(
+		"{}",
+		message!(
+			"parse-invalid-expression",
+			found = found,
+			expected = format_expected_tokens(expected),
+		)
+	)]
 	#[diagnostic(code(ream::parse_error::invalid_expression))]
 	InvalidExpression {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found:    String,
 		expected: Vec<String>,
 	},
 
+	/// A literal used as the operator of a procedure call
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-not-callable"))]
+	#[diagnostic(
+		code(ream::parse_error::not_callable),
+		help("{}", message!("parse-not-callable.help", quoted = quoted))
+	)]
+	NotCallable {
+		#[label(
+			"{}",
+			message!(
+				"parse-not-callable.label",
+				article = if kind.starts_with(['A', 'E', 'I', 'O', 'U']) { "an" } else { "a" },
+				kind = kind,
+			)
+		)]
+		loc:    Span,
+		kind:   String,
+		quoted: String,
+	},
+
 	/// Invalid annotation type
 	#[allow(missing_docs)]
-	#[error("Invalid Annotation Type: found `{found}`, expected one of `:type`, `:doc`")]
+	#[error("{}", message!("parse-invalid-annotation", found = found))]
 	#[diagnostic(code(ream::parse_error::invalid_annotation))]
 	InvalidAnnotation {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: String,
 	},
 
 	/// Invalid Datum
 	#[allow(missing_docs)]
-	#[error(
-		"Invalid Datum: found `{found}`, expected one of `Identifier`, `Boolean`, `Integer`, \
-		 `Float`, `Character`, `String`, `Atom`, `(`"
-	)]
+	#[error("{}", message!("parse-invalid-datum", found = found))]
 	#[diagnostic(code(ream::parse_error::invalid_datum))]
 	InvalidDatum {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
+
+		found: String,
+	},
+
+	/// Invalid Pattern
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-invalid-pattern", found = found))]
+	#[diagnostic(code(ream::parse_error::invalid_pattern))]
+	InvalidPattern {
+		#[label("{}", message!("label-here"))]
+		loc: Span,
+
+		found: String,
+	},
+
+	/// Invalid type specification
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-invalid-typespec", found = found))]
+	#[diagnostic(code(ream::parse_error::invalid_typespec))]
+	InvalidTypeSpec {
+		#[label("{}", message!("label-here"))]
+		loc: Span,
+
+		found: String,
+	},
+
+	/// Invalid algebraic type
+	#[allow(missing_docs)]
+	#[error("{}", message!("parse-invalid-algebraic-type", found = found))]
+	#[diagnostic(code(ream::parse_error::invalid_algebraic_type))]
+	InvalidAlgebraicType {
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: String,
 	},
 
 	/// Invalid Formals
 	#[allow(missing_docs)]
-	#[error("Invalid Formals: found `{found}`, expected one of `Identifier`, `(`")]
+	#[error("{}", message!("parse-invalid-formals", found = found))]
 	#[diagnostic(code(ream::parse_error::invalid_formals))]
 	InvalidFormals {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 
 		found: String,
 	},
 }
 
+impl ParseError {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind { ErrorKind::Parse }
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> { primary_span(self) }
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+}
+
+/// Any error related to macro expansion
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum ExpandError {
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!(
+			"expand-wrong-argument-count",
+			name = name,
+			expected = expected,
+			found = found,
+		)
+	)]
+	#[diagnostic(code(ream::expand_error::wrong_argument_count))]
+	WrongArgumentCount {
+		#[label("{}", message!("label-here"))]
+		loc:      Span,
+		name:     String,
+		expected: String,
+		found:    usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("expand-not-an-identifier", name = name, formal = formal)
+	)]
+	#[diagnostic(code(ream::expand_error::not_an_identifier))]
+	NotAnIdentifier {
+		#[label("{}", message!("expand-not-an-identifier.label"))]
+		loc:    Span,
+		name:   String,
+		formal: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("expand-misplaced-rest", rest = rest))]
+	#[diagnostic(
+		code(ream::expand_error::misplaced_rest),
+		help("{}", message!("expand-misplaced-rest.help"))
+	)]
+	MisplacedRest {
+		#[label("{}", message!("label-here"))]
+		loc:  Span,
+		rest: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("expand-recursion-limit", name = name, limit = limit))]
+	#[diagnostic(code(ream::expand_error::recursion_limit))]
+	RecursionLimit {
+		#[label("{}", message!("expand-recursion-limit.label"))]
+		loc:   Span,
+		name:  String,
+		limit: usize,
+	},
+}
+
+impl ExpandError {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind { ErrorKind::Macro }
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> { primary_span(self) }
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+}
+
+/// Any error found while checking a program against its `:type`
+/// annotations
+///
+/// Every error points at both the offending expression and the annotation
+/// it contradicts
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum TypeError {
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("type-mismatch", name = name, expected = expected, found = found)
+	)]
+	#[diagnostic(code(ream::type_error::mismatch))]
+	Mismatch {
+		#[label("{}", message!("type-mismatch.label"))]
+		loc:        Span,
+		#[label("{}", message!("label-annotated"))]
+		annotation: Span,
+		name:       String,
+		expected:   String,
+		found:      String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!(
+			"type-argument-mismatch",
+			name = name,
+			position = position,
+			expected = expected,
+			found = found,
+		)
+	)]
+	#[diagnostic(code(ream::type_error::argument_mismatch))]
+	ArgumentMismatch {
+		#[label("{}", message!("type-argument-mismatch.label"))]
+		loc:        Span,
+		#[label("{}", message!("label-annotated"))]
+		annotation: Span,
+		name:       String,
+		position:   usize,
+		expected:   String,
+		found:      String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("type-return-mismatch", name = name, expected = expected, found = found)
+	)]
+	#[diagnostic(code(ream::type_error::return_mismatch))]
+	ReturnMismatch {
+		#[label("{}", message!("type-return-mismatch.label"))]
+		loc:        Span,
+		#[label("{}", message!("label-annotated"))]
+		annotation: Span,
+		name:       String,
+		expected:   String,
+		found:      String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("type-wrong-argument-count", name = name, expected = expected, found = found)
+	)]
+	#[diagnostic(code(ream::type_error::wrong_argument_count))]
+	WrongArgumentCount {
+		#[label("{}", message!("type-wrong-argument-count.label"))]
+		loc:        Span,
+		#[label("{}", message!("label-annotated"))]
+		annotation: Span,
+		name:       String,
+		expected:   usize,
+		found:      usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("type-not-callable", name = name, spec = spec))]
+	#[diagnostic(code(ream::type_error::not_callable))]
+	NotCallable {
+		#[label("{}", message!("type-not-callable.label"))]
+		loc:        Span,
+		#[label("{}", message!("label-annotated"))]
+		annotation: Span,
+		name:       String,
+		spec:       String,
+	},
+}
+
+impl TypeError {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind { ErrorKind::Type }
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> { primary_span(self) }
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+}
+
+/// Every error found while checking the types of a program, when there is
+/// more than one
+#[derive(Debug, Diagnostic, Error)]
+#[error("{}", message!("type-multiple", count = errors.len()))]
+#[diagnostic(code(ream::type_error::multiple))]
+pub struct TypeErrors {
+	/// The errors in the order they were found
+	#[related]
+	pub errors: Vec<TypeError>,
+}
+
+/// Every error found while parsing a single file, when there is more than
+/// one
+#[derive(Debug, Diagnostic, Error)]
+#[error("{}", message!("parse-multiple", count = errors.len()))]
+#[diagnostic(code(ream::parse_error::multiple))]
+pub struct ParseErrors {
+	/// The errors in the order they were found
+	#[related]
+	pub errors: Vec<miette::Report>,
+}
+
 /// Any error related to evaluation
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum EvalError {
 	#[allow(missing_docs)]
-	#[error("Could not find value for `{id}` in this scope")]
+	#[error("{}", message!("eval-unknown-identifier", id = id))]
 	#[diagnostic(code(ream::eval_error::unknown_identifier))]
 	UnknownIdentifier {
-		#[label = "here"]
-		loc: SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc: Span,
 		id:  String,
 	},
 
 	#[allow(missing_docs)]
-	#[error("`{name}` is not a function")]
-	#[diagnostic(code(ream::eval_error::not_a_function))]
+	#[error("{}", message!("eval-constant-redefinition", name = name))]
+	#[diagnostic(
+		code(ream::eval_error::constant_redefinition),
+		help("{}", message!("eval-constant-redefinition.help"))
+	)]
+	ConstantRedefinition {
+		#[label("{}", message!("eval-constant-redefinition.label"))]
+		loc:     Span,
+		name:    String,
+		#[label("{}", message!("eval-constant-redefinition.label-defined"))]
+		defined: Span,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-not-a-function"))]
+	#[diagnostic(
+		code(ream::eval_error::not_a_function),
+		help("{}", message!("eval-not-a-function.help"))
+	)]
 	NotAFunction {
-		#[label = "here"]
-		loc:  SourceSpan,
+		#[label("{}", message!("eval-not-a-function.label", name = name))]
+		loc:  Span,
 		name: String,
 	},
 
 	#[allow(missing_docs)]
-	#[error("`{callee}` takes {expected} arguments, got {found}")]
+	#[error(
+		"{}",
+		message!(
+			"eval-wrong-argument-count",
+			callee = callee,
+			expected = expected,
+			found = found,
+		)
+	)]
 	#[diagnostic(code(ream::eval_error::wrong_argument_count))]
 	WrongArgumentCount {
-		#[label = "here"]
-		loc:      SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc:      Span,
 		callee:   String,
 		expected: usize,
 		found:    usize,
 	},
 
 	#[allow(missing_docs)]
-	#[error("Wrong type, expected `{expected}` found `{found}`")]
+	#[error(
+		"{}",
+		message!("eval-capability-denied", callee = callee, capability = capability)
+	)]
+	#[diagnostic(code(ream::eval_error::capability_denied))]
+	CapabilityDenied {
+		#[label("{}", message!("label-here"))]
+		loc:        Span,
+		callee:     String,
+		capability: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-timeout", timeout = timeout))]
+	#[diagnostic(code(ream::eval_error::timeout))]
+	Timeout {
+		#[label("{}", message!("eval-timeout.label"))]
+		loc:     Span,
+		timeout: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-quota-exceeded", quota = quota))]
+	#[diagnostic(code(ream::eval_error::quota_exceeded))]
+	QuotaExceeded {
+		#[label("{}", message!("eval-quota-exceeded.label"))]
+		loc:   Span,
+		quota: usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-pattern-mismatch", expected = expected, found = found)
+	)]
+	#[diagnostic(code(ream::eval_error::pattern_mismatch))]
+	PatternMismatch {
+		#[label("{}", message!("eval-pattern-mismatch.label"))]
+		loc:      Span,
+		expected: String,
+		found:    String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-no-match", value = value))]
+	#[diagnostic(code(ream::eval_error::no_match))]
+	NoMatch {
+		#[label("{}", message!("eval-no-match.label"))]
+		loc:   Span,
+		value: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-no-matching-clause", name = name, arguments = arguments))]
+	#[diagnostic(code(ream::eval_error::no_matching_clause))]
+	NoMatchingClause {
+		#[label("{}", message!("eval-no-matching-clause.label"))]
+		loc:       Span,
+		name:      String,
+		arguments: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!(
+			"eval-missing-implementation",
+			type_name = type_name,
+			protocol = protocol,
+			method = method,
+		)
+	)]
+	#[diagnostic(code(ream::eval_error::missing_implementation))]
+	MissingImplementation {
+		#[label("{}", message!("label-here"))]
+		loc:       Span,
+		protocol:  String,
+		method:    String,
+		type_name: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!(
+			"eval-invalid-implementation",
+			protocol = protocol,
+			expected = expected,
+			found = found,
+		)
+	)]
+	#[diagnostic(code(ream::eval_error::invalid_implementation))]
+	InvalidImplementation {
+		#[label("{}", message!("label-here"))]
+		loc:      Span,
+		protocol: String,
+		expected: usize,
+		found:    usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-unknown-field", type_name = type_name, field = field)
+	)]
+	#[diagnostic(code(ream::eval_error::unknown_field))]
+	UnknownField {
+		#[label("{}", message!("label-here"))]
+		loc:       Span,
+		type_name: String,
+		field:     String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-missing-field", type_name = type_name, field = field)
+	)]
+	#[diagnostic(code(ream::eval_error::missing_field))]
+	MissingField {
+		#[label("{}", message!("label-here"))]
+		loc:       Span,
+		type_name: String,
+		field:     String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-duplicate-field", type_name = type_name, field = field)
+	)]
+	#[diagnostic(code(ream::eval_error::duplicate_field))]
+	DuplicateField {
+		#[label("{}", message!("eval-duplicate-field.label"))]
+		loc:       Span,
+		type_name: String,
+		field:     String,
+		#[label("{}", message!("eval-duplicate-field.first"))]
+		first:     Span,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-missing-field-value", type_name = type_name, field = field)
+	)]
+	#[diagnostic(code(ream::eval_error::missing_field_value))]
+	MissingFieldValue {
+		#[label("{}", message!("label-here"))]
+		loc:       Span,
+		type_name: String,
+		field:     String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-invalid-regex", message = message))]
+	#[diagnostic(code(ream::eval_error::invalid_regex))]
+	InvalidRegex {
+		#[label("{}", message!("label-here"))]
+		loc:     Span,
+		message: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-invalid-date-format", format = format))]
+	#[diagnostic(code(ream::eval_error::invalid_date_format))]
+	InvalidDateFormat {
+		#[label("{}", message!("label-here"))]
+		loc:    Span,
+		format: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-invalid-environment-variable", name = name, reason = reason))]
+	#[diagnostic(code(ream::eval_error::invalid_environment_variable))]
+	InvalidEnvironmentVariable {
+		#[label("{}", message!("label-here"))]
+		loc:    Span,
+		name:   String,
+		reason: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error(
+		"{}",
+		message!("eval-wrong-type", expected = expected, found = found)
+	)]
 	#[diagnostic(code(ream::eval_error::wrong_type))]
 	WrongType {
-		#[label = "here"]
-		loc:      SourceSpan,
+		#[label("{}", message!("label-here"))]
+		loc:      Span,
 		expected: String,
 		found:    String,
 	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-output-failed", message = message))]
+	#[diagnostic(code(ream::eval_error::output_failed))]
+	OutputFailed {
+		#[label("{}", message!("label-here"))]
+		loc:     Span,
+		message: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-raised", payload = payload))]
+	#[diagnostic(code(ream::eval_error::raised))]
+	Raised {
+		#[label("{}", message!("eval-raised.label"))]
+		loc:     Span,
+		payload: Value,
+	},
+
+	#[allow(missing_docs)]
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	Expand(#[from] ExpandError),
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-include-not-found", file = file))]
+	#[diagnostic(
+		code(ream::eval_error::include_not_found),
+		help("{}", message!("eval-include-not-found.help", searched = searched))
+	)]
+	IncludeNotFound {
+		#[label("{}", message!("eval-include-not-found.label"))]
+		loc:      Span,
+		file:     String,
+		searched: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-include-cycle", file = file))]
+	#[diagnostic(
+		code(ream::eval_error::include_cycle),
+		help("{}", message!("eval-include-cycle.help", chain = chain))
+	)]
+	IncludeCycle {
+		#[label("{}", message!("eval-include-cycle.label"))]
+		loc:   Span,
+		file:  String,
+		chain: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-include-failed", file = file))]
+	#[diagnostic(code(ream::eval_error::include_failed))]
+	IncludeFailed {
+		#[label("{}", message!("eval-include-failed.label"))]
+		loc:   Span,
+		file:  String,
+		#[related]
+		cause: Vec<IncludedError>,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-unwrap-err", payload = payload))]
+	#[diagnostic(code(ream::eval_error::unwrap_err))]
+	UnwrapErr {
+		#[label("{}", message!("label-here"))]
+		loc:     Span,
+		payload: Value,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-arithmetic-overflow"))]
+	#[diagnostic(
+		code(ream::eval_error::arithmetic_overflow),
+		help("{}", message!("eval-arithmetic-overflow.help"))
+	)]
+	ArithmeticOverflow {
+		#[label("{}", message!("eval-arithmetic-overflow.label"))]
+		loc: Span,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-division-by-zero"))]
+	#[diagnostic(code(ream::eval_error::division_by_zero))]
+	DivisionByZero {
+		#[label("{}", message!("eval-division-by-zero.label"))]
+		loc: Span,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-unhashable", type_name = type_name))]
+	#[diagnostic(
+		code(ream::eval_error::unhashable),
+		help("{}", message!("eval-unhashable.help"))
+	)]
+	Unhashable {
+		#[label("{}", message!("label-here"))]
+		loc:       Span,
+		type_name: String,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-index-out-of-bounds", index = index, len = len))]
+	#[diagnostic(code(ream::eval_error::index_out_of_bounds))]
+	IndexOutOfBounds {
+		#[label("{}", message!("eval-index-out-of-bounds.label"))]
+		loc:   Span,
+		index: i64,
+		len:   usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-invalid-range", start = start, end = end))]
+	#[diagnostic(code(ream::eval_error::invalid_range))]
+	InvalidRange {
+		#[label("{}", message!("eval-invalid-range.label"))]
+		loc:   Span,
+		start: usize,
+		end:   usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-value-too-deep", limit = limit))]
+	#[diagnostic(code(ream::eval_error::value_too_deep))]
+	ValueTooDeep {
+		#[label("{}", message!("eval-value-too-deep.label"))]
+		loc:   Span,
+		limit: usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-value-too-large", limit = limit))]
+	#[diagnostic(code(ream::eval_error::value_too_large))]
+	ValueTooLarge {
+		#[label("{}", message!("eval-value-too-large.label"))]
+		loc:   Span,
+		limit: usize,
+	},
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("eval-string-too-long", limit = limit))]
+	#[diagnostic(code(ream::eval_error::string_too_long))]
+	StringTooLong {
+		#[label("{}", message!("eval-string-too-long.label"))]
+		loc:   Span,
+		limit: usize,
+	},
+}
+
+impl EvalError {
+	/// Get the [`ErrorKind`] of this error
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::UnknownIdentifier { .. } => ErrorKind::Name,
+			Self::ConstantRedefinition { .. } => ErrorKind::Name,
+			Self::NotAFunction { .. } => ErrorKind::Call,
+			Self::WrongArgumentCount { .. } => ErrorKind::Call,
+			Self::CapabilityDenied { .. } => ErrorKind::Capability,
+			Self::Timeout { .. } => ErrorKind::Limit,
+			Self::QuotaExceeded { .. } => ErrorKind::Limit,
+			Self::ValueTooDeep { .. } => ErrorKind::Limit,
+			Self::ValueTooLarge { .. } => ErrorKind::Limit,
+			Self::StringTooLong { .. } => ErrorKind::Limit,
+			Self::PatternMismatch { .. } => ErrorKind::Type,
+			Self::NoMatch { .. } => ErrorKind::Type,
+			Self::NoMatchingClause { .. } => ErrorKind::Type,
+			Self::MissingImplementation { .. } => ErrorKind::Type,
+			Self::InvalidImplementation { .. } => ErrorKind::Type,
+			Self::UnknownField { .. } => ErrorKind::Type,
+			Self::MissingField { .. } => ErrorKind::Type,
+			Self::DuplicateField { .. } => ErrorKind::Type,
+			Self::MissingFieldValue { .. } => ErrorKind::Type,
+			Self::InvalidRegex { .. } => ErrorKind::Type,
+			Self::InvalidDateFormat { .. } => ErrorKind::Type,
+			Self::InvalidEnvironmentVariable { .. } => ErrorKind::Type,
+			Self::WrongType { .. } => ErrorKind::Type,
+			Self::OutputFailed { .. } => ErrorKind::Io,
+			Self::Raised { .. } => ErrorKind::User,
+			Self::UnwrapErr { .. } => ErrorKind::User,
+			Self::ArithmeticOverflow { .. } => ErrorKind::Arithmetic,
+			Self::DivisionByZero { .. } => ErrorKind::Arithmetic,
+			Self::Unhashable { .. } => ErrorKind::Type,
+			Self::IndexOutOfBounds { .. } => ErrorKind::Type,
+			Self::InvalidRange { .. } => ErrorKind::Type,
+			Self::IncludeNotFound { .. } => ErrorKind::Include,
+			Self::IncludeCycle { .. } => ErrorKind::Include,
+			Self::IncludeFailed { .. } => ErrorKind::Include,
+			Self::Expand(e) => e.kind(),
+		}
+	}
+
+	/// Get the primary location of this error, if it has one
+	pub fn span(&self) -> Option<Span> { primary_span(self) }
+
+	/// Get the message of this error, without any source code or labels
+	pub fn message(&self) -> String { self.to_string() }
+
+	/// Get the payload of an error raised using `(error ...)`, or of an
+	/// `err` value that was unwrapped
+	///
+	/// Values of types with a printer are a [`Value::Printed`], which the
+	/// message of the error displays them as
+	pub fn payload(&self) -> Option<&Value> {
+		match self {
+			Self::Raised { payload, .. } => Some(payload),
+			Self::UnwrapErr { payload, .. } => Some(payload),
+			_ => None,
+		}
+	}
+}
+
+/// Any error related to parsing a message catalog
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum CatalogError {
+	#[allow(missing_docs)]
+	#[error("{}", message!("catalog-invalid-entry", line = line, text = text))]
+	#[diagnostic(
+		code(ream::catalog_error::invalid_entry),
+		help("{}", message!("catalog-invalid-entry.help"))
+	)]
+	InvalidEntry { line: usize, text: String },
+
+	#[allow(missing_docs)]
+	#[error("{}", message!("catalog-orphan-attribute", line = line))]
+	#[diagnostic(code(ream::catalog_error::orphan_attribute))]
+	OrphanAttribute { line: usize },
+}
+
+/// An error that happened in an included file, shown below the inclusion
+/// that caused it
+///
+/// Its spans point into the [`SourceMap`](crate::SourceMap) of the
+/// interpreter, which the error it is part of should be displayed with
+#[derive(Clone)]
+pub struct IncludedError(pub(crate) Arc<miette::Report>);
+
+impl IncludedError {
+	/// Wrap an error that happened in an included file
+	pub(crate) fn new(error: miette::Report) -> Self { Self(Arc::new(error)) }
+}
+
+impl fmt::Debug for IncludedError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Debug::fmt(&*self.0, f) }
+}
+
+impl fmt::Display for IncludedError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(&*self.0, f) }
+}
+
+impl std::error::Error for IncludedError {}
+
+impl Diagnostic for IncludedError {
+	fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> { self.0.code() }
+
+	fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> { self.0.help() }
+
+	fn source_code(&self) -> Option<&dyn SourceCode> { self.0.source_code() }
+
+	fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> { self.0.labels() }
+
+	fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+		self.0.related()
+	}
 }
 
 fn format_expected_symbols(ex: &[char]) -> String {
 	if ex.len() == 1 {
 		format!("`{}`", ex[0])
 	} else {
-		format!("one of {}", ex.iter().map(|e| format!("`{}`", e)).collect::<Vec<_>>().join(", "))
+		let list = ex.iter().map(|e| format!("`{}`", e)).collect::<Vec<_>>().join(", ");
+		message!("expected-one-of", list = list)
 	}
 }
 
@@ -229,6 +1114,119 @@ fn format_expected_tokens(ex: &[String]) -> String {
 	if ex.len() == 1 {
 		format!("`{}`", ex[0])
 	} else {
-		format!("one of {}", ex.iter().map(|e| format!("`{}`", e)).collect::<Vec<_>>().join(", "))
+		let list = ex.iter().map(|e| format!("`{}`", e)).collect::<Vec<_>>().join(", ");
+		message!("expected-one-of", list = list)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		lex_str,
+		parse_str,
+		Capabilities,
+		Error,
+		ErrorKind,
+		EvalError,
+		Interpreter,
+		LexError,
+		MemoryProvider,
+		ParseError,
+		Value,
+	};
+
+	fn run(source: &str) -> EvalError {
+		let program = parse_str(source).unwrap();
+
+		Interpreter::new(Capabilities::PURE)
+			.with_source_provider(MemoryProvider::new())
+			.run(program)
+			.unwrap_err()
+	}
+
+	#[test]
+	fn raised_errors_keep_their_payload() {
+		let err = run("(error (tuple 1 \"two\"))");
+
+		let payload = Value::Tuple(vec![Value::Integer(1), Value::String("two".to_string())]);
+		assert_eq!(err.payload(), Some(&payload));
+		assert_eq!(err.message(), "(tuple 1 two)");
+	}
+
+	#[test]
+	fn unwrapped_errs_keep_their_payload() {
+		let err = run("(unwrap (err (list 1 2)))");
+
+		let payload = Value::List(vec![Value::Integer(1), Value::Integer(2)]);
+		assert!(matches!(err, EvalError::UnwrapErr { .. }));
+		assert_eq!(err.payload(), Some(&payload));
+	}
+
+	#[test]
+	fn payloads_are_displayed_by_printers() {
+		let err = run(r#"
+(define-type P (Product (:x Integer) (:y Integer)))
+(define-printer :P (lambda (p) "point"))
+(error (P :x 1 :y 2))
+"#);
+
+		let Some(Value::Printed { value, repr }) = err.payload() else {
+			panic!("expected a printed payload");
+		};
+		assert_eq!(value.type_name(), "P");
+		assert_eq!(repr, "point");
+		assert_eq!(err.message(), "point");
+	}
+
+	#[test]
+	fn other_errors_have_no_payload() { assert_eq!(run("(car 1)").payload(), None); }
+
+	#[test]
+	fn evaluation_errors_are_classified() {
+		let cases = [
+			("(+ undefined 1)", ErrorKind::Name),
+			("(let x 1)\n(x 2)", ErrorKind::Call),
+			("(car 1 2)", ErrorKind::Call),
+			("(car 1)", ErrorKind::Type),
+			("(match 1 (2 2))", ErrorKind::Type),
+			("(getenv \"HOME\")", ErrorKind::Capability),
+			("(error 1)", ErrorKind::User),
+			("(unwrap (err 1))", ErrorKind::User),
+			("(include \"missing.rm\")", ErrorKind::Include),
+			("(define-macro m (x) x)\n(m)", ErrorKind::Macro),
+			("(/ 1 0)", ErrorKind::Arithmetic),
+		];
+
+		for (source, kind) in cases {
+			let err = run(source);
+
+			assert_eq!(err.kind(), kind, "`{source}` failed with {err:?}");
+			assert_eq!(Error::from(err).kind(), kind);
+		}
+	}
+
+	#[test]
+	fn exceeded_limits_are_classified() {
+		let program = parse_str("(list 1 2 3)").unwrap();
+		let err = Interpreter::new(Capabilities::PURE)
+			.with_value_quota(1)
+			.run(program)
+			.unwrap_err();
+
+		assert_eq!(err.kind(), ErrorKind::Limit);
+	}
+
+	#[test]
+	fn lex_parse_and_io_errors_are_classified() {
+		let lex = lex_str("\"unterminated").unwrap_err();
+		let lex = lex.downcast::<LexError>().unwrap();
+		assert_eq!(Error::from(lex).kind(), ErrorKind::Lex);
+
+		let parse = parse_str("(let)").unwrap_err();
+		let parse = parse.downcast::<ParseError>().unwrap();
+		assert_eq!(Error::from(parse).kind(), ErrorKind::Parse);
+
+		let io = std::io::Error::from(std::io::ErrorKind::NotFound);
+		assert_eq!(Error::from(io).kind(), ErrorKind::Io);
 	}
 }
@@ -0,0 +1,239 @@
+//! Runtime support for algebraic data types
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::assertion::assert_type;
+use super::{ReamType, ReamValue, Scope};
+use crate::ast::{Identifier, Literal, NamedTypeSpec, TypeConstructor, TypeSpec};
+use crate::{EvalError, Span};
+
+/// Give an algebraic type definition its runtime meaning by binding its
+/// constructor in the given scope
+pub(super) fn define_type<'s>(
+	target: Identifier<'s>,
+	spec: TypeSpec<'s>,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	match spec {
+		TypeSpec::Constructor(TypeConstructor::Product { span, fields }) => {
+			let specs = fields.iter().map(|f| f.spec.clone());
+			let constructor = ReamType::ProductConstructor {
+				name:   target.id,
+				fields: field_names(&fields).into_iter().zip(specs).collect(),
+			};
+			scope.borrow_mut().define(target, ReamValue { span, t: constructor })
+		},
+		TypeSpec::Constructor(TypeConstructor::Sum { fields, .. }) => {
+			// Every variant gets its own constructor named after it
+			for (variant, field) in field_names(&fields).into_iter().zip(&fields) {
+				let constructor = ReamType::VariantConstructor {
+					name: target.id,
+					variant,
+					has_payload: field.spec.is_some(),
+				};
+				let id = Identifier { span: field.span, id: variant };
+				scope.borrow_mut().define(id, ReamValue { span: field.span, t: constructor })?;
+			}
+
+			Ok(())
+		},
+		// The parser only accepts sum and product types in type definitions
+		_ => unreachable!(),
+	}
+}
+
+/// Get the names of a list of named type specifications, without their
+/// leading `:`
+fn field_names<'s>(fields: &[NamedTypeSpec<'s>]) -> Vec<&'s str> {
+	fields
+		.iter()
+		.map(|f| {
+			match f.name {
+				Literal::Atom { a, .. } => &a[1..],
+				// The parser only produces atom names
+				_ => unreachable!(),
+			}
+		})
+		.collect()
+}
+
+/// Construct a value of a product type from labeled arguments of the form
+/// `<atom> <value>` given in any order
+///
+/// Every field has to be given exactly once, and values of fields with a
+/// declared type are checked against it like `the` checks them
+pub(super) fn construct_product<'s>(
+	name: &'s str,
+	fields: &[(&'s str, Option<TypeSpec<'s>>)],
+	args: Vec<ReamValue<'s>>,
+	loc: Span,
+) -> Result<ReamType<'s>, EvalError> {
+	let mut labeled: Vec<(&str, Span, ReamValue<'s>)> = vec![];
+	let mut args = args.into_iter();
+	while let Some(label) = args.next() {
+		let ReamType::Atom(label_name) = label.t else {
+			return Err(EvalError::WrongType {
+				loc:      label.span,
+				expected: "Atom".to_string(),
+				found:    label.t.type_name(),
+			});
+		};
+		let label_name = &label_name[1..];
+
+		let Some((_, spec)) = fields.iter().find(|(f, _)| *f == label_name) else {
+			return Err(EvalError::UnknownField {
+				loc:       label.span,
+				type_name: name.to_string(),
+				field:     label_name.to_string(),
+			});
+		};
+
+		if let Some((_, first, _)) = labeled.iter().find(|(l, ..)| *l == label_name) {
+			return Err(EvalError::DuplicateField {
+				loc:       label.span,
+				type_name: name.to_string(),
+				field:     label_name.to_string(),
+				first:     *first,
+			});
+		}
+
+		let Some(value) = args.next() else {
+			return Err(EvalError::MissingFieldValue {
+				loc:       label.span,
+				type_name: name.to_string(),
+				field:     label_name.to_string(),
+			});
+		};
+
+		let value = match spec {
+			Some(spec) => {
+				let span = value.span;
+
+				assert_type(spec, value, span)?
+			},
+			None => value,
+		};
+
+		labeled.push((label_name, label.span, value));
+	}
+
+	let fields = fields
+		.iter()
+		.map(|(f, _)| {
+			match labeled.iter().position(|(l, ..)| l == f) {
+				Some(i) => Ok((*f, labeled.swap_remove(i).2)),
+				None => {
+					Err(EvalError::MissingField {
+						loc,
+						type_name: name.to_string(),
+						field: f.to_string(),
+					})
+				},
+			}
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	Ok(ReamType::Product { name, fields })
+}
+
+/// Construct a value of a sum type holding the given variant, taking a
+/// single payload argument if the variant has one
+pub(super) fn construct_variant<'s>(
+	name: &'s str,
+	variant: &'s str,
+	has_payload: bool,
+	mut args: Vec<ReamValue<'s>>,
+	loc: Span,
+) -> Result<ReamType<'s>, EvalError> {
+	let expected = usize::from(has_payload);
+
+	if args.len() != expected {
+		return Err(EvalError::WrongArgumentCount {
+			loc,
+			callee: variant.to_string(),
+			expected,
+			found: args.len(),
+		});
+	}
+
+	Ok(ReamType::Variant { name, variant, payload: args.pop().map(Box::new) })
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, EvalError, Interpreter, Value};
+
+	const POINT: &str = "(define-type Point (Product (:x Float) (:y Integer) :tag))\n";
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		let program = parse_str(source).unwrap();
+
+		Interpreter::new(Capabilities::PURE).run(program)
+	}
+
+	/// Construct a point from the given arguments, returning the error and
+	/// the source code it points at
+	fn construct(args: &str) -> (EvalError, String) {
+		let source = format!("{POINT}(Point {args})");
+		let err = run(&source).unwrap_err();
+		let loc = err.span().unwrap();
+
+		(err, source[loc.range()].to_string())
+	}
+
+	#[test]
+	fn fields_are_given_in_any_order() {
+		let point = run(&format!("{POINT}(Point :tag \"a\" :y 2 :x 1.5)")).unwrap();
+
+		assert_eq!(point.to_string(), "(Point :x 1.5 :y 2 :tag a)");
+	}
+
+	#[test]
+	fn field_values_are_converted_to_their_declared_type() {
+		let x = run(&format!("{POINT}(get (Point :x 1 :y 2 :tag 3) :x)")).unwrap();
+
+		assert_eq!(x, Value::Float(1.0));
+	}
+
+	#[test]
+	fn field_values_of_the_wrong_type_are_rejected() {
+		let (err, at) = construct(":x 1.0 :y (list 2) :tag 3");
+
+		let EvalError::WrongType { expected, found, .. } = err else { panic!("{err:?}") };
+		assert_eq!((expected.as_str(), found.as_str()), ("Integer", "List"));
+		assert_eq!(at, "(list 2)");
+	}
+
+	#[test]
+	fn duplicate_fields_are_rejected() {
+		let (err, at) = construct(":x 1.0 :y 2 :x 3.0 :tag 4");
+
+		let EvalError::DuplicateField { field, .. } = err else { panic!("{err:?}") };
+		assert_eq!(field, "x");
+		assert_eq!(at, ":x");
+	}
+
+	#[test]
+	fn fields_without_a_value_are_rejected() {
+		let (err, at) = construct(":x 1.0 :y 2 :tag");
+
+		let EvalError::MissingFieldValue { field, .. } = err else { panic!("{err:?}") };
+		assert_eq!(field, "tag");
+		assert_eq!(at, ":tag");
+	}
+
+	#[test]
+	fn missing_and_unknown_fields_are_rejected() {
+		let (err, _) = construct(":x 1.0 :tag 2");
+		assert!(matches!(err, EvalError::MissingField { field, .. } if field == "y"));
+
+		let (err, at) = construct(":x 1.0 :y 2 :tag 3 :z 4");
+		assert!(matches!(err, EvalError::UnknownField { field, .. } if field == "z"));
+		assert_eq!(at, ":z");
+
+		let (err, at) = construct(":x 1.0 2 :y");
+		assert!(matches!(err, EvalError::WrongType { .. }));
+		assert_eq!(at, "2");
+	}
+}
@@ -0,0 +1,134 @@
+//! Runtime type assertions, checking values against type specifications
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{ReamType, ReamValue};
+use crate::ast::{TypeConstructor, TypeSpec};
+use crate::{EvalError, Span};
+
+/// Check that a value has the type described by a type specification,
+/// converting numbers of another numeric type to it
+///
+/// Types are named the way [`ReamType::type_name`] names them. Lists, tuples
+/// and vectors are checked element by element, functions only on whether
+/// they can be called. Vectors are checked by copying them, so converting
+/// their elements leaves the original alone
+///
+/// Fails with an [`EvalError::WrongType`] pointing at `loc`
+pub(super) fn assert_type<'s>(
+	spec: &TypeSpec<'s>,
+	value: ReamValue<'s>,
+	loc: Span,
+) -> Result<ReamValue<'s>, EvalError> {
+	let span = value.span;
+
+	let t = match (spec, value.t) {
+		(TypeSpec::Identifier(name), t) => coerce(name.id, t, loc)?,
+		(TypeSpec::Constructor(TypeConstructor::List { t: spec, .. }), ReamType::List(values)) => {
+			ReamType::List(
+				values.into_iter().map(|v| assert_type(spec, v, loc)).collect::<Result<_, _>>()?,
+			)
+		},
+		(
+			TypeSpec::Constructor(TypeConstructor::Vector { t: spec, .. }),
+			ReamType::Vector(values),
+		) => {
+			let elements = values.borrow().clone();
+			let elements = elements
+				.into_iter()
+				.map(|v| assert_type(spec, v, loc))
+				.collect::<Result<Vec<_>, _>>()?;
+
+			ReamType::Vector(Rc::new(RefCell::new(elements)))
+		},
+		(TypeSpec::Constructor(TypeConstructor::Tuple { fields, .. }), ReamType::Tuple(values)) => {
+			if fields.len() != values.len() {
+				return Err(EvalError::WrongType {
+					loc,
+					expected: format!("Tuple of {} elements", fields.len()),
+					found: format!("Tuple of {} elements", values.len()),
+				});
+			}
+
+			ReamType::Tuple(
+				fields
+					.iter()
+					.zip(values)
+					.map(|(spec, v)| assert_type(spec, v, loc))
+					.collect::<Result<_, _>>()?,
+			)
+		},
+		(TypeSpec::Constructor(TypeConstructor::Function { .. }), t) if is_callable(&t) => t,
+		(TypeSpec::Constructor(constructor), t) => {
+			return Err(EvalError::WrongType {
+				loc,
+				expected: constructor_name(constructor).to_string(),
+				found: t.type_name(),
+			});
+		},
+	};
+
+	Ok(ReamValue { span, t })
+}
+
+/// Check that a value has the type with the given name, converting it if
+/// it's a number of another numeric type
+///
+/// Every number converts to a float, but only whole floats convert to
+/// integers. Integers are rationals already
+fn coerce<'s>(name: &str, t: ReamType<'s>, loc: Span) -> Result<ReamType<'s>, EvalError> {
+	let coerced = match (name, t) {
+		("Float", ReamType::Integer(i)) => ReamType::Float(i as f64),
+		#[cfg(feature = "bigint")]
+		("Float", ReamType::BigInt(i)) => {
+			ReamType::Float(num_traits::ToPrimitive::to_f64(i.as_ref()).unwrap_or(f64::NAN))
+		},
+		("Float", ReamType::Rational(r)) => ReamType::Float(*r.numer() as f64 / *r.denom() as f64),
+		// Anything outside of this range doesn't fit in an `i64`
+		("Integer", ReamType::Float(f))
+			if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 =>
+		{
+			ReamType::Integer(f as i64)
+		},
+		("Rational", t @ ReamType::Integer(_)) => t,
+		(name, t) if t.type_name() == name => t,
+		(name, t) => {
+			return Err(EvalError::WrongType {
+				loc,
+				expected: name.to_string(),
+				found: t.type_name(),
+			});
+		},
+	};
+
+	Ok(coerced)
+}
+
+/// Check if a value can be called like a function
+fn is_callable(t: &ReamType<'_>) -> bool {
+	matches!(
+		t,
+		ReamType::Primitive(_)
+			| ReamType::Host(_)
+			| ReamType::ProductConstructor { .. }
+			| ReamType::VariantConstructor { .. }
+			| ReamType::ProtocolMethod { .. }
+			| ReamType::Function { .. }
+			| ReamType::Closure { .. }
+			| ReamType::Derived(_)
+	)
+}
+
+/// Get the name of the type a type constructor builds
+fn constructor_name(constructor: &TypeConstructor<'_>) -> &'static str {
+	match constructor {
+		TypeConstructor::Bottom { .. } => "Bottom",
+		TypeConstructor::Tuple { .. } => "Tuple",
+		TypeConstructor::List { .. } => "List",
+		TypeConstructor::Vector { .. } => "Vector",
+		TypeConstructor::Function { .. } => "Function",
+		TypeConstructor::Sum { .. } => "Sum",
+		TypeConstructor::Product { .. } => "Product",
+	}
+}
@@ -0,0 +1,170 @@
+//! Capability sets used to sandbox primitives
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A group of primitives that can be made available to a program as a whole
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+	/// Pure computation, arithmetic, comparisons, data manipulation, ...
+	Pure,
+	/// Terminal input and output
+	Io,
+	/// Filesystem access
+	Fs,
+	/// Network access
+	Net,
+	/// Clocks and timers
+	Time,
+	/// Environment variables, the working directory and information about
+	/// the host process
+	Env,
+	/// Spawning subprocesses
+	Process,
+}
+
+impl Capability {
+	/// Every existing [`Capability`]
+	pub const ALL: [Self; 7] =
+		[Self::Pure, Self::Io, Self::Fs, Self::Net, Self::Time, Self::Env, Self::Process];
+
+	/// The bit representing this [`Capability`] in a [`Capabilities`] set
+	const fn bit(self) -> u8 {
+		match self {
+			Self::Pure => 1 << 0,
+			Self::Io => 1 << 1,
+			Self::Fs => 1 << 2,
+			Self::Net => 1 << 3,
+			Self::Time => 1 << 4,
+			Self::Env => 1 << 5,
+			Self::Process => 1 << 6,
+		}
+	}
+}
+
+impl fmt::Display for Capability {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Pure => write!(f, "pure"),
+			Self::Io => write!(f, "io"),
+			Self::Fs => write!(f, "fs"),
+			Self::Net => write!(f, "net"),
+			Self::Time => write!(f, "time"),
+			Self::Env => write!(f, "env"),
+			Self::Process => write!(f, "process"),
+		}
+	}
+}
+
+impl FromStr for Capability {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"pure" => Ok(Self::Pure),
+			"io" => Ok(Self::Io),
+			"fs" => Ok(Self::Fs),
+			"net" => Ok(Self::Net),
+			"time" => Ok(Self::Time),
+			"env" => Ok(Self::Env),
+			"process" => Ok(Self::Process),
+			_ => {
+				Err(format!(
+					"unknown capability `{s}`, expected one of `pure`, `io`, `fs`, `net`, `time`, \
+					 `env`, `process`"
+				))
+			},
+		}
+	}
+}
+
+/// A set of [`Capability`]s granted to a program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities(u8);
+
+impl Capabilities {
+	/// Every capability, the default for trusted programs
+	pub const ALL: Self = Self(0b1111111);
+	/// No capabilities at all
+	pub const NONE: Self = Self(0);
+	/// Only pure computation, suitable for untrusted programs
+	pub const PURE: Self = Self(0b0000001);
+
+	/// Add a [`Capability`] to the set
+	pub const fn with(self, capability: Capability) -> Self { Self(self.0 | capability.bit()) }
+
+	/// Remove a [`Capability`] from the set
+	pub const fn without(self, capability: Capability) -> Self { Self(self.0 & !capability.bit()) }
+
+	/// Check if the set contains a [`Capability`]
+	pub const fn contains(&self, capability: Capability) -> bool { self.0 & capability.bit() != 0 }
+}
+
+impl Default for Capabilities {
+	fn default() -> Self { Self::ALL }
+}
+
+impl FromIterator<Capability> for Capabilities {
+	fn from_iter<T: IntoIterator<Item = Capability>>(iter: T) -> Self {
+		iter.into_iter().fold(Self::NONE, Self::with)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parse_str, EvalError, Interpreter};
+
+	#[test]
+	fn capabilities_display_as_they_are_parsed() {
+		for capability in Capability::ALL {
+			assert_eq!(capability.to_string().parse(), Ok(capability));
+		}
+	}
+
+	#[test]
+	fn unknown_capabilities_are_rejected() {
+		let err = "disk".parse::<Capability>().unwrap_err();
+
+		assert!(err.contains("unknown capability `disk`"));
+		assert!("Pure".parse::<Capability>().is_err());
+	}
+
+	#[test]
+	fn sets_contain_what_they_were_built_from() {
+		let set: Capabilities = [Capability::Io, Capability::Net].into_iter().collect();
+
+		assert!(set.contains(Capability::Io));
+		assert!(set.contains(Capability::Net));
+		assert!(!set.contains(Capability::Pure));
+		assert_eq!(set, Capabilities::NONE.with(Capability::Net).with(Capability::Io));
+	}
+
+	#[test]
+	fn sets_add_and_remove_capabilities() {
+		assert_eq!(Capabilities::NONE.with(Capability::Pure), Capabilities::PURE);
+		assert_eq!(Capabilities::PURE.without(Capability::Pure), Capabilities::NONE);
+		assert_eq!(Capabilities::PURE.without(Capability::Io), Capabilities::PURE);
+
+		let all = Capability::ALL.into_iter().collect::<Capabilities>();
+		assert_eq!(all, Capabilities::ALL);
+		assert!(Capability::ALL.into_iter().all(|c| !Capabilities::NONE.contains(c)));
+	}
+
+	#[test]
+	fn denied_calls_are_reported_at_the_call() {
+		let source = "(fn home () (getenv \"HOME\"))\n(home)";
+		let program = parse_str(source).unwrap();
+
+		let Err(EvalError::CapabilityDenied { loc, callee, capability }) =
+			Interpreter::new(Capabilities::PURE).run(program)
+		else {
+			panic!("expected `getenv` to be denied");
+		};
+
+		assert_eq!(&source[loc.range()], "(getenv \"HOME\")");
+		assert_eq!(callee, "getenv");
+		assert_eq!(capability, "env");
+	}
+}
@@ -0,0 +1,151 @@
+//! Functions defined by several clauses, each handling the arguments that
+//! match its patterns
+//!
+//! A function like this is an ordinary function whose body matches its
+//! arguments, as a vector, against the patterns of every clause
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{ReamType, ReamValue, Scope};
+use crate::ast::{Expression, Identifier, MatchClause, Pattern};
+use crate::{EvalError, Span};
+
+/// Define a clause of a function in the given scope
+///
+/// The clause is added to the function of the same name if that was
+/// defined by clauses with as many patterns in the same scope, otherwise it
+/// starts a new function replacing whatever the name was bound to
+pub(super) fn define_clause<'s>(
+	span: Span,
+	target: Identifier<'s>,
+	patterns: Vec<Pattern<'s>>,
+	body: Vec<Expression<'s>>,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	let clause = MatchClause { span, pattern: Pattern::List { span, patterns }, guard: None, body };
+
+	let existing = scope.borrow().symbols.get(target.id).cloned();
+	let function = match existing.and_then(|f| clauses(f.t)) {
+		Some((formals, mut body)) if formals.len() == arity(&clause) => {
+			let Expression::Match { clauses, .. } = &mut body else { unreachable!() };
+			clauses.push(clause);
+
+			function(target.id, formals, body, span)
+		},
+		_ => {
+			let context = scope.borrow().context.clone();
+			let names = (0..arity(&clause))
+				.map(|n| Identifier { span, id: context.argument_name(n) })
+				.collect::<Vec<_>>();
+
+			let formals = names.iter().copied().map(Pattern::Identifier).collect();
+			let value = Expression::Vector {
+				span,
+				elements: names.into_iter().map(Expression::Identifier).collect(),
+			};
+			let body = Expression::Match { span, value: Box::new(value), clauses: vec![clause] };
+
+			function(target.id, formals, body, span)
+		},
+	};
+
+	scope.borrow_mut().define(target, function)
+}
+
+/// Get the formals and the body of a function defined by clauses, or
+/// [`None`] if the value is anything else
+fn clauses<'s>(t: ReamType<'s>) -> Option<(Vec<Pattern<'s>>, Expression<'s>)> {
+	let ReamType::Function { formals, body, .. } = t else { return None };
+
+	match &*body {
+		[body @ Expression::Match { .. }] if generated(&formals) => {
+			Some((formals.to_vec(), body.clone()))
+		},
+		_ => None,
+	}
+}
+
+/// Check if the formals of a function were generated for a function defined
+/// by clauses
+pub(super) fn generated(formals: &[Pattern<'_>]) -> bool {
+	formals.iter().all(|f| matches!(f, Pattern::Identifier(id) if id.id.starts_with('#')))
+}
+
+/// Report a call to a function defined by clauses whose arguments no clause
+/// matches at the call, instead of at the definition of the function
+///
+/// Only the match generated for the function itself is reported this way,
+/// any other error is returned unchanged
+pub(super) fn at_call(
+	err: EvalError,
+	name: &str,
+	body: &[Expression<'_>],
+	args: &[ReamValue<'_>],
+	loc: Span,
+) -> EvalError {
+	match (err, body) {
+		(EvalError::NoMatch { loc: at, .. }, [body]) if at == body.span() => {
+			EvalError::NoMatchingClause {
+				loc,
+				name: name.to_string(),
+				arguments: args.iter().map(|a| a.t.to_string()).collect::<Vec<_>>().join(" "),
+			}
+		},
+		(err, _) => err,
+	}
+}
+
+/// Get the amount of arguments a clause takes
+fn arity(clause: &MatchClause<'_>) -> usize {
+	let Pattern::List { patterns, .. } = &clause.pattern else { unreachable!() };
+
+	patterns.len()
+}
+
+/// Build a function from its formals and its body
+fn function<'s>(
+	name: &'s str,
+	formals: Vec<Pattern<'s>>,
+	body: Expression<'s>,
+	span: Span,
+) -> ReamValue<'s> {
+	ReamValue {
+		span,
+		t: ReamType::Function { name, formals: formals.into(), body: Rc::from([body]) },
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, EvalError, Interpreter};
+
+	#[test]
+	fn calls_no_clause_matches_are_reported_at_the_call() {
+		let source = "(fn (f 0) 0)\n(fn (g x) (f x))\n(g 1)";
+		let program = parse_str(source).unwrap();
+
+		let Err(EvalError::NoMatchingClause { loc, name, arguments }) =
+			Interpreter::new(Capabilities::PURE).run(program)
+		else {
+			panic!("expected the call to `f` to fail");
+		};
+
+		assert_eq!(&source[loc.range()], "(f x)");
+		assert_eq!(name, "f");
+		assert_eq!(arguments, "1");
+	}
+
+	#[test]
+	fn other_failed_matches_are_reported_where_they_happen() {
+		let source = "(fn (f 0) (match 1 (2 2)))\n(f 0)";
+		let program = parse_str(source).unwrap();
+
+		let Err(EvalError::NoMatch { loc, .. }) = Interpreter::new(Capabilities::PURE).run(program)
+		else {
+			panic!("expected the match in `f` to fail");
+		};
+
+		assert_eq!(&source[loc.range()], "(match 1 (2 2))");
+	}
+}
@@ -0,0 +1,231 @@
+//! Interpreter-wide evaluation state
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::limits::ValueLimits;
+use super::{Capabilities, FileSystemProvider, ReamValue, SourceProvider};
+use crate::ast::Program;
+use crate::{EvalError, Expander, FileId, SourceMap, Span};
+
+/// State shared between every scope of a single [`Interpreter`](super::Interpreter)
+#[derive(Debug, Default)]
+pub(super) struct Context<'s> {
+	/// The capabilities granted to the running program
+	pub(super) capabilities: Capabilities,
+	/// The path of the script being run, if it was loaded from a file
+	pub(super) script_path:  RefCell<Option<PathBuf>>,
+	/// The command line arguments passed to the running program
+	pub(super) args:         RefCell<Vec<String>>,
+
+	/// Resolves and reads included files
+	pub(super) source_provider: RefCell<Provider>,
+	/// The resolved names of the files currently being included, innermost
+	/// last
+	pub(super) including:       RefCell<Vec<String>>,
+	/// The source of every file included so far and its id in the source
+	/// map, keyed by resolved name
+	pub(super) sources:         RefCell<HashMap<String, (&'s str, FileId)>>,
+	/// Files parsed ahead of their inclusion, keyed by resolved name
+	pub(super) parsed:          RefCell<HashMap<String, Result<Program<'s>, miette::Report>>>,
+	/// Every source file loaded so far, so errors can be displayed with the
+	/// file they happened in
+	pub(super) source_map:      RefCell<SourceMap>,
+
+	/// The moment the running program has to be finished by, and the
+	/// timeout it was derived from
+	pub(super) deadline:     Cell<Option<(Instant, Duration)>>,
+	/// The maximum amount of calls made and values created by a single run
+	pub(super) value_quota:  Cell<Option<usize>>,
+	/// The amount of calls made and values created by the current run so far
+	pub(super) values:       Cell<usize>,
+	/// The largest values the running program may print, compare or return
+	pub(super) value_limits: Cell<ValueLimits>,
+
+	/// Where the output of the running program goes
+	pub(super) output:   RefCell<Output>,
+	/// The functions used to display values, keyed by the name of the type
+	/// they display
+	pub(super) printers: RefCell<HashMap<String, ReamValue<'s>>>,
+	/// The names of the types whose printer is currently running, innermost
+	/// last
+	pub(super) printing: RefCell<Vec<String>>,
+
+	/// Expands macro calls before they are evaluated, remembering the macros
+	/// defined so far
+	pub(super) expander: RefCell<Expander<'s>>,
+
+	/// The names of the methods of every defined protocol, keyed by the name
+	/// of the protocol
+	pub(super) protocols:       RefCell<HashMap<&'s str, Vec<&'s str>>>,
+	/// The functions implementing the methods of a protocol for a type, keyed
+	/// by the name of the protocol and the name of the type
+	pub(super) implementations: RefCell<HashMap<(&'s str, String), Vec<ReamValue<'s>>>>,
+
+	/// The names given to the arguments of functions defined by clauses,
+	/// the name of the nth argument at index n
+	pub(super) argument_names: RefCell<Vec<&'s str>>,
+	/// The names of atoms, identifiers and types that came from the host
+	/// instead of the source code
+	pub(super) interned:       RefCell<HashSet<&'s str>>,
+}
+
+/// The sink the output of a program is written to, stdout by default
+pub(super) struct Output(pub(super) Box<dyn Write>);
+
+impl Default for Output {
+	fn default() -> Self { Self(Box::new(io::stdout())) }
+}
+
+impl fmt::Debug for Output {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("Output") }
+}
+
+/// The provider included files are loaded through, the filesystem by
+/// default
+#[derive(Clone)]
+pub(super) struct Provider(pub(super) Rc<dyn SourceProvider>);
+
+impl Default for Provider {
+	fn default() -> Self { Self(Rc::new(FileSystemProvider::default())) }
+}
+
+impl fmt::Debug for Provider {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("Provider") }
+}
+
+impl<'s> Context<'s> {
+	/// Make sure the deadline of the running program hasn't passed yet
+	///
+	/// Should only be called at points where evaluation can safely be
+	/// aborted
+	pub(super) fn check_deadline(&self, loc: Span) -> Result<(), EvalError> {
+		match self.deadline.get() {
+			Some((deadline, timeout)) if Instant::now() > deadline => {
+				Err(EvalError::Timeout { loc, timeout: format!("{timeout:?}") })
+			},
+			_ => Ok(()),
+		}
+	}
+
+	/// Account for `count` calls or new values, making sure the current run
+	/// stays within its value quota
+	pub(super) fn allocate(&self, count: usize, loc: Span) -> Result<(), EvalError> {
+		let values = self.values.get() + count;
+		self.values.set(values);
+
+		match self.value_quota.get() {
+			Some(quota) if values > quota => Err(EvalError::QuotaExceeded { loc, quota }),
+			_ => Ok(()),
+		}
+	}
+
+	/// Get the name of the nth argument of a function defined by clauses
+	pub(super) fn argument_name(&self, n: usize) -> &'s str {
+		let mut names = self.argument_names.borrow_mut();
+
+		while names.len() <= n {
+			// Argument names become part of the functions they're used in and
+			// have to live as long as they do, the `#` makes sure they can't
+			// clash with a name from the source
+			let name = Box::leak(format!("#{}", names.len()).into_boxed_str());
+			names.push(name);
+		}
+
+		names[n]
+	}
+
+	/// Get a name that came from the host as one that lives as long as the
+	/// source code, like the names used in it
+	pub(super) fn intern(&self, name: &str) -> &'s str {
+		let mut interned = self.interned.borrow_mut();
+
+		// Names have to live as long as the values they're part of, leaking
+		// each of them once keeps that from growing with every use
+		if let Some(name) = interned.get(name) {
+			return name;
+		}
+
+		let name = Box::leak(name.into());
+		interned.insert(name);
+
+		name
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parse_str, Capabilities, Interpreter, Value};
+
+	/// A program making three procedure calls
+	const CALLS: &str = "(+ (+ 1 2) (+ 3 4))";
+
+	#[test]
+	fn passed_deadlines_abort() {
+		let context = Context::default();
+		assert!(context.check_deadline(Span::default()).is_ok());
+
+		context.deadline.set(Some((Instant::now() + Duration::from_secs(60), Duration::ZERO)));
+		assert!(context.check_deadline(Span::default()).is_ok());
+
+		context.deadline.set(Some((Instant::now() - Duration::from_secs(1), Duration::ZERO)));
+		assert!(matches!(
+			context.check_deadline(Span::default()),
+			Err(EvalError::Timeout { .. })
+		));
+	}
+
+	#[test]
+	fn timeouts_abort_runs() {
+		let mut interpreter = Interpreter::new(Capabilities::PURE);
+
+		let err = interpreter.eval_with_timeout(parse_str(CALLS).unwrap(), Duration::ZERO);
+		assert!(matches!(err, Err(EvalError::Timeout { .. })));
+
+		// The deadline only applies to the run it was given for
+		assert_eq!(interpreter.run(parse_str(CALLS).unwrap()).unwrap(), Value::Integer(10));
+	}
+
+	#[test]
+	fn allocations_beyond_the_quota_fail() {
+		let context = Context::default();
+		context.value_quota.set(Some(3));
+
+		assert!(context.allocate(2, Span::default()).is_ok());
+		assert!(context.allocate(1, Span::default()).is_ok());
+		assert!(matches!(
+			context.allocate(1, Span::default()),
+			Err(EvalError::QuotaExceeded { quota: 3, .. })
+		));
+	}
+
+	#[test]
+	fn quotas_limit_each_run() {
+		let mut interpreter = Interpreter::new(Capabilities::PURE).with_value_quota(3);
+
+		for _ in 0..10 {
+			assert_eq!(interpreter.run(parse_str(CALLS).unwrap()).unwrap(), Value::Integer(10));
+		}
+
+		let err = interpreter.run(parse_str("(list 1 2 3)").unwrap()).unwrap_err();
+		assert!(matches!(err, EvalError::QuotaExceeded { quota: 3, .. }));
+	}
+
+	#[test]
+	fn quotas_limit_each_evaluated_expression() {
+		let source = CALLS.repeat(10);
+		let program = parse_str(&source).unwrap();
+		let mut interpreter = Interpreter::new(Capabilities::PURE).with_value_quota(3);
+
+		assert!(interpreter.run(program.clone()).is_err());
+		for expr in program.0 {
+			assert_eq!(interpreter.eval(expr).unwrap(), Value::Integer(10));
+		}
+	}
+}
@@ -1,9 +1,26 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Instant;
 
-use super::{Eval, ReamType, ReamValue, Scope};
-use crate::ast::{Datum, Expression, Identifier, Literal};
-use crate::EvalError;
+use im_rc::Vector;
+
+use super::adt::define_type;
+use super::assertion::assert_type;
+use super::clauses::define_clause;
+use super::include::include;
+use super::protocol::{define_protocol, implement_protocol};
+use super::{Enclosed, Eval, ReamType, ReamValue, Scope};
+use crate::ast::{
+	CaseClause,
+	Datum,
+	Expression,
+	Identifier,
+	LetBinding,
+	Literal,
+	MatchClause,
+	Pattern,
+};
+use crate::{EvalError, Span};
 
 impl<'s, 'r> Eval<'s, 'r> for Expression<'s> {
 	fn eval(self, scope: Rc<RefCell<Scope<'s>>>) -> Result<ReamValue<'s>, EvalError> {
@@ -16,21 +33,65 @@ impl<'s, 'r> Eval<'s, 'r> for Expression<'s> {
 			},
 			Self::Literal(lit) => lit.eval(scope),
 			Self::VariableDefinition { span, target, value } => {
-				let value = value.eval(scope.clone())?;
-				scope.borrow_mut().set(target.id, value);
+				let mut value = value.eval(scope.clone())?;
+
+				// Closures take the name of the first variable they're bound to
+				if let (Pattern::Identifier(id), ReamType::Closure { name: name @ None, .. }) =
+					(&target, &mut value.t)
+				{
+					*name = Some(id.id);
+				}
+
+				target.bind(value, &scope)?;
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			Self::ConstantDefinition { span, target, value } => {
+				define_constant(target, *value, &scope)?;
 
 				Ok(ReamValue { span, t: ReamType::Unit })
 			},
 			Self::FunctionDefinition { span, target, formals, body } => {
-				let function_value = ReamValue { span, t: ReamType::Function { formals, body } };
-				scope.borrow_mut().set(target.id, function_value);
+				let function_value = ReamValue {
+					span,
+					t: ReamType::Function {
+						name:    target.id,
+						formals: formals.into(),
+						body:    body.into(),
+					},
+				};
+				scope.borrow_mut().define(target, function_value)?;
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			Self::FunctionClause { span, target, patterns, body } => {
+				define_clause(span, target, patterns, body, &scope)?;
 
 				Ok(ReamValue { span, t: ReamType::Unit })
 			},
 			Self::ClosureDefintion { span, formals, body } => {
-				let enclosed_scope = Scope::close(scope.to_owned());
+				// Closures share the scope they're defined in rather than copying
+				// it, so they can refer to definitions made after them in the same
+				// body, including the one they're bound to
+				Ok(ReamValue {
+					span,
+					t: ReamType::Closure {
+						name:           None,
+						formals:        formals.into(),
+						body:           body.into(),
+						enclosed_scope: Enclosed::Strong(scope),
+					},
+				})
+			},
+			Self::Vector { span, elements } => {
+				scope.borrow().context.allocate(elements.len(), span)?;
+
+				let elements = elements
+					.into_iter()
+					.map(|e| e.eval(scope.clone()))
+					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
 
-				Ok(ReamValue { span, t: ReamType::Closure { formals, body, enclosed_scope } })
+				Ok(ReamValue { span, t: ReamType::Vector(Rc::new(RefCell::new(elements))) })
 			},
 			Self::Sequence { span, seq } => {
 				let sequence_scope = Scope::extend(scope.to_owned());
@@ -44,8 +105,17 @@ impl<'s, 'r> Eval<'s, 'r> for Expression<'s> {
 
 				Ok(ReamValue { span, t: ret_value })
 			},
+			Self::Let { span, bindings, body } => {
+				Ok(ReamValue { span, t: eval_let(bindings, body, scope)? })
+			},
 			Self::ProcedureCall { span, operator, operands } => {
-				let operator = operator.eval(scope.clone())?;
+				let context = scope.borrow().context.clone();
+				context.check_deadline(span)?;
+				context.allocate(1, span)?;
+
+				// Report errors raised by the call at the call site rather than
+				// wherever the operator was defined
+				let operator = ReamValue { span, ..operator.eval(scope.clone())? };
 				let value = operator.apply(operands, scope)?;
 
 				Ok(ReamValue { span, t: value })
@@ -68,7 +138,54 @@ impl<'s, 'r> Eval<'s, 'r> for Expression<'s> {
 				}
 			},
 
-			_ => todo!(),
+			Self::Inclusion { span, files } => {
+				for file in files {
+					include(span, file, &scope)?;
+				}
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			Self::Match { span, value, clauses } => {
+				let value = value.eval(scope.clone())?;
+
+				Ok(ReamValue { span, t: eval_match(span, value, clauses, scope)? })
+			},
+			Self::Case { span, value, clauses, default } => {
+				let value = value.eval(scope.clone())?;
+
+				Ok(ReamValue { span, t: eval_case(value, clauses, default, scope)? })
+			},
+			Self::ProtocolDefinition { span, target, methods } => {
+				define_protocol(target, methods, &scope)?;
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			Self::ProtocolImplementation { span, protocol, target, methods } => {
+				implement_protocol(span, protocol, target, methods, &scope)?;
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			Self::TypeAlias { span, .. } => Ok(ReamValue { span, t: ReamType::Unit }),
+			Self::TypeAssertion { span, spec, value } => {
+				let value_span = value.span();
+				let value = value.eval(scope)?;
+
+				Ok(ReamValue { span, t: assert_type(&spec, value, value_span)?.t })
+			},
+			Self::Time { span, value } => {
+				Ok(ReamValue { span, t: eval_time(span, *value, scope)? })
+			},
+			// Macro calls have already been expanded, only the definition is left
+			Self::MacroDefinition { span, .. } => Ok(ReamValue { span, t: ReamType::Unit }),
+			Self::AlgebraicTypeDefintion { span, target, spec } => {
+				define_type(target, spec, &scope)?;
+
+				Ok(ReamValue { span, t: ReamType::Unit })
+			},
+			// Annotations are only there for readers and tooling
+			Self::Annotation(annotation) => {
+				Ok(ReamValue { span: annotation.span(), t: ReamType::Unit })
+			},
 		}
 	}
 }
@@ -83,33 +200,222 @@ impl<'s, 'r> Eval<'s, 'r> for Literal<'s> {
 			},
 			Self::Boolean { span, b } => Ok(ReamValue { span, t: ReamType::Boolean(b) }),
 			Self::Integer { span, i } => Ok(ReamValue { span, t: ReamType::Integer(i) }),
+			Self::Rational { span, r } => Ok(ReamValue { span, t: ReamType::Rational(r) }),
 			Self::Float { span, f } => Ok(ReamValue { span, t: ReamType::Float(f) }),
 			Self::Character { span, c } => Ok(ReamValue { span, t: ReamType::Character(c) }),
-			Self::String { span, s } => Ok(ReamValue { span, t: ReamType::String(s) }),
+			Self::String { span, s } => Ok(ReamValue { span, t: ReamType::String(s.into()) }),
 			Self::Atom { span, a } => Ok(ReamValue { span, t: ReamType::Atom(a) }),
 		}
 	}
 }
 
 impl<'s, 'r> Eval<'s, 'r> for Datum<'s> {
-	fn eval(self, _scope: Rc<RefCell<Scope<'s>>>) -> Result<ReamValue<'s>, EvalError> {
+	fn eval(self, scope: Rc<RefCell<Scope<'s>>>) -> Result<ReamValue<'s>, EvalError> {
 		match self {
 			Self::Identifier { span, id } => Ok(ReamValue { span, t: ReamType::Identifier(id) }),
 			Self::Boolean { span, b } => Ok(ReamValue { span, t: ReamType::Boolean(b) }),
 			Self::Integer { span, i } => Ok(ReamValue { span, t: ReamType::Integer(i) }),
+			Self::Rational { span, r } => Ok(ReamValue { span, t: ReamType::Rational(r) }),
 			Self::Float { span, f } => Ok(ReamValue { span, t: ReamType::Float(f) }),
 			Self::Character { span, c } => Ok(ReamValue { span, t: ReamType::Character(c) }),
-			Self::String { span, s } => Ok(ReamValue { span, t: ReamType::String(s) }),
+			Self::String { span, s } => Ok(ReamValue { span, t: ReamType::String(s.into()) }),
 			Self::Atom { span, a } => Ok(ReamValue { span, t: ReamType::Atom(a) }),
 			Self::List { span, l } => {
-				let datum_vec = Vec::<Datum<'s>>::from(l.to_owned());
-				let rvalue_vec = datum_vec
-					.into_iter()
-					.map(|d| d.eval(_scope.clone()))
-					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+				let elements = eval_data(Vec::from(l), span, scope)?;
+
+				Ok(ReamValue { span, t: ReamType::List(elements) })
+			},
+			Self::Vector { span, data } => {
+				// Always a new vector, so mutating it can't change the quotation
+				let elements = eval_data(data, span, scope)?.into_iter().collect();
+
+				Ok(ReamValue { span, t: ReamType::Vector(Rc::new(RefCell::new(elements))) })
+			},
+			Self::Unquote { span, e } => Ok(ReamValue { span, t: e.eval(scope)?.t }),
+			// The parser only allows splicing into a surrounding list or vector,
+			// which handles it above
+			Self::UnquoteSplicing { .. } => unreachable!(),
+		}
+	}
+}
+
+/// Evaluate the body of the first clause of a match expression whose
+/// pattern matches the value and whose guard, if any, is truthy
+///
+/// Guards are evaluated in the scope the pattern bound its names in, which
+/// the body is evaluated in too if the guard passes
+fn eval_match<'s>(
+	span: Span,
+	value: ReamValue<'s>,
+	clauses: Vec<MatchClause<'s>>,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<ReamType<'s>, EvalError> {
+	for clause in clauses {
+		if !clause.pattern.matches(&value.t) {
+			continue;
+		}
+
+		let clause_scope = Scope::extend(scope.clone());
+		clause.pattern.bind(value.clone(), &clause_scope)?;
+
+		if let Some(guard) = clause.guard {
+			if !guard.eval(clause_scope.clone())?.t.is_truthy() {
+				continue;
+			}
+		}
+
+		let values = clause
+			.body
+			.into_iter()
+			.map(|e| e.eval(clause_scope.clone()))
+			.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+		return Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit));
+	}
+
+	Err(EvalError::NoMatch { loc: span, value: value.t.to_string() })
+}
+
+/// Evaluate the body of the first clause of a case expression listing a
+/// datum equal to the value, or the default body if there is no such
+/// clause
+///
+/// Returns unit if no clause matches and there is no default body
+fn eval_case<'s>(
+	value: ReamValue<'s>,
+	clauses: Vec<CaseClause<'s>>,
+	default: Option<Vec<Expression<'s>>>,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<ReamType<'s>, EvalError> {
+	let mut body = default;
+	for clause in clauses {
+		let mut matches = false;
+		for datum in clause.data {
+			if datum.eval(scope.clone())?.t.equals(&value.t) {
+				matches = true;
+				break;
+			}
+		}
+
+		if matches {
+			body = Some(clause.body);
+			break;
+		}
+	}
+
+	let Some(body) = body else { return Ok(ReamType::Unit) };
 
-				Ok(ReamValue { span, t: ReamType::List(rvalue_vec) })
+	let clause_scope = Scope::extend(scope);
+	let values = body
+		.into_iter()
+		.map(|e| e.eval(clause_scope.clone()))
+		.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+	Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit))
+}
+
+/// Evaluate a timed expression, returning a tuple of its value, the time
+/// evaluating it took in seconds and the amount of values it created
+///
+/// The time is wall clock time, so it includes anything else the host was
+/// doing in the meantime
+fn eval_time<'s>(
+	span: Span,
+	value: Expression<'s>,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<ReamType<'s>, EvalError> {
+	let context = scope.borrow().context.clone();
+
+	let values = context.values.get();
+	let start = Instant::now();
+	let value = value.eval(scope)?;
+	let elapsed = start.elapsed();
+	let created = context.values.get() - values;
+
+	Ok(ReamType::Tuple(vec![
+		value,
+		ReamValue { span, t: ReamType::Float(elapsed.as_secs_f64()) },
+		ReamValue { span, t: ReamType::Integer(created as i64) },
+	]))
+}
+
+/// Evaluate a let expression, binding the values of its bindings in a
+/// scope of their own and evaluating its body in that scope
+///
+/// Every value is evaluated in the enclosing scope before any of them are
+/// bound, so bindings can't refer to each other
+fn eval_let<'s>(
+	bindings: Vec<LetBinding<'s>>,
+	body: Vec<Expression<'s>>,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<ReamType<'s>, EvalError> {
+	let (targets, values): (Vec<_>, Vec<_>) =
+		bindings.into_iter().map(|b| (b.target, b.value)).unzip();
+
+	let values = values
+		.into_iter()
+		.map(|e| e.eval(scope.clone()))
+		.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+	let let_scope = Scope::extend(scope);
+	for (target, value) in targets.into_iter().zip(values) {
+		target.bind(value, &let_scope)?;
+	}
+
+	let values = body
+		.into_iter()
+		.map(|e| e.eval(let_scope.clone()))
+		.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+	Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit))
+}
+
+/// Evaluate the value of a constant definition and bind it in the given
+/// scope
+fn define_constant<'s>(
+	target: Identifier<'s>,
+	value: Expression<'s>,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	let mut value = value.eval(scope.clone())?;
+
+	// Closures take the name of the constant they're bound to, like they
+	// do for variables
+	if let ReamType::Closure { name: name @ None, .. } = &mut value.t {
+		*name = Some(target.id);
+	}
+
+	scope.borrow_mut().define_constant(target, value)
+}
+
+/// Evaluate the elements of a quoted list or vector, splicing in the
+/// elements of the lists unquoted with `,@`
+fn eval_data<'s>(
+	data: Vec<Datum<'s>>,
+	span: Span,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<Vector<ReamValue<'s>>, EvalError> {
+	scope.borrow().context.allocate(data.len(), span)?;
+
+	let mut elements = Vector::new();
+	for datum in data {
+		let Datum::UnquoteSplicing { e, .. } = datum else {
+			elements.push_back(datum.eval(scope.clone())?);
+			continue;
+		};
+
+		let e_span = e.span();
+		match e.eval(scope.clone())?.t {
+			ReamType::List(spliced) => elements.append(spliced),
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      e_span,
+					expected: "List".to_string(),
+					found:    t.type_name(),
+				});
 			},
 		}
 	}
+
+	Ok(elements)
 }
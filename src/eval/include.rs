@@ -0,0 +1,338 @@
+//! Loading and evaluating included files
+
+use std::cell::RefCell;
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+use std::{panic, thread};
+
+use super::context::{Context, Provider};
+use super::{Eval, Scope, SourceProvider};
+use crate::ast::{Expression, Program};
+use crate::{parse_file, Error, EvalError, FileId, IncludedError, Span};
+
+/// Evaluate every expression of an included file in the given scope
+///
+/// The file is resolved and read by the [`SourceProvider`](super::SourceProvider)
+/// of the interpreter, relative to the file including it or to the script
+/// being run
+pub(super) fn include<'s>(
+	span: Span,
+	file: &'s str,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	let context = scope.borrow().context.clone();
+	let Provider(provider) = context.source_provider.borrow().clone();
+
+	if let Some(capability) = provider.capability() {
+		if !context.capabilities.contains(capability) {
+			return Err(EvalError::CapabilityDenied {
+				loc:        span,
+				callee:     "include".to_string(),
+				capability: capability.to_string(),
+			});
+		}
+	}
+
+	// The script being run counts as being included, so files are resolved
+	// relative to it and a file including the script is caught right away
+	let script = script_name(&context, provider.as_ref());
+	let ancestors =
+		script.into_iter().chain(context.including.borrow().iter().cloned()).collect::<Vec<_>>();
+
+	let resolved =
+		provider.resolve(file, ancestors.last().map(String::as_str)).map_err(|searched| {
+			EvalError::IncludeNotFound {
+				loc:      span,
+				file:     file.to_string(),
+				searched: searched.iter().map(|s| format!("`{s}`")).collect::<Vec<_>>().join(", "),
+			}
+		})?;
+
+	if ancestors.contains(&resolved) {
+		let chain = ancestors
+			.iter()
+			.chain([&resolved])
+			.map(|name| format!("`{name}`"))
+			.collect::<Vec<_>>()
+			.join(" -> ");
+
+		return Err(EvalError::IncludeCycle { loc: span, file: file.to_string(), chain });
+	}
+
+	// The spans of errors in the file point into the source map, so they
+	// are displayed with the source of the file itself
+	let failed = |error: miette::Report| {
+		EvalError::IncludeFailed {
+			loc:   span,
+			file:  file.to_string(),
+			cause: vec![IncludedError::new(error)],
+		}
+	};
+
+	let prefetched = context.parsed.borrow_mut().remove(&resolved);
+	let program = match prefetched {
+		Some(program) => program.map_err(failed)?,
+		None => {
+			let cached = context.sources.borrow().get(&resolved).copied();
+			let (source, id) = match cached {
+				Some(cached) => cached,
+				None => {
+					let source =
+						provider.read(&resolved).map_err(|e| failed(Error::from(e).into()))?;
+
+					load(&context, &resolved, source)
+				},
+			};
+
+			let program = parse_file(source, id).map_err(failed)?;
+			prefetch(&program.0, Some(resolved.clone()), &context);
+
+			program
+		},
+	};
+
+	context.including.borrow_mut().push(resolved.clone());
+	let result = program.0.into_iter().try_for_each(|expr| {
+		let expr = context.expander.borrow_mut().expand(expr)?;
+
+		expr.eval(scope.clone()).map(|_| ())
+	});
+	context.including.borrow_mut().pop();
+
+	result.map_err(|e| failed(e.into()))
+}
+
+/// Parse the files included by the top level of a program ahead of time,
+/// along with the files included by the top level of those in turn
+///
+/// Files are read one after the other, but parsed in parallel as parsing
+/// doesn't touch the interpreter. The parsed programs are kept until their
+/// inclusions are evaluated, so errors in them are still reported in the
+/// order the program runs into them. Files that can't be found or read are
+/// left for [`include`] to report
+pub(super) fn prefetch<'s>(
+	program: &[Expression<'s>],
+	includer: Option<String>,
+	context: &Context<'s>,
+) {
+	let Provider(provider) = context.source_provider.borrow().clone();
+	if provider.capability().is_some_and(|capability| !context.capabilities.contains(capability)) {
+		return;
+	}
+
+	let includer = includer.or_else(|| script_name(context, provider.as_ref()));
+	let mut pending = vec![(includer, inclusions(program))];
+
+	while !pending.is_empty() {
+		let mut batch = vec![];
+		for (includer, files) in pending.drain(..) {
+			for file in files {
+				let Ok(resolved) = provider.resolve(file, includer.as_deref()) else {
+					continue;
+				};
+				if context.sources.borrow().contains_key(&resolved) {
+					continue;
+				}
+				let Ok(source) = provider.read(&resolved) else {
+					continue;
+				};
+
+				let (source, id) = load(context, &resolved, source);
+				batch.push((resolved, source, id));
+			}
+		}
+
+		let programs = parse_all(&batch);
+		for ((resolved, ..), program) in batch.into_iter().zip(programs) {
+			if let Ok(program) = &program {
+				pending.push((Some(resolved.clone()), inclusions(&program.0)));
+			}
+
+			context.parsed.borrow_mut().insert(resolved, program);
+		}
+	}
+}
+
+/// Parse the given sources, spreading them over as many threads as there
+/// are cores
+///
+/// Returns the results in the same order as the sources
+fn parse_all(
+	sources: &[(String, &'static str, FileId)],
+) -> Vec<Result<Program<'static>, miette::Report>> {
+	let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+	if sources.len() < 2 || threads == 1 {
+		return sources.iter().map(|&(_, source, id)| parse_file(source, id)).collect();
+	}
+
+	thread::scope(|scope| {
+		let handles = sources
+			.chunks(sources.len().div_ceil(threads))
+			.map(|chunk| {
+				scope.spawn(|| {
+					chunk.iter().map(|&(_, source, id)| parse_file(source, id)).collect::<Vec<_>>()
+				})
+			})
+			.collect::<Vec<_>>();
+
+		handles
+			.into_iter()
+			.flat_map(|handle| handle.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+			.collect()
+	})
+}
+
+/// Get the names of the files included by the top level of a program
+fn inclusions<'s>(program: &[Expression<'s>]) -> Vec<&'s str> {
+	program
+		.iter()
+		.filter_map(|expr| {
+			match expr {
+				Expression::Inclusion { files, .. } => Some(files.iter().copied()),
+				_ => None,
+			}
+		})
+		.flatten()
+		.collect()
+}
+
+/// Add the source of a file to the source map and the cache of included
+/// sources
+fn load(context: &Context<'_>, resolved: &str, source: String) -> (&'static str, FileId) {
+	// Values defined by the file borrow from its source and can outlive the
+	// inclusion, so the source has to live as long as the interpreter does
+	let source: &'static str = Box::leak(source.into_boxed_str());
+	let id = context.source_map.borrow_mut().add(resolved, source);
+	context.sources.borrow_mut().insert(resolved.to_string(), (source, id));
+
+	(source, id)
+}
+
+/// Get the resolved name of the script being run, if it was loaded from a
+/// file
+fn script_name(context: &Context<'_>, provider: &dyn SourceProvider) -> Option<String> {
+	context
+		.script_path
+		.borrow()
+		.as_ref()
+		.and_then(|path| provider.resolve(&path.display().to_string(), None).ok())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, process};
+
+	use super::*;
+	use crate::{parse_str, Capabilities, Capability, Interpreter, MemoryProvider, Value};
+
+	fn provider(b: &str) -> MemoryProvider {
+		MemoryProvider::new()
+			.with_file("a.rm", "(include \"c.rm\")\n(let a c)\n")
+			.with_file("b.rm", b)
+			.with_file("c.rm", "(let c 1)\n")
+	}
+
+	/// Run a program including files from memory
+	fn run(provider: MemoryProvider, source: &str) -> Result<Value, EvalError> {
+		let mut interpreter = Interpreter::new(Capabilities::PURE).with_source_provider(provider);
+
+		interpreter.run(parse_str(source).unwrap())
+	}
+
+	/// Get the error that made a chain of inclusions fail
+	fn innermost(err: EvalError) -> EvalError {
+		match err {
+			EvalError::IncludeFailed { cause, .. } => {
+				let cause = cause[0].0.downcast_ref::<EvalError>();
+
+				innermost(cause.expect("causes should be eval errors").clone())
+			},
+			err => err,
+		}
+	}
+
+	#[test]
+	fn included_files_are_parsed_ahead_of_time() {
+		let mut interpreter =
+			Interpreter::new(Capabilities::PURE).with_source_provider(provider("(let b 2)\n"));
+		let program = parse_str("(include \"a.rm\" \"b.rm\")\n(+ a b)\n").unwrap();
+
+		prefetch(&program.0, None, &interpreter.context);
+		assert_eq!(interpreter.context.parsed.borrow().len(), 3);
+
+		assert_eq!(interpreter.run(program).unwrap(), Value::Integer(3));
+		assert!(interpreter.context.parsed.borrow().is_empty());
+	}
+
+	#[test]
+	fn errors_in_prefetched_files_are_reported_when_included() {
+		let mut interpreter =
+			Interpreter::new(Capabilities::PURE).with_source_provider(provider("(let b (\n"));
+		let program = parse_str("(include \"a.rm\")\n(include \"b.rm\")\n").unwrap();
+
+		let err = interpreter.run(program).unwrap_err();
+		assert!(matches!(&err, EvalError::IncludeFailed { file, .. } if file == "b.rm"));
+		assert_eq!(err.span().map(|span| span.offset()), Some(17));
+	}
+
+	#[test]
+	fn include_cycles_are_reported_with_their_chain() {
+		let provider = MemoryProvider::new()
+			.with_file("a.rm", "(include \"lib/b.rm\")\n")
+			.with_file("lib/b.rm", "(include \"../a.rm\")\n");
+
+		let err = innermost(run(provider, "(include \"a.rm\")\n").unwrap_err());
+		let EvalError::IncludeCycle { file, chain, .. } = err else { panic!("{err:?}") };
+
+		assert_eq!(file, "../a.rm");
+		assert_eq!(chain, "`a.rm` -> `lib/b.rm` -> `a.rm`");
+	}
+
+	#[test]
+	fn scripts_including_themselves_are_cycles() {
+		let provider = MemoryProvider::new().with_file("main.rm", "(include \"main.rm\")\n");
+		let mut interpreter = Interpreter::new(Capabilities::PURE)
+			.with_source_provider(provider)
+			.with_script_path("main.rm");
+
+		let err = interpreter.run(parse_str("(include \"main.rm\")\n").unwrap()).unwrap_err();
+		let EvalError::IncludeCycle { chain, .. } = err else { panic!("{err:?}") };
+
+		assert_eq!(chain, "`main.rm` -> `main.rm`");
+	}
+
+	#[test]
+	fn missing_files_list_the_locations_searched() {
+		let provider = MemoryProvider::new().with_file("lib/a.rm", "(include \"c.rm\")\n");
+
+		let err = innermost(run(provider, "(include \"lib/a.rm\")\n").unwrap_err());
+		let EvalError::IncludeNotFound { file, searched, .. } = err else { panic!("{err:?}") };
+
+		assert_eq!(file, "c.rm");
+		assert_eq!(searched, "`lib/c.rm`");
+	}
+
+	#[test]
+	fn including_from_the_filesystem_requires_the_fs_capability() {
+		let err = Interpreter::new(Capabilities::PURE)
+			.with_include_paths(vec![])
+			.run(parse_str("(include \"lib.rm\")\n").unwrap())
+			.unwrap_err();
+
+		assert!(matches!(&err, EvalError::CapabilityDenied { callee, .. } if callee == "include"));
+	}
+
+	#[test]
+	fn include_paths_are_searched_after_the_includer() {
+		let dir = std::env::temp_dir().join(format!("ream-include-paths-{}", process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("lib.rm"), "(let answer 42)\n").unwrap();
+
+		let value = Interpreter::new(Capabilities::PURE.with(Capability::Fs))
+			.with_include_paths(vec![dir.clone()])
+			.run(parse_str("(include \"lib.rm\")\nanswer\n").unwrap());
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert_eq!(value.unwrap(), Value::Integer(42));
+	}
+}
@@ -0,0 +1,273 @@
+//! Total ordering and hashing of runtime values, so they can be sorted and
+//! used as keys
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use num_rational::Rational64;
+
+use super::{ReamType, ReamValue};
+use crate::EvalError;
+
+/// A value that can be totally ordered and hashed
+///
+/// Only data can be a key: functions, iterators and host resources like
+/// sockets can't, neither can anything containing them
+///
+/// Keys order numbers by their value regardless of their representation,
+/// integers before equal floats. Floats are compared like
+/// [`f64::total_cmp`], so `NaN` is equal to itself and `-0.0` is less than
+/// `0.0`. Values of different types are ordered by their type
+#[derive(Clone, Debug)]
+pub(super) struct Key<'s>(ReamValue<'s>);
+
+impl<'s> Key<'s> {
+	/// Turn a value into a key, failing if it can't be ordered and hashed
+	pub(super) fn new(value: ReamValue<'s>) -> Result<Self, EvalError> {
+		if let Some(found) = unhashable(&value.t) {
+			return Err(EvalError::Unhashable {
+				loc:       value.span,
+				type_name: found.type_name(),
+			});
+		}
+
+		Ok(Self(value))
+	}
+
+	/// Get the value this key was made from
+	pub(super) fn value(&self) -> &ReamValue<'s> { &self.0 }
+
+	/// Turn this key back into the value it was made from
+	pub(super) fn into_value(self) -> ReamValue<'s> { self.0 }
+}
+
+impl<'s> PartialEq for Key<'s> {
+	fn eq(&self, other: &Self) -> bool { self.cmp(other).is_eq() }
+}
+
+impl<'s> Eq for Key<'s> {}
+
+impl<'s> PartialOrd for Key<'s> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<'s> Ord for Key<'s> {
+	fn cmp(&self, other: &Self) -> Ordering { compare(&self.0.t, &other.0.t) }
+}
+
+impl<'s> Hash for Key<'s> {
+	fn hash<H: Hasher>(&self, state: &mut H) { hash(&self.0.t, state) }
+}
+
+/// Find the first value that can't be part of a key in a value, if any
+fn unhashable<'v, 's>(t: &'v ReamType<'s>) -> Option<&'v ReamType<'s>> {
+	match t {
+		ReamType::List(values) => values.iter().find_map(|v| unhashable(&v.t)),
+		ReamType::Tuple(values) => values.iter().find_map(|v| unhashable(&v.t)),
+		ReamType::Ok(v) | ReamType::Err(v) | ReamType::Some(v) => unhashable(&v.t),
+		ReamType::Product { fields, .. } => fields.iter().find_map(|(_, v)| unhashable(&v.t)),
+		ReamType::Variant { payload, .. } => payload.as_deref().and_then(|v| unhashable(&v.t)),
+		// The keys of a map are keys already
+		ReamType::Map(map) => map.values().find_map(|v| unhashable(&v.t)),
+		// So are the elements of a set
+		ReamType::Set(_) => None,
+		_ if rank(t).is_some() => None,
+		_ => Some(t),
+	}
+}
+
+/// The position of the type of a value in the order of keys, or [`None`]
+/// if values of its type can't be keys
+fn rank(t: &ReamType<'_>) -> Option<u8> {
+	let rank = match t {
+		ReamType::Unit => 0,
+		ReamType::Boolean(_) => 1,
+		ReamType::Integer(_) | ReamType::Rational(_) | ReamType::Float(_) => 2,
+		#[cfg(feature = "bigint")]
+		ReamType::BigInt(_) => 2,
+		ReamType::Character(_) => 3,
+		ReamType::String(_) => 4,
+		ReamType::Identifier(_) => 5,
+		ReamType::Atom(_) => 6,
+		#[cfg(feature = "time")]
+		ReamType::DateTime(_) => 7,
+		#[cfg(feature = "time")]
+		ReamType::Duration(_) => 8,
+		ReamType::List(_) => 9,
+		ReamType::Tuple(_) => 10,
+		ReamType::Range { .. } => 11,
+		ReamType::None => 12,
+		ReamType::Some(_) => 13,
+		ReamType::Ok(_) => 14,
+		ReamType::Err(_) => 15,
+		ReamType::Product { .. } => 16,
+		ReamType::Variant { .. } => 17,
+		ReamType::Map(_) => 18,
+		ReamType::Set(_) => 19,
+		_ => return None,
+	};
+
+	Some(rank)
+}
+
+/// Compare two values that can be keys
+fn compare<'s>(a: &ReamType<'s>, b: &ReamType<'s>) -> Ordering {
+	let by_rank = rank(a).cmp(&rank(b));
+	if by_rank.is_ne() {
+		return by_rank;
+	}
+
+	match (a, b) {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => a.cmp(b),
+		(ReamType::Character(a), ReamType::Character(b)) => a.cmp(b),
+		(ReamType::String(a), ReamType::String(b)) => a.cmp(b),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => a.cmp(b),
+		(ReamType::Atom(a), ReamType::Atom(b)) => a.cmp(b),
+		#[cfg(feature = "time")]
+		(ReamType::DateTime(a), ReamType::DateTime(b)) => a.cmp(b),
+		#[cfg(feature = "time")]
+		(ReamType::Duration(a), ReamType::Duration(b)) => a.cmp(b),
+		(ReamType::List(a), ReamType::List(b)) => {
+			compare_all(a.iter().map(|v| &v.t), b.iter().map(|v| &v.t))
+		},
+		(ReamType::Tuple(a), ReamType::Tuple(b)) => {
+			compare_all(a.iter().map(|v| &v.t), b.iter().map(|v| &v.t))
+		},
+		(
+			ReamType::Range { start: a_start, end: a_end, step: a_step },
+			ReamType::Range { start: b_start, end: b_end, step: b_step },
+		) => (a_start, a_end, a_step).cmp(&(b_start, b_end, b_step)),
+		(ReamType::Map(a), ReamType::Map(b)) => {
+			compare_all(
+				a.iter().flat_map(|(k, v)| [&k.0.t, &v.t]),
+				b.iter().flat_map(|(k, v)| [&k.0.t, &v.t]),
+			)
+		},
+		(ReamType::Set(a), ReamType::Set(b)) => {
+			compare_all(a.iter().map(|k| &k.0.t), b.iter().map(|k| &k.0.t))
+		},
+		(ReamType::Some(a), ReamType::Some(b))
+		| (ReamType::Ok(a), ReamType::Ok(b))
+		| (ReamType::Err(a), ReamType::Err(b)) => compare(&a.t, &b.t),
+		(
+			ReamType::Product { name: a_name, fields: a_fields },
+			ReamType::Product { name: b_name, fields: b_fields },
+		) => {
+			a_name.cmp(b_name).then_with(|| {
+				compare_all(a_fields.iter().map(|(_, v)| &v.t), b_fields.iter().map(|(_, v)| &v.t))
+			})
+		},
+		(
+			ReamType::Variant { name: a_name, variant: a_variant, payload: a_payload },
+			ReamType::Variant { name: b_name, variant: b_variant, payload: b_payload },
+		) => {
+			(a_name, a_variant).cmp(&(b_name, b_variant)).then_with(|| {
+				match (a_payload, b_payload) {
+					(Some(a), Some(b)) => compare(&a.t, &b.t),
+					(a, b) => a.is_some().cmp(&b.is_some()),
+				}
+			})
+		},
+		(a, b) => compare_numbers(a, b),
+	}
+}
+
+/// Compare two sequences of values lexicographically
+fn compare_all<'v, 's: 'v>(
+	a: impl Iterator<Item = &'v ReamType<'s>>,
+	b: impl Iterator<Item = &'v ReamType<'s>>,
+) -> Ordering {
+	a.cmp_by(b, compare)
+}
+
+/// Compare two numbers of any representation by their value
+fn compare_numbers<'s>(a: &ReamType<'s>, b: &ReamType<'s>) -> Ordering {
+	match (a, b) {
+		(ReamType::Float(a), ReamType::Float(b)) => a.total_cmp(b),
+		// Exact numbers come before floats with the same value, so only
+		// numbers of the same representation are ever equal
+		(ReamType::Float(a), b) => a.total_cmp(&to_float(b)).then(Ordering::Greater),
+		(a, ReamType::Float(b)) => to_float(a).total_cmp(b).then(Ordering::Less),
+		// Big integers lie outside the range of every other exact number
+		#[cfg(feature = "bigint")]
+		(ReamType::BigInt(a), ReamType::BigInt(b)) => a.cmp(b),
+		#[cfg(feature = "bigint")]
+		(ReamType::BigInt(a), _) => a.sign().cmp(&num_bigint::Sign::NoSign),
+		#[cfg(feature = "bigint")]
+		(_, ReamType::BigInt(b)) => num_bigint::Sign::NoSign.cmp(&b.sign()),
+		(a, b) => to_ratio(a).cmp(&to_ratio(b)),
+	}
+}
+
+/// Get the floating point approximation of an exact number
+fn to_float(t: &ReamType<'_>) -> f64 {
+	match t {
+		ReamType::Integer(i) => *i as f64,
+		#[cfg(feature = "bigint")]
+		ReamType::BigInt(i) => num_traits::ToPrimitive::to_f64(i.as_ref()).unwrap_or(f64::NAN),
+		ReamType::Rational(r) => *r.numer() as f64 / *r.denom() as f64,
+		_ => unreachable!(),
+	}
+}
+
+/// Get the rational representation of an integer or rational
+fn to_ratio(t: &ReamType<'_>) -> Rational64 {
+	match t {
+		ReamType::Integer(i) => Rational64::from_integer(*i),
+		ReamType::Rational(r) => *r,
+		_ => unreachable!(),
+	}
+}
+
+/// Hash a value that can be a key, consistently with [`compare`]
+fn hash<H: Hasher>(t: &ReamType<'_>, state: &mut H) {
+	rank(t).hash(state);
+
+	match t {
+		ReamType::Boolean(b) => b.hash(state),
+		ReamType::Integer(i) => i.hash(state),
+		#[cfg(feature = "bigint")]
+		ReamType::BigInt(i) => i.hash(state),
+		ReamType::Rational(r) => r.hash(state),
+		ReamType::Float(f) => f.to_bits().hash(state),
+		ReamType::Character(c) => c.hash(state),
+		ReamType::String(s) => s.hash(state),
+		ReamType::Identifier(i) => i.hash(state),
+		ReamType::Atom(a) => a.hash(state),
+		#[cfg(feature = "time")]
+		ReamType::DateTime(d) => d.hash(state),
+		#[cfg(feature = "time")]
+		ReamType::Duration(d) => d.hash(state),
+		ReamType::List(values) => {
+			values.len().hash(state);
+			values.iter().for_each(|v| hash(&v.t, state));
+		},
+		ReamType::Tuple(values) => {
+			values.len().hash(state);
+			values.iter().for_each(|v| hash(&v.t, state));
+		},
+		ReamType::Range { start, end, step } => (start, end, step).hash(state),
+		ReamType::Map(map) => {
+			map.len().hash(state);
+			map.iter().for_each(|(k, v)| {
+				hash(&k.0.t, state);
+				hash(&v.t, state);
+			});
+		},
+		ReamType::Set(set) => {
+			set.len().hash(state);
+			set.iter().for_each(|k| hash(&k.0.t, state));
+		},
+		ReamType::Some(v) | ReamType::Ok(v) | ReamType::Err(v) => hash(&v.t, state),
+		ReamType::Product { name, fields } => {
+			name.hash(state);
+			fields.iter().for_each(|(_, v)| hash(&v.t, state));
+		},
+		ReamType::Variant { name, variant, payload } => {
+			(name, variant).hash(state);
+			payload.is_some().hash(state);
+			payload.iter().for_each(|v| hash(&v.t, state));
+		},
+		_ => (),
+	}
+}
@@ -0,0 +1,122 @@
+//! Limits on the values structural operations like printing and comparing
+//! are willing to traverse
+//!
+//! Persistent collections share their elements, so a value built in a
+//! handful of steps can contain exponentially many elements, and a vector
+//! can even contain itself. Walking such a value would hang the host or
+//! overflow its stack, checking it against these limits first makes it an
+//! error instead
+//!
+//! Strings built from a count or width, like repeated or padded ones, are
+//! limited in length before they are built for the same reason
+
+use super::ReamType;
+use crate::{EvalError, Span};
+
+/// The deepest and largest values structural operations will traverse
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ValueLimits {
+	/// The maximum amount of values nested inside each other
+	pub(super) depth: usize,
+	/// The maximum amount of values, counting every element of every
+	/// collection
+	pub(super) size:   usize,
+	/// The maximum length in bytes of strings built by repeating or padding
+	pub(super) string: usize,
+}
+
+impl Default for ValueLimits {
+	fn default() -> Self { Self { depth: 1_000, size: 1_000_000, string: 1 << 24 } }
+}
+
+impl ValueLimits {
+	/// Make sure a value is within these limits, reporting it at `loc` if it
+	/// isn't
+	pub(super) fn check(&self, t: &ReamType<'_>, loc: Span) -> Result<(), EvalError> {
+		self.visit(t, 1, &mut 0, loc)
+	}
+
+	/// Make sure a string about to be built is within these limits, where
+	/// `len` is [`None`] if computing its length overflowed, returning the
+	/// length if it is
+	pub(super) fn check_string(&self, len: Option<usize>, loc: Span) -> Result<usize, EvalError> {
+		match len {
+			Some(len) if len <= self.string => Ok(len),
+			_ => Err(EvalError::StringTooLong { loc, limit: self.string }),
+		}
+	}
+
+	/// Check a value nested `depth` levels deep, counting it and everything
+	/// inside of it towards `size`
+	fn visit(
+		&self,
+		t: &ReamType<'_>,
+		depth: usize,
+		size: &mut usize,
+		loc: Span,
+	) -> Result<(), EvalError> {
+		if depth > self.depth {
+			return Err(EvalError::ValueTooDeep { loc, limit: self.depth });
+		}
+
+		*size += 1;
+		if *size > self.size {
+			return Err(EvalError::ValueTooLarge { loc, limit: self.size });
+		}
+
+		let mut visit = |t: &ReamType<'_>| self.visit(t, depth + 1, size, loc);
+
+		match t {
+			ReamType::List(values) => values.iter().try_for_each(|v| visit(&v.t)),
+			ReamType::Tuple(values) => values.iter().try_for_each(|v| visit(&v.t)),
+			ReamType::Vector(values) => values.borrow().iter().try_for_each(|v| visit(&v.t)),
+			ReamType::Map(map) => {
+				map.iter().try_for_each(|(k, v)| visit(&k.value().t).and_then(|()| visit(&v.t)))
+			},
+			ReamType::Set(set) => set.iter().try_for_each(|k| visit(&k.value().t)),
+			ReamType::Ok(v) | ReamType::Err(v) | ReamType::Some(v) => visit(&v.t),
+			ReamType::Product { fields, .. } => fields.iter().try_for_each(|(_, v)| visit(&v.t)),
+			ReamType::Variant { payload, .. } => payload.iter().try_for_each(|v| visit(&v.t)),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, EvalError, Interpreter, Value};
+
+	fn run<'s>(mut interpreter: Interpreter<'s>, source: &'s str) -> Result<Value, EvalError> {
+		interpreter.run(parse_str(source).unwrap())
+	}
+
+	#[test]
+	fn strings_stay_within_the_string_limit() {
+		let interpreter = || Interpreter::new(Capabilities::PURE).with_string_limit(4);
+
+		assert_eq!(
+			run(interpreter(), "(string-repeat \"ab\" 2)").unwrap(),
+			Value::String("abab".to_string())
+		);
+		assert!(matches!(
+			run(interpreter(), "(string-repeat \"ab\" 3)"),
+			Err(EvalError::StringTooLong { limit: 4, .. })
+		));
+		// The two-byte fill character takes the padded string over the limit
+		assert!(matches!(
+			run(interpreter(), "(string-pad-left \"a\" 3 'é')"),
+			Err(EvalError::StringTooLong { limit: 4, .. })
+		));
+	}
+
+	#[test]
+	fn value_limits_keep_the_string_limit() {
+		let interpreter =
+			Interpreter::new(Capabilities::PURE).with_string_limit(4).with_value_limits(10, 10);
+
+		assert!(matches!(
+			run(interpreter, "(string-repeat \"ab\" 3)"),
+			Err(EvalError::StringTooLong { limit: 4, .. })
+		));
+	}
+}
@@ -2,47 +2,112 @@
 
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use crate::ast::Program;
-use crate::EvalError;
+use crate::ast::{Expression, Identifier, Program};
+use crate::{parse_file, EvalError, SourceMap, Span, Value};
 
+mod adt;
+mod assertion;
+mod capability;
+mod clauses;
+mod context;
 mod implementations;
+mod include;
+mod key;
+mod limits;
+mod pattern;
 mod primitives;
+mod protocol;
+mod source;
 mod value;
 
-use value::{ReamType, ReamValue};
+pub use capability::*;
+use context::{Context, Output, Provider};
+pub use source::*;
+use value::{CallContext, Enclosed, HostFunction, Primitive, ReamType, ReamValue};
 
 use self::primitives::*;
 
 #[derive(Debug, Clone, Default)]
 struct Scope<'s> {
-	parent:  Option<Rc<RefCell<Self>>>,
-	symbols: HashMap<&'s str, ReamValue<'s>>,
+	parent:    Option<Rc<RefCell<Self>>>,
+	symbols:   HashMap<&'s str, ReamValue<'s>>,
+	/// Where each constant in this scope was defined
+	constants: HashMap<&'s str, Span>,
+	context:   Rc<Context<'s>>,
 }
 
 impl<'s> Scope<'s> {
 	/// Get a value in the current scope
 	fn get(&self, key: &'s str) -> Option<ReamValue<'s>> {
 		match self.symbols.get(key) {
-			Some(v) => Some(v.clone()),
+			Some(v) => {
+				let mut v = v.clone();
+				if let ReamType::Closure { enclosed_scope, .. } = &mut v.t {
+					enclosed_scope.strengthen();
+				}
+
+				Some(v)
+			},
 			None => self.parent.as_ref().and_then(|p| p.borrow().get(key)),
 		}
 	}
 
 	/// Set a value in the current scope
-	fn set(&mut self, key: &'s str, value: ReamValue<'s>) { self.symbols.insert(key, value); }
+	fn set(&mut self, key: &'s str, mut value: ReamValue<'s>) {
+		if let ReamType::Closure { enclosed_scope, .. } = &mut value.t {
+			enclosed_scope.weaken_in(self);
+		}
 
-	/// Extend a new scope
-	fn extend(parent: Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
-		let symbols = HashMap::new();
+		self.symbols.insert(key, value);
+	}
 
-		Rc::new(RefCell::new(Self { parent: Some(parent), symbols }))
+	/// Define a value in the current scope, failing if the name is bound to
+	/// a constant in it
+	///
+	/// Constants can still be shadowed by definitions in nested scopes
+	fn define(&mut self, id: Identifier<'s>, value: ReamValue<'s>) -> Result<(), EvalError> {
+		if let Some(defined) = self.constants.get(id.id) {
+			return Err(EvalError::ConstantRedefinition {
+				loc:     id.span,
+				name:    id.id.to_string(),
+				defined: *defined,
+			});
+		}
+
+		self.set(id.id, value);
+
+		Ok(())
 	}
 
-	/// Close over the given scope
-	fn close(scope: Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
-		Rc::new(RefCell::new(scope.borrow().clone()))
+	/// Define a constant in the current scope, which can't be redefined in
+	/// it afterwards
+	fn define_constant(
+		&mut self,
+		id: Identifier<'s>,
+		value: ReamValue<'s>,
+	) -> Result<(), EvalError> {
+		self.define(id, value)?;
+		self.constants.insert(id.id, id.span);
+
+		Ok(())
+	}
+
+	/// Extend a new scope
+	///
+	/// Every body (of a function, closure, `seq` or `match` clause) is
+	/// evaluated in a scope of its own, so the definitions it makes are only
+	/// visible inside the body. They're visible from the point they're
+	/// evaluated on, shadow any binding of the same name outside the body, and
+	/// replace earlier definitions of the same name in the body
+	fn extend(parent: Rc<RefCell<Self>>) -> Rc<RefCell<Self>> {
+		let context = parent.borrow().context.clone();
+
+		Rc::new(RefCell::new(Self { parent: Some(parent), context, ..Default::default() }))
 	}
 }
 
@@ -50,31 +115,235 @@ trait Eval<'s, 'r> {
 	fn eval(self, scope: Rc<RefCell<Scope<'s>>>) -> Result<ReamValue<'s>, EvalError>;
 }
 
-impl<'s> Program<'s> {
-	/// Run the program
-	pub fn run(self) -> Result<(), EvalError> {
-		let mut scope_inner = Scope::default();
+/// An interpreter holding the global scope programs are evaluated in
+pub struct Interpreter<'s> {
+	global_scope: Rc<RefCell<Scope<'s>>>,
+	context:      Rc<Context<'s>>,
+}
 
-		scope_inner.set("+", ReamValue { span: (0, 0).into(), t: ADD });
-		scope_inner.set("-", ReamValue { span: (0, 0).into(), t: SUB });
-		scope_inner.set("*", ReamValue { span: (0, 0).into(), t: MUL });
-		scope_inner.set("/", ReamValue { span: (0, 0).into(), t: DIV });
+impl<'s> Interpreter<'s> {
+	/// Create a new [`Interpreter`] that only allows calls to primitives
+	/// belonging to the given [`Capabilities`]
+	///
+	/// Every primitive is installed in the global scope, calling one that
+	/// requires a capability which wasn't granted results in an
+	/// [`EvalError::CapabilityDenied`]
+	pub fn new(capabilities: Capabilities) -> Self {
+		let context = Rc::new(Context { capabilities, ..Default::default() });
+		let mut scope_inner = Scope { context: context.clone(), ..Default::default() };
 
-		scope_inner.set("==", ReamValue { span: (0, 0).into(), t: EQU });
-		scope_inner.set("!=", ReamValue { span: (0, 0).into(), t: NEQ });
-		scope_inner.set(">", ReamValue { span: (0, 0).into(), t: GT });
-		scope_inner.set(">=", ReamValue { span: (0, 0).into(), t: GTE });
-		scope_inner.set("<", ReamValue { span: (0, 0).into(), t: LT });
-		scope_inner.set("<=", ReamValue { span: (0, 0).into(), t: LTE });
+		for primitive in primitives() {
+			let ReamType::Primitive(Primitive { name, .. }) = primitive else { unreachable!() };
 
-		scope_inner.set("print", ReamValue { span: (0, 0).into(), t: PRINT });
+			scope_inner.set(name, ReamValue { span: Span::default(), t: primitive });
+		}
 
-		let global_scope = Rc::new(RefCell::new(scope_inner));
+		for (name, constant) in constants() {
+			scope_inner.set(name, ReamValue { span: Span::default(), t: constant });
+		}
 
-		for expr in self.0 {
-			expr.eval(global_scope.clone())?;
+		for name in OPERATOR_PROTOCOLS {
+			context.protocols.borrow_mut().insert(name, vec![name]);
+
+			let method = ReamType::ProtocolMethod {
+				protocol: name,
+				method:   name,
+				index:    0,
+				arity:    2,
+			};
+			scope_inner.set(name, ReamValue { span: Span::default(), t: method });
 		}
 
-		Ok(())
+		for name in ITERATION_PROTOCOLS {
+			context.protocols.borrow_mut().insert(name, vec![name]);
+		}
+
+		Self { global_scope: Rc::new(RefCell::new(scope_inner)), context }
+	}
+
+	/// Limit the amount of work a single run of this interpreter may do,
+	/// exceeding it results in an [`EvalError::QuotaExceeded`]
+	///
+	/// Every procedure call counts as one value, as does every element of a
+	/// collection built by a primitive. The count starts over on every call
+	/// to [`run`](Self::run) or [`eval`](Self::eval), so it limits the work
+	/// done by a single program or REPL input, not the amount of values alive
+	/// at any point
+	pub fn with_value_quota(self, quota: usize) -> Self {
+		self.context.value_quota.set(Some(quota));
+		self
+	}
+
+	/// Limit how deeply nested and how large the values printed, compared
+	/// or returned by programs run by this interpreter may be, exceeding
+	/// either results in an [`EvalError::ValueTooDeep`] or
+	/// [`EvalError::ValueTooLarge`]
+	///
+	/// Values are limited to a depth of 1000 and a size of a million values
+	/// by default
+	pub fn with_value_limits(self, depth: usize, size: usize) -> Self {
+		let limits = self.context.value_limits.get();
+		self.context.value_limits.set(limits::ValueLimits { depth, size, ..limits });
+		self
+	}
+
+	/// Limit how long, in bytes, the strings built by repeating or padding
+	/// in programs run by this interpreter may be, exceeding it results in
+	/// an [`EvalError::StringTooLong`]
+	///
+	/// Strings are limited to 16 MiB by default
+	pub fn with_string_limit(self, bytes: usize) -> Self {
+		let limits = self.context.value_limits.get();
+		self.context.value_limits.set(limits::ValueLimits { string: bytes, ..limits });
+		self
+	}
+
+	/// Tell programs run by this interpreter which file they were loaded from
+	pub fn with_script_path(self, path: impl Into<PathBuf>) -> Self {
+		*self.context.script_path.borrow_mut() = Some(path.into());
+		self
+	}
+
+	/// Search the given directories, in order, for files included by
+	/// programs run by this interpreter that aren't found relative to the
+	/// file including them
+	///
+	/// Replaces any [`SourceProvider`] set before with a
+	/// [`FileSystemProvider`]
+	pub fn with_include_paths(self, paths: Vec<PathBuf>) -> Self {
+		self.with_source_provider(FileSystemProvider::new(paths))
 	}
+
+	/// Load the files included by programs run by this interpreter through
+	/// the given provider, instead of from the filesystem
+	pub fn with_source_provider(self, provider: impl SourceProvider + 'static) -> Self {
+		*self.context.source_provider.borrow_mut() = Provider(Rc::new(provider));
+		self
+	}
+
+	/// Pass command line arguments to programs run by this interpreter
+	pub fn with_args(self, args: Vec<String>) -> Self {
+		*self.context.args.borrow_mut() = args;
+		self
+	}
+
+	/// Send the output of programs run by this interpreter, like that of
+	/// `print`, to the given sink instead of stdout
+	pub fn with_output(self, output: impl Write + 'static) -> Self {
+		*self.context.output.borrow_mut() = Output(Box::new(output));
+		self
+	}
+
+	/// Install a function implemented by the embedder under the given name,
+	/// next to the primitives
+	///
+	/// The function is passed its arguments as [`Value`]s and returns the
+	/// value of the call, or a payload that is raised as an
+	/// [`EvalError::Raised`] like `(error <payload>)` would. Calling it
+	/// without the given [`Capability`] results in an
+	/// [`EvalError::CapabilityDenied`] without running it
+	pub fn with_function(
+		self,
+		name: &'static str,
+		capability: Capability,
+		f: impl Fn(Vec<Value>) -> Result<Value, Value> + 'static,
+	) -> Self {
+		let host = HostFunction { name, capability, f: Box::new(f) };
+		let value = ReamValue { span: Span::default(), t: ReamType::Host(Rc::new(host)) };
+
+		self.global_scope.borrow_mut().set(name, value);
+		self
+	}
+
+	/// Parse a source file, adding it to the [`SourceMap`] of this
+	/// interpreter
+	///
+	/// Errors in programs loaded this way, and in the files they include,
+	/// should be displayed with [`source_map`](Self::source_map) as their
+	/// source code
+	pub fn parse(&self, name: &str, source: &'s str) -> Result<Program<'s>, miette::Error> {
+		let file = self.context.source_map.borrow_mut().add(name, source);
+
+		parse_file(source, file)
+	}
+
+	/// Get every source file loaded by this interpreter so far
+	pub fn source_map(&self) -> SourceMap { self.context.source_map.borrow().clone() }
+
+	/// Run a program in the global scope of this interpreter
+	///
+	/// Returns the value of the last expression, or [`Value::Unit`] if the
+	/// program is empty
+	pub fn run(&mut self, program: Program<'s>) -> Result<Value, EvalError> {
+		self.context.values.set(0);
+		include::prefetch(&program.0, None, &self.context);
+
+		let mut last = Value::Unit;
+		for expr in program.0 {
+			last = self.eval_expression(expr)?;
+		}
+
+		Ok(last)
+	}
+
+	/// Evaluate a single expression in the global scope of this interpreter
+	///
+	/// Macro calls in the expression are expanded first. Definitions made by
+	/// the expression, including macro definitions, stay visible to
+	/// everything evaluated afterwards, which is what lets a REPL build up
+	/// state one input at a time
+	///
+	/// Values of types with a printer registered by `define-printer` are
+	/// returned as a [`Value::Printed`], displaying the way the printer does
+	pub fn eval(&mut self, expr: Expression<'s>) -> Result<Value, EvalError> {
+		self.context.values.set(0);
+
+		self.eval_expression(expr)
+	}
+
+	/// Evaluate a single expression in the global scope of this interpreter,
+	/// as part of the quota of the current run
+	fn eval_expression(&mut self, expr: Expression<'s>) -> Result<Value, EvalError> {
+		let expr = self.context.expander.borrow_mut().expand(expr)?;
+		let span = expr.span();
+
+		let value = expr.eval(self.global_scope.clone())?;
+		self.context.value_limits.get().check(&value.t, span)?;
+
+		// Printers are called just like they would be by `print`
+		let call_context = CallContext {
+			loc:     span,
+			callee:  "print",
+			context: self.context.clone(),
+			scope:   self.global_scope.clone(),
+		};
+
+		call_context.to_value(&value)
+	}
+
+	/// Run a program in the global scope of this interpreter, aborting with
+	/// an [`EvalError::Timeout`] if it takes longer than `timeout`
+	///
+	/// The deadline is checked before every procedure call
+	pub fn eval_with_timeout(
+		&mut self,
+		program: Program<'s>,
+		timeout: Duration,
+	) -> Result<Value, EvalError> {
+		self.context.deadline.set(Some((Instant::now() + timeout, timeout)));
+		let result = self.run(program);
+		self.context.deadline.set(None);
+
+		result
+	}
+}
+
+impl<'s> Default for Interpreter<'s> {
+	fn default() -> Self { Self::new(Capabilities::default()) }
+}
+
+impl<'s> Program<'s> {
+	/// Run the program with every capability available, returning the value
+	/// of its last expression
+	pub fn run(self) -> Result<Value, EvalError> { Interpreter::default().run(self) }
 }
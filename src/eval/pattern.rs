@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{ReamType, ReamValue, Scope};
+use crate::ast::{Literal, Pattern};
+use crate::EvalError;
+
+impl<'s> Pattern<'s> {
+	/// Destructure a value according to this pattern, binding every
+	/// identifier in the pattern to its corresponding part of the value in
+	/// the given scope
+	pub(super) fn bind(
+		&self,
+		value: ReamValue<'s>,
+		scope: &Rc<RefCell<Scope<'s>>>,
+	) -> Result<(), EvalError> {
+		match self {
+			Self::Identifier(id) => scope.borrow_mut().define(*id, value),
+			Self::Wildcard { .. } => Ok(()),
+			Self::Literal(literal) => {
+				let expected = literal_value(literal);
+
+				if !expected.equals(&value.t) {
+					return Err(EvalError::PatternMismatch {
+						loc:      literal.span(),
+						expected: format!("`{expected}`"),
+						found:    format!("`{}`", value.t),
+					});
+				}
+
+				Ok(())
+			},
+			Self::Variant { variant, payload: None, .. }
+				if matches!(value.t, ReamType::Atom(a) if a[1..] == **variant) =>
+			{
+				Ok(())
+			},
+			Self::List { span, patterns } => {
+				// Tuples and vectors destructure just like lists
+				let values = match value.t {
+					ReamType::List(values) => values.into_iter().collect(),
+					ReamType::Tuple(values) => values,
+					ReamType::Vector(values) => values.borrow().clone(),
+					t => {
+						return Err(EvalError::PatternMismatch {
+							loc:      *span,
+							expected: format!("{} elements", patterns.len()),
+							found:    format!("`{}`", t.type_name()),
+						});
+					},
+				};
+
+				if values.len() != patterns.len() {
+					return Err(EvalError::PatternMismatch {
+						loc:      *span,
+						expected: format!("{} elements", patterns.len()),
+						found:    format!("{} elements", values.len()),
+					});
+				}
+
+				patterns.iter().zip(values).try_for_each(|(p, v)| p.bind(v, scope))
+			},
+			Self::Cons { span, head, tail } => {
+				let mut values = match value.t {
+					ReamType::List(values) if !values.is_empty() => values,
+					t => {
+						return Err(EvalError::PatternMismatch {
+							loc:      *span,
+							expected: "a non-empty list".to_string(),
+							found:    format!("`{}`", t.type_name()),
+						});
+					},
+				};
+
+				// Unwrap is safe as the list was just checked to not be empty
+				let first = values.pop_front().unwrap();
+				head.bind(first, scope)?;
+				tail.bind(ReamValue { span: value.span, t: ReamType::List(values) }, scope)
+			},
+			Self::Variant { span, variant, payload } => {
+				let Some((value_variant, value_payload)) = variant_of(&value.t) else {
+					return Err(EvalError::PatternMismatch {
+						loc:      *span,
+						expected: format!("variant `{variant}`"),
+						found:    format!("`{}`", value.t.type_name()),
+					});
+				};
+
+				if *variant != value_variant {
+					return Err(EvalError::PatternMismatch {
+						loc:      *span,
+						expected: format!("variant `{variant}`"),
+						found:    format!("variant `{value_variant}`"),
+					});
+				}
+
+				match (payload, value_payload) {
+					(Some(p), Some(v)) => p.bind(v.clone(), scope),
+					(Some(_), None) => {
+						Err(EvalError::PatternMismatch {
+							loc:      *span,
+							expected: format!("variant `{variant}` with a payload"),
+							found:    format!("variant `{variant}` without a payload"),
+						})
+					},
+					(None, _) => Ok(()),
+				}
+			},
+		}
+	}
+
+	/// Check if a value matches this pattern without binding anything
+	pub(super) fn matches(&self, value: &ReamType<'s>) -> bool {
+		match (self, value) {
+			(Self::Identifier(_), _) => true,
+			(Self::Wildcard { .. }, _) => true,
+			(Self::Literal(literal), value) => literal_value(literal).equals(value),
+			(Self::List { patterns, .. }, ReamType::List(values)) => {
+				patterns.len() == values.len()
+					&& patterns.iter().zip(values).all(|(p, v)| p.matches(&v.t))
+			},
+			(Self::List { patterns, .. }, ReamType::Tuple(values)) => {
+				patterns.len() == values.len()
+					&& patterns.iter().zip(values).all(|(p, v)| p.matches(&v.t))
+			},
+			(Self::List { patterns, .. }, ReamType::Vector(values)) => {
+				let values = values.borrow();
+
+				patterns.len() == values.len()
+					&& patterns.iter().zip(values.iter()).all(|(p, v)| p.matches(&v.t))
+			},
+			(Self::Cons { head, tail, .. }, ReamType::List(values)) => {
+				match values.head() {
+					Some(first) => {
+						head.matches(&first.t) && tail.matches(&ReamType::List(values.skip(1)))
+					},
+					None => false,
+				}
+			},
+			(Self::Variant { variant, payload: None, .. }, ReamType::Atom(a)) => {
+				a[1..] == **variant
+			},
+			(Self::Variant { variant, payload, .. }, value) => {
+				let Some((value_variant, value_payload)) = variant_of(value) else { return false };
+
+				*variant == value_variant
+					&& match (payload, value_payload) {
+						(Some(p), Some(v)) => p.matches(&v.t),
+						(Some(_), None) => false,
+						(None, _) => true,
+					}
+			},
+			_ => false,
+		}
+	}
+}
+
+/// Get the variant and payload of a value that variant patterns can match,
+/// results match like a sum type with the variants `ok` and `err`, options
+/// like one with the variants `some` and `none`
+fn variant_of<'v, 's>(t: &'v ReamType<'s>) -> Option<(&'v str, Option<&'v ReamValue<'s>>)> {
+	match t {
+		ReamType::Variant { variant, payload, .. } => Some((variant, payload.as_deref())),
+		ReamType::Ok(v) => Some(("ok", Some(v))),
+		ReamType::Err(e) => Some(("err", Some(e))),
+		ReamType::Some(v) => Some(("some", Some(v))),
+		ReamType::None => Some(("none", None)),
+		_ => None,
+	}
+}
+
+/// Get the value matched by a literal pattern
+fn literal_value<'s>(literal: &Literal<'s>) -> ReamType<'s> {
+	match *literal {
+		Literal::Boolean { b, .. } => ReamType::Boolean(b),
+		Literal::Integer { i, .. } => ReamType::Integer(i),
+		Literal::Rational { r, .. } => ReamType::Rational(r),
+		Literal::Float { f, .. } => ReamType::Float(f),
+		Literal::Character { c, .. } => ReamType::Character(c),
+		Literal::String { s, .. } => ReamType::String(s.into()),
+		// The parser only turns the literals above into patterns
+		Literal::Quotation { .. } | Literal::Atom { .. } => unreachable!(),
+	}
+}
@@ -0,0 +1,172 @@
+use super::expect_arguments;
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Get the value of a field of a product type value
+///
+/// `(get <value> <field>)` where field is an atom naming the field
+pub(super) const GET<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "get",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value, field]: [_; 2] = expect_arguments(c, a)?;
+
+		let (name, mut fields) = expect_product(value)?;
+		let i = field_index(name, &fields, &field)?;
+
+		Ok(fields.swap_remove(i).1.t)
+	},
+});
+
+/// Create a copy of a product type value with one field replaced
+///
+/// `(with <value> <field> <new>)` where field is an atom naming the field
+pub(super) const WITH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "with",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value, field, new]: [_; 3] = expect_arguments(c, a)?;
+
+		let (name, mut fields) = expect_product(value)?;
+		let i = field_index(name, &fields, &field)?;
+
+		c.context.allocate(1, c.loc)?;
+		fields[i].1 = new;
+
+		Ok(ReamType::Product { name, fields })
+	},
+});
+
+/// Check if a value of a sum type holds a given variant
+///
+/// `(variant? <value> <variant>)` where variant is an atom naming the variant
+pub(super) const IS_VARIANT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "variant?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value, variant]: [_; 2] = expect_arguments(c, a)?;
+
+		let ReamType::Atom(variant_name) = variant.t else {
+			return Err(EvalError::WrongType {
+				loc:      variant.span,
+				expected: "Atom".to_string(),
+				found:    variant.t.type_name(),
+			});
+		};
+
+		match value.t {
+			ReamType::Variant { variant, .. } => {
+				Ok(ReamType::Boolean(variant == &variant_name[1..]))
+			},
+			t => {
+				Err(EvalError::WrongType {
+					loc:      value.span,
+					expected: "Sum".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Take apart a value that should be of a product type
+#[allow(clippy::type_complexity)]
+fn expect_product<'s>(
+	value: ReamValue<'s>,
+) -> Result<(&'s str, Vec<(&'s str, ReamValue<'s>)>), EvalError> {
+	match value.t {
+		ReamType::Product { name, fields } => Ok((name, fields)),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Product".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Find the index of the field named by an atom
+fn field_index<'s>(
+	name: &'s str,
+	fields: &[(&'s str, ReamValue<'s>)],
+	field: &ReamValue<'s>,
+) -> Result<usize, EvalError> {
+	let ReamType::Atom(field_name) = field.t else {
+		return Err(EvalError::WrongType {
+			loc:      field.span,
+			expected: "Atom".to_string(),
+			found:    field.t.type_name(),
+		});
+	};
+
+	let field_name = &field_name[1..];
+
+	fields.iter().position(|(f, _)| *f == field_name).ok_or_else(|| {
+		EvalError::UnknownField {
+			loc:       field.span,
+			type_name: name.to_string(),
+			field:     field_name.to_string(),
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, EvalError, Interpreter, Value};
+
+	const POINT: &str = r#"
+(define-type Point (Product (:x Integer) (:y Integer)))
+(let p (Point :x 1 :y 2))
+"#;
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		let program = parse_str(source).unwrap();
+
+		Interpreter::new(Capabilities::PURE).run(program)
+	}
+
+	/// Run a source after defining a point `p`, returning the type it was
+	/// expected to have and the one it had
+	fn wrong_type(source: &str) -> (String, String) {
+		match run(&format!("{POINT}{source}")) {
+			Err(EvalError::WrongType { expected, found, .. }) => (expected, found),
+			result => panic!("expected a wrong type, got {result:?}"),
+		}
+	}
+
+	#[test]
+	fn only_products_have_fields() {
+		assert_eq!(wrong_type("(get (list 1) :x)"), ("Product".into(), "List".into()));
+		assert_eq!(wrong_type("(with 1 :x 2)"), ("Product".into(), "Integer".into()));
+	}
+
+	#[test]
+	fn fields_are_named_by_atoms() {
+		assert_eq!(wrong_type("(get p \"x\")"), ("Atom".into(), "String".into()));
+		assert_eq!(wrong_type("(with p 'x' 2)"), ("Atom".into(), "Character".into()));
+	}
+
+	#[test]
+	fn unknown_fields_are_rejected() {
+		let err = run(&format!("{POINT}(with p :z 3)")).unwrap_err();
+
+		assert!(matches!(err, EvalError::UnknownField { field, .. } if field == "z"));
+	}
+
+	#[test]
+	fn variants_are_named_by_atoms() {
+		let source = "(define-type S (Sum :a :b))\n(variant? (a) \"a\")";
+
+		let err = run(source).unwrap_err();
+
+		assert!(matches!(err, EvalError::WrongType { expected, .. } if expected == "Atom"));
+	}
+
+	#[test]
+	fn only_sum_types_hold_variants() {
+		assert_eq!(wrong_type("(variant? p :x)"), ("Sum".into(), "Point".into()));
+		assert_eq!(wrong_type("(variant? (some 1) :some)"), ("Sum".into(), "Option".into()));
+	}
+}
@@ -0,0 +1,193 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+
+use super::{expect_arguments, expect_string, io_result};
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::{EvalError, Span};
+
+/// Get the current date and time in UTC
+///
+/// `(date-now)`
+pub(super) const DATE_NOW<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-now",
+	capability: Capability::Time,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		Ok(ReamType::DateTime(Utc::now()))
+	},
+});
+
+/// Format a date using `strftime` style specifiers
+///
+/// `(date-format <date> <format>)`
+pub(super) const DATE_FORMAT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-format",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [date, format]: [_; 2] = expect_arguments(c, a)?;
+
+		let date = expect_date(date)?;
+		let span = format.span;
+		let format = expect_string(format)?;
+		let items = expect_format(&format, span)?;
+
+		Ok(ReamType::String(date.format_with_items(items.into_iter()).to_string().into()))
+	},
+});
+
+/// Parse a date using `strftime` style specifiers
+///
+/// `(date-parse <string> <format>)` returns an `ok` holding the date, or an
+/// `err` holding a message describing why the string didn't match
+///
+/// Formats without an offset are read as UTC, and formats without a time of
+/// day as midnight
+pub(super) const DATE_PARSE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-parse",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string, format]: [_; 2] = expect_arguments(c, a)?;
+
+		let string = expect_string(string)?;
+		let span = format.span;
+		let format = expect_string(format)?;
+		expect_format(&format, span)?;
+
+		let parsed = DateTime::parse_from_str(&string, &format)
+			.map(|t| t.with_timezone(&Utc))
+			.or_else(|_| NaiveDateTime::parse_from_str(&string, &format).map(|t| t.and_utc()))
+			.or_else(|_| {
+				NaiveDate::parse_from_str(&string, &format)
+					.map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+			});
+
+		Ok(io_result(c, parsed.map(ReamType::DateTime)))
+	},
+});
+
+/// Create a duration from a number of milliseconds
+///
+/// `(duration-millis <integer>)`
+pub(super) const DURATION_MILLIS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "duration-millis",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [millis]: [_; 1] = expect_arguments(c, a)?;
+
+		let ReamType::Integer(millis) = millis.t else {
+			return Err(EvalError::WrongType {
+				loc:      millis.span,
+				expected: "Integer".to_string(),
+				found:    millis.t.type_name(),
+			});
+		};
+
+		Ok(ReamType::Duration(Duration::milliseconds(millis)))
+	},
+});
+
+/// Get the length of a duration in milliseconds
+///
+/// `(duration->millis <duration>)` returns a float, as durations can be
+/// negative
+pub(super) const DURATION_TO_MILLIS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "duration->millis",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [duration]: [_; 1] = expect_arguments(c, a)?;
+
+		let duration = expect_duration(duration)?;
+
+		Ok(ReamType::Float(duration.num_milliseconds() as f64))
+	},
+});
+
+/// Move a date forward by a duration
+///
+/// `(date-add <date> <duration>)`
+pub(super) const DATE_ADD<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-add",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [date, duration]: [_; 2] = expect_arguments(c, a)?;
+
+		let date = expect_date(date)?;
+		let duration = expect_duration(duration)?;
+
+		Ok(ReamType::DateTime(date + duration))
+	},
+});
+
+/// Move a date back by a duration
+///
+/// `(date-sub <date> <duration>)`
+pub(super) const DATE_SUB<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-sub",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [date, duration]: [_; 2] = expect_arguments(c, a)?;
+
+		let date = expect_date(date)?;
+		let duration = expect_duration(duration)?;
+
+		Ok(ReamType::DateTime(date - duration))
+	},
+});
+
+/// Get the duration between two dates
+///
+/// `(date-diff <a> <b>)` returns `a - b`, which is negative if `a` comes
+/// before `b`
+pub(super) const DATE_DIFF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "date-diff",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [a, b]: [_; 2] = expect_arguments(c, a)?;
+
+		let a = expect_date(a)?;
+		let b = expect_date(b)?;
+
+		Ok(ReamType::Duration(a - b))
+	},
+});
+
+/// Check that a format string only contains valid specifiers
+fn expect_format(format: &str, span: Span) -> Result<Vec<Item<'_>>, EvalError> {
+	let items = StrftimeItems::new(format).collect::<Vec<_>>();
+
+	if items.contains(&Item::Error) {
+		return Err(EvalError::InvalidDateFormat { loc: span, format: format.to_string() });
+	}
+
+	Ok(items)
+}
+
+/// Take apart a value that should be a date
+fn expect_date(value: ReamValue<'_>) -> Result<DateTime<Utc>, EvalError> {
+	match value.t {
+		ReamType::DateTime(t) => Ok(t),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "DateTime".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be a duration
+fn expect_duration(value: ReamValue<'_>) -> Result<Duration, EvalError> {
+	match value.t {
+		ReamType::Duration(d) => Ok(d),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Duration".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
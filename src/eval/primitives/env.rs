@@ -0,0 +1,245 @@
+use im_rc::Vector;
+
+use super::{expect_arguments, expect_string, io_result};
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Get the value of an environment variable
+///
+/// `(getenv <name>)` returns a `some` holding the value, or `none` if the
+/// variable isn't set or isn't valid unicode
+pub(super) const GETENV<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "getenv",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let [name]: [_; 1] = expect_arguments(c, a)?;
+
+		let name = expect_string(name)?;
+
+		match std::env::var(name.as_ref()) {
+			Ok(value) => {
+				let value = ReamValue { span: c.loc, t: ReamType::String(value.into()) };
+
+				Ok(ReamType::Some(Box::new(value)))
+			},
+			Err(_) => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Set the value of an environment variable for the running process
+///
+/// `(setenv <name> <value>)`, the name can't be empty or contain `=` or NUL
+/// characters and the value can't contain NUL characters
+pub(super) const SETENV<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "setenv",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let [name, value]: [_; 2] = expect_arguments(c, a)?;
+
+		let name = expect_string(name)?;
+		let value = expect_string(value)?;
+
+		let reason = if name.is_empty() {
+			Some("names can't be empty")
+		} else if name.contains('=') {
+			Some("names can't contain `=`")
+		} else if name.contains('\0') {
+			Some("names can't contain NUL characters")
+		} else if value.contains('\0') {
+			Some("values can't contain NUL characters")
+		} else {
+			None
+		};
+
+		if let Some(reason) = reason {
+			return Err(EvalError::InvalidEnvironmentVariable {
+				loc:    c.loc,
+				name:   name.to_string(),
+				reason: reason.to_string(),
+			});
+		}
+
+		std::env::set_var(name.as_ref(), value.as_ref());
+
+		Ok(ReamType::Unit)
+	},
+});
+
+/// Get the current working directory
+///
+/// `(cwd)` returns an `ok` holding the path, or an `err` holding a message
+/// describing why it couldn't be determined
+pub(super) const CWD<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "cwd",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		let cwd = std::env::current_dir()
+			.map(|path| ReamType::String(path.to_string_lossy().into_owned().into()));
+
+		Ok(io_result(c, cwd))
+	},
+});
+
+/// Get the name of the operating system the interpreter is running on
+///
+/// `(platform)` returns a string like `"linux"`, `"macos"` or `"windows"`
+pub(super) const PLATFORM<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "platform",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		Ok(ReamType::String(std::env::consts::OS.into()))
+	},
+});
+
+/// Get the path of the running script
+///
+/// `(script-path)` returns a `some` holding the path, or `none` if the
+/// program wasn't loaded from a file
+pub(super) const SCRIPT_PATH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "script-path",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		match c.context.script_path.borrow().as_ref() {
+			Some(path) => {
+				let path = path.to_string_lossy().into_owned();
+				let path = ReamValue { span: c.loc, t: ReamType::String(path.into()) };
+
+				Ok(ReamType::Some(Box::new(path)))
+			},
+			None => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Get the command line arguments passed to the running program
+///
+/// `(args)` returns a list of strings, when run from the command line these
+/// are the arguments following `--`
+pub(super) const ARGS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "args",
+	capability: Capability::Env,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		let args = c
+			.context
+			.args
+			.borrow()
+			.iter()
+			.map(|arg| ReamValue { span: c.loc, t: ReamType::String(arg.clone().into()) })
+			.collect::<Vector<_>>();
+		c.context.allocate(args.len(), c.loc)?;
+
+		Ok(ReamType::List(args))
+	},
+});
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, Capability, EvalError, Interpreter, Value};
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		Interpreter::new(Capabilities::ALL).run(parse_str(source).unwrap())
+	}
+
+	fn rejection(source: &str) -> String {
+		match run(source) {
+			Err(EvalError::InvalidEnvironmentVariable { reason, .. }) => reason,
+			result => panic!("expected an invalid environment variable, got {result:?}"),
+		}
+	}
+
+	#[test]
+	fn setenv_sets_variables() {
+		let value = run("(setenv \"REAM_SETENV_TEST\" \"value\")\n(getenv \"REAM_SETENV_TEST\")");
+
+		assert_eq!(value.unwrap(), Value::Some(Box::new(Value::String("value".to_string()))));
+	}
+
+	#[test]
+	fn setenv_rejects_empty_names() {
+		assert_eq!(rejection("(setenv \"\" \"x\")"), "names can't be empty");
+	}
+
+	#[test]
+	fn setenv_rejects_names_containing_equals() {
+		assert_eq!(rejection("(setenv \"A=B\" \"x\")"), "names can't contain `=`");
+	}
+
+	#[test]
+	fn setenv_rejects_nul_characters() {
+		assert_eq!(rejection("(setenv \"A\0B\" \"x\")"), "names can't contain NUL characters");
+		assert_eq!(rejection("(setenv \"A\" \"x\0y\")"), "values can't contain NUL characters");
+	}
+
+	#[test]
+	fn getenv_returns_none_for_unset_variables() {
+		assert_eq!(run("(getenv \"REAM_GETENV_UNSET_TEST\")").unwrap(), Value::None);
+	}
+
+	#[test]
+	fn cwd_is_the_current_directory() {
+		let cwd = std::env::current_dir().unwrap().to_string_lossy().into_owned();
+
+		assert_eq!(run("(cwd)").unwrap(), Value::Ok(Box::new(Value::String(cwd))));
+	}
+
+	#[test]
+	fn platform_is_the_operating_system() {
+		assert_eq!(run("(platform)").unwrap(), Value::String(std::env::consts::OS.to_string()));
+	}
+
+	#[test]
+	fn script_path_is_only_known_for_scripts() {
+		assert_eq!(run("(script-path)").unwrap(), Value::None);
+
+		let value = Interpreter::new(Capabilities::ALL)
+			.with_script_path("scripts/main.rm")
+			.run(parse_str("(script-path)").unwrap());
+		let path = Value::String("scripts/main.rm".to_string());
+
+		assert_eq!(value.unwrap(), Value::Some(Box::new(path)));
+	}
+
+	#[test]
+	fn args_are_the_arguments_given_to_the_interpreter() {
+		assert_eq!(run("(args)").unwrap(), Value::List(vec![]));
+
+		let value = Interpreter::new(Capabilities::ALL)
+			.with_args(vec!["one".to_string(), "two".to_string()])
+			.run(parse_str("(args)").unwrap());
+		let args = vec![Value::String("one".to_string()), Value::String("two".to_string())];
+
+		assert_eq!(value.unwrap(), Value::List(args));
+	}
+
+	#[test]
+	fn environment_primitives_require_the_env_capability() {
+		let sources = [
+			"(getenv \"HOME\")",
+			"(setenv \"A\" \"B\")",
+			"(cwd)",
+			"(platform)",
+			"(script-path)",
+			"(args)",
+		];
+
+		for source in sources {
+			let result = Interpreter::new(Capabilities::ALL.without(Capability::Env))
+				.run(parse_str(source).unwrap());
+
+			assert!(
+				matches!(result, Err(EvalError::CapabilityDenied { .. })),
+				"{source} gave {result:?}"
+			);
+		}
+	}
+}
@@ -0,0 +1,28 @@
+use super::{expect_arguments, io_result};
+use crate::eval::value::{Primitive, ReamType};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Read the contents of a file
+///
+/// `(read-file <path>)` returns an `ok` holding the contents of the file, or
+/// an `err` holding a message describing why it couldn't be read
+pub(super) const READ_FILE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "read-file",
+	capability: Capability::Fs,
+	f:          |c, a| {
+		let [path]: [_; 1] = expect_arguments(c, a)?;
+
+		let ReamType::String(p) = &path.t else {
+			return Err(EvalError::WrongType {
+				loc:      path.span,
+				expected: "String".to_string(),
+				found:    path.t.type_name(),
+			});
+		};
+
+		let contents = std::fs::read_to_string(p.as_ref()).map(|s| ReamType::String(s.into()));
+
+		Ok(io_result(c, contents))
+	},
+});
@@ -0,0 +1,93 @@
+//! Combinators building functions out of other values
+
+use std::rc::Rc;
+
+use super::expect_arguments;
+use crate::eval::value::{Derived, Primitive, ReamType};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Return the argument unchanged
+///
+/// `(identity <value>)`
+pub(super) const IDENTITY<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "identity",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(value.t)
+	},
+});
+
+/// Create a function that ignores its arguments and always returns the
+/// same value
+///
+/// `(constantly <value>)`
+pub(super) const CONSTANTLY<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "constantly",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Derived(Rc::new(Derived::Constant(value))))
+	},
+});
+
+/// Create a function that calls the last function with its arguments, and
+/// every function before it with the result of the one after it
+///
+/// `(compose <function>+)`
+pub(super) const COMPOSE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "compose",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if a.is_empty() {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: 1,
+				found:    0,
+			});
+		}
+
+		Ok(ReamType::Derived(Rc::new(Derived::Composition(a))))
+	},
+});
+
+/// Create a function that calls a function with the given arguments
+/// followed by its own
+///
+/// `(partial <function> <value>*)`
+pub(super) const PARTIAL<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "partial",
+	capability: Capability::Pure,
+	f:          |c, mut a| {
+		if a.is_empty() {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: 1,
+				found:    0,
+			});
+		}
+
+		let function = a.remove(0);
+
+		Ok(ReamType::Derived(Rc::new(Derived::Partial { function, bound: a })))
+	},
+});
+
+/// Create a function that calls a function with its first two arguments
+/// swapped
+///
+/// `(flip <function>)`
+pub(super) const FLIP<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "flip",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [function]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Derived(Rc::new(Derived::Flipped(function))))
+	},
+});
@@ -0,0 +1,89 @@
+//! Integer arithmetic, promoting results that don't fit in an `i64` to big
+//! integers when the `bigint` feature is enabled
+
+#[cfg(feature = "bigint")]
+use std::rc::Rc;
+
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+#[cfg(feature = "bigint")]
+use num_integer::Integer;
+#[cfg(feature = "bigint")]
+use num_rational::Rational64;
+
+use super::ReamType;
+
+/// Turn a big integer into a value, demoting it to a regular integer if it
+/// fits
+///
+/// This keeps every integer in the `i64` range a [`ReamType::Integer`], so
+/// two equal integers always have the same representation
+#[cfg(feature = "bigint")]
+pub(super) fn from_big<'s>(i: BigInt) -> ReamType<'s> {
+	match i64::try_from(&i) {
+		Ok(i) => ReamType::Integer(i),
+		Err(_) => ReamType::BigInt(Rc::new(i)),
+	}
+}
+
+/// Get the big integer representation of an integer value
+///
+/// Only called on values matched by [`is_integer`]
+#[cfg(feature = "bigint")]
+pub(super) fn to_big(t: ReamType<'_>) -> BigInt {
+	match t {
+		ReamType::Integer(i) => BigInt::from(i),
+		ReamType::BigInt(i) => Rc::unwrap_or_clone(i),
+		_ => unreachable!(),
+	}
+}
+
+/// Check if a value is an integer of either representation
+#[cfg(feature = "bigint")]
+pub(super) fn is_integer(t: &ReamType<'_>) -> bool {
+	matches!(t, ReamType::Integer(_) | ReamType::BigInt(_))
+}
+
+/// Divide two big integers, producing a rational if the division doesn't
+/// come out even
+///
+/// Returns [`None`] if the numerator or denominator of that rational don't
+/// fit in an `i64`
+#[cfg(feature = "bigint")]
+pub(super) fn div_big<'s>(a: BigInt, b: BigInt) -> Option<ReamType<'s>> {
+	let (quotient, remainder) = a.div_rem(&b);
+	if remainder == BigInt::ZERO {
+		return Some(from_big(quotient));
+	}
+
+	let gcd = a.gcd(&b);
+	let (numerator, denominator) = (a / &gcd, b / gcd);
+	let numerator = i64::try_from(numerator).ok()?;
+	let denominator = i64::try_from(denominator).ok()?;
+
+	Some(ReamType::Rational(Rational64::new(numerator, denominator)))
+}
+
+/// Generate an operation on two `i64`s that promotes its result to a big
+/// integer on overflow, or returns [`None`] without the `bigint` feature
+macro_rules! promoting {
+	($name:ident $checked:ident $op:tt) => {
+		#[cfg(feature = "bigint")]
+		pub(super) fn $name<'s>(a: i64, b: i64) -> Option<ReamType<'s>> {
+			match a.$checked(b) {
+				Some(i) => Some(ReamType::Integer(i)),
+				None => Some(from_big(BigInt::from(a) $op BigInt::from(b))),
+			}
+		}
+
+		#[cfg(not(feature = "bigint"))]
+		pub(super) fn $name<'s>(a: i64, b: i64) -> Option<ReamType<'s>> {
+			a.$checked(b).map(ReamType::Integer)
+		}
+	};
+}
+
+promoting!(add checked_add +);
+promoting!(sub checked_sub -);
+promoting!(mul checked_mul *);
+promoting!(div checked_div /);
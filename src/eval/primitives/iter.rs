@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::expect_arguments;
+use crate::eval::value::{Iteration, Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Create a range of integers
+///
+/// `(range <start> <end> <step>?)` counts from `start` up to but excluding
+/// `end`, `step` defaults to 1
+pub(super) const RANGE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "range",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if !(2..=3).contains(&a.len()) {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: 3,
+				found:    a.len(),
+			});
+		}
+
+		let mut bounds = a.into_iter().map(|v| {
+			match v.t {
+				ReamType::Integer(i) => Ok(i),
+				t => {
+					Err(EvalError::WrongType {
+						loc:      v.span,
+						expected: "Integer".to_string(),
+						found:    t.type_name(),
+					})
+				},
+			}
+		});
+
+		// Unwraps are safe as there are at least 2 arguments
+		let start = bounds.next().unwrap()?;
+		let end = bounds.next().unwrap()?;
+		let step = bounds.next().transpose()?.unwrap_or(1);
+
+		if step == 0 {
+			return Err(EvalError::WrongType {
+				loc:      c.loc,
+				expected: "non-zero step".to_string(),
+				found:    "0".to_string(),
+			});
+		}
+
+		Ok(ReamType::Range { start, end, step })
+	},
+});
+
+/// Create an iterator over a collection
+///
+/// `(iter <collection>)` accepts lists, tuples, strings, ranges and
+/// iterators, or any type implementing the `iter` protocol
+pub(super) const ITER<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "iter",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if let Some(result) = c.overload("iter", &a) {
+			return result;
+		}
+
+		let [collection]: [_; 1] = expect_arguments(c, a)?;
+
+		let iteration = match collection.t {
+			ReamType::List(values) => Iteration::List(values),
+			ReamType::Tuple(values) => Iteration::Values(values.into_iter()),
+			// Iterating a vector sees the elements it had when iteration started
+			ReamType::Vector(values) => Iteration::Values(values.borrow().clone().into_iter()),
+			// Iterating a map produces `#(key value)` tuples, ordered by key
+			ReamType::Map(map) => {
+				let pairs = map
+					.into_iter()
+					.map(|(k, v)| {
+						ReamValue {
+							span: collection.span,
+							t:    ReamType::Tuple(vec![k.into_value(), v]),
+						}
+					})
+					.collect::<Vec<_>>();
+
+				Iteration::Values(pairs.into_iter())
+			},
+			ReamType::Set(set) => {
+				let elements = set.into_iter().map(|k| k.into_value()).collect::<Vec<_>>();
+
+				Iteration::Values(elements.into_iter())
+			},
+			ReamType::String(string) => {
+				Iteration::Characters { span: collection.span, string, idx: 0 }
+			},
+			ReamType::Range { start, end, step } => {
+				Iteration::Range { span: collection.span, next: start, end, step }
+			},
+			ReamType::Iterator(it) => return Ok(ReamType::Iterator(it)),
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      collection.span,
+					expected: "Iterable".to_string(),
+					found:    t.type_name(),
+				});
+			},
+		};
+
+		Ok(ReamType::Iterator(Rc::new(RefCell::new(iteration))))
+	},
+});
+
+/// Advance an iterator
+///
+/// `(next <iterator>)` returns a `some` holding the next element, or `none`
+/// once the iterator is exhausted, types implementing the `next` protocol
+/// are advanced by their implementation instead
+pub(super) const NEXT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "next",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if let Some(result) = c.overload("next", &a) {
+			return result;
+		}
+
+		let [iterator]: [_; 1] = expect_arguments(c, a)?;
+
+		let ReamType::Iterator(it) = iterator.t else {
+			return Err(EvalError::WrongType {
+				loc:      iterator.span,
+				expected: "Iterator".to_string(),
+				found:    iterator.t.type_name(),
+			});
+		};
+
+		let next = it.borrow_mut().next();
+
+		Ok(match next {
+			Some(v) => ReamType::Some(Box::new(v)),
+			None => ReamType::None,
+		})
+	},
+});
@@ -0,0 +1,614 @@
+use std::cmp::Ordering;
+#[cfg(feature = "bigint")]
+use std::rc::Rc;
+
+use im_rc::Vector;
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+use num_rational::Rational64;
+#[cfg(feature = "bigint")]
+use num_traits::ToPrimitive;
+
+#[cfg(feature = "bigint")]
+use super::integer::from_big;
+use super::rational::to_float;
+use super::{checked, expect_arguments, expect_count, expect_index, integer, rational};
+use crate::eval::key::Key;
+use crate::eval::value::{CallContext, Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::{EvalError, Span};
+
+/// Create a list holding all given values
+///
+/// `(list <value>*)`
+pub(super) const LIST<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "list",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		c.context.allocate(a.len(), c.loc)?;
+
+		Ok(ReamType::List(a.into()))
+	},
+});
+
+/// Prepend a value to a list
+///
+/// `(cons <value> <list>)`
+pub(super) const CONS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "cons",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [head, tail]: [_; 2] = expect_arguments(c, a)?;
+
+		let mut elements = expect_list(tail)?;
+		elements.push_front(head);
+		c.context.allocate(1, c.loc)?;
+
+		Ok(ReamType::List(elements))
+	},
+});
+
+/// Get the first element of a list
+///
+/// `(car <list>)`, the list can't be empty
+pub(super) const CAR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "car",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let (head, _) = expect_non_empty(list)?;
+
+		Ok(head.t)
+	},
+});
+
+/// Get all but the first element of a list
+///
+/// `(cdr <list>)`, the list can't be empty
+pub(super) const CDR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "cdr",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let (_, tail) = expect_non_empty(list)?;
+
+		Ok(ReamType::List(tail))
+	},
+});
+
+/// Concatenate lists
+///
+/// `(append <list>*)`, appending no lists results in an empty list
+pub(super) const APPEND<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "append",
+	capability: Capability::Pure,
+	f:          |_, a| {
+		let mut appended = Vector::new();
+		for list in a {
+			appended.append(expect_list(list)?);
+		}
+
+		Ok(ReamType::List(appended))
+	},
+});
+
+/// Get the amount of elements in a list
+///
+/// `(length <list>)`
+pub(super) const LENGTH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "length",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Integer(expect_list(list)?.len() as i64))
+	},
+});
+
+/// Sort a list
+///
+/// `(sort <list>)` sorts numbers by their value and other values by their
+/// type first, every element has to be orderable
+pub(super) const SORT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "sort",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let mut keys = expect_list(list)?.into_iter().map(Key::new).collect::<Result<Vec<_>, _>>()?;
+		keys.sort();
+
+		Ok(ReamType::List(keys.into_iter().map(Key::into_value).collect()))
+	},
+});
+
+/// Look up the value associated with a key in a list of `(key value)` pairs
+///
+/// `(assoc <key> <list>)` returns a `some` holding the value of the first
+/// pair whose key equals `key`, or `none` if there is no such pair
+pub(super) const ASSOC<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "assoc",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [key, list]: [_; 2] = expect_arguments(c, a)?;
+
+		let ReamType::List(pairs) = list.t else {
+			return Err(EvalError::WrongType {
+				loc:      list.span,
+				expected: "List".to_string(),
+				found:    list.t.type_name(),
+			});
+		};
+
+		for pair in pairs {
+			let pair_span = pair.span;
+			let ReamType::List(mut pair) = pair.t else {
+				return Err(EvalError::WrongType {
+					loc:      pair_span,
+					expected: "List".to_string(),
+					found:    pair.t.type_name(),
+				});
+			};
+
+			if pair.len() != 2 {
+				return Err(EvalError::WrongType {
+					loc:      pair_span,
+					expected: "(key value) pair".to_string(),
+					found:    format!("List of length {}", pair.len()),
+				});
+			}
+
+			if pair[0].t.equals(&key.t) {
+				return Ok(ReamType::Some(Box::new(pair.remove(1))));
+			}
+		}
+
+		Ok(ReamType::None)
+	},
+});
+
+/// Get an element of a list
+///
+/// `(nth <list> <index>)` where index is zero-based, or counts back from the
+/// end if it's negative
+pub(super) const NTH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "nth",
+	capability: Capability::Pure,
+	f:          list_ref,
+});
+
+/// Get an element of a list, named like `vector-ref` and `tuple-ref`
+///
+/// `(list-ref <list> <index>)` where index is zero-based, or counts back from
+/// the end if it's negative
+pub(super) const LIST_REF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "list-ref",
+	capability: Capability::Pure,
+	f:          list_ref,
+});
+
+/// Get the element of a list at an index, for [`NTH`] and [`LIST_REF`]
+fn list_ref<'s>(c: &CallContext<'s>, a: Vec<ReamValue<'s>>) -> Result<ReamType<'s>, EvalError> {
+	let [list, index]: [_; 2] = expect_arguments(c, a)?;
+
+	let mut elements = expect_list(list)?;
+	let i = expect_index(c, index, elements.len(), false)?;
+
+	Ok(elements.remove(i).t)
+}
+
+/// Get the last element of a list
+///
+/// `(last <list>)` returns a `some` holding the last element, or `none` if
+/// the list is empty
+pub(super) const LAST<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "last",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		match expect_list(list)?.pop_back() {
+			Some(last) => Ok(ReamType::Some(Box::new(last))),
+			None => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Get the first elements of a list
+///
+/// `(take <list> <count>)`, the whole list is returned if it has fewer than
+/// `count` elements
+pub(super) const TAKE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "take",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list, count]: [_; 2] = expect_arguments(c, a)?;
+
+		let mut elements = expect_list(list)?;
+		let count = expect_count(count)?.min(elements.len());
+		elements.truncate(count);
+
+		Ok(ReamType::List(elements))
+	},
+});
+
+/// Get all but the first elements of a list
+///
+/// `(drop <list> <count>)`, an empty list is returned if it has fewer than
+/// `count` elements
+pub(super) const DROP<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "drop",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list, count]: [_; 2] = expect_arguments(c, a)?;
+
+		let mut elements = expect_list(list)?;
+		let count = expect_count(count)?.min(elements.len());
+
+		Ok(ReamType::List(elements.split_off(count)))
+	},
+});
+
+/// Pair up the elements of two lists
+///
+/// `(zip <list> <list>)` returns a list of `(a b)` pairs, stopping at the
+/// end of the shorter list
+pub(super) const ZIP<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "zip",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [left, right]: [_; 2] = expect_arguments(c, a)?;
+
+		let left = expect_list(left)?;
+		let right = expect_list(right)?;
+
+		let pairs = left
+			.into_iter()
+			.zip(right)
+			.map(|(l, r)| ReamValue { span: c.loc, t: ReamType::List(Vector::from(vec![l, r])) })
+			.collect::<Vector<_>>();
+		c.context.allocate(pairs.len(), c.loc)?;
+
+		Ok(ReamType::List(pairs))
+	},
+});
+
+/// Flatten a list of lists by one level
+///
+/// `(flatten <list>)`, elements that aren't lists are kept as they are
+pub(super) const FLATTEN<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "flatten",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let mut flattened = Vector::new();
+		for element in expect_list(list)? {
+			match element.t {
+				ReamType::List(inner) => flattened.append(inner),
+				_ => flattened.push_back(element),
+			}
+		}
+
+		Ok(ReamType::List(flattened))
+	},
+});
+
+/// Find the position of a value in a list
+///
+/// `(index-of <list> <value>)` returns a `some` holding the zero-based index
+/// of the first element equal to `value`, or `none` if there is no such
+/// element
+pub(super) const INDEX_OF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "index-of",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list, value]: [_; 2] = expect_arguments(c, a)?;
+
+		let span = value.span;
+		match expect_list(list)?.iter().position(|element| element.t.equals(&value.t)) {
+			Some(i) => {
+				Ok(ReamType::Some(Box::new(ReamValue { span, t: ReamType::Integer(i as i64) })))
+			},
+			None => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Add up a list of numbers
+///
+/// `(sum <list>)`, the result is exact if every element is an integer or
+/// rational and a float otherwise, the sum of an empty list is `0`
+pub(super) const SUM<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "sum",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let span = list.span;
+		let sum = expect_numbers(list)?.into_iter().try_fold(Number::Integer(0), |acc, n| {
+			Some(match (acc, n) {
+				(Number::Integer(a), Number::Integer(b)) => integer::add(a, b)?.into(),
+				#[cfg(feature = "bigint")]
+				(a, b) if a.is_integer() && b.is_integer() => {
+					from_big(a.into_big() + b.into_big()).into()
+				},
+				(a, b) if a.is_exact() && b.is_exact() => {
+					rational::add(a.into_ratio(), b.into_ratio())?.into()
+				},
+				(a, b) => Number::Float(a.as_float() + b.as_float()),
+			})
+		});
+
+		checked(c.loc, span, sum.map(ReamType::from))
+	},
+});
+
+/// Multiply a list of numbers
+///
+/// `(product <list>)`, the result is exact if every element is an integer
+/// or rational and a float otherwise, the product of an empty list is `1`
+pub(super) const PRODUCT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "product",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let span = list.span;
+		let product = expect_numbers(list)?.into_iter().try_fold(Number::Integer(1), |acc, n| {
+			Some(match (acc, n) {
+				(Number::Integer(a), Number::Integer(b)) => integer::mul(a, b)?.into(),
+				#[cfg(feature = "bigint")]
+				(a, b) if a.is_integer() && b.is_integer() => {
+					from_big(a.into_big() * b.into_big()).into()
+				},
+				(a, b) if a.is_exact() && b.is_exact() => {
+					rational::mul(a.into_ratio(), b.into_ratio())?.into()
+				},
+				(a, b) => Number::Float(a.as_float() * b.as_float()),
+			})
+		});
+
+		checked(c.loc, span, product.map(ReamType::from))
+	},
+});
+
+/// Get the largest number in a list
+///
+/// `(maximum <list>)` returns a `some` holding the largest element, or
+/// `none` if the list is empty. Numbers are compared exactly unless one of
+/// them is a float
+pub(super) const MAXIMUM<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "maximum",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let maximum = expect_numbers(list)?
+			.into_iter()
+			.reduce(|a, b| if b.compare(&a) == Some(Ordering::Greater) { b } else { a });
+
+		Ok(some_number(maximum, c.loc))
+	},
+});
+
+/// Get the smallest number in a list
+///
+/// `(minimum <list>)` returns a `some` holding the smallest element, or
+/// `none` if the list is empty. Numbers are compared exactly unless one of
+/// them is a float
+pub(super) const MINIMUM<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "minimum",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let minimum = expect_numbers(list)?
+			.into_iter()
+			.reduce(|a, b| if b.compare(&a) == Some(Ordering::Less) { b } else { a });
+
+		Ok(some_number(minimum, c.loc))
+	},
+});
+
+/// Get the arithmetic mean of a list of numbers
+///
+/// `(average <list>)` returns a `some` holding the mean as a float, or
+/// `none` if the list is empty
+pub(super) const AVERAGE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "average",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [list]: [_; 1] = expect_arguments(c, a)?;
+
+		let numbers = expect_numbers(list)?;
+		let average = (!numbers.is_empty()).then(|| {
+			let total = numbers.iter().map(|n| n.as_float()).sum::<f64>();
+
+			Number::Float(total / numbers.len() as f64)
+		});
+
+		Ok(some_number(average, c.loc))
+	},
+});
+
+/// A number taken out of a list by one of the aggregate primitives
+enum Number {
+	Integer(i64),
+	#[cfg(feature = "bigint")]
+	BigInt(Rc<BigInt>),
+	Rational(Rational64),
+	Float(f64),
+}
+
+impl Number {
+	fn as_float(&self) -> f64 {
+		match self {
+			Self::Integer(i) => *i as f64,
+			// Unwrap is safe as converting a big integer to a float saturates
+			// to infinity instead of failing
+			#[cfg(feature = "bigint")]
+			Self::BigInt(i) => i.to_f64().unwrap(),
+			Self::Rational(r) => to_float(*r),
+			Self::Float(f) => *f,
+		}
+	}
+
+	/// Compare two numbers, exactly unless one of them is a float, in which
+	/// case they are compared as floats
+	fn compare(&self, other: &Self) -> Option<Ordering> {
+		match (self, other) {
+			(Self::Float(_), _) | (_, Self::Float(_)) => {
+				self.as_float().partial_cmp(&other.as_float())
+			},
+			// Denominators are always positive, so cross-multiplying keeps
+			// the order
+			#[cfg(feature = "bigint")]
+			(Self::BigInt(_), _) | (_, Self::BigInt(_)) => {
+				let (a_numer, a_denom) = self.to_big_fraction();
+				let (b_numer, b_denom) = other.to_big_fraction();
+
+				Some((a_numer * b_denom).cmp(&(b_numer * a_denom)))
+			},
+			_ => Some(self.to_ratio().cmp(&other.to_ratio())),
+		}
+	}
+
+	/// Get the rational representation of an integer or rational
+	fn to_ratio(&self) -> Rational64 {
+		match self {
+			Self::Integer(i) => Rational64::from_integer(*i),
+			Self::Rational(r) => *r,
+			_ => unreachable!(),
+		}
+	}
+
+	/// Get the numerator and denominator of any number but a float as big
+	/// integers
+	#[cfg(feature = "bigint")]
+	fn to_big_fraction(&self) -> (BigInt, BigInt) {
+		match self {
+			Self::Integer(i) => (BigInt::from(*i), BigInt::from(1)),
+			Self::BigInt(i) => (BigInt::clone(i), BigInt::from(1)),
+			Self::Rational(r) => (BigInt::from(*r.numer()), BigInt::from(*r.denom())),
+			Self::Float(_) => unreachable!(),
+		}
+	}
+
+	#[cfg(feature = "bigint")]
+	fn is_integer(&self) -> bool { matches!(self, Self::Integer(_) | Self::BigInt(_)) }
+
+	fn is_exact(&self) -> bool { matches!(self, Self::Integer(_) | Self::Rational(_)) }
+
+	/// Get the rational representation of an integer or rational
+	fn into_ratio(self) -> Rational64 {
+		match self {
+			Self::Integer(i) => Rational64::from_integer(i),
+			Self::Rational(r) => r,
+			_ => unreachable!(),
+		}
+	}
+
+	/// Get the big integer representation of an integer
+	#[cfg(feature = "bigint")]
+	fn into_big(self) -> BigInt {
+		match self {
+			Self::Integer(i) => BigInt::from(i),
+			Self::BigInt(i) => Rc::unwrap_or_clone(i),
+			_ => unreachable!(),
+		}
+	}
+}
+
+impl<'s> From<ReamType<'s>> for Number {
+	/// Only called on the results of exact arithmetic
+	fn from(t: ReamType<'s>) -> Self {
+		match t {
+			ReamType::Integer(i) => Self::Integer(i),
+			#[cfg(feature = "bigint")]
+			ReamType::BigInt(i) => Self::BigInt(i),
+			ReamType::Rational(r) => Self::Rational(r),
+			_ => unreachable!(),
+		}
+	}
+}
+
+impl<'s> From<Number> for ReamType<'s> {
+	fn from(n: Number) -> Self {
+		match n {
+			Number::Integer(i) => Self::Integer(i),
+			#[cfg(feature = "bigint")]
+			Number::BigInt(i) => Self::BigInt(i),
+			Number::Rational(r) => Self::Rational(r),
+			Number::Float(f) => Self::Float(f),
+		}
+	}
+}
+
+/// Wrap the result of an aggregate that is undefined for empty lists in an
+/// option
+fn some_number<'s>(n: Option<Number>, span: Span) -> ReamType<'s> {
+	match n {
+		Some(n) => ReamType::Some(Box::new(ReamValue { span, t: n.into() })),
+		None => ReamType::None,
+	}
+}
+
+/// Take apart a value that should be a list of numbers
+fn expect_numbers(value: ReamValue<'_>) -> Result<Vec<Number>, EvalError> {
+	expect_list(value)?
+		.into_iter()
+		.map(|element| {
+			match element.t {
+				ReamType::Integer(i) => Ok(Number::Integer(i)),
+				#[cfg(feature = "bigint")]
+				ReamType::BigInt(i) => Ok(Number::BigInt(i)),
+				ReamType::Rational(r) => Ok(Number::Rational(r)),
+				ReamType::Float(f) => Ok(Number::Float(f)),
+				t => {
+					Err(EvalError::WrongType {
+						loc:      element.span,
+						expected: "Integer or Rational or Float".to_string(),
+						found:    t.type_name(),
+					})
+				},
+			}
+		})
+		.collect()
+}
+
+/// Take apart a value that should be a list
+fn expect_list(value: ReamValue<'_>) -> Result<Vector<ReamValue<'_>>, EvalError> {
+	match value.t {
+		ReamType::List(elements) => Ok(elements),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "List".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be a non-empty list into its first element
+/// and the rest
+fn expect_non_empty(
+	value: ReamValue<'_>,
+) -> Result<(ReamValue<'_>, Vector<ReamValue<'_>>), EvalError> {
+	let span = value.span;
+	let mut elements = expect_list(value)?;
+
+	match elements.pop_front() {
+		Some(head) => Ok((head, elements)),
+		None => {
+			Err(EvalError::WrongType {
+				loc:      span,
+				expected: "non-empty List".to_string(),
+				found:    "empty List".to_string(),
+			})
+		},
+	}
+}
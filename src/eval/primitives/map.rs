@@ -0,0 +1,117 @@
+use im_rc::OrdMap;
+
+use super::expect_arguments;
+use crate::eval::key::Key;
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Create a map holding the given keys and values
+///
+/// `(make-map <entry>*)` where entry is `<key> <value>`, later entries
+/// replace earlier ones with an equal key
+pub(super) const MAKE_MAP<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "make-map",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if a.len() % 2 != 0 {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: a.len() + 1,
+				found:    a.len(),
+			});
+		}
+		c.context.allocate(a.len() / 2, c.loc)?;
+
+		let mut map = OrdMap::new();
+		let mut a = a.into_iter();
+		while let (Some(key), Some(value)) = (a.next(), a.next()) {
+			map.insert(Key::new(key)?, value);
+		}
+
+		Ok(ReamType::Map(map))
+	},
+});
+
+/// Look up the value associated with a key in a map
+///
+/// `(map-get <map> <key>)` returns a `some` holding the value, or `none` if
+/// the map doesn't contain the key
+pub(super) const MAP_GET<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "map-get",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [map, key]: [_; 2] = expect_arguments(c, a)?;
+
+		let mut map = expect_map(map)?;
+
+		match map.remove(&Key::new(key)?) {
+			Some(value) => Ok(ReamType::Some(Box::new(value))),
+			None => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Associate a value with a key in a map
+///
+/// `(map-set <map> <key> <value>)` returns a new map, replacing the value of
+/// an equal key if there is one, the original map is left unchanged
+pub(super) const MAP_SET<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "map-set",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [map, key, value]: [_; 3] = expect_arguments(c, a)?;
+
+		let map = expect_map(map)?;
+		c.context.allocate(1, c.loc)?;
+
+		Ok(ReamType::Map(map.update(Key::new(key)?, value)))
+	},
+});
+
+/// Remove a key and its value from a map
+///
+/// `(map-remove <map> <key>)` returns a new map, the original map is left
+/// unchanged
+pub(super) const MAP_REMOVE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "map-remove",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [map, key]: [_; 2] = expect_arguments(c, a)?;
+
+		let map = expect_map(map)?;
+
+		Ok(ReamType::Map(map.without(&Key::new(key)?)))
+	},
+});
+
+/// Get the keys of a map
+///
+/// `(map-keys <map>)` returns a list of the keys, in order
+pub(super) const MAP_KEYS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "map-keys",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [map]: [_; 1] = expect_arguments(c, a)?;
+
+		let map = expect_map(map)?;
+		c.context.allocate(map.len(), c.loc)?;
+
+		Ok(ReamType::List(map.keys().map(|k| k.value().clone()).collect()))
+	},
+});
+
+/// Take apart a value that should be a map
+fn expect_map(value: ReamValue<'_>) -> Result<OrdMap<Key<'_>, ReamValue<'_>>, EvalError> {
+	match value.t {
+		ReamType::Map(map) => Ok(map),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Map".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
@@ -0,0 +1,931 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Write;
+
+use super::value::{CallContext, Primitive, ReamString, ReamType, ReamValue};
+use crate::eval::key::Key;
+use crate::eval::Capability;
+use crate::{Combine, EvalError, Span};
+
+macro_rules! count {
+    () => (0usize);
+    ( $x:tt $($xs:tt)* ) => (1usize + count!($($xs)*));
+}
+
+macro_rules! generate_primitive {
+	(
+		$prim_vis:vis $prim_name:ident $name:literal [$capability:ident]
+		$( overload $protocol:literal $( => $convert:ident )? )?
+		($($argument:ident),*) => {
+		$(
+			$( #[$attr:meta] )*
+			($( $argument_matcher:pat ),+) $( if $guard:expr )? => Ok($result:expr)
+		),+
+
+		$(
+			$( #[$err_attr:meta] )*
+			( $( $error_matcher:pat_param ),+ ) => Err($err_result:expr)
+		),*
+	}) => {
+		// #[rustfmt::skip]
+		$prim_vis const $prim_name<'s>: ReamType<'s> = ReamType::Primitive::<'s>(Primitive {
+			name: $name,
+			capability: Capability::$capability,
+			f: |c, a| {
+				const __EXPECTED_ARG_COUNT: usize = count!($( $argument )*);
+				let __given_arg_count = a.len();
+
+				if __EXPECTED_ARG_COUNT != a.len() {
+					return Err(EvalError::WrongArgumentCount {
+						loc:      c.loc,
+						callee:   c.callee.to_string(),
+						expected: __EXPECTED_ARG_COUNT,
+						found:    __given_arg_count,
+					});
+				}
+
+				$(
+					if let Some(result) = c.overload($protocol, &a) {
+						return result $( .and_then(|r| $convert(c, r)) )?;
+					}
+				)?
+
+				let [$( $argument ),*]: [_; __EXPECTED_ARG_COUNT] = a.try_into().unwrap();
+
+				#[allow(unused_parens)]
+				match ($( $argument.t ),*) {
+					$(
+						$( #[$attr] )*
+						($( $argument_matcher ),+) $( if $guard )? => {
+							Ok::<ReamType, EvalError>($result)
+						},
+					)+
+
+					$(
+						$( #[$err_attr] )*
+						($( $error_matcher ),+ ) => {
+							Err::<ReamType, EvalError>($err_result)
+						},
+					)*
+				}
+			},
+		});
+	};
+}
+
+mod adt;
+#[cfg(feature = "time")]
+mod date;
+mod env;
+mod fs;
+mod function;
+mod integer;
+mod iter;
+mod list;
+mod map;
+mod net;
+mod option;
+mod process;
+mod rational;
+#[cfg(feature = "regex")]
+mod regex;
+mod result;
+mod set;
+mod string;
+mod tuple;
+mod vector;
+
+use adt::*;
+#[cfg(feature = "time")]
+use date::*;
+use env::*;
+use fs::*;
+use function::*;
+#[cfg(feature = "bigint")]
+use integer::{from_big, is_integer, to_big};
+use iter::*;
+use list::*;
+use map::*;
+use net::*;
+use option::*;
+use process::*;
+use rational::{is_exact, to_ratio};
+#[cfg(feature = "regex")]
+use regex::*;
+use result::*;
+use set::*;
+use string::*;
+use tuple::*;
+use vector::*;
+
+/// Turn the result of an arithmetic operation that returns [`None`] on
+/// overflow into an [`EvalError::ArithmeticOverflow`] spanning the operands
+/// from `first` up to `last`
+fn checked(
+	first: Span,
+	last: Span,
+	result: Option<ReamType<'_>>,
+) -> Result<ReamType<'_>, EvalError> {
+	result.ok_or_else(|| EvalError::ArithmeticOverflow { loc: first.combine(&last) })
+}
+
+/// Divide two integers or rationals, failing if the divisor is zero or the
+/// result overflows
+fn divide<'s>(
+	dividend: Span,
+	divisor: Span,
+	x: ReamType<'s>,
+	y: ReamType<'s>,
+) -> Result<ReamType<'s>, EvalError> {
+	// Big integers and rationals are never zero, so this is the only zero
+	if let ReamType::Integer(0) = y {
+		return Err(EvalError::DivisionByZero { loc: divisor });
+	}
+
+	let result = match (x, y) {
+		(ReamType::Integer(x), ReamType::Integer(y)) => rational::divide(x, y),
+		// Rationals too large for an `i64` overflow, just like integers do
+		// without the `bigint` feature
+		#[cfg(feature = "bigint")]
+		(x, y) if is_integer(&x) && is_integer(&y) => integer::div_big(to_big(x), to_big(y)),
+		(x, y) => rational::div(to_ratio(x), to_ratio(y)),
+	};
+
+	checked(dividend, divisor, result)
+}
+
+generate_primitive! {
+	pub(super) ADD "+" [Pure] overload "add" (a, b) => {
+		(ReamType::Integer(x), ReamType::Integer(y)) => Ok(
+			checked(a.span, b.span, integer::add(x, y))?
+		),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(from_big(to_big(a) + to_big(b))),
+		(x, y) if is_exact(&x) && is_exact(&y) => Ok(
+			checked(a.span, b.span, rational::add(to_ratio(x), to_ratio(y)))?
+		),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Float(a + b))
+
+		(a_t @ ReamType::Integer(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Float(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		#[cfg(feature = "bigint")]
+		(a_t @ ReamType::BigInt(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Rational(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) SUB "-" [Pure] overload "sub" (a, b) => {
+		(ReamType::Integer(x), ReamType::Integer(y)) => Ok(
+			checked(a.span, b.span, integer::sub(x, y))?
+		),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(from_big(to_big(a) - to_big(b))),
+		(x, y) if is_exact(&x) && is_exact(&y) => Ok(
+			checked(a.span, b.span, rational::sub(to_ratio(x), to_ratio(y)))?
+		),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Float(a - b))
+
+		(a_t @ ReamType::Integer(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Float(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		#[cfg(feature = "bigint")]
+		(a_t @ ReamType::BigInt(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Rational(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) MUL "*" [Pure] overload "mul" (a, b) => {
+		(ReamType::Integer(x), ReamType::Integer(y)) => Ok(
+			checked(a.span, b.span, integer::mul(x, y))?
+		),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(from_big(to_big(a) * to_big(b))),
+		(x, y) if is_exact(&x) && is_exact(&y) => Ok(
+			checked(a.span, b.span, rational::mul(to_ratio(x), to_ratio(y)))?
+		),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Float(a * b))
+
+		(a_t @ ReamType::Integer(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Float(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		#[cfg(feature = "bigint")]
+		(a_t @ ReamType::BigInt(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Rational(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) DIV "/" [Pure] overload "div" (a, b) => {
+		(x, y) if is_exact(&x) && is_exact(&y) => Ok(divide(a.span, b.span, x, y)?),
+		#[cfg(feature = "bigint")]
+		(x, y) if is_integer(&x) && is_integer(&y) => Ok(divide(a.span, b.span, x, y)?),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Float(a / b))
+
+		(a_t @ ReamType::Integer(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Float(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		#[cfg(feature = "bigint")]
+		(a_t @ ReamType::BigInt(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+		(a_t @ ReamType::Rational(_), b_t) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: a_t.type_name(),
+			found: b_t.type_name(),
+		}),
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) EQU "==" [Pure] overload "equals" (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a == b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) == to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) == to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a == b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(true))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) NEQ "!=" [Pure] overload "equals" => not (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a != b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) != to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) != to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a != b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(false))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) GT ">" [Pure] overload "compare" => is_greater (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a & !b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a > b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) > to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) > to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a > b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a > b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a > b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a > b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a > b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(false))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) GTE ">=" [Pure] overload "compare" => is_greater_or_equal (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a >= b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) >= to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) >= to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a >= b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(false))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) LT "<" [Pure] overload "compare" => is_less (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a & !b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a < b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) < to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) < to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a < b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a < b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a < b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a < b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a < b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(false))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) LTE "<=" [Pure] overload "compare" => is_less_or_equal (a, b) => {
+		(ReamType::Boolean(a), ReamType::Boolean(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::Integer(a), ReamType::Integer(b)) => Ok(ReamType::Boolean(a <= b)),
+		#[cfg(feature = "bigint")]
+		(a, b) if is_integer(&a) && is_integer(&b) => Ok(ReamType::Boolean(to_big(a) <= to_big(b))),
+		(a, b) if is_exact(&a) && is_exact(&b) => Ok(ReamType::Boolean(to_ratio(a) <= to_ratio(b))),
+		(ReamType::Float(a), ReamType::Float(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::Character(a), ReamType::Character(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::String(a), ReamType::String(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::Identifier(a), ReamType::Identifier(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::Atom(a), ReamType::Atom(b)) => Ok(ReamType::Boolean(a <= b)),
+		(ReamType::Unit, ReamType::Unit) => Ok(ReamType::Boolean(false))
+
+		(a_t, _) => Err(EvalError::WrongType {
+			loc: b.span,
+			expected: "Boolean or Integer or Rational or Float or Character or String or \
+					   Identifier or Atom or Unit".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) IS_NAN "nan?" [Pure] (a) => {
+		(ReamType::Float(a)) => Ok(ReamType::Boolean(a.is_nan())),
+		(ReamType::Integer(_) | ReamType::Rational(_)) => Ok(ReamType::Boolean(false)),
+		#[cfg(feature = "bigint")]
+		(ReamType::BigInt(_)) => Ok(ReamType::Boolean(false))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) IS_INFINITE "infinite?" [Pure] (a) => {
+		(ReamType::Float(a)) => Ok(ReamType::Boolean(a.is_infinite())),
+		(ReamType::Integer(_) | ReamType::Rational(_)) => Ok(ReamType::Boolean(false)),
+		#[cfg(feature = "bigint")]
+		(ReamType::BigInt(_)) => Ok(ReamType::Boolean(false))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) IS_FINITE "finite?" [Pure] (a) => {
+		(ReamType::Float(a)) => Ok(ReamType::Boolean(a.is_finite())),
+		(ReamType::Integer(_) | ReamType::Rational(_)) => Ok(ReamType::Boolean(true)),
+		#[cfg(feature = "bigint")]
+		(ReamType::BigInt(_)) => Ok(ReamType::Boolean(true))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Integer or Rational or Float".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+/// Hash a value
+///
+/// `(hash <value>)`, values that are equal as keys always have the same hash,
+/// which stays the same between runs
+pub(super) const HASH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "hash",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		let mut hasher = DefaultHasher::new();
+		Key::new(value)?.hash(&mut hasher);
+
+		Ok(ReamType::Integer(hasher.finish() as i64))
+	},
+});
+
+/// Check if two values are structurally equal
+///
+/// `(equal? <a> <b>)`, collections are equal if they contain equal values,
+/// both values have to stay within the value limits of the interpreter
+pub(super) const EQUAL<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "equal?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [a, b]: [_; 2] = expect_arguments(c, a)?;
+
+		let limits = c.context.value_limits.get();
+		limits.check(&a.t, c.loc)?;
+		limits.check(&b.t, c.loc)?;
+
+		Ok(ReamType::Boolean(a.t.equals(&b.t)))
+	},
+});
+
+pub(super) const PRINT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "print",
+	capability: Capability::Io,
+	f:          |c, a| {
+		let [a]: [_; 1] = expect_arguments(c, a)?;
+		c.context.value_limits.get().check(&a.t, c.loc)?;
+
+		// Render before taking hold of the output, printers may print as well
+		let rendered = c.render(&a)?;
+
+		writeln!(c.context.output.borrow_mut().0, "{rendered}")
+			.map_err(|e| EvalError::OutputFailed { loc: c.loc, message: e.to_string() })?;
+
+		Ok(ReamType::Unit)
+	},
+});
+
+/// Register a function used to display values of a given type
+///
+/// `(define-printer <type> <printer>)` where type is an atom or string
+/// naming the type, and printer is a function taking a value of that type
+/// and returning a string
+pub(super) const DEFINE_PRINTER<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "define-printer",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [type_name, printer]: [_; 2] = expect_arguments(c, a)?;
+
+		let type_name = match type_name.t {
+			ReamType::Atom(a) => a.trim_start_matches(':').to_string(),
+			ReamType::String(s) => s.to_string(),
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      type_name.span,
+					expected: "Atom or String".to_string(),
+					found:    t.type_name(),
+				});
+			},
+		};
+
+		match printer.t {
+			ReamType::Primitive(_)
+			| ReamType::Host(_)
+			| ReamType::Function { .. }
+			| ReamType::Closure { .. } => {
+				c.context.printers.borrow_mut().insert(type_name, printer);
+
+				Ok(ReamType::Unit)
+			},
+			t => {
+				Err(EvalError::WrongType {
+					loc:      printer.span,
+					expected: "Function".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Abort evaluation with an error carrying the given value as its payload
+///
+/// `(error <payload>)`
+pub(super) const ERROR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "error",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [payload]: [_; 1] = expect_arguments(c, a)?;
+		c.context.value_limits.get().check(&payload.t, c.loc)?;
+
+		Err(EvalError::Raised { loc: c.loc, payload: c.to_value(&payload)? })
+	},
+});
+
+/// Make sure a primitive received exactly `N` arguments and return them as
+/// an array
+fn expect_arguments<'s, const N: usize>(
+	c: &CallContext<'s>,
+	a: Vec<ReamValue<'s>>,
+) -> Result<[ReamValue<'s>; N], EvalError> {
+	let found = a.len();
+
+	a.try_into().map_err(|_| {
+		EvalError::WrongArgumentCount {
+			loc: c.loc,
+			callee: c.callee.to_string(),
+			expected: N,
+			found,
+		}
+	})
+}
+
+/// Take apart a value that should be a string
+fn expect_string(value: ReamValue<'_>) -> Result<ReamString<'_>, EvalError> {
+	match value.t {
+		ReamType::String(s) => Ok(s),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "String".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be an index or element count
+fn expect_count(value: ReamValue<'_>) -> Result<usize, EvalError> {
+	match value.t {
+		ReamType::Integer(i) => {
+			usize::try_from(i).map_err(|_| {
+				EvalError::WrongType {
+					loc:      value.span,
+					expected: "non-negative Integer".to_string(),
+					found:    i.to_string(),
+				}
+			})
+		},
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Integer".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be an index into a collection of `len`
+/// elements, or the end of one if `inclusive`
+///
+/// Every indexing primitive goes through here, so they all agree that
+/// indices are zero-based and that negative ones count back from the end,
+/// -1 being the last element. Integers outside of the collection are
+/// reported as an [`EvalError::IndexOutOfBounds`] at the call site
+fn expect_index(
+	c: &CallContext<'_>,
+	value: ReamValue<'_>,
+	len: usize,
+	inclusive: bool,
+) -> Result<usize, EvalError> {
+	let index = match value.t {
+		ReamType::Integer(i) => i,
+		t => {
+			return Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Integer".to_string(),
+				found:    t.type_name(),
+			});
+		},
+	};
+
+	let from_start = if index < 0 { len as i64 + index } else { index };
+	let end = if inclusive { len as i64 } else { len as i64 - 1 };
+
+	if !(0..=end).contains(&from_start) {
+		return Err(EvalError::IndexOutOfBounds { loc: c.loc, index, len });
+	}
+
+	Ok(from_start as usize)
+}
+
+/// Wrap the result of a fallible operation in an `ok` or `err` value, where an
+/// error becomes a string holding its message
+fn io_result<'s, E: Display>(c: &CallContext<'s>, result: Result<ReamType<'s>, E>) -> ReamType<'s> {
+	match result {
+		Ok(t) => ReamType::Ok(Box::new(ReamValue { span: c.loc, t })),
+		Err(e) => {
+			ReamType::Err(Box::new(ReamValue {
+				span: c.loc,
+				t:    ReamType::String(e.to_string().into()),
+			}))
+		},
+	}
+}
+
+/// Negate the result of an `equals` implementation
+fn not<'s>(c: &CallContext<'s>, result: ReamType<'s>) -> Result<ReamType<'s>, EvalError> {
+	match result {
+		ReamType::Boolean(b) => Ok(ReamType::Boolean(!b)),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      c.loc,
+				expected: "Boolean".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Interpret the result of a `compare` implementation, which should be one
+/// of the atoms `:less`, `:equal` or `:greater`
+fn ordering<'s>(c: &CallContext<'s>, result: ReamType<'s>) -> Result<Ordering, EvalError> {
+	match result {
+		ReamType::Atom(":less") => Ok(Ordering::Less),
+		ReamType::Atom(":equal") => Ok(Ordering::Equal),
+		ReamType::Atom(":greater") => Ok(Ordering::Greater),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      c.loc,
+				expected: "one of `:less`, `:equal`, `:greater`".to_string(),
+				found:    t.to_string(),
+			})
+		},
+	}
+}
+
+fn is_greater<'s>(c: &CallContext<'s>, result: ReamType<'s>) -> Result<ReamType<'s>, EvalError> {
+	Ok(ReamType::Boolean(ordering(c, result)?.is_gt()))
+}
+
+fn is_greater_or_equal<'s>(
+	c: &CallContext<'s>,
+	result: ReamType<'s>,
+) -> Result<ReamType<'s>, EvalError> {
+	Ok(ReamType::Boolean(ordering(c, result)?.is_ge()))
+}
+
+fn is_less<'s>(c: &CallContext<'s>, result: ReamType<'s>) -> Result<ReamType<'s>, EvalError> {
+	Ok(ReamType::Boolean(ordering(c, result)?.is_lt()))
+}
+
+fn is_less_or_equal<'s>(
+	c: &CallContext<'s>,
+	result: ReamType<'s>,
+) -> Result<ReamType<'s>, EvalError> {
+	Ok(ReamType::Boolean(ordering(c, result)?.is_le()))
+}
+
+/// Every protocol the arithmetic and comparison primitives fall back to
+/// when their first operand isn't a built-in value
+///
+/// Each protocol has a single method with the same name taking two
+/// arguments
+pub(super) const OPERATOR_PROTOCOLS: [&str; 6] = ["add", "sub", "mul", "div", "equals", "compare"];
+
+/// Every protocol the iteration primitives fall back to when their argument
+/// isn't a built-in collection or iterator
+///
+/// Each protocol has a single method with the same name taking one argument,
+/// unlike the operator protocols their method is not bound in the global
+/// scope as the primitive of the same name already is
+pub(super) const ITERATION_PROTOCOLS: [&str; 2] = ["iter", "next"];
+
+/// Every value bound in the global scope that isn't a primitive
+pub(super) fn constants<'s>() -> Vec<(&'static str, ReamType<'s>)> {
+	vec![("none", ReamType::None)]
+}
+
+/// Every primitive
+pub(super) fn primitives<'s>() -> Vec<ReamType<'s>> {
+	#[allow(unused_mut)]
+	let mut primitives = vec![
+		ADD,
+		SUB,
+		MUL,
+		DIV,
+		EQU,
+		NEQ,
+		GT,
+		GTE,
+		LT,
+		LTE,
+		IS_NAN,
+		IS_INFINITE,
+		IS_FINITE,
+		HASH,
+		EQUAL,
+		PRINT,
+		DEFINE_PRINTER,
+		ERROR,
+		OK,
+		ERR,
+		IS_OK,
+		IS_ERR,
+		UNWRAP,
+		UNWRAP_OR,
+		PARSE_NUMBER,
+		READ_FILE,
+		SOME,
+		IS_SOME,
+		IS_NONE,
+		MAP_OPTION,
+		OR_ELSE,
+		IDENTITY,
+		CONSTANTLY,
+		COMPOSE,
+		PARTIAL,
+		FLIP,
+		LIST,
+		CONS,
+		CAR,
+		CDR,
+		APPEND,
+		LENGTH,
+		SORT,
+		ASSOC,
+		NTH,
+		LIST_REF,
+		LAST,
+		TAKE,
+		DROP,
+		ZIP,
+		FLATTEN,
+		INDEX_OF,
+		SUM,
+		PRODUCT,
+		MAXIMUM,
+		MINIMUM,
+		AVERAGE,
+		TUPLE,
+		TUPLE_REF,
+		VECTOR,
+		VECTOR_REF,
+		VECTOR_SET,
+		VECTOR_LENGTH,
+		MAKE_MAP,
+		MAP_GET,
+		MAP_SET,
+		MAP_REMOVE,
+		MAP_KEYS,
+		SET,
+		SET_ADD,
+		SET_MEMBER,
+		SET_UNION,
+		SET_INTERSECT,
+		GET,
+		WITH,
+		IS_VARIANT,
+		SB,
+		SB_PUSH,
+		SB_BUILD,
+		STRING_LENGTH,
+		STRING_APPEND,
+		STRING_REF,
+		STRING_SLICE,
+		SUBSTRING,
+		STRING_SPLIT,
+		STRING_LENGTH_GRAPHEMES,
+		STRING_CHARS,
+		STRING_TO_LIST,
+		STRING_GRAPHEMES,
+		STRING_UPCASE,
+		STRING_DOWNCASE,
+		STRING_FOLDCASE,
+		STRING_NFC,
+		STRING_NFD,
+		STRING_PAD_LEFT,
+		STRING_PAD_RIGHT,
+		STRING_JUSTIFY,
+		STRING_REPEAT,
+		CHAR_ALPHABETIC,
+		CHAR_NUMERIC,
+		CHAR_WHITESPACE,
+		CHAR_UPCASE,
+		CHAR_DOWNCASE,
+		CHAR_TO_INTEGER,
+		INTEGER_TO_CHAR,
+		GETENV,
+		SETENV,
+		CWD,
+		PLATFORM,
+		SCRIPT_PATH,
+		ARGS,
+		RUN_COMMAND,
+		TCP_CONNECT,
+		TCP_LISTEN,
+		TCP_ACCEPT,
+		SOCKET_READ,
+		SOCKET_WRITE,
+		SOCKET_CLOSE,
+		RANGE,
+		ITER,
+		NEXT,
+	];
+
+	#[cfg(feature = "regex")]
+	primitives.extend([REGEX, RE_IS_MATCH, RE_FIND, RE_CAPTURES, RE_REPLACE]);
+
+	#[cfg(feature = "time")]
+	primitives.extend([
+		DATE_NOW,
+		DATE_FORMAT,
+		DATE_PARSE,
+		DURATION_MILLIS,
+		DURATION_TO_MILLIS,
+		DATE_ADD,
+		DATE_SUB,
+		DATE_DIFF,
+	]);
+
+	primitives
+}
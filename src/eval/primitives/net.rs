@@ -0,0 +1,314 @@
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::rc::Rc;
+
+use super::{expect_arguments, expect_count, expect_string, io_result};
+use crate::eval::value::{Primitive, ReamType, ReamValue, Socket};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// The largest number of bytes read from a socket at once, no matter how many
+/// were asked for
+const READ_LIMIT: usize = 64 * 1024;
+
+/// Open a TCP connection
+///
+/// `(tcp-connect <host> <port>)` returns an `ok` holding the socket, or an
+/// `err` holding a message describing why the connection failed
+pub(super) const TCP_CONNECT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "tcp-connect",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [host, port]: [_; 2] = expect_arguments(c, a)?;
+
+		let host = expect_string(host)?;
+		let port = expect_port(port)?;
+
+		let socket = TcpStream::connect((host.as_ref(), port));
+
+		Ok(io_result(c, socket.map(|s| ReamType::Socket(Rc::new(Socket::new(s))))))
+	},
+});
+
+/// Listen for TCP connections on a port of every local interface
+///
+/// `(tcp-listen <port>)` returns an `ok` holding the listener, or an `err`
+/// holding a message describing why the port couldn't be bound
+pub(super) const TCP_LISTEN<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "tcp-listen",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [port]: [_; 1] = expect_arguments(c, a)?;
+
+		let port = expect_port(port)?;
+
+		let listener = TcpListener::bind(("0.0.0.0", port));
+
+		Ok(io_result(c, listener.map(|l| ReamType::Listener(Rc::new(l)))))
+	},
+});
+
+/// Wait for a connection on a listener
+///
+/// `(tcp-accept <listener>)` returns an `ok` holding the socket of the new
+/// connection, or an `err` holding a message describing what went wrong
+pub(super) const TCP_ACCEPT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "tcp-accept",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [listener]: [_; 1] = expect_arguments(c, a)?;
+
+		let ReamType::Listener(listener) = listener.t else {
+			return Err(EvalError::WrongType {
+				loc:      listener.span,
+				expected: "Listener".to_string(),
+				found:    listener.t.type_name(),
+			});
+		};
+
+		let socket = listener.accept().map(|(s, _)| ReamType::Socket(Rc::new(Socket::new(s))));
+
+		Ok(io_result(c, socket))
+	},
+});
+
+/// Read from a socket
+///
+/// `(socket-read <socket> <count>)` reads at most `count` bytes, up to
+/// [`READ_LIMIT`] at a time, returning an `ok` holding them as a string, which
+/// is empty once the other end closed the connection, or an `err` holding a
+/// message describing what went wrong
+///
+/// A character cut off at the end of a read is held back and completed by
+/// the next one, so a read only comes back empty when the connection closed
+pub(super) const SOCKET_READ<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "socket-read",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [socket, count]: [_; 2] = expect_arguments(c, a)?;
+
+		let socket = expect_socket(socket)?;
+		let mut buffer = vec![0; expect_count(count)?.min(READ_LIMIT)];
+		let mut pending = socket.pending.borrow_mut();
+
+		let read = loop {
+			match (&socket.stream).read(&mut buffer) {
+				// Whatever is left of a cut off character won't be completed
+				Ok(0) => break Ok(String::from_utf8_lossy(&std::mem::take(&mut *pending)).into()),
+				Ok(n) => {
+					pending.extend_from_slice(&buffer[..n]);
+
+					let complete = pending.len() - incomplete_suffix(&pending);
+					if complete > 0 {
+						let string = String::from_utf8_lossy(&pending[..complete]).into_owned();
+						pending.drain(..complete);
+
+						break Ok(string);
+					}
+				},
+				Err(e) => break Err(e),
+			}
+		};
+
+		Ok(io_result(c, read.map(|s| ReamType::String(s.into()))))
+	},
+});
+
+/// Write a string to a socket
+///
+/// `(socket-write <socket> <string>)` returns an `ok` holding unit once the
+/// entire string was written, or an `err` holding a message describing what
+/// went wrong
+pub(super) const SOCKET_WRITE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "socket-write",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [socket, string]: [_; 2] = expect_arguments(c, a)?;
+
+		let socket = expect_socket(socket)?;
+		let string = expect_string(string)?;
+
+		Ok(io_result(c, (&socket.stream).write_all(string.as_bytes()).map(|_| ReamType::Unit)))
+	},
+});
+
+/// Close both directions of a socket
+///
+/// `(socket-close <socket>)`
+pub(super) const SOCKET_CLOSE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "socket-close",
+	capability: Capability::Net,
+	f:          |c, a| {
+		let [socket]: [_; 1] = expect_arguments(c, a)?;
+
+		let socket = expect_socket(socket)?;
+
+		Ok(io_result(c, socket.stream.shutdown(Shutdown::Both).map(|_| ReamType::Unit)))
+	},
+});
+
+/// Count the bytes at the end of `bytes` that start a character whose
+/// remaining bytes haven't been read yet
+fn incomplete_suffix(bytes: &[u8]) -> usize {
+	for back in 1..=bytes.len().min(3) {
+		let width = match bytes[bytes.len() - back] {
+			// A continuation byte, the character starts further back
+			0x80..=0xBF => continue,
+			0xC0..=0xDF => 2,
+			0xE0..=0xEF => 3,
+			0xF0..=0xF7 => 4,
+			_ => return 0,
+		};
+
+		return if width > back { back } else { 0 };
+	}
+
+	0
+}
+
+/// Take apart a value that should be a port number
+fn expect_port(value: ReamValue<'_>) -> Result<u16, EvalError> {
+	match value.t {
+		ReamType::Integer(port) if (0..=u16::MAX as i64).contains(&port) => Ok(port as u16),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "port number".to_string(),
+				found:    t.to_string(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be a socket
+fn expect_socket(value: ReamValue<'_>) -> Result<Rc<Socket>, EvalError> {
+	match value.t {
+		ReamType::Socket(socket) => Ok(socket),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Socket".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::TcpListener;
+
+	use crate::{parse_str, Capabilities, Capability, EvalError, Interpreter, Value};
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		Interpreter::new(Capabilities::ALL).run(parse_str(source).unwrap())
+	}
+
+	fn string(s: &str) -> Value { Value::String(s.to_string()) }
+
+	#[test]
+	fn sockets_talk_over_loopback() {
+		// Bind and release a port the OS considers free
+		let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+		let value = run(&format!(
+			"(let listener (unwrap (tcp-listen {port})))
+			(let client (unwrap (tcp-connect \"127.0.0.1\" {port})))
+			(let server (unwrap (tcp-accept listener)))
+			(unwrap (socket-write client \"ping\"))
+			(let received (unwrap (socket-read server 4)))
+			(unwrap (socket-write server \"pong\"))
+			(unwrap (socket-close server))
+			(list received (socket-read client 16) (socket-read client 16))\n"
+		));
+
+		assert_eq!(
+			value.unwrap(),
+			Value::List(vec![
+				string("ping"),
+				Value::Ok(Box::new(string("pong"))),
+				Value::Ok(Box::new(string(""))),
+			])
+		);
+	}
+
+	#[test]
+	fn characters_cut_off_by_a_read_are_completed() {
+		let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+		// `é` takes two bytes, so reading one or two bytes at a time cuts it off
+		let value = run(&format!(
+			"(let listener (unwrap (tcp-listen {port})))
+			(let client (unwrap (tcp-connect \"127.0.0.1\" {port})))
+			(let server (unwrap (tcp-accept listener)))
+			(unwrap (socket-write client \"é\"))
+			(unwrap (socket-write client \"aé\"))
+			(list (socket-read server 1) (socket-read server 2) (socket-read server 2))\n"
+		));
+
+		assert_eq!(
+			value.unwrap(),
+			Value::List(vec![
+				Value::Ok(Box::new(string("é"))),
+				Value::Ok(Box::new(string("a"))),
+				Value::Ok(Box::new(string("é"))),
+			])
+		);
+	}
+
+	#[test]
+	fn huge_reads_are_capped() {
+		let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+		let value = run(&format!(
+			"(let listener (unwrap (tcp-listen {port})))
+			(let client (unwrap (tcp-connect \"127.0.0.1\" {port})))
+			(let server (unwrap (tcp-accept listener)))
+			(unwrap (socket-write client \"ping\"))
+			(socket-read server {})\n",
+			i64::MAX
+		));
+
+		assert_eq!(value.unwrap(), Value::Ok(Box::new(string("ping"))));
+	}
+
+	#[test]
+	fn failed_connections_are_errors() {
+		let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+		let value = run(&format!("(tcp-connect \"127.0.0.1\" {port})\n")).unwrap();
+
+		assert!(matches!(value, Value::Err(_)), "{value:?}");
+	}
+
+	#[test]
+	fn ports_must_be_in_range() {
+		let sources =
+			["(tcp-listen -1)", "(tcp-listen 65536)", "(tcp-connect \"localhost\" \"80\")"];
+
+		for source in sources {
+			let result = run(source);
+
+			assert!(matches!(result, Err(EvalError::WrongType { .. })), "{source} gave {result:?}");
+		}
+	}
+
+	#[test]
+	fn sockets_must_be_sockets() {
+		let sources = ["(socket-read 1 4)", "(socket-write \"socket\" \"x\")", "(tcp-accept 1)"];
+
+		for source in sources {
+			let result = run(source);
+
+			assert!(matches!(result, Err(EvalError::WrongType { .. })), "{source} gave {result:?}");
+		}
+	}
+
+	#[test]
+	fn sockets_require_the_net_capability() {
+		let result = Interpreter::new(Capabilities::ALL.without(Capability::Net))
+			.run(parse_str("(tcp-listen 0)").unwrap());
+
+		assert!(matches!(result, Err(EvalError::CapabilityDenied { .. })), "{result:?}");
+	}
+}
@@ -0,0 +1,95 @@
+use super::expect_arguments;
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Wrap a value in an option
+///
+/// `(some <value>)`
+pub(super) const SOME<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "some",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Some(Box::new(value)))
+	},
+});
+
+generate_primitive! {
+	pub(super) IS_SOME "some?" [Pure] (a) => {
+		(ReamType::Some(_)) => Ok(ReamType::Boolean(true)),
+		(ReamType::None) => Ok(ReamType::Boolean(false))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Option".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) IS_NONE "none?" [Pure] (a) => {
+		(ReamType::Some(_)) => Ok(ReamType::Boolean(false)),
+		(ReamType::None) => Ok(ReamType::Boolean(true))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Option".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+/// Apply a function to the value inside an option, if there is one
+///
+/// `(map-option <option> <function>)`
+pub(super) const MAP_OPTION<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "map-option",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [option, function]: [_; 2] = expect_arguments(c, a)?;
+
+		match option.t {
+			ReamType::Some(v) => {
+				let span = v.span;
+				let mapped = c.call(function, vec![*v])?;
+
+				Ok(ReamType::Some(Box::new(ReamValue { span, t: mapped })))
+			},
+			ReamType::None => Ok(ReamType::None),
+			t => {
+				Err(EvalError::WrongType {
+					loc:      option.span,
+					expected: "Option".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Get the value inside an option, or call a function without arguments to
+/// produce a fallback if there is none
+///
+/// `(or-else <option> <function>)`
+pub(super) const OR_ELSE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "or-else",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [option, function]: [_; 2] = expect_arguments(c, a)?;
+
+		match option.t {
+			ReamType::Some(v) => Ok(v.t),
+			ReamType::None => c.call(function, vec![]),
+			t => {
+				Err(EvalError::WrongType {
+					loc:      option.span,
+					expected: "Option".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
@@ -0,0 +1,111 @@
+use std::process::Command;
+
+use super::{expect_arguments, expect_string};
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Run a command and wait for it to finish
+///
+/// `(run-command <program> <arguments>)` where arguments is a list of
+/// strings, returns an `ok` holding a tuple of the exit code, the standard
+/// output and the standard error of the command, or an `err` holding a
+/// message describing why it couldn't be run
+///
+/// The exit code is an option as commands killed by a signal don't have one,
+/// neither do commands exiting with a negative code
+pub(super) const RUN_COMMAND<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "run-command",
+	capability: Capability::Process,
+	f:          |c, a| {
+		let [program, arguments]: [_; 2] = expect_arguments(c, a)?;
+
+		let program = expect_string(program)?;
+
+		let ReamType::List(arguments) = arguments.t else {
+			return Err(EvalError::WrongType {
+				loc:      arguments.span,
+				expected: "List".to_string(),
+				found:    arguments.t.type_name(),
+			});
+		};
+		let arguments = arguments
+			.into_iter()
+			.map(|arg| expect_string(arg).map(|a| a.to_string()))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let output = match Command::new(program.as_ref()).args(arguments).output() {
+			Ok(output) => output,
+			Err(e) => {
+				let message = ReamValue { span: c.loc, t: ReamType::String(e.to_string().into()) };
+
+				return Ok(ReamType::Err(Box::new(message)));
+			},
+		};
+
+		let code = match output.status.code().map(i64::from) {
+			Some(code) => {
+				let code = ReamValue { span: c.loc, t: ReamType::Integer(code) };
+
+				ReamType::Some(Box::new(code))
+			},
+			None => ReamType::None,
+		};
+		let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+		let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+		c.context.allocate(3, c.loc)?;
+		let result = ReamType::Tuple(vec![
+			ReamValue { span: c.loc, t: code },
+			ReamValue { span: c.loc, t: ReamType::String(stdout.into()) },
+			ReamValue { span: c.loc, t: ReamType::String(stderr.into()) },
+		]);
+
+		Ok(ReamType::Ok(Box::new(ReamValue { span: c.loc, t: result })))
+	},
+});
+
+#[cfg(test)]
+mod tests {
+	use crate::{parse_str, Capabilities, Capability, EvalError, Interpreter, Value};
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		Interpreter::new(Capabilities::ALL).run(parse_str(source).unwrap())
+	}
+
+	#[test]
+	fn commands_report_their_exit_code_and_output() {
+		let value = run(r#"(run-command "sh" (list "-c" "echo out; echo err >&2; exit 3"))"#);
+
+		let output = Value::Tuple(vec![
+			Value::Some(Box::new(Value::Integer(3))),
+			Value::String("out\n".to_string()),
+			Value::String("err\n".to_string()),
+		]);
+		assert_eq!(value.unwrap(), Value::Ok(Box::new(output)));
+	}
+
+	#[test]
+	fn missing_programs_are_errors() {
+		let value = run(r#"(run-command "ream-run-command-missing-program" (list))"#).unwrap();
+
+		assert!(matches!(value, Value::Err(_)), "{value:?}");
+	}
+
+	#[test]
+	fn arguments_must_be_a_list_of_strings() {
+		for source in [r#"(run-command "true" "-v")"#, r#"(run-command "true" (list 1))"#] {
+			let result = run(source);
+
+			assert!(matches!(result, Err(EvalError::WrongType { .. })), "{source} gave {result:?}");
+		}
+	}
+
+	#[test]
+	fn commands_require_the_process_capability() {
+		let result = Interpreter::new(Capabilities::ALL.without(Capability::Process))
+			.run(parse_str(r#"(run-command "true" (list))"#).unwrap());
+
+		assert!(matches!(result, Err(EvalError::CapabilityDenied { .. })), "{result:?}");
+	}
+}
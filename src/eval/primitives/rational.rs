@@ -0,0 +1,57 @@
+//! Exact arithmetic on rationals and the integers that fit in an `i64`
+
+use num_rational::Rational64;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+
+use super::{integer, ReamType};
+
+/// Check if a value is a rational or a regular integer
+pub(super) fn is_exact(t: &ReamType<'_>) -> bool {
+	matches!(t, ReamType::Integer(_) | ReamType::Rational(_))
+}
+
+/// Get the rational representation of a value matched by [`is_exact`]
+pub(super) fn to_ratio(t: ReamType<'_>) -> Rational64 {
+	match t {
+		ReamType::Integer(i) => Rational64::from_integer(i),
+		ReamType::Rational(r) => r,
+		_ => unreachable!(),
+	}
+}
+
+/// Turn a rational into a value, as an integer if it is a whole number
+fn from_ratio<'s>(r: Rational64) -> ReamType<'s> {
+	if r.is_integer() {
+		ReamType::Integer(r.to_integer())
+	} else {
+		ReamType::Rational(r)
+	}
+}
+
+/// Get the floating point approximation of a rational
+pub(super) fn to_float(r: Rational64) -> f64 { *r.numer() as f64 / *r.denom() as f64 }
+
+/// Generate an operation on two rationals that returns [`None`] if the
+/// numerator or denominator of its result don't fit in an `i64`
+macro_rules! checked {
+	($name:ident $checked:ident) => {
+		pub(super) fn $name<'s>(a: Rational64, b: Rational64) -> Option<ReamType<'s>> {
+			a.$checked(&b).map(from_ratio)
+		}
+	};
+}
+
+checked!(add checked_add);
+checked!(sub checked_sub);
+checked!(mul checked_mul);
+checked!(div checked_div);
+
+/// Divide two integers, producing a rational if the division doesn't come
+/// out even
+pub(super) fn divide<'s>(a: i64, b: i64) -> Option<ReamType<'s>> {
+	match a.checked_rem(b) {
+		Some(rem) if rem != 0 => div(Rational64::from_integer(a), Rational64::from_integer(b)),
+		// The quotients that don't fit in an `i64` are up to integer division
+		_ => integer::div(a, b),
+	}
+}
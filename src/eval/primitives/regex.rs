@@ -0,0 +1,148 @@
+use std::rc::Rc;
+
+use im_rc::Vector;
+use regex::Regex;
+
+use super::{expect_arguments, expect_string};
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Compile a regular expression
+///
+/// `(regex <pattern>)` using the syntax of the `regex` crate
+pub(super) const REGEX<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "regex",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [pattern]: [_; 1] = expect_arguments(c, a)?;
+
+		let span = pattern.span;
+		let pattern = expect_string(pattern)?;
+
+		match Regex::new(&pattern) {
+			Ok(r) => Ok(ReamType::Regex(Rc::new(r))),
+			Err(e) => Err(EvalError::InvalidRegex { loc: span, message: e.to_string() }),
+		}
+	},
+});
+
+/// Check if a regular expression matches anywhere in a string
+///
+/// `(re-match? <regex> <string>)`
+pub(super) const RE_IS_MATCH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "re-match?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [regex, string]: [_; 2] = expect_arguments(c, a)?;
+
+		let regex = expect_regex(regex)?;
+		let string = expect_string(string)?;
+
+		Ok(ReamType::Boolean(regex.is_match(&string)))
+	},
+});
+
+/// Find the first match of a regular expression in a string
+///
+/// `(re-find <regex> <string>)` returns a `some` holding the matched text, or
+/// `none` if there is no match
+pub(super) const RE_FIND<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "re-find",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [regex, string]: [_; 2] = expect_arguments(c, a)?;
+
+		let regex = expect_regex(regex)?;
+		let string = expect_string(string)?;
+
+		match regex.find(&string) {
+			Some(m) => {
+				let found = ReamValue {
+					span: c.loc,
+					t:    ReamType::String(m.as_str().to_string().into()),
+				};
+
+				Ok(ReamType::Some(Box::new(found)))
+			},
+			None => Ok(ReamType::None),
+		}
+	},
+});
+
+/// Get the capture groups of the first match of a regular expression in a
+/// string
+///
+/// `(re-captures <regex> <string>)` returns a `some` holding a list with an
+/// option for every group, starting with the entire match, or `none` if there
+/// is no match
+pub(super) const RE_CAPTURES<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "re-captures",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [regex, string]: [_; 2] = expect_arguments(c, a)?;
+
+		let regex = expect_regex(regex)?;
+		let string = expect_string(string)?;
+
+		let Some(captures) = regex.captures(&string) else {
+			return Ok(ReamType::None);
+		};
+
+		let groups = captures
+			.iter()
+			.map(|group| {
+				let t = match group {
+					Some(m) => {
+						let text = ReamValue {
+							span: c.loc,
+							t:    ReamType::String(m.as_str().to_string().into()),
+						};
+
+						ReamType::Some(Box::new(text))
+					},
+					None => ReamType::None,
+				};
+
+				ReamValue { span: c.loc, t }
+			})
+			.collect::<Vector<_>>();
+		c.context.allocate(groups.len(), c.loc)?;
+
+		Ok(ReamType::Some(Box::new(ReamValue { span: c.loc, t: ReamType::List(groups) })))
+	},
+});
+
+/// Replace every match of a regular expression in a string
+///
+/// `(re-replace <regex> <string> <replacement>)` where replacement can refer
+/// to capture groups as `$1`, `$name`, ...
+pub(super) const RE_REPLACE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "re-replace",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [regex, string, replacement]: [_; 3] = expect_arguments(c, a)?;
+
+		let regex = expect_regex(regex)?;
+		let string = expect_string(string)?;
+		let replacement = expect_string(replacement)?;
+
+		let replaced = regex.replace_all(&string, replacement.as_ref()).into_owned();
+
+		Ok(ReamType::String(replaced.into()))
+	},
+});
+
+/// Take apart a value that should be a compiled regular expression
+fn expect_regex(value: ReamValue<'_>) -> Result<Rc<Regex>, EvalError> {
+	match value.t {
+		ReamType::Regex(r) => Ok(r),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Regex".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
@@ -0,0 +1,136 @@
+use super::{expect_arguments, io_result};
+use crate::eval::value::{Primitive, ReamType};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Wrap a value in a successful result
+///
+/// `(ok <value>)`
+pub(super) const OK<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "ok",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Ok(Box::new(value)))
+	},
+});
+
+/// Wrap a value in a failed result
+///
+/// `(err <value>)`
+pub(super) const ERR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "err",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [value]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Err(Box::new(value)))
+	},
+});
+
+generate_primitive! {
+	pub(super) IS_OK "ok?" [Pure] (a) => {
+		(ReamType::Ok(_)) => Ok(ReamType::Boolean(true)),
+		(ReamType::Err(_)) => Ok(ReamType::Boolean(false))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Result".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+generate_primitive! {
+	pub(super) IS_ERR "err?" [Pure] (a) => {
+		(ReamType::Ok(_)) => Ok(ReamType::Boolean(false)),
+		(ReamType::Err(_)) => Ok(ReamType::Boolean(true))
+
+		(a_t) => Err(EvalError::WrongType {
+			loc: a.span,
+			expected: "Result".to_string(),
+			found: a_t.type_name(),
+		})
+	}
+}
+
+/// Get the value out of a successful result, raising an error if it failed
+///
+/// `(unwrap <result>)`
+pub(super) const UNWRAP<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "unwrap",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [result]: [_; 1] = expect_arguments(c, a)?;
+
+		match result.t {
+			ReamType::Ok(v) => Ok(v.t),
+			ReamType::Err(e) => {
+				c.context.value_limits.get().check(&e.t, c.loc)?;
+
+				Err(EvalError::UnwrapErr { loc: c.loc, payload: c.to_value(&e)? })
+			},
+			t => {
+				Err(EvalError::WrongType {
+					loc:      result.span,
+					expected: "Result".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Get the value out of a successful result, or a default if it failed
+///
+/// `(unwrap-or <result> <default>)`
+pub(super) const UNWRAP_OR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "unwrap-or",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [result, default]: [_; 2] = expect_arguments(c, a)?;
+
+		match result.t {
+			ReamType::Ok(v) => Ok(v.t),
+			ReamType::Err(_) => Ok(default.t),
+			t => {
+				Err(EvalError::WrongType {
+					loc:      result.span,
+					expected: "Result".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Parse a string into an integer or a float
+///
+/// `(parse-number <string>)` returns an `ok` holding the number, or an `err`
+/// holding a message describing why the string isn't a number
+pub(super) const PARSE_NUMBER<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "parse-number",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		let ReamType::String(s) = &string.t else {
+			return Err(EvalError::WrongType {
+				loc:      string.span,
+				expected: "String".to_string(),
+				found:    string.t.type_name(),
+			});
+		};
+
+		let number = if let Ok(i) = s.parse::<i64>() {
+			Ok(ReamType::Integer(i))
+		} else if let Ok(f) = s.parse::<f64>() {
+			Ok(ReamType::Float(f))
+		} else {
+			Err(format!("`{s}` is not a valid number"))
+		};
+
+		Ok(io_result(c, number))
+	},
+});
@@ -0,0 +1,99 @@
+use im_rc::OrdSet;
+
+use super::expect_arguments;
+use crate::eval::key::Key;
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Create a set holding the given values
+///
+/// `(set <value>*)`, equal values are only held once
+pub(super) const SET<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "set",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		c.context.allocate(a.len(), c.loc)?;
+
+		let set = a.into_iter().map(Key::new).collect::<Result<OrdSet<_>, _>>()?;
+
+		Ok(ReamType::Set(set))
+	},
+});
+
+/// Add a value to a set
+///
+/// `(set-add <set> <value>)` returns a new set, the original set is left
+/// unchanged
+pub(super) const SET_ADD<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "set-add",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [set, value]: [_; 2] = expect_arguments(c, a)?;
+
+		let set = expect_set(set)?;
+		c.context.allocate(1, c.loc)?;
+
+		Ok(ReamType::Set(set.update(Key::new(value)?)))
+	},
+});
+
+/// Check if a set holds a value
+///
+/// `(set-member? <set> <value>)`
+pub(super) const SET_MEMBER<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "set-member?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [set, value]: [_; 2] = expect_arguments(c, a)?;
+
+		let set = expect_set(set)?;
+
+		Ok(ReamType::Boolean(set.contains(&Key::new(value)?)))
+	},
+});
+
+/// Get the values held by either of two sets
+///
+/// `(set-union <set> <set>)`
+pub(super) const SET_UNION<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "set-union",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [a, b]: [_; 2] = expect_arguments(c, a)?;
+
+		let (a, b) = (expect_set(a)?, expect_set(b)?);
+		c.context.allocate(b.len(), c.loc)?;
+
+		Ok(ReamType::Set(a.union(b)))
+	},
+});
+
+/// Get the values held by both of two sets
+///
+/// `(set-intersect <set> <set>)`
+pub(super) const SET_INTERSECT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "set-intersect",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [a, b]: [_; 2] = expect_arguments(c, a)?;
+
+		let (a, b) = (expect_set(a)?, expect_set(b)?);
+
+		Ok(ReamType::Set(a.intersection(b)))
+	},
+});
+
+/// Take apart a value that should be a set
+fn expect_set(value: ReamValue<'_>) -> Result<OrdSet<Key<'_>>, EvalError> {
+	match value.t {
+		ReamType::Set(set) => Ok(set),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Set".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
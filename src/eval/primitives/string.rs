@@ -0,0 +1,670 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+use im_rc::Vector;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{expect_arguments, expect_count, expect_index, expect_string};
+use crate::eval::value::{CallContext, Primitive, ReamString, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::{Combine, EvalError};
+
+/// Create a new, empty string builder
+///
+/// `(sb)`
+///
+/// Pushing onto a string builder appends to a single growing buffer, making
+/// it far cheaper than repeatedly appending strings in a loop
+pub(super) const SB<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "sb",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let []: [_; 0] = expect_arguments(c, a)?;
+
+		Ok(ReamType::StringBuilder(Rc::new(RefCell::new(String::new()))))
+	},
+});
+
+/// Append a string or character to a string builder
+///
+/// `(sb-push! <builder> <value>)`
+pub(super) const SB_PUSH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "sb-push!",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [builder, value]: [_; 2] = expect_arguments(c, a)?;
+
+		let buffer = expect_builder(builder)?;
+
+		match value.t {
+			ReamType::String(s) => buffer.borrow_mut().push_str(&s),
+			ReamType::Character(ch) => buffer.borrow_mut().push(ch),
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      value.span,
+					expected: "String or Character".to_string(),
+					found:    t.type_name(),
+				});
+			},
+		}
+
+		Ok(ReamType::Unit)
+	},
+});
+
+/// Get the string built so far by a string builder
+///
+/// `(sb-build <builder>)`, the builder can keep being used afterwards
+pub(super) const SB_BUILD<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "sb-build",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [builder]: [_; 1] = expect_arguments(c, a)?;
+
+		let buffer = expect_builder(builder)?;
+		let built = buffer.borrow().clone();
+
+		Ok(ReamType::String(built.into()))
+	},
+});
+
+/// Count the unicode scalar values of a string
+///
+/// `(string-length <string>)`
+pub(super) const STRING_LENGTH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-length",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Integer(expect_string(string)?.chars().count() as i64))
+	},
+});
+
+/// Concatenate strings
+///
+/// `(string-append <string>*)`, appending no strings results in an empty
+/// string
+pub(super) const STRING_APPEND<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-append",
+	capability: Capability::Pure,
+	f:          |_, a| {
+		let mut appended = String::new();
+		for string in a {
+			appended.push_str(&expect_string(string)?);
+		}
+
+		Ok(ReamType::String(appended.into()))
+	},
+});
+
+/// Get the part of a string between two character indices
+///
+/// `(substring <string> <start> <end>?)` where the indices are zero-based,
+/// or count back from the end if they're negative, and `end` is exclusive,
+/// defaulting to the length of the string
+pub(super) const SUBSTRING<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "substring",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		if !(2..=3).contains(&a.len()) {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: 3,
+				found:    a.len(),
+			});
+		}
+
+		let mut a = a.into_iter();
+		// Unwraps are safe as there are at least 2 arguments
+		let string = expect_string(a.next().unwrap())?;
+		let start = a.next().unwrap();
+		let end = a.next();
+
+		// The byte offset of every character boundary, including the end
+		let boundaries = string
+			.char_indices()
+			.map(|(i, _)| i)
+			.chain([string.len()])
+			.collect::<Vec<_>>();
+		let length = boundaries.len() - 1;
+
+		let span = end.as_ref().map_or(start.span, |end| start.span.combine(&end.span));
+		let end = match end {
+			Some(end) => expect_index(c, end, length, true)?,
+			None => length,
+		};
+		let start = expect_index(c, start, length, true)?;
+
+		if start > end {
+			return Err(EvalError::InvalidRange { loc: span, start, end });
+		}
+
+		Ok(ReamType::String(slice(&string, boundaries[start]..boundaries[end])))
+	},
+});
+
+/// Get the character at a character index in a string
+///
+/// `(string-ref <string> <index>)` where the index is zero-based, or counts
+/// back from the end if it's negative
+pub(super) const STRING_REF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-ref",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string, index]: [_; 2] = expect_arguments(c, a)?;
+		let string = expect_string(string)?;
+
+		let length = string.chars().count();
+		let i = expect_index(c, index, length, false)?;
+
+		// Unwrap is safe as the index is less than the length
+		Ok(ReamType::Character(string.chars().nth(i).unwrap()))
+	},
+});
+
+/// Get the part of a string between two character indices
+///
+/// `(string-slice <string> <start> <end>)` where the indices are
+/// zero-based, or count back from the end if they're negative, and `end` is
+/// exclusive
+///
+/// Unlike `substring` the end is required, and a start after the end gives
+/// an empty string
+pub(super) const STRING_SLICE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-slice",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string, start, end]: [_; 3] = expect_arguments(c, a)?;
+		let string = expect_string(string)?;
+
+		// The byte offset of every character boundary, including the end
+		let boundaries = string
+			.char_indices()
+			.map(|(i, _)| i)
+			.chain([string.len()])
+			.collect::<Vec<_>>();
+		let length = boundaries.len() - 1;
+
+		let start = expect_index(c, start, length, true)?;
+		let end = expect_index(c, end, length, true)?.max(start);
+
+		Ok(ReamType::String(slice(&string, boundaries[start]..boundaries[end])))
+	},
+});
+
+/// Split a string on every occurrence of a separator
+///
+/// `(string-split <string> <separator>)` where separator is a non-empty
+/// string or a character
+pub(super) const STRING_SPLIT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-split",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string, separator]: [_; 2] = expect_arguments(c, a)?;
+
+		let span = string.span;
+		let string = expect_string(string)?;
+		let separator = match separator.t {
+			ReamType::String(s) if !s.is_empty() => s.to_string(),
+			ReamType::Character(ch) => ch.to_string(),
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      separator.span,
+					expected: "non-empty String or Character".to_string(),
+					found:    t.type_name(),
+				});
+			},
+		};
+
+		let mut start = 0;
+		let mut pieces = Vector::new();
+		for (i, _) in string.match_indices(&separator) {
+			pieces.push_back(slice(&string, start..i));
+			start = i + separator.len();
+		}
+		pieces.push_back(slice(&string, start..string.len()));
+		c.context.allocate(pieces.len(), c.loc)?;
+
+		Ok(ReamType::List(
+			pieces.into_iter().map(|s| ReamValue { span, t: ReamType::String(s) }).collect(),
+		))
+	},
+});
+
+/// Count the user-perceived characters (extended grapheme clusters) of a
+/// string
+///
+/// `(string-length-graphemes <string>)`
+pub(super) const STRING_LENGTH_GRAPHEMES<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-length-graphemes",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		let string = expect_string(string)?;
+
+		Ok(ReamType::Integer(string.graphemes(true).count() as i64))
+	},
+});
+
+/// Split a string into a list of its unicode scalar values
+///
+/// `(string-chars <string>)`
+pub(super) const STRING_CHARS<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-chars",
+	capability: Capability::Pure,
+	f:          to_list,
+});
+
+/// Split a string into a list of its unicode scalar values
+///
+/// `(string->list <string>)`, the same as `string-chars`
+pub(super) const STRING_TO_LIST<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string->list",
+	capability: Capability::Pure,
+	f:          to_list,
+});
+
+/// Split a string into a list of strings holding its user-perceived
+/// characters (extended grapheme clusters)
+///
+/// `(string-graphemes <string>)`
+pub(super) const STRING_GRAPHEMES<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-graphemes",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		let span = string.span;
+		let string = expect_string(string)?;
+
+		let graphemes = string
+			.graphemes(true)
+			.map(|g| ReamValue { span, t: ReamType::String(g.to_string().into()) })
+			.collect::<Vector<_>>();
+		c.context.allocate(graphemes.len(), c.loc)?;
+
+		Ok(ReamType::List(graphemes))
+	},
+});
+
+/// Convert a string to upper case
+///
+/// `(string-upcase <string>)`
+pub(super) const STRING_UPCASE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-upcase",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::String(expect_string(string)?.to_uppercase().into()))
+	},
+});
+
+/// Convert a string to lower case
+///
+/// `(string-downcase <string>)`
+pub(super) const STRING_DOWNCASE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-downcase",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::String(expect_string(string)?.to_lowercase().into()))
+	},
+});
+
+/// Fold the case of a string so that strings differing only in case compare
+/// equal
+///
+/// `(string-foldcase <string>)`, full unicode case folding is approximated by
+/// lower casing the compatibility decomposition of the string
+pub(super) const STRING_FOLDCASE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-foldcase",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		let folded = expect_string(string)?.nfkd().collect::<String>().to_lowercase();
+
+		Ok(ReamType::String(folded.nfc().collect::<String>().into()))
+	},
+});
+
+/// Convert a string to unicode normalization form C (canonical composition)
+///
+/// `(string-nfc <string>)`
+pub(super) const STRING_NFC<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-nfc",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::String(expect_string(string)?.nfc().collect::<String>().into()))
+	},
+});
+
+/// Convert a string to unicode normalization form D (canonical
+/// decomposition)
+///
+/// `(string-nfd <string>)`
+pub(super) const STRING_NFD<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-nfd",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::String(expect_string(string)?.nfd().collect::<String>().into()))
+	},
+});
+
+/// Pad the start of a string until it is at least as wide as the given
+/// amount of terminal columns
+///
+/// `(string-pad-left <string> <width> <char>?)`, pads with spaces unless
+/// another character is given
+pub(super) const STRING_PAD_LEFT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-pad-left",
+	capability: Capability::Pure,
+	f:          |c, a| pad(c, a, Alignment::Right),
+});
+
+/// Pad the end of a string until it is at least as wide as the given amount
+/// of terminal columns
+///
+/// `(string-pad-right <string> <width> <char>?)`, pads with spaces unless
+/// another character is given
+pub(super) const STRING_PAD_RIGHT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-pad-right",
+	capability: Capability::Pure,
+	f:          |c, a| pad(c, a, Alignment::Left),
+});
+
+/// Align a string within the given amount of terminal columns
+///
+/// `(string-justify <string> <width> <alignment> <char>?)` where alignment
+/// is one of `:left`, `:right` or `:center`, pads with spaces unless another
+/// character is given
+pub(super) const STRING_JUSTIFY<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-justify",
+	capability: Capability::Pure,
+	f:          |c, mut a| {
+		if !(3..=4).contains(&a.len()) {
+			return Err(EvalError::WrongArgumentCount {
+				loc:      c.loc,
+				callee:   c.callee.to_string(),
+				expected: 4,
+				found:    a.len(),
+			});
+		}
+
+		let alignment = a.remove(2);
+		let alignment = match alignment.t {
+			ReamType::Atom(":left") => Alignment::Left,
+			ReamType::Atom(":right") => Alignment::Right,
+			ReamType::Atom(":center") => Alignment::Center,
+			t => {
+				return Err(EvalError::WrongType {
+					loc:      alignment.span,
+					expected: "one of `:left`, `:right`, `:center`".to_string(),
+					found:    t.to_string(),
+				});
+			},
+		};
+
+		pad(c, a, alignment)
+	},
+});
+
+/// Repeat a string a number of times
+///
+/// `(string-repeat <string> <count>)`, the result has to stay within the
+/// string limit of the interpreter
+pub(super) const STRING_REPEAT<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "string-repeat",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [string, count]: [_; 2] = expect_arguments(c, a)?;
+
+		let string = expect_string(string)?;
+		let count = expect_count(count)?;
+
+		c.context.value_limits.get().check_string(string.len().checked_mul(count), c.loc)?;
+
+		Ok(ReamType::String(string.repeat(count).into()))
+	},
+});
+
+/// Check whether a character is alphabetic
+///
+/// `(char-alphabetic? <char>)`
+pub(super) const CHAR_ALPHABETIC<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char-alphabetic?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Boolean(expect_character(ch)?.is_alphabetic()))
+	},
+});
+
+/// Check whether a character is numeric
+///
+/// `(char-numeric? <char>)`
+pub(super) const CHAR_NUMERIC<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char-numeric?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Boolean(expect_character(ch)?.is_numeric()))
+	},
+});
+
+/// Check whether a character is whitespace
+///
+/// `(char-whitespace? <char>)`
+pub(super) const CHAR_WHITESPACE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char-whitespace?",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Boolean(expect_character(ch)?.is_whitespace()))
+	},
+});
+
+/// Convert a character to upper case
+///
+/// `(char-upcase <char>)`, characters whose upper case form is more than one
+/// character (eg. `ß`) are returned unchanged
+pub(super) const CHAR_UPCASE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char-upcase",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		let ch = expect_character(ch)?;
+
+		Ok(ReamType::Character(single_char(ch.to_uppercase()).unwrap_or(ch)))
+	},
+});
+
+/// Convert a character to lower case
+///
+/// `(char-downcase <char>)`, characters whose lower case form is more than
+/// one character are returned unchanged
+pub(super) const CHAR_DOWNCASE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char-downcase",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		let ch = expect_character(ch)?;
+
+		Ok(ReamType::Character(single_char(ch.to_lowercase()).unwrap_or(ch)))
+	},
+});
+
+/// Get the unicode scalar value of a character
+///
+/// `(char->integer <char>)`
+pub(super) const CHAR_TO_INTEGER<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "char->integer",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [ch]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Integer(i64::from(u32::from(expect_character(ch)?))))
+	},
+});
+
+/// Get the character with a unicode scalar value
+///
+/// `(integer->char <integer>)`, surrogates and values above `0x10FFFF` aren't
+/// characters
+pub(super) const INTEGER_TO_CHAR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "integer->char",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [i]: [_; 1] = expect_arguments(c, a)?;
+
+		match i.t {
+			ReamType::Integer(n) => {
+				let ch = u32::try_from(n).ok().and_then(char::from_u32);
+
+				ch.map(ReamType::Character).ok_or_else(|| {
+					EvalError::WrongType {
+						loc:      i.span,
+						expected: "unicode scalar value".to_string(),
+						found:    n.to_string(),
+					}
+				})
+			},
+			t => {
+				Err(EvalError::WrongType {
+					loc:      i.span,
+					expected: "Integer".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	},
+});
+
+/// Split a string given as `<string>` into a list of its unicode scalar
+/// values
+fn to_list<'s>(c: &CallContext<'s>, a: Vec<ReamValue<'s>>) -> Result<ReamType<'s>, EvalError> {
+	let [string]: [_; 1] = expect_arguments(c, a)?;
+
+	let span = string.span;
+	let string = expect_string(string)?;
+
+	let chars = string
+		.chars()
+		.map(|ch| ReamValue { span, t: ReamType::Character(ch) })
+		.collect::<Vector<_>>();
+	c.context.allocate(chars.len(), c.loc)?;
+
+	Ok(ReamType::List(chars))
+}
+
+/// Get a part of a string, still borrowing from the source code if the
+/// string did
+fn slice<'s>(string: &ReamString<'s>, range: Range<usize>) -> ReamString<'s> {
+	match string {
+		ReamString::Borrowed(s) => ReamString::Borrowed(&s[range]),
+		ReamString::Shared(s) => ReamString::Shared(s[range].into()),
+	}
+}
+
+/// Take apart a value that should be a string builder
+fn expect_builder(value: ReamValue<'_>) -> Result<Rc<RefCell<String>>, EvalError> {
+	match value.t {
+		ReamType::StringBuilder(buffer) => Ok(buffer),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "StringBuilder".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Take apart a value that should be a character
+fn expect_character(value: ReamValue<'_>) -> Result<char, EvalError> {
+	match value.t {
+		ReamType::Character(ch) => Ok(ch),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Character".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
+
+/// Get the only character of a case mapping, if it maps to exactly one
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+	let ch = chars.next()?;
+
+	chars.next().is_none().then_some(ch)
+}
+
+/// Where a padded string ends up within its padding
+enum Alignment {
+	Left,
+	Right,
+	Center,
+}
+
+/// Pad a string given as `<string> <width> <char>?` to the given alignment
+///
+/// The width of the string is measured in terminal columns, taking wide and
+/// zero-width characters into account. The result has to stay within the
+/// string limit of the interpreter
+fn pad<'s>(
+	c: &CallContext<'s>,
+	a: Vec<ReamValue<'s>>,
+	alignment: Alignment,
+) -> Result<ReamType<'s>, EvalError> {
+	if !(2..=3).contains(&a.len()) {
+		return Err(EvalError::WrongArgumentCount {
+			loc:      c.loc,
+			callee:   c.callee.to_string(),
+			expected: 3,
+			found:    a.len(),
+		});
+	}
+
+	let mut a = a.into_iter();
+	// Unwraps are safe as there are at least 2 arguments
+	let string = expect_string(a.next().unwrap())?;
+	let width = expect_count(a.next().unwrap())?;
+	let fill = a.next().map(expect_character).transpose()?.unwrap_or(' ');
+
+	let missing = width.saturating_sub(string.width());
+	let (before, after) = match alignment {
+		Alignment::Left => (0, missing),
+		Alignment::Right => (missing, 0),
+		Alignment::Center => (missing / 2, missing - missing / 2),
+	};
+
+	// The fill character may take up more than one byte
+	let size = fill.len_utf8().checked_mul(missing).and_then(|n| n.checked_add(string.len()));
+	let size = c.context.value_limits.get().check_string(size, c.loc)?;
+
+	let mut padded = String::with_capacity(size);
+	padded.extend(std::iter::repeat_n(fill, before));
+	padded.push_str(&string);
+	padded.extend(std::iter::repeat_n(fill, after));
+
+	Ok(ReamType::String(padded.into()))
+}
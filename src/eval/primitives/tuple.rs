@@ -0,0 +1,41 @@
+use super::{expect_arguments, expect_index};
+use crate::eval::value::{Primitive, ReamType};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Create a tuple holding all given values
+///
+/// `(tuple <value>*)`
+pub(super) const TUPLE<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "tuple",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		c.context.allocate(a.len(), c.loc)?;
+
+		Ok(ReamType::Tuple(a))
+	},
+});
+
+/// Get an element of a tuple
+///
+/// `(tuple-ref <tuple> <index>)` where index is zero-based, or counts back
+/// from the end if it's negative
+pub(super) const TUPLE_REF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "tuple-ref",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [tuple, index]: [_; 2] = expect_arguments(c, a)?;
+
+		let ReamType::Tuple(mut elements) = tuple.t else {
+			return Err(EvalError::WrongType {
+				loc:      tuple.span,
+				expected: "Tuple".to_string(),
+				found:    tuple.t.type_name(),
+			});
+		};
+
+		let i = expect_index(c, index, elements.len(), false)?;
+
+		Ok(elements.swap_remove(i).t)
+	},
+});
@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{expect_arguments, expect_index};
+use crate::eval::value::{Primitive, ReamType, ReamValue};
+use crate::eval::Capability;
+use crate::EvalError;
+
+/// Create a vector holding all given values
+///
+/// `(vector <value>*)`
+pub(super) const VECTOR<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "vector",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		c.context.allocate(a.len(), c.loc)?;
+
+		Ok(ReamType::Vector(Rc::new(RefCell::new(a))))
+	},
+});
+
+/// Get an element of a vector
+///
+/// `(vector-ref <vector> <index>)` where index is zero-based, or counts back
+/// from the end if it's negative
+pub(super) const VECTOR_REF<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "vector-ref",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [vector, index]: [_; 2] = expect_arguments(c, a)?;
+
+		let elements = expect_vector(vector)?;
+		let elements = elements.borrow();
+		let i = expect_index(c, index, elements.len(), false)?;
+
+		Ok(elements[i].t.clone())
+	},
+});
+
+/// Replace an element of a vector
+///
+/// `(vector-set! <vector> <index> <value>)` where index is zero-based, or
+/// counts back from the end if it's negative, every copy of the vector sees
+/// the new element
+pub(super) const VECTOR_SET<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "vector-set!",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [vector, index, value]: [_; 3] = expect_arguments(c, a)?;
+
+		let elements = expect_vector(vector)?;
+		let mut elements = elements.borrow_mut();
+		let i = expect_index(c, index, elements.len(), false)?;
+
+		elements[i] = value;
+
+		Ok(ReamType::Unit)
+	},
+});
+
+/// Count the elements of a vector
+///
+/// `(vector-length <vector>)`
+pub(super) const VECTOR_LENGTH<'s>: ReamType<'s> = ReamType::Primitive(Primitive {
+	name:       "vector-length",
+	capability: Capability::Pure,
+	f:          |c, a| {
+		let [vector]: [_; 1] = expect_arguments(c, a)?;
+
+		Ok(ReamType::Integer(expect_vector(vector)?.borrow().len() as i64))
+	},
+});
+
+/// Take apart a value that should be a vector
+fn expect_vector(value: ReamValue<'_>) -> Result<Rc<RefCell<Vec<ReamValue<'_>>>>, EvalError> {
+	match value.t {
+		ReamType::Vector(elements) => Ok(elements),
+		t => {
+			Err(EvalError::WrongType {
+				loc:      value.span,
+				expected: "Vector".to_string(),
+				found:    t.type_name(),
+			})
+		},
+	}
+}
@@ -0,0 +1,99 @@
+//! Protocols, functions dispatching on the type of their first argument
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{Eval, ReamType, ReamValue, Scope};
+use crate::ast::{Expression, Identifier, ProtocolMethod};
+use crate::{EvalError, Span};
+
+/// Register a protocol and bind each of its methods in the given scope
+pub(super) fn define_protocol<'s>(
+	target: Identifier<'s>,
+	methods: Vec<ProtocolMethod<'s>>,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	let context = scope.borrow().context.clone();
+	context.protocols.borrow_mut().insert(target.id, methods.iter().map(|m| m.target.id).collect());
+
+	for (index, method) in methods.into_iter().enumerate() {
+		let value = ReamType::ProtocolMethod {
+			protocol: target.id,
+			method: method.target.id,
+			index,
+			arity: method.formals.len(),
+		};
+
+		scope.borrow_mut().define(method.target, ReamValue { span: method.span, t: value })?;
+	}
+
+	Ok(())
+}
+
+/// Register the functions implementing every method of a protocol for a type
+pub(super) fn implement_protocol<'s>(
+	span: Span,
+	protocol: Identifier<'s>,
+	target: Identifier<'s>,
+	methods: Vec<Expression<'s>>,
+	scope: &Rc<RefCell<Scope<'s>>>,
+) -> Result<(), EvalError> {
+	let context = scope.borrow().context.clone();
+
+	let Some(expected) = context.protocols.borrow().get(protocol.id).map(Vec::len) else {
+		return Err(EvalError::UnknownIdentifier {
+			loc: protocol.span,
+			id:  protocol.id.to_string(),
+		});
+	};
+
+	if methods.len() != expected {
+		return Err(EvalError::InvalidImplementation {
+			loc: span,
+			protocol: protocol.id.to_string(),
+			expected,
+			found: methods.len(),
+		});
+	}
+
+	let methods = methods
+		.into_iter()
+		.map(|m| m.eval(scope.clone()))
+		.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+	context.implementations.borrow_mut().insert((protocol.id, target.id.to_string()), methods);
+
+	Ok(())
+}
+
+/// Call the implementation of a protocol method for the type of the first
+/// argument
+pub(super) fn dispatch<'s>(
+	protocol: &'s str,
+	method: &'s str,
+	index: usize,
+	args: Vec<ReamValue<'s>>,
+	loc: Span,
+	scope: Rc<RefCell<Scope<'s>>>,
+) -> Result<ReamType<'s>, EvalError> {
+	// Unwrap is safe as the arity of methods is at least 1
+	let type_name = args.first().unwrap().t.type_name();
+
+	let context = scope.borrow().context.clone();
+	let implementation = context
+		.implementations
+		.borrow()
+		.get(&(protocol, type_name.clone()))
+		.map(|methods| methods[index].clone());
+
+	let Some(implementation) = implementation else {
+		return Err(EvalError::MissingImplementation {
+			loc,
+			protocol: protocol.to_string(),
+			method: method.to_string(),
+			type_name,
+		});
+	};
+
+	ReamValue { span: loc, ..implementation }.call(args, scope)
+}
@@ -0,0 +1,151 @@
+//! Where the sources of included files come from
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use super::Capability;
+
+/// Resolves and reads the files included by programs, letting embedders
+/// serve them from somewhere other than the filesystem
+pub trait SourceProvider {
+	/// The capability a program needs to include files through this
+	/// provider, if any
+	fn capability(&self) -> Option<Capability> { None }
+
+	/// Find the file `name` refers to when it is included by the file
+	/// `includer`, or by the top level of a program if there is none
+	///
+	/// Returns a name uniquely identifying the file, which is passed as the
+	/// `includer` of the files it includes in turn, or the locations that
+	/// were searched if it couldn't be found
+	fn resolve(&self, name: &str, includer: Option<&str>) -> Result<String, Vec<String>>;
+
+	/// Read the source of a file returned by [`SourceProvider::resolve`]
+	fn read(&self, resolved: &str) -> io::Result<String>;
+}
+
+/// Serves included files from the filesystem, relative to the file
+/// including them or to the current directory, and from a list of include
+/// paths after that
+///
+/// Files are identified by their canonical path, and can only be included
+/// by programs granted [`Capability::Fs`]
+#[derive(Clone, Debug, Default)]
+pub struct FileSystemProvider {
+	include_paths: Vec<PathBuf>,
+}
+
+impl FileSystemProvider {
+	/// Create a new [`FileSystemProvider`] that searches the given
+	/// directories, in order, for files that aren't found relative to the
+	/// file including them
+	pub fn new(include_paths: Vec<PathBuf>) -> Self { Self { include_paths } }
+}
+
+impl SourceProvider for FileSystemProvider {
+	fn capability(&self) -> Option<Capability> { Some(Capability::Fs) }
+
+	fn resolve(&self, name: &str, includer: Option<&str>) -> Result<String, Vec<String>> {
+		let base = includer
+			.and_then(|includer| Path::new(includer).parent())
+			.filter(|dir| !dir.as_os_str().is_empty())
+			.map(Path::to_path_buf)
+			.or_else(|| env::current_dir().ok())
+			.unwrap_or_default();
+
+		let dirs = [base].into_iter().chain(self.include_paths.iter().cloned()).collect::<Vec<_>>();
+
+		dirs.iter()
+			.find_map(|dir| dir.join(name).canonicalize().ok())
+			.map(|path| path.display().to_string())
+			.ok_or_else(|| dirs.iter().map(|dir| dir.display().to_string()).collect())
+	}
+
+	fn read(&self, resolved: &str) -> io::Result<String> { fs::read_to_string(resolved) }
+}
+
+/// Serves included files from memory, for sandboxed programs or sources
+/// embedded in the host application
+///
+/// Names are `/` separated paths, relative names are resolved against the
+/// directory of the file including them
+#[derive(Clone, Debug, Default)]
+pub struct MemoryProvider {
+	files: HashMap<String, String>,
+}
+
+impl MemoryProvider {
+	/// Create a new, empty [`MemoryProvider`]
+	pub fn new() -> Self { Self::default() }
+
+	/// Add a file that can be included under the given name
+	pub fn with_file(mut self, name: &str, source: impl Into<String>) -> Self {
+		self.files.insert(normalize(name), source.into());
+		self
+	}
+}
+
+impl SourceProvider for MemoryProvider {
+	fn resolve(&self, name: &str, includer: Option<&str>) -> Result<String, Vec<String>> {
+		let path = match includer.and_then(|includer| includer.rsplit_once('/')) {
+			Some((dir, _)) if !name.starts_with('/') => normalize(&format!("{dir}/{name}")),
+			_ => normalize(name),
+		};
+
+		if self.files.contains_key(&path) {
+			Ok(path)
+		} else {
+			Err(vec![path])
+		}
+	}
+
+	fn read(&self, resolved: &str) -> io::Result<String> {
+		self.files.get(resolved).cloned().ok_or_else(|| io::ErrorKind::NotFound.into())
+	}
+}
+
+/// Remove `.` and `..` segments and repeated separators from a `/`
+/// separated path
+fn normalize(path: &str) -> String {
+	let mut segments: Vec<&str> = vec![];
+	for segment in path.split('/') {
+		match segment {
+			"" | "." => {},
+			".." => {
+				segments.pop();
+			},
+			segment => segments.push(segment),
+		}
+	}
+
+	let normalized = segments.join("/");
+	if path.starts_with('/') {
+		format!("/{normalized}")
+	} else {
+		normalized
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parse_str, Capabilities, Interpreter, Value};
+
+	#[test]
+	fn memory_provider_resolves_relative_to_includer() {
+		let provider = MemoryProvider::new()
+			.with_file("lib/a.rm", "(include \"b.rm\")\n(let a (+ b 1))\n")
+			.with_file("lib/b.rm", "(let b 41)\n");
+
+		assert_eq!(provider.resolve("b.rm", Some("lib/a.rm")), Ok("lib/b.rm".to_string()));
+		assert_eq!(provider.resolve("./lib/../lib/a.rm", None), Ok("lib/a.rm".to_string()));
+		assert_eq!(provider.resolve("c.rm", Some("lib/a.rm")), Err(vec!["lib/c.rm".to_string()]));
+
+		// Includes through a memory provider don't need filesystem access
+		let mut interpreter = Interpreter::new(Capabilities::PURE).with_source_provider(provider);
+		let program = parse_str("(include \"lib/a.rm\")\na\n").unwrap();
+
+		assert_eq!(interpreter.run(program).unwrap(), Value::Integer(42));
+	}
+}
@@ -1,133 +1,764 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::Infallible;
 use std::fmt;
-use std::rc::Rc;
+use std::hash::{Hash, Hasher};
+use std::net::{TcpListener, TcpStream};
+use std::ops::Deref;
+use std::rc::{Rc, Weak};
 
-use miette::SourceSpan;
+use im_rc::{OrdMap, OrdSet, Vector};
+use num_rational::Rational64;
 
-use super::{Eval, Scope};
-use crate::ast::{Expression, Identifier};
-use crate::EvalError;
+use super::adt::{construct_product, construct_variant};
+use super::clauses;
+use super::key::Key;
+use super::protocol::dispatch;
+use super::{Capability, Context, Eval, Scope};
+use crate::ast::{Expression, Pattern, TypeSpec};
+use crate::value::format_float;
+use crate::{EvalError, Span, Value};
 
-type Primitive<'s> = fn(
-	operator_location: SourceSpan,
-	operator_id: String,
-	arguments: Vec<Expression<'s>>,
-	scope: Rc<RefCell<Scope<'s>>>,
-) -> Result<ReamType<'s>, EvalError>;
+/// The signature of the rust function implementing a [`Primitive`]
+pub(super) type PrimitiveFn<'s> =
+	fn(context: &CallContext<'s>, arguments: Vec<ReamValue<'s>>) -> Result<ReamType<'s>, EvalError>;
+
+/// A function implemented by the host
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Primitive<'s> {
+	/// The name the primitive is installed under
+	pub(super) name:       &'static str,
+	/// The capability a program needs to be allowed to call the primitive
+	pub(super) capability: Capability,
+	pub(super) f:          PrimitiveFn<'s>,
+}
+
+/// The signature of a function registered by the embedder, see
+/// [`Interpreter::with_function`](super::Interpreter::with_function)
+pub(super) type HostFn = dyn Fn(Vec<Value>) -> Result<Value, Value>;
+
+/// A function implemented by the embedder
+pub(super) struct HostFunction {
+	/// The name the function is installed under
+	pub(super) name:       &'static str,
+	/// The capability a program needs to be allowed to call the function
+	pub(super) capability: Capability,
+	pub(super) f:          Box<HostFn>,
+}
+
+impl fmt::Debug for HostFunction {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("HostFunction")
+			.field("name", &self.name)
+			.field("capability", &self.capability)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Handle given to a [`Primitive`] when it is called
+pub(super) struct CallContext<'s> {
+	/// The location of the operator of the call
+	pub(super) loc:     Span,
+	/// The name of the called primitive
+	pub(super) callee:  &'static str,
+	/// The context of the interpreter the call happens in
+	pub(super) context: Rc<Context<'s>>,
+	/// The scope the call happens in
+	pub(super) scope:   Rc<RefCell<Scope<'s>>>,
+}
+
+impl<'s> CallContext<'s> {
+	/// Make sure the running program was granted the given [`Capability`]
+	pub(super) fn require(&self, capability: Capability) -> Result<(), EvalError> {
+		if self.context.capabilities.contains(capability) {
+			Ok(())
+		} else {
+			Err(EvalError::CapabilityDenied {
+				loc:        self.loc,
+				callee:     self.callee.to_string(),
+				capability: capability.to_string(),
+			})
+		}
+	}
+
+	/// Call a function value with the given arguments
+	pub(super) fn call(
+		&self,
+		function: ReamValue<'s>,
+		args: Vec<ReamValue<'s>>,
+	) -> Result<ReamType<'s>, EvalError> {
+		function.call(args, self.scope.clone())
+	}
+
+	/// Call the implementation of a single-method operator protocol for the
+	/// type of the first argument, if the first argument isn't a number and
+	/// its type implements the protocol
+	pub(super) fn overload(
+		&self,
+		protocol: &'static str,
+		args: &[ReamValue<'s>],
+	) -> Option<Result<ReamType<'s>, EvalError>> {
+		let first = args.first()?;
+		if matches!(first.t, ReamType::Integer(_) | ReamType::Rational(_) | ReamType::Float(_)) {
+			return None;
+		}
+		#[cfg(feature = "bigint")]
+		if matches!(first.t, ReamType::BigInt(_)) {
+			return None;
+		}
+
+		let key = (protocol, first.t.type_name());
+		if !self.context.implementations.borrow().contains_key(&key) {
+			return None;
+		}
+
+		Some(dispatch(protocol, protocol, 0, args.to_vec(), self.loc, self.scope.clone()))
+	}
+
+	/// Display a value with the printer registered for its type, if there is
+	/// one and it isn't already displaying a value
+	///
+	/// Values of the same type displayed by the printer itself are displayed
+	/// as if it wasn't registered, so a printer can't end up calling itself
+	/// forever
+	fn print(&self, value: &ReamValue<'s>) -> Result<Option<String>, EvalError> {
+		let type_name = value.t.type_name();
+
+		let printer = self.context.printers.borrow().get(&type_name).cloned();
+		let Some(printer) = printer else { return Ok(None) };
+
+		if self.context.printing.borrow().contains(&type_name) {
+			return Ok(None);
+		}
+
+		self.context.printing.borrow_mut().push(type_name);
+		let result = self.call(printer, vec![value.clone()]);
+		self.context.printing.borrow_mut().pop();
+
+		match result? {
+			ReamType::String(s) => Ok(Some(s.to_string())),
+			t => {
+				Err(EvalError::WrongType {
+					loc:      self.loc,
+					expected: "String".to_string(),
+					found:    t.type_name(),
+				})
+			},
+		}
+	}
+
+	/// Convert a value into a public [`Value`], keeping the way printers
+	/// registered with `define-printer` display it and the values it contains
+	pub(super) fn to_value(&self, value: &ReamValue<'s>) -> Result<Value, EvalError> {
+		if self.context.printers.borrow().is_empty() {
+			return Ok(value.t.to_value());
+		}
+
+		let converted = value.t.to_value_with(&|v| self.to_value(v))?;
+
+		match self.print(value)? {
+			Some(repr) => Ok(Value::Printed { value: Box::new(converted), repr }),
+			None => Ok(converted),
+		}
+	}
+
+	/// Convert a public [`Value`] into a value of the running program, like
+	/// the result of a function registered by the embedder
+	///
+	/// Every value converted counts towards the value quota. Opaque values
+	/// only describe what they were, so they can't be converted
+	pub(super) fn import_value(&self, value: Value) -> Result<ReamType<'s>, EvalError> {
+		self.context.allocate(1, self.loc)?;
+
+		let convert = |value| -> Result<_, EvalError> {
+			Ok(ReamValue { span: self.loc, t: self.import_value(value)? })
+		};
+		let boxed = |value: Box<Value>| convert(*value).map(Box::new);
+		let key = |value| Key::new(convert(value)?);
+
+		let t = match value {
+			Value::Unit => ReamType::Unit,
+			Value::Boolean(b) => ReamType::Boolean(b),
+			Value::Integer(i) => ReamType::Integer(i),
+			// Big integers that fit are integers, like the results of arithmetic
+			#[cfg(feature = "bigint")]
+			Value::BigInt(i) => {
+				match i64::try_from(&i) {
+					Ok(i) => ReamType::Integer(i),
+					Err(_) => ReamType::BigInt(Rc::new(i)),
+				}
+			},
+			Value::Rational(r) if r.is_integer() => ReamType::Integer(r.to_integer()),
+			Value::Rational(r) => ReamType::Rational(r),
+			Value::Float(f) => ReamType::Float(f),
+			Value::Character(c) => ReamType::Character(c),
+			Value::String(s) => ReamType::String(s.into()),
+			Value::Identifier(i) => ReamType::Identifier(self.context.intern(&i)),
+			Value::Atom(a) => ReamType::Atom(self.context.intern(&a)),
+			Value::List(l) => ReamType::List(l.into_iter().map(convert).collect::<Result<_, _>>()?),
+			Value::Tuple(t) => {
+				ReamType::Tuple(t.into_iter().map(convert).collect::<Result<_, _>>()?)
+			},
+			Value::Vector(v) => {
+				let elements = v.into_iter().map(convert).collect::<Result<_, _>>()?;
+
+				ReamType::Vector(Rc::new(RefCell::new(elements)))
+			},
+			Value::Map(m) => {
+				ReamType::Map(
+					m.into_iter()
+						.map(|(k, v)| Ok((key(k)?, convert(v)?)))
+						.collect::<Result<_, EvalError>>()?,
+				)
+			},
+			Value::Set(s) => ReamType::Set(s.into_iter().map(key).collect::<Result<_, _>>()?),
+			Value::Ok(v) => ReamType::Ok(boxed(v)?),
+			Value::Err(e) => ReamType::Err(boxed(e)?),
+			Value::Some(v) => ReamType::Some(boxed(v)?),
+			Value::None => ReamType::None,
+			Value::Product { name, fields } => {
+				ReamType::Product {
+					name:   self.context.intern(&name),
+					fields: fields
+						.into_iter()
+						.map(|(f, v)| Ok((self.context.intern(&f), convert(v)?)))
+						.collect::<Result<_, EvalError>>()?,
+				}
+			},
+			Value::Variant { name, variant, payload } => {
+				ReamType::Variant {
+					name:    self.context.intern(&name),
+					variant: self.context.intern(&variant),
+					payload: payload.map(boxed).transpose()?,
+				}
+			},
+			Value::Printed { value, .. } => self.import_value(*value)?,
+			Value::Opaque { type_name, .. } => {
+				return Err(EvalError::WrongType {
+					loc:      self.loc,
+					expected: "a value that isn't opaque".to_string(),
+					found:    type_name,
+				});
+			},
+		};
+
+		Ok(t)
+	}
+
+	/// Render a value the way `print` displays it, consulting the printers
+	/// registered with `define-printer`
+	pub(super) fn render(&self, value: &ReamValue<'s>) -> Result<String, EvalError> {
+		if let Some(repr) = self.print(value)? {
+			return Ok(repr);
+		}
+
+		match &value.t {
+			ReamType::List(l) => {
+				let repr = l.iter().map(|v| self.render(v)).collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("({})", repr.join(", ")))
+			},
+			ReamType::Tuple(t) => {
+				let repr = t
+					.iter()
+					.map(|v| Ok(format!(" {}", self.render(v)?)))
+					.collect::<Result<String, EvalError>>()?;
+
+				Ok(format!("(tuple{repr})"))
+			},
+			ReamType::Vector(v) => {
+				let repr =
+					v.borrow().iter().map(|v| self.render(v)).collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("#({})", repr.join(" ")))
+			},
+			ReamType::Map(m) => {
+				let repr = m
+					.iter()
+					.map(|(k, v)| Ok(format!("{} {}", self.render(k.value())?, self.render(v)?)))
+					.collect::<Result<Vec<_>, EvalError>>()?;
+
+				Ok(format!("{{{}}}", repr.join(", ")))
+			},
+			ReamType::Set(s) => {
+				let repr =
+					s.iter().map(|k| self.render(k.value())).collect::<Result<Vec<_>, _>>()?;
+
+				Ok(format!("#{{{}}}", repr.join(", ")))
+			},
+			ReamType::Ok(v) => Ok(format!("(ok {})", self.render(v)?)),
+			ReamType::Err(e) => Ok(format!("(err {})", self.render(e)?)),
+			ReamType::Some(v) => Ok(format!("(some {})", self.render(v)?)),
+			ReamType::Product { name, fields } => {
+				let repr = fields
+					.iter()
+					.map(|(f, v)| Ok(format!(" :{f} {}", self.render(v)?)))
+					.collect::<Result<String, EvalError>>()?;
+
+				Ok(format!("({name}{repr})"))
+			},
+			ReamType::Variant { variant, payload: Some(payload), .. } => {
+				Ok(format!("({variant} {})", self.render(payload)?))
+			},
+			t => Ok(t.to_string()),
+		}
+	}
+}
+
+/// A TCP connection, along with the start of a character that was cut off
+/// at the end of the last read
+#[derive(Debug)]
+pub(super) struct Socket {
+	pub(super) stream:  TcpStream,
+	pub(super) pending: RefCell<Vec<u8>>,
+}
+
+impl Socket {
+	pub(super) fn new(stream: TcpStream) -> Self { Self { stream, pending: RefCell::default() } }
+}
 
 #[derive(Debug, Clone)]
 pub(super) struct ReamValue<'s> {
-	pub(super) span: SourceSpan,
+	pub(super) span: Span,
 	pub(super) t:    ReamType<'s>,
 }
 
 #[derive(Debug, Clone)]
 pub(super) enum ReamType<'s> {
 	Boolean(bool),
-	Integer(u64),
+	Integer(i64),
+	/// An integer outside of the range of [`ReamType::Integer`], produced
+	/// when integer arithmetic overflows
+	#[cfg(feature = "bigint")]
+	BigInt(Rc<num_bigint::BigInt>),
+	/// A fraction in lowest terms with a denominator other than 1, whole
+	/// numbers are always an [`ReamType::Integer`]
+	Rational(Rational64),
 	Float(f64),
 	Character(char),
-	String(&'s str),
+	String(ReamString<'s>),
+	StringBuilder(Rc<RefCell<String>>),
+	#[cfg(feature = "regex")]
+	Regex(Rc<regex::Regex>),
+	#[cfg(feature = "time")]
+	DateTime(chrono::DateTime<chrono::Utc>),
+	#[cfg(feature = "time")]
+	Duration(chrono::Duration),
+	Socket(Rc<Socket>),
+	Listener(Rc<TcpListener>),
 	Identifier(&'s str),
 	Atom(&'s str),
-	List(Vec<ReamValue<'s>>),
+	/// A persistent list, cheap to clone and to take apart from either end
+	List(Vector<ReamValue<'s>>),
+	Tuple(Vec<ReamValue<'s>>),
+	/// A mutable array, shared between every copy of the value
+	Vector(Rc<RefCell<Vec<ReamValue<'s>>>>),
+	/// A persistent map from keys to values, ordered by its keys
+	Map(OrdMap<Key<'s>, ReamValue<'s>>),
+	/// A persistent set of keys, ordered like them
+	Set(OrdSet<Key<'s>>),
+	Range {
+		start: i64,
+		end:   i64,
+		step:  i64,
+	},
+	Iterator(Rc<RefCell<Iteration<'s>>>),
+	Ok(Box<ReamValue<'s>>),
+	Err(Box<ReamValue<'s>>),
+	Some(Box<ReamValue<'s>>),
+	None,
+	Product {
+		name:   &'s str,
+		fields: Vec<(&'s str, ReamValue<'s>)>,
+	},
+	Variant {
+		name:    &'s str,
+		variant: &'s str,
+		payload: Option<Box<ReamValue<'s>>>,
+	},
 
 	Primitive(Primitive<'s>),
+	Host(Rc<HostFunction>),
+	/// Constructs a product type, from its field names and their declared
+	/// types
+	ProductConstructor {
+		name:   &'s str,
+		fields: Rc<[(&'s str, Option<TypeSpec<'s>>)]>,
+	},
+	VariantConstructor {
+		name:        &'s str,
+		variant:     &'s str,
+		has_payload: bool,
+	},
+	ProtocolMethod {
+		protocol: &'s str,
+		method:   &'s str,
+		index:    usize,
+		arity:    usize,
+	},
 	Function {
-		formals: Vec<Identifier<'s>>,
-		body:    Vec<Expression<'s>>,
+		name:    &'s str,
+		formals: Rc<[Pattern<'s>]>,
+		body:    Rc<[Expression<'s>]>,
 	},
 	Closure {
-		formals:        Vec<Identifier<'s>>,
-		body:           Vec<Expression<'s>>,
-		enclosed_scope: Rc<RefCell<Scope<'s>>>,
+		/// The name of the variable the closure was first bound to, if any
+		name:           Option<&'s str>,
+		formals:        Rc<[Pattern<'s>]>,
+		body:           Rc<[Expression<'s>]>,
+		enclosed_scope: Enclosed<'s>,
 	},
+	/// A function built out of other values by `compose`, `partial`, `flip`
+	/// or `constantly`
+	Derived(Rc<Derived<'s>>),
 
 	Unit,
 }
 
+/// The scope a closure encloses
+///
+/// A closure bound in the scope it encloses would keep that scope alive
+/// through its own symbols, so it only holds it weakly while stored there
+#[derive(Debug, Clone)]
+pub(super) enum Enclosed<'s> {
+	Strong(Rc<RefCell<Scope<'s>>>),
+	Weak(Weak<RefCell<Scope<'s>>>),
+}
+
+impl<'s> Enclosed<'s> {
+	/// Get the enclosed scope
+	pub(super) fn scope(&self) -> Rc<RefCell<Scope<'s>>> {
+		match self {
+			Self::Strong(scope) => scope.clone(),
+			// Safe as a weak reference is only stored in the scope it points
+			// to, and is strengthened whenever it's read out of it
+			Self::Weak(scope) => scope.upgrade().unwrap(),
+		}
+	}
+
+	/// Hold the enclosed scope weakly if it's `scope`
+	pub(super) fn weaken_in(&mut self, scope: &Scope<'s>) {
+		if let Self::Strong(enclosed) = self {
+			if std::ptr::eq(enclosed.as_ptr(), scope) {
+				*self = Self::Weak(Rc::downgrade(enclosed));
+			}
+		}
+	}
+
+	/// Hold the enclosed scope strongly again
+	pub(super) fn strengthen(&mut self) { *self = Self::Strong(self.scope()); }
+}
+
+/// The contents of a string value
+///
+/// String literals borrow from the source code, strings built at runtime are
+/// shared between every copy of the value, so cloning a string never copies
+/// its contents
+#[derive(Clone)]
+pub(super) enum ReamString<'s> {
+	Borrowed(&'s str),
+	Shared(Rc<str>),
+}
+
+impl<'s> Deref for ReamString<'s> {
+	type Target = str;
+
+	fn deref(&self) -> &str {
+		match self {
+			Self::Borrowed(s) => s,
+			Self::Shared(s) => s,
+		}
+	}
+}
+
+impl<'s> AsRef<str> for ReamString<'s> {
+	fn as_ref(&self) -> &str { self }
+}
+
+impl<'s> From<&'s str> for ReamString<'s> {
+	fn from(s: &'s str) -> Self { Self::Borrowed(s) }
+}
+
+impl<'s> From<String> for ReamString<'s> {
+	fn from(s: String) -> Self { Self::Shared(s.into()) }
+}
+
+impl<'s> PartialEq for ReamString<'s> {
+	fn eq(&self, other: &Self) -> bool { **self == **other }
+}
+
+impl<'s> Eq for ReamString<'s> {}
+
+impl<'s> PartialOrd for ReamString<'s> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<'s> Ord for ReamString<'s> {
+	fn cmp(&self, other: &Self) -> Ordering { (**self).cmp(&**other) }
+}
+
+impl<'s> Hash for ReamString<'s> {
+	fn hash<H: Hasher>(&self, state: &mut H) { (**self).hash(state) }
+}
+
+impl<'s> fmt::Debug for ReamString<'s> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Debug::fmt(&**self, f) }
+}
+
+impl<'s> fmt::Display for ReamString<'s> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(&**self, f) }
+}
+
+/// A function built out of other values
+#[derive(Debug, Clone)]
+pub(super) enum Derived<'s> {
+	/// Calls the last function with the arguments, then every function
+	/// before it with the result of the one after it
+	Composition(Vec<ReamValue<'s>>),
+	/// Calls the function with the bound arguments followed by the arguments
+	Partial { function: ReamValue<'s>, bound: Vec<ReamValue<'s>> },
+	/// Calls the function with its first two arguments swapped
+	Flipped(ReamValue<'s>),
+	/// Ignores its arguments and returns the value
+	Constant(ReamValue<'s>),
+}
+
+impl<'s> Derived<'s> {
+	/// Call this function with a list of already evaluated arguments
+	fn call(
+		&self,
+		mut args: Vec<ReamValue<'s>>,
+		loc: Span,
+		scope: Rc<RefCell<Scope<'s>>>,
+	) -> Result<ReamType<'s>, EvalError> {
+		match self {
+			Self::Composition(functions) => {
+				let Some((innermost, rest)) = functions.split_last() else { unreachable!() };
+
+				let mut result = innermost.clone().call(args, scope.clone())?;
+				for function in rest.iter().rev() {
+					let arg = ReamValue { span: loc, t: result };
+					result = function.clone().call(vec![arg], scope.clone())?;
+				}
+
+				Ok(result)
+			},
+			Self::Partial { function, bound } => {
+				let args = bound.iter().cloned().chain(args).collect();
+
+				function.clone().call(args, scope)
+			},
+			Self::Flipped(function) => {
+				if args.len() < 2 {
+					return Err(EvalError::WrongArgumentCount {
+						loc,
+						callee: "flip".to_string(),
+						expected: 2,
+						found: args.len(),
+					});
+				}
+
+				args.swap(0, 1);
+				function.clone().call(args, scope)
+			},
+			Self::Constant(value) => Ok(value.t.clone()),
+		}
+	}
+}
+
+/// The state of an iterator over a built-in collection
+#[derive(Debug, Clone)]
+pub(super) enum Iteration<'s> {
+	List(Vector<ReamValue<'s>>),
+	Values(std::vec::IntoIter<ReamValue<'s>>),
+	Characters { span: Span, string: ReamString<'s>, idx: usize },
+	Range { span: Span, next: i64, end: i64, step: i64 },
+}
+
+impl<'s> Iterator for Iteration<'s> {
+	type Item = ReamValue<'s>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self {
+			Self::List(values) => values.pop_front(),
+			Self::Values(values) => values.next(),
+			Self::Characters { span, string, idx } => {
+				let c = string[*idx..].chars().next()?;
+				*idx += c.len_utf8();
+
+				Some(ReamValue { span: *span, t: ReamType::Character(c) })
+			},
+			Self::Range { span, next, end, step } => {
+				// Ranges with a negative step count down towards their end
+				if (*step > 0 && next >= end) || (*step < 0 && next <= end) {
+					return None;
+				}
+
+				let value = *next;
+				*next = next.saturating_add(*step);
+
+				Some(ReamValue { span: *span, t: ReamType::Integer(value) })
+			},
+		}
+	}
+}
+
 impl<'s> ReamValue<'s> {
+	/// Apply this value to a list of arguments, evaluating them in the given
+	/// scope first
 	pub(super) fn apply(
 		self,
 		args: Vec<Expression<'s>>,
 		scope: Rc<RefCell<Scope<'s>>>,
+	) -> Result<ReamType<'s>, EvalError> {
+		let arg_values = args
+			.into_iter()
+			.map(|o| o.eval(scope.clone()))
+			.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+		self.call(arg_values, scope)
+	}
+
+	/// Apply this value to a list of already evaluated arguments
+	pub(super) fn call(
+		self,
+		args: Vec<ReamValue<'s>>,
+		scope: Rc<RefCell<Scope<'s>>>,
 	) -> Result<ReamType<'s>, EvalError> {
 		match self.t {
-			ReamType::Primitive(prim) => prim(self.span, self.t.type_name(), args, scope),
-			ReamType::Function { formals, body } => {
-				if formals.len() != args.len() {
-					return Err(EvalError::WrongArgumentCount {
-						loc:      self.span,
-						callee:   "TODO".to_string(),
-						expected: formals.len(),
-						found:    args.len(),
-					});
-				}
+			ReamType::Primitive(prim) => {
+				let context = scope.borrow().context.clone();
+				let call_context =
+					CallContext { loc: self.span, callee: prim.name, context, scope };
 
-				let arg_values = args
-					.into_iter()
-					.map(|o| o.eval(scope.clone()))
-					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+				call_context.require(prim.capability)?;
 
-				// Create a new scope with the formals set to their respective argument
-				let execution_scope = Scope::extend(scope);
-				formals
-					.iter()
-					.map(|f| f.id)
-					.zip(arg_values)
-					.for_each(|(k, v)| execution_scope.borrow_mut().set(k, v));
+				(prim.f)(&call_context, args)
+			},
+			ReamType::Host(host) => {
+				let context = scope.borrow().context.clone();
+				let call_context =
+					CallContext { loc: self.span, callee: host.name, context, scope };
 
-				let values = body
-					.into_iter()
-					.map(|e| e.eval(execution_scope.clone()))
-					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+				call_context.require(host.capability)?;
 
-				Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit))
+				let limits = call_context.context.value_limits.get();
+				let args = args
+					.iter()
+					.map(|a| {
+						limits.check(&a.t, self.span)?;
+						call_context.to_value(a)
+					})
+					.collect::<Result<_, _>>()?;
+
+				match (host.f)(args) {
+					Ok(value) => call_context.import_value(value),
+					Err(payload) => Err(EvalError::Raised { loc: self.span, payload }),
+				}
+			},
+			ReamType::ProductConstructor { name, fields } => {
+				construct_product(name, &fields, args, self.span)
 			},
-			ReamType::Closure { formals, body, enclosed_scope } => {
-				if formals.len() != args.len() {
+			ReamType::VariantConstructor { name, variant, has_payload } => {
+				construct_variant(name, variant, has_payload, args, self.span)
+			},
+			ReamType::ProtocolMethod { protocol, method, index, arity } => {
+				if arity != args.len() {
 					return Err(EvalError::WrongArgumentCount {
 						loc:      self.span,
-						callee:   "TODO".to_string(),
-						expected: formals.len(),
+						callee:   method.to_string(),
+						expected: arity,
 						found:    args.len(),
 					});
 				}
 
-				let arg_values = args
-					.into_iter()
-					.map(|o| o.eval(scope.clone()))
-					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
-
-				// Create a new scope with the formals set to their respective argument
-				let execution_scope = Scope::extend(enclosed_scope);
-				formals
-					.iter()
-					.map(|f| f.id)
-					.zip(arg_values)
-					.for_each(|(k, v)| execution_scope.borrow_mut().set(k, v));
+				dispatch(protocol, method, index, args, self.span, scope)
+			},
+			ReamType::Function { name, formals, body } if clauses::generated(&formals) => {
+				let arguments = args.clone();
 
-				let values = body
-					.into_iter()
-					.map(|e| e.eval(execution_scope.clone()))
-					.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+				call_procedure(name, &formals, &body, scope, args, self.span)
+					.map_err(|err| clauses::at_call(err, name, &body, &arguments, self.span))
+			},
+			ReamType::Function { name, formals, body } => {
+				call_procedure(name, &formals, &body, scope, args, self.span)
+			},
+			ReamType::Closure { name, formals, body, enclosed_scope } => {
+				let name = name.unwrap_or("<lambda>");
 
-				Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit))
+				call_procedure(name, &formals, &body, enclosed_scope.scope(), args, self.span)
 			},
+			ReamType::Derived(derived) => derived.call(args, self.span, scope),
 
 			_ => Err(EvalError::NotAFunction { loc: self.span, name: self.t.type_name() }),
 		}
 	}
 }
 
+/// Call a function or closure named `name`, binding its formals to the arguments in a new
+/// scope extending `parent` and evaluating its body there
+///
+/// Functions extend the scope they're called from, closures the scope they
+/// enclosed
+fn call_procedure<'s>(
+	name: &str,
+	formals: &[Pattern<'s>],
+	body: &[Expression<'s>],
+	parent: Rc<RefCell<Scope<'s>>>,
+	args: Vec<ReamValue<'s>>,
+	loc: Span,
+) -> Result<ReamType<'s>, EvalError> {
+	if formals.len() != args.len() {
+		return Err(EvalError::WrongArgumentCount {
+			loc,
+			callee: name.to_string(),
+			expected: formals.len(),
+			found: args.len(),
+		});
+	}
+
+	// Create a new scope with the formals set to their respective argument
+	let execution_scope = Scope::extend(parent);
+	formals.iter().zip(args).try_for_each(|(f, v)| f.bind(v, &execution_scope))?;
+
+	let values = body
+		.iter()
+		.map(|e| e.clone().eval(execution_scope.clone()))
+		.collect::<Result<Vec<ReamValue<'s>>, EvalError>>()?;
+
+	Ok(values.last().cloned().map(|v| v.t).unwrap_or(ReamType::Unit))
+}
+
 impl<'s> fmt::Display for ReamType<'s> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
 			Self::Boolean(b) => write!(f, "{b}"),
 			Self::Integer(i) => write!(f, "{i}"),
-			Self::Float(fl) => write!(f, "{fl}"),
+			#[cfg(feature = "bigint")]
+			Self::BigInt(i) => write!(f, "{i}"),
+			Self::Rational(r) => write!(f, "{r}"),
+			Self::Float(fl) => write!(f, "{}", format_float(*fl)),
 			Self::Character(c) => write!(f, "{c}"),
 			Self::String(s) => write!(f, "{s}"),
+			Self::StringBuilder(_) => write!(f, "string-builder"),
+			#[cfg(feature = "regex")]
+			Self::Regex(r) => write!(f, "regex {}", r.as_str()),
+			#[cfg(feature = "time")]
+			Self::DateTime(t) => write!(f, "{}", t.to_rfc3339()),
+			#[cfg(feature = "time")]
+			Self::Duration(d) => write!(f, "{d}"),
+			Self::Socket(s) => {
+				match s.stream.peer_addr() {
+					Ok(addr) => write!(f, "socket {addr}"),
+					Err(_) => write!(f, "socket"),
+				}
+			},
+			Self::Listener(l) => {
+				match l.local_addr() {
+					Ok(addr) => write!(f, "listener {addr}"),
+					Err(_) => write!(f, "listener"),
+				}
+			},
 			Self::Identifier(i) => write!(f, "{i}"),
 			Self::Atom(a) => write!(f, "{a}"),
 			Self::List(l) => {
@@ -135,29 +766,177 @@ impl<'s> fmt::Display for ReamType<'s> {
 
 				write!(f, "({repr})")
 			},
-			Self::Primitive(_) => write!(f, "primitive"),
-			Self::Function { formals: _, body: _ } => write!(f, "function"),
-			Self::Closure { formals: _, body: _, enclosed_scope: _ } => write!(f, "closure"),
+			Self::Tuple(t) => {
+				let repr: String = t.iter().map(|v| format!(" {}", v.t)).collect();
+
+				write!(f, "(tuple{repr})")
+			},
+			Self::Vector(v) => {
+				let repr: String =
+					v.borrow().iter().map(|v| v.t.to_string()).collect::<Vec<_>>().join(" ");
+
+				write!(f, "#({repr})")
+			},
+			Self::Map(m) => {
+				let repr: String = m
+					.iter()
+					.map(|(k, v)| format!("{} {}", k.value().t, v.t))
+					.collect::<Vec<_>>()
+					.join(", ");
+
+				write!(f, "{{{repr}}}")
+			},
+			Self::Set(s) => {
+				let repr: String =
+					s.iter().map(|k| k.value().t.to_string()).collect::<Vec<_>>().join(", ");
+
+				write!(f, "#{{{repr}}}")
+			},
+			Self::Range { start, end, step } => write!(f, "(range {start} {end} {step})"),
+			Self::Iterator(_) => write!(f, "iterator"),
+			Self::Ok(v) => write!(f, "(ok {})", v.t),
+			Self::Err(e) => write!(f, "(err {})", e.t),
+			Self::Some(v) => write!(f, "(some {})", v.t),
+			Self::None => write!(f, "none"),
+			Self::Product { name, fields } => {
+				let repr: String = fields.iter().map(|(f, v)| format!(" :{f} {}", v.t)).collect();
+
+				write!(f, "({name}{repr})")
+			},
+			Self::Variant { name: _, variant, payload: Some(payload) } => {
+				write!(f, "({variant} {})", payload.t)
+			},
+			Self::Variant { name: _, variant, payload: None } => write!(f, "({variant})"),
+			Self::Primitive(prim) => write!(f, "primitive {}", prim.name),
+			Self::Host(host) => write!(f, "primitive {}", host.name),
+			Self::ProductConstructor { name, fields: _ } => write!(f, "constructor {name}"),
+			Self::VariantConstructor { name, variant, has_payload: _ } => {
+				write!(f, "constructor {name}:{variant}")
+			},
+			Self::ProtocolMethod { protocol, method, .. } => {
+				write!(f, "method {protocol}/{method}")
+			},
+			Self::Function { .. } => write!(f, "function"),
+			Self::Closure { .. } => write!(f, "closure"),
+			Self::Derived(_) => write!(f, "function"),
 			Self::Unit => write!(f, "()"),
 		}
 	}
 }
 
 impl<'s> ReamType<'s> {
+	/// Convert this value into a public [`Value`] detached from the
+	/// interpreter
+	pub(super) fn to_value(&self) -> Value {
+		match self.to_value_with(&|v| Ok::<_, Infallible>(v.t.to_value())) {
+			Ok(value) => value,
+		}
+	}
+
+	/// Convert this value into a public [`Value`], converting the values it
+	/// contains with `convert`
+	fn to_value_with<E>(
+		&self,
+		convert: &dyn Fn(&ReamValue<'s>) -> Result<Value, E>,
+	) -> Result<Value, E> {
+		let all = |values: &mut dyn Iterator<Item = &ReamValue<'s>>| {
+			values.map(convert).collect::<Result<Vec<_>, E>>()
+		};
+		let boxed = |v: &ReamValue<'s>| convert(v).map(Box::new);
+
+		let value = match self {
+			Self::Unit => Value::Unit,
+			Self::Boolean(b) => Value::Boolean(*b),
+			Self::Integer(i) => Value::Integer(*i),
+			#[cfg(feature = "bigint")]
+			Self::BigInt(i) => Value::BigInt(i.as_ref().clone()),
+			Self::Rational(r) => Value::Rational(*r),
+			Self::Float(f) => Value::Float(*f),
+			Self::Character(c) => Value::Character(*c),
+			Self::String(s) => Value::String(s.to_string()),
+			Self::Identifier(i) => Value::Identifier(i.to_string()),
+			Self::Atom(a) => Value::Atom(a.to_string()),
+			Self::List(l) => Value::List(all(&mut l.iter())?),
+			Self::Tuple(t) => Value::Tuple(all(&mut t.iter())?),
+			Self::Vector(v) => Value::Vector(all(&mut v.borrow().iter())?),
+			Self::Map(m) => {
+				Value::Map(
+					m.iter()
+						.map(|(k, v)| Ok((convert(k.value())?, convert(v)?)))
+						.collect::<Result<_, E>>()?,
+				)
+			},
+			Self::Set(s) => Value::Set(all(&mut s.iter().map(|k| k.value()))?),
+			Self::Ok(v) => Value::Ok(boxed(v)?),
+			Self::Err(e) => Value::Err(boxed(e)?),
+			Self::Some(v) => Value::Some(boxed(v)?),
+			Self::None => Value::None,
+			Self::Product { name, fields } => {
+				Value::Product {
+					name:   name.to_string(),
+					fields: fields
+						.iter()
+						.map(|(f, v)| Ok((f.to_string(), convert(v)?)))
+						.collect::<Result<_, E>>()?,
+				}
+			},
+			Self::Variant { name, variant, payload } => {
+				Value::Variant {
+					name:    name.to_string(),
+					variant: variant.to_string(),
+					payload: payload.as_deref().map(boxed).transpose()?,
+				}
+			},
+			t => Value::Opaque { type_name: t.type_name(), repr: t.to_string() },
+		};
+
+		Ok(value)
+	}
+
 	/// Render the name of this type as a string
 	pub(super) fn type_name(&self) -> String {
 		match self {
 			Self::Boolean(_) => "Boolean".to_string(),
 			Self::Integer(_) => "Integer".to_string(),
+			#[cfg(feature = "bigint")]
+			Self::BigInt(_) => "Integer".to_string(),
+			Self::Rational(_) => "Rational".to_string(),
 			Self::Float(_) => "Float".to_string(),
 			Self::Character(_) => "Character".to_string(),
 			Self::String(_) => "String".to_string(),
+			Self::StringBuilder(_) => "StringBuilder".to_string(),
+			#[cfg(feature = "regex")]
+			Self::Regex(_) => "Regex".to_string(),
+			#[cfg(feature = "time")]
+			Self::DateTime(_) => "DateTime".to_string(),
+			#[cfg(feature = "time")]
+			Self::Duration(_) => "Duration".to_string(),
+			Self::Socket(_) => "Socket".to_string(),
+			Self::Listener(_) => "Listener".to_string(),
 			Self::Identifier(_) => "Identifier".to_string(),
 			Self::Atom(_) => "Atom".to_string(),
 			Self::List(_) => "List".to_string(),
-			Self::Primitive(_) => "Primitive".to_string(),
-			Self::Function { formals: _, body: _ } => "Function".to_string(),
-			Self::Closure { formals: _, body: _, enclosed_scope: _ } => "Closure".to_string(),
+			Self::Tuple(_) => "Tuple".to_string(),
+			Self::Vector(_) => "Vector".to_string(),
+			Self::Map(_) => "Map".to_string(),
+			Self::Set(_) => "Set".to_string(),
+			Self::Range { .. } => "Range".to_string(),
+			Self::Iterator(_) => "Iterator".to_string(),
+			Self::Ok(_) => "Result".to_string(),
+			Self::Err(_) => "Result".to_string(),
+			Self::Some(_) => "Option".to_string(),
+			Self::None => "Option".to_string(),
+			Self::Product { name, fields: _ } => name.to_string(),
+			Self::Variant { name, variant: _, payload: _ } => name.to_string(),
+			Self::Primitive(_) | Self::Host(_) => "Primitive".to_string(),
+			Self::ProductConstructor { name: _, fields: _ } => "Constructor".to_string(),
+			Self::VariantConstructor { name: _, variant: _, has_payload: _ } => {
+				"Constructor".to_string()
+			},
+			Self::ProtocolMethod { .. } => "Method".to_string(),
+			Self::Function { .. } => "Function".to_string(),
+			Self::Closure { .. } => "Closure".to_string(),
+			Self::Derived(_) => "Function".to_string(),
 			Self::Unit => "Unit".to_string(),
 		}
 	}
@@ -167,16 +946,212 @@ impl<'s> ReamType<'s> {
 		match self {
 			Self::Boolean(b) => *b,
 			Self::Integer(i) => *i != 0,
+			// Big integers are never zero
+			#[cfg(feature = "bigint")]
+			Self::BigInt(_) => true,
+			// Neither are rationals, which are never whole
+			Self::Rational(_) => true,
 			Self::Float(f) => *f != 0.0,
 			Self::Character(_) => true,
 			Self::String(s) => !s.is_empty(),
+			Self::StringBuilder(_) => true,
+			#[cfg(feature = "regex")]
+			Self::Regex(_) => true,
+			#[cfg(feature = "time")]
+			Self::DateTime(_) => true,
+			#[cfg(feature = "time")]
+			Self::Duration(d) => !d.is_zero(),
+			Self::Socket(_) => true,
+			Self::Listener(_) => true,
 			Self::Identifier(_) => true,
 			Self::Atom(_) => true,
 			Self::List(l) => !l.is_empty(),
+			Self::Tuple(t) => !t.is_empty(),
+			Self::Vector(v) => !v.borrow().is_empty(),
+			Self::Map(m) => !m.is_empty(),
+			Self::Set(s) => !s.is_empty(),
+			// A range is empty if its step moves away from its end
+			Self::Range { start, end, step } => {
+				(*step > 0 && start < end) || (*step < 0 && start > end)
+			},
+			Self::Iterator(_) => true,
+			Self::Ok(_) => true,
+			Self::Err(_) => true,
+			Self::Some(_) => true,
+			Self::None => false,
+			Self::Product { name: _, fields: _ } => true,
+			Self::Variant { name: _, variant: _, payload: _ } => true,
 			Self::Primitive(_) => true,
-			Self::Function { formals: _, body: _ } => true,
-			Self::Closure { formals: _, body: _, enclosed_scope: _ } => true,
+			Self::Host(_) => true,
+			Self::ProductConstructor { name: _, fields: _ } => true,
+			Self::VariantConstructor { name: _, variant: _, has_payload: _ } => true,
+			Self::ProtocolMethod { .. } => true,
+			Self::Function { .. } => true,
+			Self::Closure { .. } => true,
+			Self::Derived(_) => true,
 			Self::Unit => true,
 		}
 	}
+
+	/// Check if two values are structurally equal
+	///
+	/// Functions are never equal to anything, not even themselves
+	pub(super) fn equals(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Boolean(a), Self::Boolean(b)) => a == b,
+			(Self::Integer(a), Self::Integer(b)) => a == b,
+			#[cfg(feature = "bigint")]
+			(Self::BigInt(a), Self::BigInt(b)) => a == b,
+			(Self::Rational(a), Self::Rational(b)) => a == b,
+			(Self::Float(a), Self::Float(b)) => a == b,
+			(Self::Character(a), Self::Character(b)) => a == b,
+			(Self::String(a), Self::String(b)) => a == b,
+			(Self::StringBuilder(a), Self::StringBuilder(b)) => Rc::ptr_eq(a, b),
+			#[cfg(feature = "regex")]
+			(Self::Regex(a), Self::Regex(b)) => a.as_str() == b.as_str(),
+			#[cfg(feature = "time")]
+			(Self::DateTime(a), Self::DateTime(b)) => a == b,
+			#[cfg(feature = "time")]
+			(Self::Duration(a), Self::Duration(b)) => a == b,
+			(Self::Socket(a), Self::Socket(b)) => Rc::ptr_eq(a, b),
+			(Self::Listener(a), Self::Listener(b)) => Rc::ptr_eq(a, b),
+			(Self::Identifier(a), Self::Identifier(b)) => a == b,
+			(Self::Atom(a), Self::Atom(b)) => a == b,
+			(Self::List(a), Self::List(b)) => {
+				a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.t.equals(&b.t))
+			},
+			(Self::Tuple(a), Self::Tuple(b)) => {
+				a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.t.equals(&b.t))
+			},
+			(Self::Vector(a), Self::Vector(b)) => {
+				let (a, b) = (a.borrow(), b.borrow());
+
+				a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.t.equals(&b.t))
+			},
+			(Self::Map(a), Self::Map(b)) => {
+				a.len() == b.len()
+					&& a.iter().zip(b).all(|((ak, av), (bk, bv))| ak == bk && av.t.equals(&bv.t))
+			},
+			(Self::Set(a), Self::Set(b)) => a == b,
+			(
+				Self::Range { start: a_start, end: a_end, step: a_step },
+				Self::Range { start: b_start, end: b_end, step: b_step },
+			) => a_start == b_start && a_end == b_end && a_step == b_step,
+			(Self::Iterator(a), Self::Iterator(b)) => Rc::ptr_eq(a, b),
+			(Self::Ok(a), Self::Ok(b)) => a.t.equals(&b.t),
+			(Self::Err(a), Self::Err(b)) => a.t.equals(&b.t),
+			(Self::Some(a), Self::Some(b)) => a.t.equals(&b.t),
+			(Self::None, Self::None) => true,
+			(
+				Self::Product { name: a, fields: a_fields },
+				Self::Product { name: b, fields: b_fields },
+			) => a == b && a_fields.iter().zip(b_fields).all(|((_, a), (_, b))| a.t.equals(&b.t)),
+			(
+				Self::Variant { name: a, variant: a_variant, payload: a_payload },
+				Self::Variant { name: b, variant: b_variant, payload: b_payload },
+			) => {
+				a == b
+					&& a_variant == b_variant
+					&& match (a_payload, b_payload) {
+						(Some(a), Some(b)) => a.t.equals(&b.t),
+						(a, b) => a.is_none() && b.is_none(),
+					}
+			},
+			(Self::Unit, Self::Unit) => true,
+			_ => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parse_str, Capabilities, Interpreter};
+
+	/// Run a program, returning the interpreter it ran in
+	fn run(source: &str) -> Interpreter<'_> {
+		let mut interpreter = Interpreter::new(Capabilities::PURE);
+		interpreter.run(parse_str(source).unwrap()).unwrap();
+
+		interpreter
+	}
+
+	/// Get the value bound to a global variable
+	fn global<'s>(interpreter: &Interpreter<'s>, name: &'s str) -> ReamType<'s> {
+		interpreter.global_scope.borrow().get(name).unwrap().t
+	}
+
+	#[test]
+	fn strings_passed_to_functions_are_shared() {
+		let interpreter =
+			run("(fn id (x) x)\n(let s (string-append \"a\" \"b\"))\n(let t (id (id s)))\n");
+
+		let ReamType::String(s) = global(&interpreter, "s") else { unreachable!() };
+		let ReamType::String(t) = global(&interpreter, "t") else { unreachable!() };
+
+		assert!(matches!(s, ReamString::Shared(_)));
+		assert_eq!(s.as_ptr(), t.as_ptr());
+	}
+
+	#[test]
+	fn procedures_share_their_formals_and_body() {
+		let interpreter = run("(fn f (x) x)\n(let g (lambda (y) y))\n(let h g)\n");
+
+		let ReamType::Function { formals, body, .. } = global(&interpreter, "f") else {
+			unreachable!()
+		};
+		let ReamType::Function { formals: formals_again, body: body_again, .. } =
+			global(&interpreter, "f")
+		else {
+			unreachable!()
+		};
+		assert!(Rc::ptr_eq(&formals, &formals_again));
+		assert!(Rc::ptr_eq(&body, &body_again));
+
+		let ReamType::Closure { body: g, .. } = global(&interpreter, "g") else { unreachable!() };
+		let ReamType::Closure { body: h, .. } = global(&interpreter, "h") else { unreachable!() };
+		assert!(Rc::ptr_eq(&g, &h));
+	}
+
+	#[test]
+	fn local_closures_do_not_keep_their_scope_alive() {
+		let mut interpreter = run(
+			"(fn count-down (n) (let f (lambda (n) (if (== n 0) 0 (f (- n 1))))) (f n))\n",
+		);
+		let before = Rc::strong_count(&interpreter.global_scope);
+
+		// Each call's scope has the global scope as its parent, so any of them
+		// kept alive by the closure bound in it would show up here
+		for _ in 0..100 {
+			interpreter.run(parse_str("(count-down 3)\n").unwrap()).unwrap();
+		}
+
+		assert_eq!(Rc::strong_count(&interpreter.global_scope), before);
+	}
+
+	#[test]
+	fn escaped_closures_keep_their_scope_alive() {
+		let interpreter = run(
+			"(fn make () (let f (lambda (n) (if (== n 0) :done (f (- n 1))))) f)
+			(let g (make))
+			(let result (g 3))\n",
+		);
+
+		assert!(matches!(global(&interpreter, "result"), ReamType::Atom(":done")));
+	}
+
+	#[test]
+	fn top_level_closures_do_not_keep_the_global_scope_alive() {
+		let interpreter = run(
+			"(let f (lambda (n) (if (== n 0) 0 (f (- n 1)))))
+			(const g (lambda () (f 3)))
+			(let result (g))\n",
+		);
+		let global_scope = Rc::downgrade(&interpreter.global_scope);
+
+		assert!(matches!(global(&interpreter, "result"), ReamType::Integer(0)));
+
+		drop(interpreter);
+		assert!(global_scope.upgrade().is_none());
+	}
 }
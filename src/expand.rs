@@ -0,0 +1,670 @@
+//! Macro expansion, run on every top-level expression right before it is
+//! evaluated
+
+use std::collections::HashMap;
+
+use crate::ast::{
+	CaseClause,
+	Datum,
+	Expression,
+	Identifier,
+	LetBinding,
+	Literal,
+	MatchClause,
+	Pattern,
+	Program,
+};
+use crate::{ExpandError, Span};
+
+/// How many expansions can be nested inside each other before a macro is
+/// assumed to expand forever
+const RECURSION_LIMIT: usize = 256;
+
+/// A macro defined using `define-macro`
+#[derive(Clone, Debug)]
+struct Macro<'s> {
+	formals: Vec<Identifier<'s>>,
+	rest:    Option<Identifier<'s>>,
+	body:    Expression<'s>,
+}
+
+/// Replaces calls to macros with the code they expand to, remembering every
+/// macro defined so far
+///
+/// Macros are templates, the arguments of a call are substituted into the
+/// body of the macro without being evaluated. The operands collected by a
+/// rest parameter are spliced in wherever it appears in a list of
+/// expressions, eg. `(seq body)`. Names bound by the body itself
+/// are renamed to fresh names, so they can't clash with the names used by
+/// the arguments
+#[derive(Debug, Default)]
+pub struct Expander<'s> {
+	macros:  HashMap<&'s str, Macro<'s>>,
+	/// The amount of fresh names generated so far
+	gensyms: usize,
+}
+
+impl<'s> Expander<'s> {
+	/// Create a new [`Expander`] that doesn't know about any macros yet
+	pub fn new() -> Self { Self::default() }
+
+	/// Expand every macro call in a program
+	///
+	/// Macros can only be used by expressions following their definition
+	pub fn expand_program(&mut self, program: Program<'s>) -> Result<Program<'s>, ExpandError> {
+		program.0.into_iter().map(|expr| self.expand(expr)).collect::<Result<_, _>>().map(Program)
+	}
+
+	/// Expand every macro call in an expression, and remember every macro it
+	/// defines
+	pub fn expand(&mut self, expr: Expression<'s>) -> Result<Expression<'s>, ExpandError> {
+		self.expand_nested(expr, 0)
+	}
+
+	/// Expand an expression that is the result of `depth` nested expansions
+	fn expand_nested(
+		&mut self,
+		expr: Expression<'s>,
+		depth: usize,
+	) -> Result<Expression<'s>, ExpandError> {
+		let expanded = match expr {
+			Expression::MacroDefinition { span, target, formals, rest, body } => {
+				let definition = Macro { formals: formals.clone(), rest, body: (*body).clone() };
+				self.macros.insert(target.id, definition);
+
+				Expression::MacroDefinition { span, target, formals, rest, body }
+			},
+			Expression::ProcedureCall { span, operator, operands } => {
+				let definition = match operator.as_ref() {
+					Expression::Identifier(id) => {
+						self.macros.get(id.id).cloned().map(|m| (id.id, m))
+					},
+					_ => None,
+				};
+
+				let Some((name, definition)) = definition else {
+					return Ok(Expression::ProcedureCall {
+						span,
+						operator: Box::new(self.expand_nested(*operator, depth)?),
+						operands: self.expand_all(operands, depth)?,
+					});
+				};
+
+				if depth >= RECURSION_LIMIT {
+					return Err(ExpandError::RecursionLimit {
+						loc:   span,
+						name:  name.to_string(),
+						limit: RECURSION_LIMIT,
+					});
+				}
+
+				let instantiated = self.instantiate(name, &definition, span, operands)?;
+
+				self.expand_nested(instantiated, depth + 1)?
+			},
+			Expression::VariableDefinition { span, target, value } => {
+				Expression::VariableDefinition {
+					span,
+					target,
+					value: Box::new(self.expand_nested(*value, depth)?),
+				}
+			},
+			Expression::ConstantDefinition { span, target, value } => {
+				Expression::ConstantDefinition {
+					span,
+					target,
+					value: Box::new(self.expand_nested(*value, depth)?),
+				}
+			},
+			Expression::FunctionDefinition { span, target, formals, body } => {
+				Expression::FunctionDefinition {
+					span,
+					target,
+					formals,
+					body: self.expand_all(body, depth)?,
+				}
+			},
+			Expression::FunctionClause { span, target, patterns, body } => {
+				Expression::FunctionClause {
+					span,
+					target,
+					patterns,
+					body: self.expand_all(body, depth)?,
+				}
+			},
+			Expression::ClosureDefintion { span, formals, body } => {
+				Expression::ClosureDefintion { span, formals, body: self.expand_all(body, depth)? }
+			},
+			Expression::Sequence { span, seq } => {
+				Expression::Sequence { span, seq: self.expand_all(seq, depth)? }
+			},
+			Expression::Let { span, bindings, body } => {
+				let bindings = bindings
+					.into_iter()
+					.map(|binding| {
+						Ok(LetBinding {
+							value: self.expand_nested(binding.value, depth)?,
+							..binding
+						})
+					})
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Let { span, bindings, body: self.expand_all(body, depth)? }
+			},
+			Expression::Vector { span, elements } => {
+				Expression::Vector { span, elements: self.expand_all(elements, depth)? }
+			},
+			Expression::TypeAssertion { span, spec, value } => {
+				Expression::TypeAssertion {
+					span,
+					spec,
+					value: Box::new(self.expand_nested(*value, depth)?),
+				}
+			},
+			Expression::Time { span, value } => {
+				Expression::Time { span, value: Box::new(self.expand_nested(*value, depth)?) }
+			},
+			Expression::Conditional { span, test, consequent, alternate } => {
+				Expression::Conditional {
+					span,
+					test: Box::new(self.expand_nested(*test, depth)?),
+					consequent: Box::new(self.expand_nested(*consequent, depth)?),
+					alternate: alternate
+						.map(|alt| self.expand_nested(*alt, depth).map(Box::new))
+						.transpose()?,
+				}
+			},
+			Expression::Match { span, value, clauses } => {
+				let clauses = clauses
+					.into_iter()
+					.map(|clause| {
+						Ok(MatchClause {
+							guard: clause
+								.guard
+								.map(|g| self.expand_nested(g, depth))
+								.transpose()?,
+							body: self.expand_all(clause.body, depth)?,
+							..clause
+						})
+					})
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Match {
+					span,
+					value: Box::new(self.expand_nested(*value, depth)?),
+					clauses,
+				}
+			},
+			Expression::Case { span, value, clauses, default } => {
+				let clauses = clauses
+					.into_iter()
+					.map(|clause| {
+						Ok(CaseClause { body: self.expand_all(clause.body, depth)?, ..clause })
+					})
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Case {
+					span,
+					value: Box::new(self.expand_nested(*value, depth)?),
+					clauses,
+					default: default.map(|d| self.expand_all(d, depth)).transpose()?,
+				}
+			},
+			Expression::ProtocolImplementation { span, protocol, target, methods } => {
+				Expression::ProtocolImplementation {
+					span,
+					protocol,
+					target,
+					methods: self.expand_all(methods, depth)?,
+				}
+			},
+
+			Expression::Literal(Literal::Quotation { span, q }) => {
+				Expression::Literal(Literal::Quotation { span, q: self.expand_datum(q, depth)? })
+			},
+
+			expr => expr,
+		};
+
+		Ok(expanded)
+	}
+
+	/// Expand the unquoted expressions of a datum that is the result of
+	/// `depth` nested expansions
+	fn expand_datum(&mut self, datum: Datum<'s>, depth: usize) -> Result<Datum<'s>, ExpandError> {
+		let expanded = match datum {
+			Datum::Unquote { span, e } => {
+				Datum::Unquote { span, e: Box::new(self.expand_nested(*e, depth)?) }
+			},
+			Datum::UnquoteSplicing { span, e } => {
+				Datum::UnquoteSplicing { span, e: Box::new(self.expand_nested(*e, depth)?) }
+			},
+			Datum::List { span, l } => {
+				let data = Vec::from(l)
+					.into_iter()
+					.map(|d| self.expand_datum(d, depth))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Datum::List { span, l: data.into() }
+			},
+			Datum::Vector { span, data } => {
+				let data = data
+					.into_iter()
+					.map(|d| self.expand_datum(d, depth))
+					.collect::<Result<_, _>>()?;
+
+				Datum::Vector { span, data }
+			},
+			datum => datum,
+		};
+
+		Ok(expanded)
+	}
+
+	/// Expand a list of expressions that are the result of `depth` nested
+	/// expansions
+	fn expand_all(
+		&mut self,
+		exprs: Vec<Expression<'s>>,
+		depth: usize,
+	) -> Result<Vec<Expression<'s>>, ExpandError> {
+		exprs.into_iter().map(|expr| self.expand_nested(expr, depth)).collect()
+	}
+
+	/// Substitute the operands of a call to a macro into its body
+	fn instantiate(
+		&mut self,
+		name: &'s str,
+		definition: &Macro<'s>,
+		span: Span,
+		operands: Vec<Expression<'s>>,
+	) -> Result<Expression<'s>, ExpandError> {
+		let arity = definition.formals.len();
+		let (arity_matches, expected) = match definition.rest {
+			Some(_) => (operands.len() >= arity, format!("at least {arity}")),
+			None => (operands.len() == arity, arity.to_string()),
+		};
+
+		if !arity_matches {
+			return Err(ExpandError::WrongArgumentCount {
+				loc: span,
+				name: name.to_string(),
+				expected,
+				found: operands.len(),
+			});
+		}
+
+		let mut operands = operands.into_iter();
+		let bindings = definition.formals.iter().map(|f| f.id).zip(operands.by_ref()).collect();
+		let rest = definition.rest.map(|r| (r.id, operands.collect()));
+
+		let mut bound = vec![];
+		bound_names(&definition.body, &mut bound);
+
+		let mut renames = HashMap::new();
+		for id in bound {
+			let is_parameter =
+				definition.formals.iter().chain(&definition.rest).any(|f| f.id == id);
+
+			if !is_parameter && !renames.contains_key(id) {
+				let fresh = self.gensym(id);
+				renames.insert(id, fresh);
+			}
+		}
+
+		Instantiation { name, bindings, rest, renames }.expression(definition.body.clone())
+	}
+
+	/// Generate a fresh name based on an existing one
+	fn gensym(&mut self, id: &str) -> &'s str {
+		self.gensyms += 1;
+
+		// Generated names become part of the AST and have to live as long as it
+		// does, the `#` makes sure they can't clash with a name from the source
+		Box::leak(format!("{id}#{}", self.gensyms).into_boxed_str())
+	}
+}
+
+/// The substitutions made while instantiating the body of a single macro
+/// call
+struct Instantiation<'s> {
+	/// The name of the macro being instantiated
+	name:     &'s str,
+	/// The operand passed for each formal parameter
+	bindings: HashMap<&'s str, Expression<'s>>,
+	/// The rest parameter and the operands it collected
+	rest:     Option<(&'s str, Vec<Expression<'s>>)>,
+	/// The fresh names given to the names bound by the body
+	renames:  HashMap<&'s str, &'s str>,
+}
+
+impl<'s> Instantiation<'s> {
+	/// Instantiate an expression of the body
+	fn expression(&self, expr: Expression<'s>) -> Result<Expression<'s>, ExpandError> {
+		let instantiated = match expr {
+			Expression::Identifier(id) => {
+				if let Some(operand) = self.bindings.get(id.id) {
+					operand.clone()
+				} else if self.rest.as_ref().is_some_and(|(rest, _)| *rest == id.id) {
+					return Err(ExpandError::MisplacedRest {
+						loc:  id.span,
+						rest: id.id.to_string(),
+					});
+				} else {
+					Expression::Identifier(self.rename(id))
+				}
+			},
+			Expression::VariableDefinition { span, target, value } => {
+				Expression::VariableDefinition {
+					span,
+					target: self.pattern(target)?,
+					value: Box::new(self.expression(*value)?),
+				}
+			},
+			Expression::ConstantDefinition { span, target, value } => {
+				Expression::ConstantDefinition {
+					span,
+					target: self.name(target)?,
+					value: Box::new(self.expression(*value)?),
+				}
+			},
+			Expression::FunctionDefinition { span, target, formals, body } => {
+				Expression::FunctionDefinition {
+					span,
+					target: self.name(target)?,
+					formals: self.patterns(formals)?,
+					body: self.expressions(body)?,
+				}
+			},
+			Expression::FunctionClause { span, target, patterns, body } => {
+				Expression::FunctionClause {
+					span,
+					target: self.name(target)?,
+					patterns: self.patterns(patterns)?,
+					body: self.expressions(body)?,
+				}
+			},
+			Expression::ClosureDefintion { span, formals, body } => {
+				Expression::ClosureDefintion {
+					span,
+					formals: self.patterns(formals)?,
+					body: self.expressions(body)?,
+				}
+			},
+			Expression::Sequence { span, seq } => {
+				Expression::Sequence { span, seq: self.expressions(seq)? }
+			},
+			Expression::Let { span, bindings, body } => {
+				let bindings = bindings
+					.into_iter()
+					.map(|binding| {
+						Ok(LetBinding {
+							span:   binding.span,
+							target: self.pattern(binding.target)?,
+							value:  self.expression(binding.value)?,
+						})
+					})
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Let { span, bindings, body: self.expressions(body)? }
+			},
+			Expression::Vector { span, elements } => {
+				Expression::Vector { span, elements: self.expressions(elements)? }
+			},
+			Expression::ProcedureCall { span, operator, operands } => {
+				Expression::ProcedureCall {
+					span,
+					operator: Box::new(self.expression(*operator)?),
+					operands: self.expressions(operands)?,
+				}
+			},
+			Expression::TypeAssertion { span, spec, value } => {
+				Expression::TypeAssertion { span, spec, value: Box::new(self.expression(*value)?) }
+			},
+			Expression::Time { span, value } => {
+				Expression::Time { span, value: Box::new(self.expression(*value)?) }
+			},
+			Expression::Conditional { span, test, consequent, alternate } => {
+				Expression::Conditional {
+					span,
+					test: Box::new(self.expression(*test)?),
+					consequent: Box::new(self.expression(*consequent)?),
+					alternate: alternate
+						.map(|alt| self.expression(*alt).map(Box::new))
+						.transpose()?,
+				}
+			},
+			Expression::Match { span, value, clauses } => {
+				let clauses = clauses
+					.into_iter()
+					.map(|clause| {
+						Ok(MatchClause {
+							span:    clause.span,
+							pattern: self.pattern(clause.pattern)?,
+							guard:   clause.guard.map(|g| self.expression(g)).transpose()?,
+							body:    self.expressions(clause.body)?,
+						})
+					})
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Match { span, value: Box::new(self.expression(*value)?), clauses }
+			},
+			Expression::Case { span, value, clauses, default } => {
+				let clauses = clauses
+					.into_iter()
+					.map(|clause| Ok(CaseClause { body: self.expressions(clause.body)?, ..clause }))
+					.collect::<Result<_, ExpandError>>()?;
+
+				Expression::Case {
+					span,
+					value: Box::new(self.expression(*value)?),
+					clauses,
+					default: default.map(|d| self.expressions(d)).transpose()?,
+				}
+			},
+			Expression::ProtocolImplementation { span, protocol, target, methods } => {
+				Expression::ProtocolImplementation {
+					span,
+					protocol,
+					target,
+					methods: self.expressions(methods)?,
+				}
+			},
+
+			Expression::Literal(Literal::Quotation { span, q }) => {
+				Expression::Literal(Literal::Quotation { span, q: self.datum(q)? })
+			},
+
+			expr => expr,
+		};
+
+		Ok(instantiated)
+	}
+
+	/// Instantiate the unquoted expressions of a datum of the body
+	fn datum(&self, datum: Datum<'s>) -> Result<Datum<'s>, ExpandError> {
+		let instantiated = match datum {
+			Datum::Unquote { span, e } => {
+				Datum::Unquote { span, e: Box::new(self.expression(*e)?) }
+			},
+			Datum::UnquoteSplicing { span, e } => {
+				Datum::UnquoteSplicing { span, e: Box::new(self.expression(*e)?) }
+			},
+			Datum::List { span, l } => {
+				let data = Vec::from(l)
+					.into_iter()
+					.map(|d| self.datum(d))
+					.collect::<Result<Vec<_>, _>>()?;
+
+				Datum::List { span, l: data.into() }
+			},
+			Datum::Vector { span, data } => {
+				let data = data.into_iter().map(|d| self.datum(d)).collect::<Result<_, _>>()?;
+
+				Datum::Vector { span, data }
+			},
+			datum => datum,
+		};
+
+		Ok(instantiated)
+	}
+
+	/// Instantiate a list of expressions of the body, splicing in the operands
+	/// collected by the rest parameter wherever it appears
+	fn expressions(&self, exprs: Vec<Expression<'s>>) -> Result<Vec<Expression<'s>>, ExpandError> {
+		let mut instantiated = vec![];
+
+		for expr in exprs {
+			match (&expr, &self.rest) {
+				(Expression::Identifier(id), Some((rest, operands))) if id.id == *rest => {
+					instantiated.extend(operands.iter().cloned());
+				},
+				_ => instantiated.push(self.expression(expr)?),
+			}
+		}
+
+		Ok(instantiated)
+	}
+
+	/// Instantiate a pattern of the body
+	fn pattern(&self, pattern: Pattern<'s>) -> Result<Pattern<'s>, ExpandError> {
+		let instantiated = match pattern {
+			Pattern::Identifier(id) => Pattern::Identifier(self.name(id)?),
+			Pattern::List { span, patterns } => {
+				Pattern::List { span, patterns: self.patterns(patterns)? }
+			},
+			Pattern::Cons { span, head, tail } => {
+				Pattern::Cons {
+					span,
+					head: Box::new(self.pattern(*head)?),
+					tail: Box::new(self.pattern(*tail)?),
+				}
+			},
+			Pattern::Variant { span, variant, payload } => {
+				Pattern::Variant {
+					span,
+					variant,
+					payload: payload.map(|p| self.pattern(*p).map(Box::new)).transpose()?,
+				}
+			},
+			pattern => pattern,
+		};
+
+		Ok(instantiated)
+	}
+
+	/// Instantiate a list of patterns of the body
+	fn patterns(&self, patterns: Vec<Pattern<'s>>) -> Result<Vec<Pattern<'s>>, ExpandError> {
+		patterns.into_iter().map(|p| self.pattern(p)).collect()
+	}
+
+	/// Instantiate an identifier of the body in a position where only a name
+	/// is allowed, so any operand substituted for it has to be an identifier
+	/// as well
+	fn name(&self, id: Identifier<'s>) -> Result<Identifier<'s>, ExpandError> {
+		match self.bindings.get(id.id) {
+			Some(Expression::Identifier(operand)) => Ok(*operand),
+			Some(operand) => {
+				Err(ExpandError::NotAnIdentifier {
+					loc:    operand.span(),
+					name:   self.name.to_string(),
+					formal: id.id.to_string(),
+				})
+			},
+			None => Ok(self.rename(id)),
+		}
+	}
+
+	/// Give an identifier of the body its fresh name, if it has one
+	fn rename(&self, id: Identifier<'s>) -> Identifier<'s> {
+		match self.renames.get(id.id) {
+			Some(fresh) => Identifier { span: id.span, id: fresh },
+			None => id,
+		}
+	}
+}
+
+/// Collect every name bound by an expression, through definitions, formal
+/// parameters and patterns
+fn bound_names<'s>(expr: &Expression<'s>, names: &mut Vec<&'s str>) {
+	match expr {
+		Expression::VariableDefinition { target, value, .. } => {
+			pattern_names(target, names);
+			bound_names(value, names);
+		},
+		Expression::ConstantDefinition { target, value, .. } => {
+			names.push(target.id);
+			bound_names(value, names);
+		},
+		Expression::FunctionDefinition { target, formals, body, .. } => {
+			names.push(target.id);
+			formals.iter().for_each(|f| pattern_names(f, names));
+			body.iter().for_each(|e| bound_names(e, names));
+		},
+		Expression::FunctionClause { target, patterns, body, .. } => {
+			names.push(target.id);
+			patterns.iter().for_each(|p| pattern_names(p, names));
+			body.iter().for_each(|e| bound_names(e, names));
+		},
+		Expression::ClosureDefintion { formals, body, .. } => {
+			formals.iter().for_each(|f| pattern_names(f, names));
+			body.iter().for_each(|e| bound_names(e, names));
+		},
+		Expression::Sequence { seq, .. } | Expression::Vector { elements: seq, .. } => {
+			seq.iter().for_each(|e| bound_names(e, names))
+		},
+		Expression::Let { bindings, body, .. } => {
+			for binding in bindings {
+				pattern_names(&binding.target, names);
+				bound_names(&binding.value, names);
+			}
+			body.iter().for_each(|e| bound_names(e, names));
+		},
+		Expression::ProcedureCall { operator, operands, .. } => {
+			bound_names(operator, names);
+			operands.iter().for_each(|e| bound_names(e, names));
+		},
+		Expression::TypeAssertion { value, .. } | Expression::Time { value, .. } => {
+			bound_names(value, names)
+		},
+		Expression::Conditional { test, consequent, alternate, .. } => {
+			bound_names(test, names);
+			bound_names(consequent, names);
+			if let Some(alternate) = alternate {
+				bound_names(alternate, names);
+			}
+		},
+		Expression::Case { value, clauses, default, .. } => {
+			bound_names(value, names);
+			clauses.iter().flat_map(|c| &c.body).for_each(|e| bound_names(e, names));
+			default.iter().flatten().for_each(|e| bound_names(e, names));
+		},
+		Expression::Match { value, clauses, .. } => {
+			bound_names(value, names);
+			for clause in clauses {
+				pattern_names(&clause.pattern, names);
+				clause.guard.iter().for_each(|e| bound_names(e, names));
+				clause.body.iter().for_each(|e| bound_names(e, names));
+			}
+		},
+		Expression::ProtocolImplementation { methods, .. } => {
+			methods.iter().for_each(|e| bound_names(e, names));
+		},
+
+		_ => (),
+	}
+}
+
+/// Collect every name bound by a pattern
+pub(crate) fn pattern_names<'s>(pattern: &Pattern<'s>, names: &mut Vec<&'s str>) {
+	match pattern {
+		Pattern::Identifier(id) => names.push(id.id),
+		Pattern::List { patterns, .. } => patterns.iter().for_each(|p| pattern_names(p, names)),
+		Pattern::Cons { head, tail, .. } => {
+			pattern_names(head, names);
+			pattern_names(tail, names);
+		},
+		Pattern::Variant { payload: Some(payload), .. } => pattern_names(payload, names),
+		_ => (),
+	}
+}
@@ -0,0 +1,694 @@
+//! Extended explanations of diagnostic codes
+
+/// Every diagnostic code along with a longer explanation of what causes it
+/// and how to fix it
+pub const EXPLANATIONS: &[(&str, &str)] = &[
+	(
+		"ream::io_error",
+		"A file could not be read or written.
+
+This usually means the source file passed to `ream` doesn't exist, or that
+the current user isn't allowed to read it.",
+	),
+	(
+		"ream::lex_error",
+		"The source code contains text that doesn't form valid tokens.
+
+The more specific `ream::lex_error::*` code of the underlying error says what
+exactly went wrong.",
+	),
+	(
+		"ream::parse_error",
+		"The tokens of the source code don't form valid expressions.
+
+The more specific `ream::parse_error::*` code of the underlying error says
+what exactly went wrong.",
+	),
+	(
+		"ream::eval_error",
+		"Something went wrong while running the program.
+
+The more specific `ream::eval_error::*` code of the underlying error says what
+exactly went wrong.",
+	),
+	(
+		"ream::lex_error::unexpected_eof",
+		"The source code ended while a token was still being read.
+
+This happens when the last token of a file isn't followed by anything, eg. a
+file ending in `#t` without a closing parenthesis or a trailing newline.",
+	),
+	(
+		"ream::lex_error::unexpected_symbol",
+		"A character appeared where it can't start or continue a token.
+
+    #x
+
+After a `#` only `t`, `f` and `(` are allowed, as booleans and the `#(`
+opening a vector are the only tokens starting with `#`. The same code is used for characters that \
+		 can't start any
+token at all, or that appear where a closing `'` was expected.",
+	),
+	(
+		"ream::lex_error::unterminated_string",
+		"A string literal was never closed.
+
+    (print \"hello)
+
+Every `\"` starting a string needs a matching `\"` ending it.",
+	),
+	(
+		"ream::lex_error::unterminated_character",
+		"The source code ended in the middle of a character literal.
+
+    (print 'a
+
+Character literals hold exactly one character between two `'`, eg. `'a'`.",
+	),
+	(
+		"ream::lex_error::invalid_boolean",
+		"A token starting with `#` isn't a boolean.
+
+    (if #tru 1 2)
+
+Booleans are written as `#t` or `#true`, and `#f` or `#false`.",
+	),
+	(
+		"ream::lex_error::invalid_escape",
+		"A `\\` in a character literal isn't followed by a known escape.
+
+    '\\q'
+
+The supported escapes are `\\n`, `\\r`, `\\t`, `\\\\`, `\\0` and `\\'`.",
+	),
+	(
+		"ream::lex_error::invalid_number",
+		"A number literal contains a digit that isn't allowed in its base.
+
+    0b102
+
+`0b` numbers can only use the digits 0-1, `0o` numbers the digits 0-7 and
+`0x` numbers the digits 0-9 and a-f. Digits can be separated with `_`.
+
+Integers are 64 bit signed numbers, so they also have to lie between
+-9223372036854775808 and 9223372036854775807. Rationals like `1/3` are
+written as two decimal integers, and their denominator can't be zero.",
+	),
+	(
+		"ream::parse_error::unexpected_eof",
+		"The source code ended in the middle of an expression.
+
+    `
+
+A backtick has to be followed by the data it quotes.",
+	),
+	(
+		"ream::parse_error::unmatched_close_paren",
+		"A `)` doesn't close any `(`.
+
+    (print 1))
+
+Remove the extra `)`, or add the `(` it was meant to close.",
+	),
+	(
+		"ream::parse_error::unclosed_paren",
+		"A `(` was never closed.
+
+    (print (+ 1 2)
+
+Every `(` needs a matching `)`, the diagnostic points at the innermost one
+that is still open at the end of the file.",
+	),
+	(
+		"ream::parse_error::unexpected_token",
+		"A token appeared where the surrounding form doesn't allow it.
+
+    (fn 5 (x) x)
+
+The diagnostic lists the tokens that would have been accepted, here `fn`
+expects the name of the function.",
+	),
+	(
+		"ream::parse_error::invalid_expression",
+		"A token that can't start an expression appeared where an expression was
+expected.
+
+    (print List)
+
+Expressions are literals, identifiers, quotes or parenthesized forms. Type
+keywords like `List` can only appear in type specifications, and unquotes
+only inside a quasiquote.",
+	),
+	(
+		"ream::parse_error::not_callable",
+		"A literal is used as the operator of a form.
+
+    (1 2 3)
+
+The first element of a parenthesized form is called with the others as its
+arguments, so it has to evaluate to a function. To write a list of values,
+quote it instead:
+
+    `(1 2 3)",
+	),
+	(
+		"ream::parse_error::invalid_annotation",
+		"A form starting with an atom isn't a known annotation.
+
+    (:note x \"text\")
+
+Forms starting with an atom are annotations, only `:type` and `:doc` exist.",
+	),
+	(
+		"ream::parse_error::invalid_datum",
+		"A token that can't be part of quoted data appeared in a quote.
+
+    `(1 ,@xs)   ; fine
+    `,@xs       ; nothing to splice into
+
+Quoted data can contain literals, identifiers and lists. Unquotes (`,` and
+`,@`) are only allowed in backtick quasiquotes, and `,@` only inside a list.",
+	),
+	(
+		"ream::parse_error::invalid_pattern",
+		"A token that can't start a pattern appeared where a pattern was expected.
+
+    (let #(a b) v)
+
+Patterns are identifiers, `_`, literals, variant atoms or parenthesized
+lists of patterns. Vectors are destructured by list patterns too.",
+	),
+	(
+		"ream::parse_error::invalid_typespec",
+		"A token that can't start a type appeared in a type specification.
+
+    (type-alias Id 5)
+
+Types are identifiers or one of the type constructors `Bottom`, `Tuple`,
+`List`, `Function`, `Sum` and `Product`.",
+	),
+	(
+		"ream::parse_error::invalid_algebraic_type",
+		"`define-type` was given a type that isn't a sum or product type.
+
+    (define-type Point (List Integer))
+
+Only `Sum` and `Product` types can be defined, use `type-alias` to name
+other types.",
+	),
+	(
+		"ream::parse_error::invalid_formals",
+		"The formal parameters of a function, lambda or macro are malformed.
+
+    (fn f 5 x)
+
+Formal parameters are a single identifier or a parenthesized list of
+patterns.",
+	),
+	(
+		"ream::parse_error::multiple",
+		"Parsing found more than one error.
+
+Every error is listed below this one, each with its own code.",
+	),
+	(
+		"ream::expand_error::wrong_argument_count",
+		"A macro was called with the wrong amount of arguments.
+
+    (define-macro square (x) (* x x))
+    (square 1 2)
+
+Macros with a rest parameter, eg. `(test . body)`, accept any amount of
+extra arguments, others need exactly one argument per formal parameter.",
+	),
+	(
+		"ream::expand_error::not_an_identifier",
+		"A macro uses one of its parameters as a name, but was given an expression.
+
+    (define-macro define-zero (name) (let name 0))
+    (define-zero (f x))
+
+Parameters that end up where a name is required, like the target of `let`,
+have to be passed identifiers.",
+	),
+	(
+		"ream::expand_error::misplaced_rest",
+		"The rest parameter of a macro is used where only one expression fits.
+
+    (define-macro all (. xs) (seq xs))  ; fine, spliced into `seq`
+    (define-macro bad (. xs) (if xs 1 2))
+
+The expressions collected by a rest parameter are spliced into the
+surrounding list, so it can only be used among the operands of a call or in
+the body of `seq`, `let`, `fn`, `lambda` or `match`.",
+	),
+	(
+		"ream::expand_error::recursion_limit",
+		"Expanding a macro produced another call to a macro, over and over.
+
+    (define-macro forever (x) (forever x))
+
+Macros are expanded before the program runs, so a macro that always expands
+to a call to itself never finishes, even if the call would never be
+evaluated.",
+	),
+	(
+		"ream::type_error::mismatch",
+		"A definition doesn't have the type its `:type` annotation gives it.
+
+    (:type answer Integer)
+    (let answer \"forty-two\")
+
+Annotations are checked before the program runs, against the types that
+are known without running it: those of literals, `the` assertions,
+functions and other annotated names. Numbers aren't converted implicitly,
+so an integer has to be turned into a float with `(the Float ...)` before
+it can be used as one.",
+	),
+	(
+		"ream::type_error::argument_mismatch",
+		"A function annotated with a `Function` type was passed an argument of
+another type.
+
+    (:type double (Function Integer Integer))
+    (fn double (x) (* x 2))
+    (double \"two\")
+
+The types of the arguments are listed first in the annotation, the type of
+the result last.",
+	),
+	(
+		"ream::type_error::return_mismatch",
+		"The body of a function annotated with a `Function` type ends in an
+expression of another type than the result.
+
+    (:type greet (Function String String))
+    (fn greet (name) 42)
+
+The parameters of an annotated function have the types of its arguments
+while checking its body, so returning a parameter is checked too.",
+	),
+	(
+		"ream::type_error::wrong_argument_count",
+		"A function annotated with a `Function` type was called with another
+amount of arguments than the annotation lists.
+
+    (:type add (Function (Integer Integer) Integer))
+    (fn add (a b) (+ a b))
+    (add 1)
+
+Several arguments are grouped in parentheses in the annotation.",
+	),
+	(
+		"ream::type_error::not_callable",
+		"A name annotated with a type that isn't a function was called.
+
+    (:type limit Integer)
+    (let limit 10)
+    (limit 5)
+
+Only names annotated with a `Function` type, or not annotated at all, can be
+called.",
+	),
+	(
+		"ream::type_error::multiple",
+		"Checking types found more than one error.
+
+Every error is listed below this one, each with its own code.",
+	),
+	(
+		"ream::eval_error::unknown_identifier",
+		"An identifier doesn't refer to anything in scope.
+
+    (print y)
+
+Make sure the name is spelled correctly and defined using `let` or `fn`
+before it is used.",
+	),
+	(
+		"ream::eval_error::constant_redefinition",
+		"A name defined using `const` was defined again in the same scope.
+
+    (const limit 10)
+    (let limit 20)
+
+Constants can't be replaced by `let`, `fn` or another `const` once they're
+defined. A definition of the same name inside a nested body, like that of a
+`fn`, `lambda` or `seq`, shadows the constant instead of replacing it.",
+	),
+	(
+		"ream::eval_error::not_a_function",
+		"The operator of a call evaluated to something that can't be called.
+
+    (let x 5)
+    (x 1)
+
+Only functions, closures and primitives can be called.",
+	),
+	(
+		"ream::eval_error::wrong_argument_count",
+		"A function was called with the wrong amount of arguments.
+
+    (fn add (a b) (+ a b))
+    (add 1)
+
+The diagnostic names the called function and how many arguments it takes.",
+	),
+	(
+		"ream::eval_error::capability_denied",
+		"A primitive needs a capability the program wasn't granted.
+
+    ream --allow pure script.rm
+
+Primitives are grouped into capabilities like `io`, `fs` and `net`. Grant
+the missing one with `--allow`, or leave `--allow` out to grant all of them.",
+	),
+	(
+		"ream::eval_error::timeout",
+		"The program didn't finish within the time limit it was run with.
+
+Time limits are set by embedders using `Interpreter::eval_with_timeout`.",
+	),
+	(
+		"ream::eval_error::quota_exceeded",
+		"The program made more calls and created more values than it was allowed
+to in a single run.
+
+Every procedure call and every element of a collection built by a primitive
+counts towards the quota, which starts over for every program run and every
+REPL input. Value quotas are set by embedders using
+`Interpreter::with_value_quota`.",
+	),
+	(
+		"ream::eval_error::pattern_mismatch",
+		"A value doesn't have the shape a `let` or function parameter pattern
+expects.
+
+    (let (a b) `(1 2 3))
+
+The list has three elements, but the pattern only has room for two.",
+	),
+	(
+		"ream::eval_error::no_match",
+		"None of the clauses of a `match` match the value, either because none of
+their patterns match it or because the `:when` guards of the ones that do
+are all false.
+
+    (match :three (:one 1) (:two 2))
+
+Add a clause for the missing case, or a catch-all `_` clause at the end.",
+	),
+	(
+		"ream::eval_error::no_matching_clause",
+		"A function defined by `fn` clauses was called with arguments that none of
+its clauses match.
+
+    (fn (sign 0) :zero)
+    (fn (sign 1) :one)
+    (sign 5)
+
+Add a clause for the missing case, or a clause with a catch-all `_` pattern
+for every argument after the others.",
+	),
+	(
+		"ream::eval_error::missing_implementation",
+		"A protocol method was called on a type that doesn't implement the
+protocol.
+
+Add an `impl` of the protocol for the type.",
+	),
+	(
+		"ream::eval_error::invalid_implementation",
+		"An `impl` defines a different amount of methods than its protocol
+declares.
+
+Every method declared by `defprotocol` has to be implemented, and no others.",
+	),
+	(
+		"ream::eval_error::unknown_field",
+		"A product type doesn't have the field that was accessed or set.
+
+Check the spelling of the field against the `define-type` of the type.",
+	),
+	(
+		"ream::eval_error::missing_field",
+		"A product type was constructed without one of its fields.
+
+Every field of a product type has to be given a value when it is created.",
+	),
+	(
+		"ream::eval_error::duplicate_field",
+		"A product type was constructed with the same field given twice.
+
+    (define-type Point (Product (:x Integer) (:y Integer)))
+    (Point :x 1 :x 2)
+
+Give every field exactly once.",
+	),
+	(
+		"ream::eval_error::missing_field_value",
+		"A product type was constructed with a field name that isn't followed by a
+value.
+
+    (define-type Point (Product (:x Integer) (:y Integer)))
+    (Point :x 1 :y)
+
+Fields are given as a field name followed by its value.",
+	),
+	(
+		"ream::eval_error::invalid_regex",
+		"The pattern passed to `regex` isn't a valid regular expression.
+
+    (regex \"(unclosed\")
+
+The message describes what is wrong with the pattern.",
+	),
+	(
+		"ream::eval_error::invalid_date_format",
+		"A date format string contains an unknown specifier.
+
+    (date-format (date-now) \"%Q\")
+
+Formats use strftime-style specifiers like `%Y`, `%m` and `%d`.",
+	),
+	(
+		"ream::eval_error::invalid_environment_variable",
+		"`setenv` was given a name or value the operating system can't store.
+
+    (setenv \"A=B\" \"x\")
+
+Names have to be non-empty and can't contain `=` or NUL characters, values
+can't contain NUL characters either.",
+	),
+	(
+		"ream::eval_error::wrong_type",
+		"A value of the wrong type was passed to a primitive or operator, or
+didn't have the type a `the` expression asserted it has.
+
+    (+ 1 \"2\")
+    (the Integer 2.5)
+
+The diagnostic points at the offending value and says which type was
+expected.",
+	),
+	(
+		"ream::eval_error::output_failed",
+		"Writing the output of the program failed.
+
+This happens when stdout is closed, or when the writer an embedder passed to
+`Interpreter::with_output` returns an error.",
+	),
+	(
+		"ream::eval_error::raised",
+		"The program raised an error itself.
+
+    (error \"something went wrong\")
+
+The message is the payload given to `error`.",
+	),
+	(
+		"ream::eval_error::include_not_found",
+		"An included file doesn't exist.
+
+    (include \"missing.rm\")
+
+Files are looked up relative to the file including them first, and in the
+directories passed with `-I` after that.",
+	),
+	(
+		"ream::eval_error::include_cycle",
+		"A file includes itself, directly or through other files.
+
+    ; a.rm
+    (include \"b.rm\")
+    ; b.rm
+    (include \"a.rm\")
+
+The help text shows the chain of includes leading back to the file.",
+	),
+	(
+		"ream::eval_error::include_failed",
+		"An error happened while lexing, parsing or running an included file.
+
+The underlying error is shown below this one, with the source of the
+included file.",
+	),
+	(
+		"ream::eval_error::unwrap_err",
+		"`unwrap` was called on an `err` value.
+
+    (unwrap (err \"nope\"))
+
+Check the result with `ok?` first, or use `unwrap-or` to provide a fallback.",
+	),
+	(
+		"ream::eval_error::arithmetic_overflow",
+		"The result of an arithmetic operation doesn't fit in 64 bits.
+
+    (+ 9223372036854775807 1/2)
+
+Integers only overflow when ream was built without the `bigint` feature,
+otherwise they grow as large as needed. The numerator and denominator of a
+rational always have to fit in 64 bits. Use floats where an approximate
+result will do.",
+	),
+	(
+		"ream::eval_error::division_by_zero",
+		"An integer or rational was divided by zero.
+
+    (/ 1 0)
+
+The diagnostic points at the divisor. Dividing floats by zero isn't an error,
+it results in infinity or NaN instead, which `infinite?` and `nan?` check for.",
+	),
+	(
+		"ream::eval_error::unhashable",
+		"A value that can't be ordered or hashed was sorted or used as a key.
+
+    (sort (list 1 (lambda (x) x)))
+
+Numbers, characters, strings, atoms, lists, tuples, maps, sets, options,
+results and the values of defined types can be sorted and hashed, as long as
+everything they contain can be too. Functions, iterators, mutable values like
+vectors and string builders, regexes and sockets can't.",
+	),
+	(
+		"ream::eval_error::index_out_of_bounds",
+		"An index outside of a list, vector, tuple or string was used.
+
+    (list-ref (list 1 2 3) 3)
+
+Indices start at 0, so the last valid index is one less than the length.
+Negative indices count back from the end, -1 being the last element, and
+can't go further back than the first one.",
+	),
+	(
+		"ream::eval_error::invalid_range",
+		"A range of indices started after it ended.
+
+    (substring \"hello\" 3 1)
+
+The start of a range is inclusive and its end exclusive, so a start equal
+to the end gives an empty range, but a start after the end doesn't give a
+range at all.",
+	),
+	(
+		"ream::eval_error::value_too_deep",
+		"A value was nested too deeply to be printed, compared or returned.
+
+    (let v (vector 0))
+    (vector-set! v 0 v)
+    (print v)
+
+A vector containing itself is infinitely deep. Printing, comparing with
+`equal?` and returning values walk every value nested inside them, so they
+refuse values nested deeper than a limit instead of never finishing.
+
+Value limits are set by embedders using `Interpreter::with_value_limits`.",
+	),
+	(
+		"ream::eval_error::value_too_large",
+		"A value contained too many values to be printed, compared or returned.
+
+    (fn grow (x n) (if (== n 0) x (grow (list x x) (- n 1))))
+    (print (grow 0 30))
+
+Collections share the values they contain, so a value built in a few steps
+can contain exponentially many of them. Printing, comparing with `equal?`
+and returning values walk every one of them, so they refuse values larger
+than a limit instead of exhausting memory.
+
+Value limits are set by embedders using `Interpreter::with_value_limits`.",
+	),
+	(
+		"ream::eval_error::string_too_long",
+		"A string built by repeating or padding would have been longer than a
+limit.
+
+    (string-repeat \"abc\" 1000000000000)
+
+Strings are built all at once, so an enormous count or width would exhaust
+memory before the program gets to use the result. Such strings are refused
+instead.
+
+The limit is set by embedders using `Interpreter::with_string_limit`.",
+	),
+	(
+		"ream::catalog_error::invalid_entry",
+		"A line of a message catalog isn't a comment, message or attribute.
+
+    lex-invalid-boolean: Invalid Boolean
+
+Messages are written as `id = value`, and their attributes as
+`.name = value` on the indented lines following the message.",
+	),
+	(
+		"ream::catalog_error::orphan_attribute",
+		"A message catalog has an attribute before its first message.
+
+    .help = add a closing `\"` to end the string
+    lex-unterminated-string = Unterminated String
+
+Attributes belong to the message above them, so they have to come after
+it.",
+	),
+];
+
+/// Get the extended explanation of a diagnostic code
+///
+/// The `ream::` prefix of the code can be left out
+pub fn explain(code: &str) -> Option<&'static str> {
+	let code = code.strip_prefix("ream::").unwrap_or(code);
+
+	EXPLANATIONS
+		.iter()
+		.find(|(c, _)| c.strip_prefix("ream::") == Some(code))
+		.map(|(_, explanation)| *explanation)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_code_is_explained() {
+		let errors = include_str!("error.rs");
+
+		for code in errors.split("code(ream::").skip(1).filter_map(|s| s.split(')').next()) {
+			if code == "test" {
+				continue;
+			}
+
+			assert!(explain(code).is_some(), "`{code}` has no explanation");
+		}
+	}
+
+	#[test]
+	fn prefix_is_optional() {
+		assert_eq!(
+			explain("ream::parse_error::not_callable"),
+			explain("parse_error::not_callable")
+		);
+		assert!(explain("parse_error::nonexistent").is_none());
+	}
+}
@@ -1,13 +1,12 @@
 use std::iter::Peekable;
 use std::str::Chars;
 
-use miette::SourceSpan;
+use miette::Error;
+use num_rational::Rational64;
 use unicode_xid::UnicodeXID;
 
-use crate::{LexError, Token, TokenType};
-
-static NON_DECIMAL_FLOAT_LITERAL: &str =
-	"this number appears to be a float, however floats can only be created using decimal notation";
+use crate::messages::message;
+use crate::{FileId, LexError, Span, Token, TokenType};
 
 /// A lexer for a single source file
 #[allow(missing_docs)]
@@ -19,25 +18,99 @@ pub struct Lexer<'s> {
 
 	/// The start of the current token
 	start: usize,
-	/// The current index into the character list
+	/// The current byte index into the source
 	idx:   usize,
+	/// The file every span points into
+	file:  FileId,
+
+	/// Whether to turn errors into [`Error`](TokenType::Error) tokens and
+	/// keep going
+	recover: bool,
+	/// Every error encountered in recovery mode
+	errors:  Vec<LexError>,
+}
+
+/// Lex an entire source file, stopping at the first error
+pub fn lex_str(source: &str) -> Result<Vec<Token<'_>>, Error> {
+	Ok(Lexer::new(source).collect::<Result<Vec<_>, _>>()?)
 }
 
 impl<'s> Iterator for Lexer<'s> {
 	type Item = Result<Token<'s>, LexError>;
 
-	fn next(&mut self) -> Option<Self::Item> { self.lex_token() }
+	fn next(&mut self) -> Option<Self::Item> {
+		let result = match self.lex_token()? {
+			Err(e) if self.recover => Ok(self.recover_from(e)),
+			result => result,
+		};
+
+		Some(result)
+	}
 }
 
 impl<'s> Lexer<'s> {
 	/// Create a new lexer
 	pub fn new(source: &'s str) -> Self {
 		let chars = source.chars().peekable();
-		let len = source.chars().count();
+		let len = source.len();
+
+		Self {
+			source,
+			chars,
+			len,
+			start: 0,
+			idx: 0,
+			file: FileId::default(),
+			recover: false,
+			errors: vec![],
+		}
+	}
+
+	/// Set the file the spans of every token and error point into
+	pub fn with_file(mut self, file: FileId) -> Self {
+		self.file = file;
+		self
+	}
 
-		Self { source, chars, len, start: 0, idx: 0 }
+	/// Put the lexer in recovery mode, where an invalid token produces an
+	/// [`Error`](TokenType::Error) token instead of an error and lexing
+	/// continues after it
+	///
+	/// The errors themselves can be retrieved with [`errors`](Self::errors)
+	pub fn with_recovery(mut self) -> Self {
+		self.recover = true;
+		self
 	}
 
+	/// Get every error encountered so far in recovery mode
+	pub fn errors(&self) -> &[LexError] { &self.errors }
+
+	/// Record an error and skip the rest of the invalid token, returning an
+	/// [`Error`](TokenType::Error) token spanning all of it
+	fn recover_from(&mut self, error: LexError) -> Token<'s> {
+		self.errors.push(error);
+
+		while let Some(&c) = self.peek() {
+			if Self::is_delimiter(c) {
+				break;
+			}
+
+			self.next();
+		}
+
+		// Always make progress, even if the error was on a delimiter
+		if self.idx == self.start {
+			self.next();
+		}
+
+		let raw = &self.source[self.start..self.idx];
+
+		Token { span: self.span(self.start, raw.len()), t: TokenType::Error(raw) }
+	}
+
+	/// Create a span in the file being lexed
+	fn span(&self, start: usize, len: usize) -> Span { Span::new(self.file, start..start + len) }
+
 	/// Peek at the next [`char`]
 	///
 	/// Returns [`None`] if no characters are left
@@ -47,8 +120,10 @@ impl<'s> Lexer<'s> {
 	///
 	/// Returns [`None`] if no characters are left
 	fn next(&mut self) -> Option<char> {
-		self.idx += 1;
-		self.chars.next()
+		let c = self.chars.next()?;
+		self.idx += c.len_utf8();
+
+		Some(c)
 	}
 
 	/// Check if a character can start an identifier
@@ -74,7 +149,12 @@ impl<'s> Lexer<'s> {
 
 	/// Check if a character is a delimiter
 	fn is_delimiter(c: char) -> bool {
-		c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == '\'' || c == ';' || c == '`'
+		c.is_whitespace()
+			|| c == '(' || c == ')'
+			|| c == '"'
+			|| c == '\''
+			|| c == ';' || c == '`'
+			|| c == ','
 	}
 
 	/// Lex a single token
@@ -87,19 +167,36 @@ impl<'s> Lexer<'s> {
 		self.start = self.idx;
 
 		match self.next()? {
-			'(' => Some(Ok(Token { span: (self.start, 1).into(), t: TokenType::LeftParen })),
-			')' => Some(Ok(Token { span: (self.start, 1).into(), t: TokenType::RightParen })),
-			'.' => Some(Ok(Token { span: (self.start, 1).into(), t: TokenType::Period })),
-			'`' => Some(Ok(Token { span: (self.start, 1).into(), t: TokenType::Backtick })),
+			'(' => Some(Ok(Token { span: self.span(self.start, 1), t: TokenType::LeftParen })),
+			')' => Some(Ok(Token { span: self.span(self.start, 1), t: TokenType::RightParen })),
+			'.' => Some(Ok(Token { span: self.span(self.start, 1), t: TokenType::Period })),
+			'`' => Some(Ok(Token { span: self.span(self.start, 1), t: TokenType::Backtick })),
+			',' => {
+				if self.peek() == Some(&'@') {
+					self.next();
+
+					Some(Ok(Token { span: self.span(self.start, 2), t: TokenType::CommaAt }))
+				} else {
+					Some(Ok(Token { span: self.span(self.start, 1), t: TokenType::Comma }))
+				}
+			},
 			':' => Some(self.make_atom_token()),
 			'#' => {
 				match self.peek()? {
 					't' | 'f' => Some(self.make_boolean_token()),
+					'(' => {
+						self.next();
+
+						Some(Ok(Token {
+							span: self.span(self.start, 2),
+							t:    TokenType::HashParen,
+						}))
+					},
 					&c => {
 						Some(Err(LexError::UnexpectedSymbol {
-							loc:      (self.start, 1).into(),
+							loc:      self.span(self.start, 1),
 							found:    c,
-							expected: vec!['t', 'f'],
+							expected: vec!['t', 'f', '('],
 						}))
 					},
 				}
@@ -107,8 +204,9 @@ impl<'s> Lexer<'s> {
 			'\'' => Some(self.make_character_token()),
 			'"' => Some(self.make_string_token()),
 			n if n.is_ascii_digit() => Some(self.make_number_token()),
+			'-' if self.peek().is_some_and(char::is_ascii_digit) => Some(self.make_number_token()),
 			c if Self::is_id_start(c) => Some(self.make_identifier_token()),
-			c => Some(Err(LexError::UnknownSymbol { loc: (self.start, 1).into(), found: c })),
+			c => Some(Err(LexError::UnknownSymbol { loc: self.span(self.start, 1), found: c })),
 		}
 	}
 
@@ -145,7 +243,7 @@ impl<'s> Lexer<'s> {
 		// Return early if the immediately following character is None
 		let mut peek = match self.peek() {
 			Some(p) => *p,
-			None => return Err(LexError::UnexpectedEof { loc: (self.idx, 1).into() }),
+			None => return Err(LexError::UnexpectedEof { loc: self.span(self.idx, 1) }),
 		};
 
 		while pred(peek) {
@@ -154,7 +252,7 @@ impl<'s> Lexer<'s> {
 			self.next().unwrap();
 
 			if self.idx >= self.len {
-				return Err(LexError::UnexpectedEof { loc: (self.idx, 1).into() });
+				return Err(LexError::UnexpectedEof { loc: self.span(self.idx, 1) });
 			}
 
 			// Unwrap is safe as idx < len
@@ -169,7 +267,7 @@ impl<'s> Lexer<'s> {
 	fn make_atom_token(&mut self) -> Result<Token<'s>, LexError> {
 		let atom = self.take_chars_while(|c| !Self::is_delimiter(c))?;
 
-		Ok(Token { span: (self.start, atom.len()).into(), t: TokenType::Atom(atom) })
+		Ok(Token { span: self.span(self.start, atom.len()), t: TokenType::Atom(atom) })
 	}
 
 	/// Attempt to make a boolean starting from the lexers current position
@@ -178,19 +276,19 @@ impl<'s> Lexer<'s> {
 		let raw = self.take_chars_while(|c| !Self::is_delimiter(c))?;
 
 		if raw == "#t" || raw == "#true" {
-			Ok(Token { span: (self.start, raw.len()).into(), t: TokenType::Boolean(true) })
+			Ok(Token { span: self.span(self.start, raw.len()), t: TokenType::Boolean(true) })
 		} else if raw == "#f" || raw == "#false" {
-			Ok(Token { span: (self.start, raw.len()).into(), t: TokenType::Boolean(false) })
+			Ok(Token { span: self.span(self.start, raw.len()), t: TokenType::Boolean(false) })
 		} else {
 			Err(LexError::InvalidBoolean {
-				loc:   (self.start, raw.len()).into(),
+				loc:   self.span(self.start, raw.len()),
 				found: raw.to_string(),
 			})
 		}
 	}
 
 	/// Convert a string with a 2 character escape code into its corresponding character
-	fn unescape_string_to_char(&self, string: &str, loc: SourceSpan) -> Result<char, LexError> {
+	fn unescape_string_to_char(&self, string: &str, loc: Span) -> Result<char, LexError> {
 		match string {
 			"\\n" => Ok('\n'),
 			"\\r" => Ok('\r'),
@@ -217,7 +315,7 @@ impl<'s> Lexer<'s> {
 		let chr = match self.next() {
 			Some(c) => c,
 			None => {
-				return Err(LexError::UnexpectedEof { loc: (self.start + 1, 1).into() });
+				return Err(LexError::UnterminatedCharacter { loc: self.span(self.start, 1) });
 			},
 		};
 
@@ -225,20 +323,20 @@ impl<'s> Lexer<'s> {
 			let escaped = match self.next() {
 				Some(c) => c,
 				None => {
-					return Err(LexError::UnexpectedEof { loc: (self.start + 2, 1).into() });
+					return Err(LexError::UnterminatedCharacter { loc: self.span(self.start, 1) });
 				},
 			};
 
 			let close = match self.next() {
 				Some(c) => c,
 				None => {
-					return Err(LexError::UnexpectedEof { loc: (self.start + 3, 1).into() });
+					return Err(LexError::UnterminatedCharacter { loc: self.span(self.start, 1) });
 				},
 			};
 
 			if close != '\'' {
 				return Err(LexError::UnexpectedSymbol {
-					loc:      (self.start + 3, 1).into(),
+					loc:      self.span(self.start + 3, 1),
 					found:    close,
 					expected: vec!['\''],
 				});
@@ -248,10 +346,10 @@ impl<'s> Lexer<'s> {
 			unescaped_str.push(escaped);
 
 			let escaped_char =
-				self.unescape_string_to_char(&unescaped_str, (self.start + 1, 2).into())?;
+				self.unescape_string_to_char(&unescaped_str, self.span(self.start + 1, 2))?;
 
 			return Ok(Token {
-				span: (self.start, 4).into(),
+				span: self.span(self.start, 4),
 				t:    TokenType::Character(escaped_char),
 			});
 		}
@@ -259,19 +357,22 @@ impl<'s> Lexer<'s> {
 		let close = match self.next() {
 			Some(c) => c,
 			None => {
-				return Err(LexError::UnexpectedEof { loc: (self.start + 2, 1).into() });
+				return Err(LexError::UnterminatedCharacter { loc: self.span(self.start, 1) });
 			},
 		};
 
 		if close != '\'' {
 			return Err(LexError::UnexpectedSymbol {
-				loc:      (self.start + 2, 1).into(),
+				loc:      self.span(self.start + 2, 1),
 				found:    close,
 				expected: vec!['\''],
 			});
 		}
 
-		Ok(Token { span: (self.start, 3).into(), t: TokenType::Character(chr) })
+		Ok(Token {
+			span: self.span(self.start, self.idx - self.start),
+			t:    TokenType::Character(chr),
+		})
 	}
 
 	/// Attempt to make a string starting from the lexers current position
@@ -281,11 +382,10 @@ impl<'s> Lexer<'s> {
 		let mut peek = match self.peek() {
 			Some(c) => *c,
 			None => {
-				return Err(LexError::UnexpectedEof { loc: (self.start + 1, 1).into() });
+				return Err(LexError::UnterminatedString { loc: self.span(self.start, 1) });
 			},
 		};
 
-		let mut i = 0;
 		let mut prev = ' ';
 		// Keep looping until a `"` without a preceding `\` is found
 		while !(peek == '"' && prev != '\\') {
@@ -294,13 +394,12 @@ impl<'s> Lexer<'s> {
 			self.next().unwrap();
 
 			if self.idx >= self.len {
-				return Err(LexError::UnexpectedEof { loc: (self.start + i + 2, 1).into() });
+				return Err(LexError::UnterminatedString { loc: self.span(self.start, 1) });
 			}
 
 			prev = peek;
 			// Unwrap is safe as idx < len
 			peek = *self.peek().unwrap();
-			i += 1;
 		}
 
 		// Take the closing quote
@@ -313,7 +412,7 @@ impl<'s> Lexer<'s> {
 		let string_literal = &self.source[self.start + 1..self.idx - 1];
 
 		Ok(Token {
-			span: (self.start, string_literal.len()).into(),
+			span: self.span(self.start, string_literal.len()),
 			t:    TokenType::String(string_literal),
 		})
 	}
@@ -321,87 +420,125 @@ impl<'s> Lexer<'s> {
 	/// Attempt to make a number starting from the lexers current position
 	/// in the source
 	///
-	/// Can make decimal, hex, octal, or binary integers, or decimal floats.
+	/// Can make decimal, hex, octal, or binary integers, or decimal floats
+	/// and rationals, optionally preceded by a `-`.
 	fn make_number_token(&mut self) -> Result<Token<'s>, LexError> {
 		let raw = self.take_chars_while(|c| {
 			c.is_ascii_hexdigit()
 				|| c == 'x' || c == 'X'
 				|| c == 'o' || c == 'O'
 				|| c == '_' || c == '.'
+				|| c == '/'
 		})?;
 
-		let raw = raw.replace('_', "");
+		self.validate_digits(raw)?;
 
-		if (raw.starts_with("0x") || raw.starts_with("0o") || raw.starts_with("0b"))
-			&& raw.contains('.')
-		{
-			return Err(LexError::InvalidNumber {
-				loc:   (self.start, raw.len()).into(),
-				help:  Some(NON_DECIMAL_FLOAT_LITERAL.to_string()),
-				found: raw,
-			});
-		}
+		let raw = raw.replace('_', "");
+		let invalid = |help| {
+			LexError::InvalidNumber {
+				loc: self.span(self.start, raw.len()),
+				help,
+				found: raw.to_string(),
+			}
+		};
 
 		if raw.contains('.') {
-			let float = raw.parse::<f64>().map_err(|_| {
-				LexError::InvalidNumber {
-					loc:   (self.start, raw.len()).into(),
-					help:  None,
-					found: raw.to_string(),
-				}
-			})?;
+			if matches!(raw.trim_start_matches('-').get(..2), Some("0x" | "0o" | "0b")) {
+				return Err(invalid(Some(message!("lex-invalid-number.help-non-decimal-float"))));
+			}
+
+			let float = raw.parse::<f64>().map_err(|_| invalid(None))?;
 
 			return Ok(Token {
-				span: (self.start, raw.len()).into(),
+				span: self.span(self.start, raw.len()),
 				t:    TokenType::Float(float),
 			});
 		}
 
-		let num = if raw.starts_with("0x") {
-			u64::from_str_radix(raw.trim_start_matches("0x"), 16).map_err(|_| {
-				LexError::InvalidNumber {
-					loc:   (self.start, raw.len()).into(),
-					help:  None,
-					found: raw.to_string(),
-				}
-			})?
-		} else if raw.starts_with("0o") {
-			u64::from_str_radix(raw.trim_start_matches("0o"), 8).map_err(|_| {
-				LexError::InvalidNumber {
-					loc:   (self.start, raw.len()).into(),
-					help:  None,
-					found: raw.to_string(),
-				}
-			})?
-		} else if raw.starts_with("0b") {
-			u64::from_str_radix(raw.trim_start_matches("0b"), 2).map_err(|_| {
-				LexError::InvalidNumber {
-					loc:   (self.start, raw.len()).into(),
-					help:  None,
-					found: raw.to_string(),
+		let parse_integer = |raw: &str| {
+			let (negative, magnitude) = match raw.strip_prefix('-') {
+				Some(magnitude) => (true, magnitude),
+				None => (false, raw),
+			};
+
+			let (radix, digits) = match magnitude.get(..2) {
+				Some("0x") => (16, &magnitude[2..]),
+				Some("0o") => (8, &magnitude[2..]),
+				Some("0b") => (2, &magnitude[2..]),
+				_ => (10, magnitude),
+			};
+
+			let magnitude = u64::from_str_radix(digits, radix).map_err(|_| invalid(None))?;
+			if negative {
+				0i64.checked_sub_unsigned(magnitude)
+			} else {
+				i64::try_from(magnitude).ok()
+			}
+			.ok_or_else(|| invalid(Some(message!("lex-invalid-number.help-range"))))
+		};
+
+		let t = match raw.split_once('/') {
+			Some((numerator, denominator)) => {
+				let numerator = parse_integer(numerator)?;
+				let denominator = parse_integer(denominator)?;
+				if denominator == 0 {
+					return Err(invalid(Some(message!(
+						"lex-invalid-number.help-zero-denominator"
+					))));
 				}
-			})?
-		} else {
-			raw.parse::<u64>().map_err(|_| {
-				LexError::InvalidNumber {
-					loc:   (self.start, raw.len()).into(),
-					help:  None,
-					found: raw.to_string(),
+
+				// Rationals are always stored in lowest terms, and the ones that
+				// turn out to be whole are integers
+				let ratio = Rational64::new(numerator, denominator);
+				if ratio.is_integer() {
+					TokenType::Integer(ratio.to_integer())
+				} else {
+					TokenType::Rational(ratio)
 				}
-			})?
+			},
+			None => TokenType::Integer(parse_integer(&raw)?),
+		};
+
+		Ok(Token { span: self.span(self.start, raw.len()), t })
+	}
+
+	/// Check that every digit of a number literal is valid for its radix,
+	/// pointing at the first one that isn't
+	///
+	/// Underscores are allowed anywhere after the prefix, and decimal numbers
+	/// may contain a `.` or a `/`
+	fn validate_digits(&self, raw: &str) -> Result<(), LexError> {
+		let sign = if raw.starts_with('-') { 1 } else { 0 };
+
+		let (prefix, radix, allowed) = match raw.get(sign..sign + 2) {
+			Some("0x") => ("0x", 16, message!("lex-invalid-number.help-hexadecimal")),
+			Some("0o") => ("0o", 8, message!("lex-invalid-number.help-octal")),
+			Some("0b") => ("0b", 2, message!("lex-invalid-number.help-binary")),
+			_ => ("", 10, message!("lex-invalid-number.help-decimal")),
 		};
 
-		Ok(Token { span: (self.start, raw.len()).into(), t: TokenType::Integer(num) })
+		let start = sign + prefix.len();
+		let invalid = raw[start..].char_indices().find(|&(_, c)| {
+			!(c.is_digit(radix) || c == '_' || (radix == 10 && (c == '.' || c == '/')))
+		});
+
+		match invalid {
+			Some((offset, _)) => {
+				Err(LexError::InvalidNumber {
+					loc:   self.span(self.start + start + offset, 1),
+					help:  Some(allowed),
+					found: raw.to_string(),
+				})
+			},
+			None => Ok(()),
+		}
 	}
 
 	/// Attempt to make an identifier starting from the lexers current position
 	///
 	/// Recognizes keywords
 	fn make_identifier_token(&mut self) -> Result<Token<'s>, LexError> {
-		let raw = match self.take_chars_while(Self::is_id_continue) {
-			Ok(id) => id,
-			Err(e) => return Err(e),
-		};
+		let raw = self.take_chars_while(Self::is_id_continue)?;
 
 		Ok(self.match_identifier(raw))
 	}
@@ -410,27 +547,183 @@ impl<'s> Lexer<'s> {
 	fn match_identifier(&self, id: &'s str) -> Token<'s> {
 		match id {
 			"Bottom" => {
-				Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwBottom }
+				Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwBottom }
+			},
+			"Tuple" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwTuple }
 			},
-			"Tuple" => Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwTuple },
-			"List" => Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwList },
+			"List" => Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwList },
 			"Function" => {
-				Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwFunction }
+				Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwFunction }
 			},
-			"Sum" => Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwSum },
+			"Sum" => Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwSum },
 			"Product" => {
-				Token { span: (self.start, id.len()).into(), t: TokenType::TypeKwProduct }
+				Token { span: self.span(self.start, id.len()), t: TokenType::TypeKwProduct }
 			},
 
-			"quote" => Token { span: (self.start, id.len()).into(), t: TokenType::KwQuote },
-			"let" => Token { span: (self.start, id.len()).into(), t: TokenType::KwLet },
-			"fn" => Token { span: (self.start, id.len()).into(), t: TokenType::KwFn },
-			"lambda" => Token { span: (self.start, id.len()).into(), t: TokenType::KwLambda },
-			"seq" => Token { span: (self.start, id.len()).into(), t: TokenType::KwSeq },
-			"if" => Token { span: (self.start, id.len()).into(), t: TokenType::KwIf },
-			"include" => Token { span: (self.start, id.len()).into(), t: TokenType::KwInclude },
+			"nan" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::Float(f64::NAN) }
+			},
+			"inf" => {
+				Token {
+					span: self.span(self.start, id.len()),
+					t:    TokenType::Float(f64::INFINITY),
+				}
+			},
+			"-inf" => {
+				Token {
+					span: self.span(self.start, id.len()),
+					t:    TokenType::Float(f64::NEG_INFINITY),
+				}
+			},
+
+			"quote" => Token { span: self.span(self.start, id.len()), t: TokenType::KwQuote },
+			"let" => Token { span: self.span(self.start, id.len()), t: TokenType::KwLet },
+			"const" => Token { span: self.span(self.start, id.len()), t: TokenType::KwConst },
+			"fn" => Token { span: self.span(self.start, id.len()), t: TokenType::KwFn },
+			"lambda" => Token { span: self.span(self.start, id.len()), t: TokenType::KwLambda },
+			"seq" => Token { span: self.span(self.start, id.len()), t: TokenType::KwSeq },
+			"if" => Token { span: self.span(self.start, id.len()), t: TokenType::KwIf },
+			"cond" => Token { span: self.span(self.start, id.len()), t: TokenType::KwCond },
+			"include" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::KwInclude }
+			},
+			"match" => Token { span: self.span(self.start, id.len()), t: TokenType::KwMatch },
+			"case" => Token { span: self.span(self.start, id.len()), t: TokenType::KwCase },
+			"defprotocol" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::KwDefProtocol }
+			},
+			"impl" => Token { span: self.span(self.start, id.len()), t: TokenType::KwImpl },
+			"type-alias" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::KwTypeAlias }
+			},
+			"define-type" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::KwDefineType }
+			},
+			"define-macro" => {
+				Token { span: self.span(self.start, id.len()), t: TokenType::KwDefineMacro }
+			},
+			"the" => Token { span: self.span(self.start, id.len()), t: TokenType::KwThe },
+			"time" => Token { span: self.span(self.start, id.len()), t: TokenType::KwTime },
 
-			_ => Token { span: (self.start, id.len()).into(), t: TokenType::Identifier(id) },
+			_ => Token { span: self.span(self.start, id.len()), t: TokenType::Identifier(id) },
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn types<'s>(tokens: &[Token<'s>]) -> Vec<TokenType<'s>> {
+		tokens.iter().map(|t| t.t).collect()
+	}
+
+	#[test]
+	fn lex_str_lexes_a_whole_file() {
+		let tokens = lex_str("(print \"hi\" 1)\n").unwrap();
+
+		assert_eq!(
+			types(&tokens),
+			[
+				TokenType::LeftParen,
+				TokenType::Identifier("print"),
+				TokenType::String("hi"),
+				TokenType::Integer(1),
+				TokenType::RightParen,
+			]
+		);
+	}
+
+	#[test]
+	fn lex_str_stops_at_the_first_error() {
+		let error = lex_str("(print \"oops)\n(+ 1 2)\n").unwrap_err();
+
+		assert_eq!(error.code().unwrap().to_string(), "ream::lex_error::unterminated_string");
+	}
+
+	#[test]
+	fn recovery_turns_invalid_tokens_into_error_tokens() {
+		let mut lexer = Lexer::new("(+ 1 #q 2)\n(print 0b102)\n").with_recovery();
+		let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+		assert_eq!(
+			types(&tokens),
+			[
+				TokenType::LeftParen,
+				TokenType::Identifier("+"),
+				TokenType::Integer(1),
+				TokenType::Error("#q"),
+				TokenType::Integer(2),
+				TokenType::RightParen,
+				TokenType::LeftParen,
+				TokenType::Identifier("print"),
+				TokenType::Error("0b102"),
+				TokenType::RightParen,
+			]
+		);
+		assert_eq!(tokens[3].span.range(), 5..7);
+		assert_eq!(lexer.errors().len(), 2);
+	}
+
+	#[test]
+	fn recovery_continues_after_unterminated_characters() {
+		let mut lexer = Lexer::new("'ab (+ 1 2)\n").with_recovery();
+		let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+		assert_eq!(
+			types(&tokens),
+			[
+				TokenType::Error("'ab"),
+				TokenType::LeftParen,
+				TokenType::Identifier("+"),
+				TokenType::Integer(1),
+				TokenType::Integer(2),
+				TokenType::RightParen,
+			]
+		);
+		assert_eq!(lexer.errors().len(), 1);
+	}
+
+	#[test]
+	fn errors_stop_lexing_without_recovery() {
+		let mut lexer = Lexer::new("(+ 1 #q 2)\n");
+
+		assert_eq!(lexer.by_ref().take_while(Result::is_ok).count(), 3);
+		assert!(lexer.errors().is_empty());
+	}
+
+	/// Get the span and help of the error lexing a single number
+	fn invalid_number(source: &str) -> (std::ops::Range<usize>, String) {
+		match Lexer::new(source).lex_token() {
+			Some(Err(LexError::InvalidNumber { loc, help, .. })) => (loc.range(), help.unwrap()),
+			result => panic!("expected an invalid number, got {result:?}"),
+		}
+	}
+
+	#[test]
+	fn invalid_digits_are_pointed_at() {
+		assert_eq!(invalid_number("0b1234\n").0, 3..4);
+		assert_eq!(invalid_number("0o9\n").0, 2..3);
+		assert_eq!(invalid_number("-0x1fx\n").0, 5..6);
+		assert_eq!(invalid_number("12a4\n").0, 2..3);
+	}
+
+	#[test]
+	fn invalid_digits_explain_the_allowed_digits() {
+		assert_eq!(invalid_number("0b2\n").1, "`0b` numbers can only contain the digits 0 and 1");
+		assert_eq!(invalid_number("0o8\n").1, "`0o` numbers can only contain the digits 0-7");
+		assert_eq!(
+			invalid_number("0x1o\n").1,
+			"`0x` numbers can only contain the digits 0-9 and a-f"
+		);
+		assert_eq!(invalid_number("1f\n").1, "decimal numbers can only contain the digits 0-9");
+	}
+
+	#[test]
+	fn digits_may_be_separated_by_underscores() {
+		let tokens = lex_str("0b1010_1010 1_000\n").unwrap();
+
+		assert_eq!(types(&tokens), [TokenType::Integer(0b1010_1010), TokenType::Integer(1000)]);
+	}
+}
@@ -1,25 +1,44 @@
 //! Ream language library
 
 #![warn(missing_docs)]
-#![feature(assert_matches)]
-#![feature(let_chains)]
-#![feature(lazy_cell)]
-#![feature(type_alias_impl_trait)]
 #![allow(incomplete_features)]
 #![feature(generic_const_items)]
+#![feature(iter_order_by)]
 
 pub mod ast;
 mod error;
 mod eval;
+mod expand;
+mod explain;
 mod lex;
+mod messages;
 mod parse;
+mod report;
+mod source_map;
+mod span;
+mod stats;
 mod token;
+mod token_stream;
+mod typecheck;
+mod value;
 
 pub use error::*;
+pub use eval::*;
+pub use expand::*;
+pub use explain::*;
 pub use lex::*;
-use miette::SourceSpan;
+pub use messages::*;
+#[cfg(feature = "bigint")]
+pub use num_bigint;
+pub use num_rational;
 pub use parse::*;
+pub use report::*;
+pub use source_map::*;
+pub use span::*;
+pub use stats::*;
 pub use token::*;
+pub use token_stream::*;
+pub use value::*;
 
 trait Combine {
 	/// Combine two items into one
@@ -29,25 +48,80 @@ trait Combine {
 	fn increment(&self) -> Self;
 }
 
-impl Combine for SourceSpan {
+impl Combine for Span {
+	/// Get the smallest span covering both spans, regardless of which one
+	/// comes first
+	///
+	/// Both spans should point into the same file, the result points into
+	/// the file of `self`
 	fn combine(&self, other: &Self) -> Self {
-		// Start at the first span
-		let start = self.offset() as isize;
+		let start = self.offset().min(other.offset());
+		let end = (self.offset() + self.len()).max(other.offset() + other.len());
 
-		// Keep going for the length of the first span, compensate for a
-		// potential gap between the first and second span, and keep going for
-		// the length of the second span
-		let first_len = self.len() as isize;
-		let len =
-			first_len + (other.offset() as isize - (start + first_len)) + other.len() as isize;
-
-		(start as usize, len as usize).into()
+		Span::new(self.file(), start..end)
 	}
 
 	fn increment(&self) -> Self {
 		let start = self.offset() + self.len();
-		let len = 1;
 
-		(start, len).into()
+		Span::new(self.file(), start..start + 1)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn span(offset: usize, len: usize) -> Span {
+		Span::new(FileId::default(), offset..offset + len)
+	}
+
+	#[test]
+	fn combine_in_order() {
+		assert_eq!(span(2, 3).combine(&span(5, 4)), span(2, 7));
+	}
+
+	#[test]
+	fn combine_with_gap() {
+		assert_eq!(span(0, 1).combine(&span(10, 2)), span(0, 12));
+	}
+
+	#[test]
+	fn combine_out_of_order() {
+		assert_eq!(span(10, 2).combine(&span(0, 1)), span(0, 12));
+	}
+
+	#[test]
+	fn combine_overlapping() {
+		assert_eq!(span(2, 6).combine(&span(4, 6)), span(2, 8));
+		assert_eq!(span(4, 6).combine(&span(2, 6)), span(2, 8));
+	}
+
+	#[test]
+	fn combine_contained() {
+		assert_eq!(span(0, 10).combine(&span(3, 2)), span(0, 10));
+		assert_eq!(span(3, 2).combine(&span(0, 10)), span(0, 10));
+	}
+
+	#[test]
+	fn combine_empty() {
+		assert_eq!(span(0, 0).combine(&span(5, 3)), span(0, 8));
+		assert_eq!(span(5, 0).combine(&span(5, 0)), span(5, 0));
+	}
+
+	#[test]
+	fn combine_is_commutative() {
+		let spans = [span(0, 0), span(0, 3), span(2, 1), span(4, 4), span(7, 0)];
+
+		for a in spans {
+			for b in spans {
+				assert_eq!(a.combine(&b), b.combine(&a), "{a:?} {b:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn increment() {
+		assert_eq!(span(3, 2).increment(), span(5, 1));
 	}
 }
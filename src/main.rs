@@ -1,61 +1,558 @@
 use std::borrow::Cow;
-use std::fs::File;
-use std::io::Read;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
 
-use clap::Parser as ArgParser;
+use clap::{Parser as ArgParser, Subcommand};
 use miette::NamedSource;
-use ream::{Error, Lexer, Parser};
+use ream::{
+	explain,
+	lex_str,
+	parse_str,
+	set_catalog,
+	set_diagnostic_style,
+	Capabilities,
+	Catalog,
+	Capability,
+	DiagnosticStyle,
+	Error,
+	FileSystemProvider,
+	Interpreter,
+	LexError,
+	ParseError,
+	SourceProvider,
+};
 
 #[derive(ArgParser, Clone)]
 #[command(author, version, about, long_about=None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-	/// The source file
-	source_file: String,
+	#[command(subcommand)]
+	command: Option<Command>,
+
+	/// The source file, starts a REPL if omitted
+	source_file: Option<String>,
 
 	/// How verbose the output should be
 	#[arg(short='v', long="verbose", action=clap::ArgAction::Count)]
 	verbosity: u8,
 
+	/// Render errors without colors or unicode, for CI logs and piped output
+	#[arg(long = "plain", visible_alias = "no-color", global = true)]
+	plain: bool,
+
+	/// A Fluent file replacing the English diagnostic messages, messages it
+	/// doesn't contain stay in English
+	#[arg(long = "messages", global = true)]
+	messages: Option<PathBuf>,
+
 	/// Whether or not to show the output of the lexer
 	#[arg(short = 'l', long = "lex")]
 	show_lex: bool,
+
+	/// Whether or not to show statistics about the structure of the program,
+	/// like how deeply its expressions are nested
+	#[arg(long = "stats")]
+	show_stats: bool,
+
+	/// Start a REPL after running the source file, in which everything the
+	/// file defined is still available
+	#[arg(long = "repl-after")]
+	repl_after: bool,
+
+	/// The capability sets available to the program, `pure` only sandboxes
+	/// untrusted programs, defaults to all capabilities
+	#[arg(short = 'a', long = "allow", value_delimiter = ',')]
+	allow: Option<Vec<Capability>>,
+
+	/// Extra directories searched for included files, after the directory of
+	/// the including file
+	#[arg(short = 'I', long = "include-path")]
+	include_paths: Vec<PathBuf>,
+
+	/// Arguments passed on to the program, available through `(args)`
+	#[arg(last = true)]
+	program_args: Vec<String>,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+	/// Generate the `main.rs` of a Rust program that runs the source file
+	///
+	/// The program isn't translated to Rust, the generated code embeds the
+	/// source and every file it includes, and runs them with the interpreter
+	/// of the `ream` crate it depends on. Compiling it gives an executable
+	/// that doesn't need the source files or `ream` to be installed
+	Build {
+		/// The source file
+		source_file: String,
+
+		/// Where to write the generated Rust source, defaults to stdout
+		#[arg(short = 'o', long = "output")]
+		output: Option<String>,
+
+		/// The capability sets available to the program, defaults to all
+		/// capabilities
+		#[arg(short = 'a', long = "allow", value_delimiter = ',')]
+		allow: Option<Vec<Capability>>,
+
+		/// Extra directories searched for included files, after the directory
+		/// of the including file
+		#[arg(short = 'I', long = "include-path")]
+		include_paths: Vec<PathBuf>,
+	},
+
+	/// Start an interactive read-eval-print loop
+	Repl,
+
+	/// Show the extended explanation of a diagnostic code, eg.
+	/// `ream::lex_error::invalid_boolean`
+	Explain {
+		/// The diagnostic code, the `ream::` prefix can be left out
+		code: String,
+	},
 }
 
 fn main() -> miette::Result<()> {
 	let args = Args::parse();
 
-	let mut source_file = File::open(args.source_file.clone()).map_err(Error::from)?;
+	if args.plain {
+		// Unwrap is safe as nothing else installs a hook
+		set_diagnostic_style(DiagnosticStyle::Plain).unwrap();
+	}
+
+	if let Some(path) = &args.messages {
+		let source = fs::read_to_string(path).map_err(Error::from)?;
+		set_catalog(Catalog::parse(&source)?);
+	}
+
+	if let Some(Command::Build { source_file, output, allow, include_paths }) = &args.command {
+		let capabilities = capabilities(allow.as_deref());
+		let provider = FileSystemProvider::new(include_paths.clone());
+
+		return build(source_file, output.as_deref(), capabilities, &provider);
+	}
+
+	if let Some(Command::Explain { code }) = &args.command {
+		return match explain(code) {
+			Some(explanation) => {
+				println!("{explanation}");
+				Ok(())
+			},
+			None => Err(miette::miette!("`{code}` is not a known diagnostic code")),
+		};
+	}
+
+	let Some(source_file) = args.source_file.clone() else {
+		return repl(&args);
+	};
+
+	let source = read_source(&source_file)?;
+
+	process_file(&source_file, &source, &args)
+}
+
+/// Read an entire source file
+fn read_source(path: &str) -> miette::Result<Cow<'static, str>> {
+	let mut source_file = File::open(path).map_err(Error::from)?;
 	let mut source = String::new();
 	source_file.read_to_string(&mut source).map_err(Error::from)?;
 
-	let source: Cow<str> = source.into();
+	Ok(source.into())
+}
+
+/// Check that a source file and every file it includes parse, and write a
+/// Rust program embedding them
+fn build(
+	source_file: &str,
+	output: Option<&str>,
+	capabilities: Capabilities,
+	provider: &FileSystemProvider,
+) -> miette::Result<()> {
+	let source = read_source(source_file)?;
+	let named_source = NamedSource::new(source_file, source.clone());
+	parse_str(&source).map_err(|err| err.with_source_code(named_source))?;
+
+	let script = provider
+		.resolve(source_file, None)
+		.map_err(|_| miette::miette!("could not find `{source_file}`"))?;
+	let (files, inclusions) = collect_includes(&script, &source, provider)?;
+
+	let generated = emit_rust(&source, source_file, &script, &files, &inclusions, capabilities);
+
+	match output {
+		Some(path) => fs::write(path, generated).map_err(Error::from)?,
+		None => print!("{generated}"),
+	}
+
+	Ok(())
+}
+
+/// Every file included by a program, directly or through other included
+/// files, as `(resolved name, source)`, and how each inclusion was
+/// resolved, as `(resolved includer, included name, resolved name)`
+type Includes = (Vec<(String, String)>, Vec<(String, String, String)>);
+
+/// Find, read and parse every file included by a script, resolving them
+/// the way running the script would
+fn collect_includes(
+	script: &str,
+	source: &str,
+	provider: &FileSystemProvider,
+) -> miette::Result<Includes> {
+	let mut files: Vec<(String, String)> = vec![];
+	let mut inclusions = vec![];
+
+	let mut pending = vec![(script.to_string(), parse_str(source)?.stats().includes)];
+	while let Some((includer, names)) = pending.pop() {
+		for name in names {
+			let resolved = provider.resolve(&name, Some(&includer)).map_err(|searched| {
+				miette::miette!(
+					"could not find `{name}` included by `{includer}`, searched in {}",
+					searched.join(", ")
+				)
+			})?;
+			inclusions.push((includer.clone(), name, resolved.clone()));
+
+			if resolved == script || files.iter().any(|(file, _)| *file == resolved) {
+				continue;
+			}
+
+			let source = provider.read(&resolved).map_err(Error::from)?;
+			let named_source = NamedSource::new(resolved.clone(), source.clone());
+			let includes = parse_str(&source)
+				.map_err(|err| err.with_source_code(named_source))?
+				.stats()
+				.includes;
+
+			pending.push((resolved.clone(), includes));
+			files.push((resolved, source));
+		}
+	}
+
+	Ok((files, inclusions))
+}
+
+/// Generate the `main.rs` of a program that runs the given source with the
+/// interpreter of the `ream` crate, passing on its command line arguments
+///
+/// Included files are served from memory by a provider that resolves every
+/// inclusion the way it was resolved when the program was built
+fn emit_rust(
+	source: &str,
+	source_file: &str,
+	script: &str,
+	files: &[(String, String)],
+	inclusions: &[(String, String, String)],
+	capabilities: Capabilities,
+) -> String {
+	let files = files
+		.iter()
+		.map(|(name, source)| format!("\t({name:?}, {}),\n", raw_string(source)))
+		.collect::<String>();
+	let inclusions = inclusions
+		.iter()
+		.map(|(includer, name, resolved)| format!("\t({includer:?}, {name:?}, {resolved:?}),\n"))
+		.collect::<String>();
+	let capabilities = Capability::ALL
+		.into_iter()
+		.filter(|capability| capabilities.contains(*capability))
+		.map(|capability| format!("Capability::{capability:?}"))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let source = raw_string(source);
+
+	format!(
+		r#"//! Generated by `ream build` from `{source_file}`, do not edit
+
+use std::io;
+
+use ream::{{Capability, Interpreter, SourceProvider}};
+
+const SOURCE_FILE: &str = {source_file:?};
+const SOURCE: &str = {source};
+
+/// The name the script was resolved to when it was built
+const SCRIPT: &str = {script:?};
+
+/// Every file included by the script, as `(resolved name, source)`
+const FILES: &[(&str, &str)] = &[
+{files}];
+
+/// How every inclusion was resolved when the script was built, as
+/// `(resolved includer, included name, resolved name)`
+const INCLUSIONS: &[(&str, &str, &str)] = &[
+{inclusions}];
+
+/// Serves the files embedded in this program
+struct Embedded;
+
+impl SourceProvider for Embedded {{
+	fn resolve(&self, name: &str, includer: Option<&str>) -> Result<String, Vec<String>> {{
+		let Some(includer) = includer else {{
+			return if name == SOURCE_FILE {{ Ok(SCRIPT.to_string()) }} else {{ Err(vec![]) }};
+		}};
+
+		INCLUSIONS
+			.iter()
+			.find(|&&(i, n, _)| i == includer && n == name)
+			.map(|&(_, _, resolved)| resolved.to_string())
+			.ok_or_else(Vec::new)
+	}}
+
+	fn read(&self, resolved: &str) -> io::Result<String> {{
+		FILES
+			.iter()
+			.find(|&&(name, _)| name == resolved)
+			.map(|&(_, source)| source.to_string())
+			.ok_or_else(|| io::ErrorKind::NotFound.into())
+	}}
+}}
+
+fn main() -> miette::Result<()> {{
+	let capabilities = [{capabilities}].into_iter().collect();
+	let mut interpreter = Interpreter::new(capabilities)
+		.with_script_path(SOURCE_FILE)
+		.with_source_provider(Embedded)
+		.with_args(std::env::args().skip(1).collect());
 
-	let named_source = NamedSource::new(args.source_file.clone(), source.clone());
+	run(&mut interpreter).map_err(|err| err.with_source_code(interpreter.source_map()))
+}}
 
-	process_file(&source, &args).map_err(|err| err.with_source_code(named_source))
+fn run(interpreter: &mut Interpreter<'static>) -> miette::Result<()> {{
+	let program = interpreter.parse(SOURCE_FILE, SOURCE)?;
+	let value = interpreter.run(program)?;
+
+	if !value.is_unit() {{
+		println!("{{value}}");
+	}}
+
+	Ok(())
+}}
+"#
+	)
+}
+
+/// Write a string as a Rust raw string literal
+fn raw_string(s: &str) -> String {
+	// Pick a delimiter that can't appear in the string
+	let mut hashes = "#".to_string();
+	while s.contains(&format!("\"{hashes}")) {
+		hashes.push('#');
+	}
+
+	format!("r{hashes}\"{s}\"{hashes}")
 }
 
-/// Separate function that actually does all the work because miette decided
-/// that [`NamedSource`] didn't need to be [`Copy`] or [`Clone`] for some
-/// reason
-fn process_file(source: &str, args: &Args) -> miette::Result<()> {
-	let lexer = Lexer::new(source);
+/// Lex, parse and run a source file, displaying errors with the source of
+/// the file they happened in
+///
+/// With `--repl-after` a REPL is started in the global scope of the file
+/// afterwards, even if running it failed, so whatever it defined up to the
+/// error can still be inspected
+fn process_file(name: &str, source: &str, args: &Args) -> miette::Result<()> {
+	let mut interpreter = Interpreter::new(capabilities(args.allow.as_deref()))
+		.with_script_path(name)
+		.with_include_paths(args.include_paths.clone())
+		.with_args(args.program_args.clone());
+
+	let result = run_file(&mut interpreter, name, source, args);
 
+	if !args.repl_after {
+		return result.map_err(|err| err.with_source_code(interpreter.source_map()));
+	}
+
+	if let Err(err) = result {
+		report(err, &interpreter);
+	}
+
+	read_eval_print(&mut interpreter, io::stdin().lock())
+}
+
+/// Separate function that actually does all the work, so every error it
+/// returns can be given the source map of the interpreter in one place
+fn run_file<'s>(
+	interpreter: &mut Interpreter<'s>,
+	name: &str,
+	source: &'s str,
+	args: &Args,
+) -> miette::Result<()> {
 	if args.show_lex {
-		let tokens = lexer.clone().collect::<Result<Vec<_>, _>>()?;
+		let tokens = lex_str(source)?;
 
 		println!("{}", tokens.iter().map(|t| format!("{t:?}")).collect::<Vec<_>>().join("\n"));
 	}
 
-	let token_iterator = lexer.peekable();
+	let root = interpreter.parse(name, source)?;
+	root.typecheck()?;
 
-	let mut parser = Parser::new(source, token_iterator);
+	println!("{:#?}", root);
 
-	let root = parser.parse()?;
+	if args.show_stats {
+		println!("{}", root.stats());
+	}
 
-	println!("{:#?}", root);
+	let value = interpreter.run(root)?;
 
-	root.run()?;
+	if !value.is_unit() {
+		println!("{value}");
+	}
 
 	Ok(())
 }
+
+/// The capabilities granted by the `--allow` flag
+fn capabilities(allow: Option<&[Capability]>) -> Capabilities {
+	match allow {
+		Some(allowed) => allowed.iter().copied().collect(),
+		None => Capabilities::ALL,
+	}
+}
+
+/// Start a REPL with a fresh interpreter
+fn repl(args: &Args) -> miette::Result<()> {
+	let mut interpreter = Interpreter::new(capabilities(args.allow.as_deref()))
+		.with_include_paths(args.include_paths.clone())
+		.with_args(args.program_args.clone());
+
+	read_eval_print(&mut interpreter, io::stdin().lock())
+}
+
+/// Read expressions line by line and evaluate them in the global scope of an
+/// interpreter, printing the value of each one
+///
+/// Input that isn't complete yet (eg. because of an unclosed parenthesis)
+/// keeps being read on the next line, errors are reported without leaving
+/// the loop
+fn read_eval_print(
+	interpreter: &mut Interpreter<'_>,
+	mut lines: impl BufRead,
+) -> miette::Result<()> {
+	let mut input = String::new();
+
+	loop {
+		print!("{}", if input.is_empty() { "> " } else { ".. " });
+		io::stdout().flush().map_err(Error::from)?;
+
+		let mut line = String::new();
+		if lines.read_line(&mut line).map_err(Error::from)? == 0 {
+			println!();
+			return Ok(());
+		}
+		input.push_str(&line);
+
+		if input.trim().is_empty() {
+			input.clear();
+			continue;
+		}
+
+		// Incomplete input is only checked here, so it isn't kept around or
+		// added to the source map until it's complete
+		if matches!(parse_str(&input), Err(err) if is_incomplete(&err)) {
+			continue;
+		}
+
+		// Values and definitions borrow from the source they were evaluated
+		// from and outlive the input, so each input is kept around for the rest
+		// of the session
+		let source: &'static str = Box::leak(std::mem::take(&mut input).into_boxed_str());
+
+		let program = match interpreter.parse("<repl>", source) {
+			Ok(program) => program,
+			Err(err) => {
+				report(err, interpreter);
+				continue;
+			},
+		};
+
+		for expr in program.0 {
+			match interpreter.eval(expr) {
+				Ok(value) if value.is_unit() => (),
+				Ok(value) => println!("{value}"),
+				Err(err) => {
+					report(err.into(), interpreter);
+					break;
+				},
+			}
+		}
+	}
+}
+
+/// Check whether an error only happened because the input ended too early
+fn is_incomplete(err: &miette::Report) -> bool {
+	matches!(
+		err.downcast_ref::<ParseError>(),
+		Some(ParseError::UnexpectedEof { .. } | ParseError::UnclosedParen { .. })
+	) || matches!(
+		err.downcast_ref::<LexError>(),
+		Some(
+			LexError::UnexpectedEof { .. }
+				| LexError::UnterminatedString { .. }
+				| LexError::UnterminatedCharacter { .. }
+		)
+	)
+}
+
+/// Print an error without stopping the REPL, with the source or included
+/// file it happened in
+fn report(err: miette::Report, interpreter: &Interpreter<'_>) {
+	eprintln!("{:?}", err.with_source_code(interpreter.source_map()));
+}
+
+#[cfg(test)]
+mod tests {
+	use ream::FileId;
+
+	use super::*;
+
+	fn allowed(args: &[&str]) -> Capabilities {
+		let args = Args::try_parse_from(["ream"].iter().chain(args)).unwrap();
+
+		capabilities(args.allow.as_deref())
+	}
+
+	#[test]
+	fn every_capability_is_allowed_by_default() {
+		assert_eq!(allowed(&["main.rm"]), Capabilities::ALL);
+	}
+
+	#[test]
+	fn allow_grants_only_the_listed_capabilities() {
+		assert_eq!(allowed(&["--allow", "pure", "main.rm"]), Capabilities::PURE);
+		assert_eq!(
+			allowed(&["-a", "pure,io", "--allow", "fs", "main.rm"]),
+			Capabilities::PURE.with(Capability::Io).with(Capability::Fs),
+		);
+	}
+
+	#[test]
+	fn unknown_capabilities_are_rejected() {
+		assert!(Args::try_parse_from(["ream", "--allow", "pure,disk", "main.rm"]).is_err());
+	}
+
+	#[test]
+	fn arguments_after_the_separator_are_passed_to_the_program() {
+		let args =
+			Args::try_parse_from(["ream", "-a", "pure", "main.rm", "--", "-a", "x"]).unwrap();
+
+		assert_eq!(args.source_file.as_deref(), Some("main.rm"));
+		assert_eq!(args.program_args, ["-a", "x"]);
+	}
+
+	#[test]
+	fn inputs_are_added_to_the_source_map_once_complete() {
+		let mut interpreter = Interpreter::new(Capabilities::PURE);
+		read_eval_print(&mut interpreter, "(+ 1\n\n2)\n".as_bytes()).unwrap();
+
+		let source_map = interpreter.source_map();
+		assert_eq!(source_map.source(FileId::default()), Some("(+ 1\n\n2)\n"));
+	}
+
+	#[test]
+	fn build_has_its_own_allow() {
+		let args = Args::try_parse_from(["ream", "build", "main.rm", "--allow", "pure"]).unwrap();
+		let Some(Command::Build { allow, .. }) = args.command else { panic!("expected build") };
+
+		assert_eq!(capabilities(allow.as_deref()), Capabilities::PURE);
+	}
+}
@@ -0,0 +1,197 @@
+//! Catalog of diagnostic messages
+//!
+//! The text of every diagnostic is looked up by key in a [`Catalog`] written
+//! in a subset of the [Fluent](https://projectfluent.org) syntax, so it can
+//! be translated or reworded without touching the errors themselves
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use crate::CatalogError;
+
+/// The English messages used for every key not found in the installed
+/// catalog
+const DEFAULT_MESSAGES: &str = include_str!("../locales/en.ftl");
+
+static DEFAULT_CATALOG: OnceLock<Catalog> = OnceLock::new();
+static CATALOG: RwLock<Option<Catalog>> = RwLock::new(None);
+
+/// A set of messages, keyed by their message id or by
+/// `<message id>.<attribute>` for attributes
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+	messages: HashMap<String, String>,
+}
+
+impl Catalog {
+	/// Parse a catalog from Fluent source
+	///
+	/// Supports comments, messages (`id = value`) and attributes
+	/// (`    .name = value`) on the lines following a message. Placeables
+	/// can only refer to variables, eg. `{ $found }`.
+	pub fn parse(source: &str) -> Result<Self, CatalogError> {
+		let mut messages = HashMap::new();
+		let mut current: Option<&str> = None;
+
+		for (idx, line) in source.lines().enumerate() {
+			let trimmed = line.trim();
+			if trimmed.is_empty() || trimmed.starts_with('#') {
+				continue;
+			}
+
+			let invalid = || CatalogError::InvalidEntry { line: idx + 1, text: line.to_string() };
+
+			let (id, value) = trimmed.split_once('=').ok_or_else(invalid)?;
+			let (id, value) = (id.trim(), value.trim());
+
+			let key = match id.strip_prefix('.') {
+				Some(attribute) if line.starts_with(char::is_whitespace) => {
+					let message = current.ok_or(CatalogError::OrphanAttribute { line: idx + 1 })?;
+					if !is_identifier(attribute) {
+						return Err(invalid());
+					}
+
+					format!("{message}.{attribute}")
+				},
+				_ => {
+					if !is_identifier(id) || line.starts_with(char::is_whitespace) {
+						return Err(invalid());
+					}
+
+					current = Some(id);
+					id.to_string()
+				},
+			};
+
+			messages.insert(key, value.to_string());
+		}
+
+		Ok(Self { messages })
+	}
+
+	/// Get the message with the given key, with its placeables replaced by
+	/// the matching arguments
+	///
+	/// Placeables without a matching argument are left as they are
+	pub fn format(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> Option<String> {
+		let mut template = self.messages.get(key)?.as_str();
+		let mut message = String::with_capacity(template.len());
+
+		while let Some(start) = template.find('{') {
+			let Some(len) = template[start..].find('}') else {
+				break;
+			};
+
+			message.push_str(&template[..start]);
+
+			let placeable = &template[start..=start + len];
+			let variable = placeable[1..len].trim().strip_prefix('$');
+			match args.iter().find(|(name, _)| Some(*name) == variable) {
+				Some((_, value)) => message.push_str(&value.to_string()),
+				None => message.push_str(placeable),
+			}
+
+			template = &template[start + len + 1..];
+		}
+		message.push_str(template);
+
+		Some(message)
+	}
+
+	/// Iterate over the keys of every message in this catalog
+	pub fn keys(&self) -> impl Iterator<Item = &str> { self.messages.keys().map(String::as_str) }
+}
+
+/// Check if a string is a valid Fluent identifier
+fn is_identifier(id: &str) -> bool {
+	id.starts_with(|c: char| c.is_ascii_alphabetic())
+		&& id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Get the catalog of English messages
+pub fn default_catalog() -> &'static Catalog {
+	DEFAULT_CATALOG.get_or_init(|| {
+		// Unwrap is safe as the default messages are checked by the tests
+		Catalog::parse(DEFAULT_MESSAGES).unwrap()
+	})
+}
+
+/// Install the catalog used for every diagnostic message from now on
+///
+/// Messages missing from the catalog fall back to English, so installing an
+/// empty catalog restores the defaults
+pub fn set_catalog(catalog: Catalog) {
+	*CATALOG.write().unwrap_or_else(PoisonError::into_inner) = Some(catalog);
+}
+
+/// Get a message from the installed catalog, falling back to the English
+/// one, or to the key itself if neither has it
+pub(crate) fn format_message(key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+	let installed = CATALOG.read().unwrap_or_else(PoisonError::into_inner);
+
+	installed
+		.as_ref()
+		.and_then(|catalog| catalog.format(key, args))
+		.or_else(|| default_catalog().format(key, args))
+		.unwrap_or_else(|| key.to_string())
+}
+
+/// Get a message from the installed catalog, passing arguments by name
+///
+/// `message!("lex-invalid-boolean", found = "#x")`
+macro_rules! message {
+	($key:literal $(, $arg:ident = $value:expr)* $(,)?) => {
+		$crate::messages::format_message(
+			$key,
+			&[$((stringify!($arg), &$value as &dyn ::std::fmt::Display)),*],
+		)
+	};
+}
+pub(crate) use message;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_key_has_a_default() {
+		let errors = [include_str!("error.rs"), include_str!("lex.rs")].concat();
+		let defaults = default_catalog();
+
+		for key in errors
+			.split("message!(")
+			.skip(1)
+			.filter_map(|s| s.trim_start().strip_prefix('"')?.split('"').next())
+		{
+			assert!(defaults.format(key, &[]).is_some(), "`{key}` has no default message");
+		}
+	}
+
+	#[test]
+	fn placeables_are_replaced() {
+		let catalog = Catalog::parse(
+			"greeting = Hello { $name }, { $missing }\n    .help = use {$name}\n# comment",
+		)
+		.unwrap();
+
+		assert_eq!(
+			catalog.format("greeting", &[("name", &"world")]).as_deref(),
+			Some("Hello world, { $missing }")
+		);
+		assert_eq!(catalog.format("greeting.help", &[("name", &1)]).as_deref(), Some("use 1"));
+		assert!(catalog.format("farewell", &[]).is_none());
+	}
+
+	#[test]
+	fn invalid_entries_are_rejected() {
+		assert!(matches!(
+			Catalog::parse("    .help = orphan"),
+			Err(CatalogError::OrphanAttribute { line: 1 })
+		));
+		assert!(matches!(
+			Catalog::parse("ok = fine\nnot a message"),
+			Err(CatalogError::InvalidEntry { line: 2, .. })
+		));
+	}
+}
@@ -1,6 +1,6 @@
-use miette::{Error, SourceSpan};
+use miette::Error;
 
-use crate::{ast, Combine, ParseError, Parser, TokenType};
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
 
 impl<'s> Parser<'s> {
 	/// Parse an annotation of the form `(<atom> <target> ...)
@@ -9,7 +9,7 @@ impl<'s> Parser<'s> {
 	/// `(` and `<atom>` already consumed
 	pub(super) fn parse_annotation(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
 		annotation_type: &'s str,
 	) -> Result<ast::Annotation<'s>, Error> {
 		match annotation_type {
@@ -27,14 +27,17 @@ impl<'s> Parser<'s> {
 
 	/// Parse a type annotation of the form `(:type <target> <typespec>)`
 	/// where target is `<identifier>`
-	/// and docstring is `<string>`
 	///
 	/// `(` and `:type` already consumed
-	fn parse_type_annotation(
-		&mut self,
-		_initial_span: SourceSpan,
-	) -> Result<ast::Annotation<'s>, Error> {
-		todo!()
+	fn parse_type_annotation(&mut self, initial_span: Span) -> Result<ast::Annotation<'s>, Error> {
+		let target = self.expect(TokenType::Identifier(""))?;
+
+		let spec = self.parse_typespec()?;
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Annotation::TypeAnnotation { span, target: target.into(), spec })
 	}
 
 	/// Parse a doc annotation of the form `(:doc <target> <docstring>)`
@@ -42,10 +45,7 @@ impl<'s> Parser<'s> {
 	/// and docstring is `<string>`
 	///
 	/// `(` and `:doc` already consumed
-	fn parse_doc_annotation(
-		&mut self,
-		initial_span: SourceSpan,
-	) -> Result<ast::Annotation<'s>, Error> {
+	fn parse_doc_annotation(&mut self, initial_span: Span) -> Result<ast::Annotation<'s>, Error> {
 		let target = self.expect(TokenType::Identifier(""))?;
 
 		let doc_str_token = self.expect(TokenType::String(""))?;
@@ -0,0 +1,64 @@
+use miette::Error;
+
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
+
+impl<'s> Parser<'s> {
+	/// Parse a macro definition of the form
+	/// `(define-macro <target> <formals> <body>)`
+	/// where target is `<identifier>`
+	/// and formals is `(<identifier>*)` or `(<identifier>* . <identifier>)`
+	/// and body is `<expression>`
+	///
+	/// `(` and `define-macro` already consumed
+	pub(super) fn parse_macro_definition(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let target_token = self.expect(TokenType::Identifier(""))?;
+		let mut macro_span = initial_span.combine(&target_token.span);
+
+		let left_paren = self.next()?;
+		if left_paren.t != TokenType::LeftParen {
+			return Err(ParseError::InvalidFormals {
+				loc:   left_paren.span,
+				found: left_paren.t.to_string(),
+			}
+			.into());
+		}
+
+		let mut formals = vec![];
+		let mut rest = None;
+
+		while self.peek()?.t != TokenType::RightParen {
+			if self.peek()?.t == TokenType::Period {
+				// Unwrap is safe as peek is some
+				self.next().unwrap();
+
+				let rest_token = self.expect(TokenType::Identifier(""))?;
+				rest = Some(rest_token.into());
+
+				break;
+			}
+
+			let formal_token = self.expect(TokenType::Identifier(""))?;
+			formals.push(formal_token.into());
+		}
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		macro_span = macro_span.combine(&right_paren.span);
+
+		let body = self.parse_expression()?;
+		macro_span = macro_span.combine(&self.prev_span);
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		macro_span = macro_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::MacroDefinition {
+			span: macro_span,
+			target: target_token.into(),
+			formals,
+			rest,
+			body: Box::new(body),
+		})
+	}
+}
@@ -1,41 +1,94 @@
 #![allow(dead_code)]
 
-use std::iter::Peekable;
-
-use miette::{Error, SourceSpan};
-
-use crate::{ast, Combine, Lexer, ParseError, Token, TokenType, EOF_TOKEN};
+use miette::Error;
+
+use crate::{
+	ast,
+	Checkpoint,
+	Combine,
+	FileId,
+	Lexer,
+	ParseError,
+	ParseErrors,
+	Span,
+	Token,
+	TokenStream,
+	TokenType,
+};
 
 mod annotation;
+mod macros;
+mod owned;
+mod pattern;
+mod protocol;
 mod quote;
+mod typespec;
+
+pub use owned::OwnedProgram;
+
+/// Lex and parse an entire source file
+pub fn parse_str(source: &str) -> Result<ast::Program<'_>, Error> {
+	parse_file(source, FileId::default())
+}
+
+/// Lex and parse an entire source file, pointing every span into the given
+/// file of a [`SourceMap`](crate::SourceMap)
+pub fn parse_file(source: &str, file: FileId) -> Result<ast::Program<'_>, Error> {
+	Parser::new(source, Lexer::new(source).with_file(file).into()).with_file(file).parse()
+}
 
 /// A parser for a single source file
 #[allow(missing_docs)]
 pub struct Parser<'s> {
 	source: &'s str,
-	tokens: Peekable<Lexer<'s>>,
+	tokens: TokenStream<'s>,
+	/// The file the tokens were lexed from
+	file:   FileId,
+
+	prev_span:   Span,
+	/// EndOfFile token handed out by [`peek`](Self::peek) once no tokens are
+	/// left, positioned right after the last token this parser consumed
+	eof:         Token<'s>,
+	/// The spans of every `(` that hasn't been closed yet, innermost last
+	open_parens: Vec<Span>,
+}
 
-	prev_span: SourceSpan,
+/// The state of a [`Parser`] that can be returned to
+#[derive(Clone, Debug)]
+struct ParserCheckpoint {
+	tokens:      Checkpoint,
+	prev_span:   Span,
+	open_parens: Vec<Span>,
 }
 
 impl<'s> Parser<'s> {
 	/// Create a new [`Parser`]
-	pub fn new(source: &'s str, tokens: Peekable<Lexer<'s>>) -> Self {
-		Self { source, tokens, prev_span: (0, 0).into() }
+	pub fn new(source: &'s str, tokens: TokenStream<'s>) -> Self {
+		let eof = Token { span: Span::default(), t: TokenType::EndOfFile };
+		let file = FileId::default();
+
+		Self { source, tokens, file, prev_span: Span::default(), eof, open_parens: vec![] }
+	}
+
+	/// Tell the parser its tokens were lexed from the given file
+	pub fn with_file(mut self, file: FileId) -> Self {
+		self.file = file;
+		self.prev_span = Span::new(file, 0..0);
+		self
 	}
 
 	/// Peek at the next [`Token`]
 	///
 	/// Returns an [`EndOfFile`](TokenType::EndOfFile) if no tokens are left
 	fn peek(&mut self) -> Result<&Token<'s>, Error> {
-		match self.tokens.peek() {
-			Some(res) => Ok(res.as_ref().map_err(|e| e.clone())?),
-			None => {
-				Ok(EOF_TOKEN.get_or_init(|| {
-					Token { span: self.prev_span.increment(), t: TokenType::EndOfFile }
-				}))
-			},
+		if self.tokens.peek().is_none() {
+			self.eof.span = self.prev_span.increment();
+
+			return Ok(&self.eof);
 		}
+
+		// Unwrap is safe as the iterator was just checked to not be empty
+		Ok(self.tokens.peek().unwrap().as_ref().map_err(|e| e.clone())?)
 	}
 
 	/// Consume and return the next [`Token`]
@@ -44,31 +97,57 @@ impl<'s> Parser<'s> {
 	fn next(&mut self) -> Result<Token<'s>, Error> {
 		let token_result = match self.tokens.next() {
 			Some(t) => t,
-			None => {
-				return Err(ParseError::UnexpectedEof { loc: self.prev_span.increment() }.into());
-			},
+			None => return Err(self.eof_error()),
 		};
 
 		match token_result {
 			Ok(t) => {
 				self.prev_span = t.span;
 
+				match t.t {
+					TokenType::LeftParen | TokenType::HashParen => self.open_parens.push(t.span),
+					TokenType::RightParen => {
+						self.open_parens.pop();
+					},
+					_ => (),
+				}
+
 				Ok(t)
 			},
 			Err(e) => Err(e.into()),
 		}
 	}
 
+	/// Remember the current position of the parser so speculative parsing
+	/// can be undone with [`rewind`](Self::rewind)
+	fn checkpoint(&self) -> ParserCheckpoint {
+		ParserCheckpoint {
+			tokens:      self.tokens.checkpoint(),
+			prev_span:   self.prev_span,
+			open_parens: self.open_parens.clone(),
+		}
+	}
+
+	/// Return to a position remembered with [`checkpoint`](Self::checkpoint),
+	/// every token consumed since will be produced again
+	fn rewind(&mut self, checkpoint: ParserCheckpoint) {
+		self.tokens.rewind(checkpoint.tokens);
+		self.prev_span = checkpoint.prev_span;
+		self.open_parens = checkpoint.open_parens;
+	}
+
+	/// Get the source code wrapped by a span
+	fn source_text(&self, span: Span) -> &'s str { &self.source[span.range()] }
+
 	/// Consume and return the next [`Token`] if it has the given [`TokenType`]
 	fn expect(&mut self, t: TokenType<'s>) -> Result<Token<'s>, Error> {
-		let token = match self.peek() {
-			Ok(t) => t,
-			Err(e) => return Err(e),
-		};
+		let token = self.peek()?;
 
 		if std::mem::discriminant(&token.t) == std::mem::discriminant(&t) {
 			// Unwrap is safe as peek returned a token
 			Ok(self.next().unwrap())
+		} else if token.t == TokenType::EndOfFile {
+			Err(self.eof_error())
 		} else {
 			Err(ParseError::UnexpectedToken {
 				loc:      token.span,
@@ -79,18 +158,56 @@ impl<'s> Parser<'s> {
 		}
 	}
 
+	/// Create the error for running out of tokens, pointing at the innermost
+	/// unclosed `(` if there is one
+	fn eof_error(&self) -> Error {
+		let end = self.prev_span.range().end;
+
+		match self.open_parens.last() {
+			Some(&open) => {
+				let close = Span::new(self.file, end..end);
+
+				ParseError::UnclosedParen { loc: open, close }.into()
+			},
+			None => ParseError::UnexpectedEof { loc: self.prev_span.increment() }.into(),
+		}
+	}
+
 	/// Parse the entire input
 	pub fn parse(&mut self) -> Result<ast::Program<'s>, Error> {
-		// let initial_span: SourceSpan = (0, 0).into();
 		let mut exprs = vec![];
+		let mut errors: Vec<Error> = vec![];
+
+		let result = loop {
+			match self.peek() {
+				Ok(token) if token.t == TokenType::EndOfFile => break Ok(()),
+				// Stray closing parentheses are skipped so the rest of the
+				// file can still be parsed
+				Ok(token) if token.t == TokenType::RightParen => {
+					errors.push(ParseError::UnmatchedCloseParen { loc: token.span }.into());
+					// Unwrap is safe as peek returned a token
+					self.next().unwrap();
+				},
+				Ok(_) => {
+					match self.parse_expression() {
+						Ok(expr) => exprs.push(expr),
+						Err(e) => break Err(e),
+					}
+				},
+				Err(e) => break Err(e),
+			}
+		};
 
-		while self.peek()?.t != TokenType::EndOfFile {
-			let expr = self.parse_expression()?;
-
-			exprs.push(expr);
+		if let Err(e) = result {
+			errors.push(e);
 		}
 
-		Ok(ast::Program(exprs))
+		match errors.len() {
+			0 => Ok(ast::Program(exprs)),
+			// Unwrap is safe as there is exactly one error
+			1 => Err(errors.pop().unwrap()),
+			_ => Err(ParseErrors { errors }.into()),
+		}
 	}
 
 	/// Parse any expression
@@ -103,6 +220,7 @@ impl<'s> Parser<'s> {
 			TokenType::Identifier(_) => Ok(ast::Expression::Identifier(token.into())),
 			TokenType::Boolean(_) => Ok(ast::Expression::Literal(token.into())),
 			TokenType::Integer(_) => Ok(ast::Expression::Literal(token.into())),
+			TokenType::Rational(_) => Ok(ast::Expression::Literal(token.into())),
 			TokenType::Float(_) => Ok(ast::Expression::Literal(token.into())),
 			TokenType::Character(_) => Ok(ast::Expression::Literal(token.into())),
 			TokenType::String(_) => Ok(ast::Expression::Literal(token.into())),
@@ -111,6 +229,7 @@ impl<'s> Parser<'s> {
 			TokenType::Backtick => Ok(self.parse_shorthand_quote(expression_span)?.into()),
 
 			TokenType::LeftParen => self.parse_parenthesized_expression(expression_span),
+			TokenType::HashParen => self.parse_vector(expression_span),
 
 			// EndOfFile is unreachable as it's filtered out in the loop in `self.parse()`
 			TokenType::EndOfFile => unreachable!(),
@@ -123,11 +242,13 @@ impl<'s> Parser<'s> {
 						"Identifier".to_string(),
 						"Boolean".to_string(),
 						"Integer".to_string(),
+						"Rational".to_string(),
 						"Float".to_string(),
 						"Character".to_string(),
 						"String".to_string(),
 						"Atom".to_string(),
 						"(".to_string(),
+						"#(".to_string(),
 					],
 				}
 				.into())
@@ -140,7 +261,7 @@ impl<'s> Parser<'s> {
 	/// `(` already consumed
 	fn parse_parenthesized_expression(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
 	) -> Result<ast::Expression<'s>, Error> {
 		let token = self.peek()?;
 
@@ -160,6 +281,10 @@ impl<'s> Parser<'s> {
 				self.next().unwrap();
 				Ok(self.parse_variable_definition(expression_span)?)
 			},
+			TokenType::KwConst => {
+				self.next().unwrap();
+				Ok(self.parse_constant_definition(expression_span)?)
+			},
 			TokenType::KwFn => {
 				self.next().unwrap();
 				Ok(self.parse_function_definition(expression_span)?)
@@ -176,27 +301,54 @@ impl<'s> Parser<'s> {
 				self.next().unwrap();
 				Ok(self.parse_conditional(expression_span)?)
 			},
+			TokenType::KwCond => {
+				self.next().unwrap();
+				Ok(self.parse_cond(expression_span)?)
+			},
 			TokenType::KwInclude => {
 				self.next().unwrap();
 				Ok(self.parse_inclusion(expression_span)?)
 			},
+			TokenType::KwMatch => {
+				self.next().unwrap();
+				Ok(self.parse_match(expression_span)?)
+			},
+			TokenType::KwCase => {
+				self.next().unwrap();
+				Ok(self.parse_case(expression_span)?)
+			},
+			TokenType::KwDefProtocol => {
+				self.next().unwrap();
+				Ok(self.parse_protocol_definition(expression_span)?)
+			},
+			TokenType::KwImpl => {
+				self.next().unwrap();
+				Ok(self.parse_protocol_implementation(expression_span)?)
+			},
+			TokenType::KwTypeAlias => {
+				self.next().unwrap();
+				Ok(self.parse_type_alias(expression_span)?)
+			},
+			TokenType::KwDefineType => {
+				self.next().unwrap();
+				Ok(self.parse_algebraic_type_definition(expression_span)?)
+			},
+			TokenType::KwDefineMacro => {
+				self.next().unwrap();
+				Ok(self.parse_macro_definition(expression_span)?)
+			},
+			TokenType::KwThe => {
+				self.next().unwrap();
+				Ok(self.parse_type_assertion(expression_span)?)
+			},
+			TokenType::KwTime => {
+				self.next().unwrap();
+				Ok(self.parse_time(expression_span)?)
+			},
 
-			// TokenType::Identifier(_) => {
-			// 	Ok(self.parse_procedure_call(expression_span, token.into())?)
-			// },
+			// Anything else is the operator of a procedure call, which can be
+			// any expression evaluating to something callable
 			_ => Ok(self.parse_procedure_call(expression_span)?),
-			// tt => {
-			// 	Err(ParseError::UnexpectedToken {
-			// 		loc:      token.span,
-			// 		found:    tt.to_string(),
-			// 		expected: vec![
-			// 			"Atom".to_string(),
-			// 			"Keyword".to_string(),
-			// 			"Identifier".to_string(),
-			// 		],
-			// 	}
-			// 	.into())
-			// },
 		}
 	}
 
@@ -205,10 +357,7 @@ impl<'s> Parser<'s> {
 	/// and operands is `<expression>*`
 	///
 	/// `(` already consumed
-	fn parse_procedure_call(
-		&mut self,
-		initial_span: SourceSpan,
-	) -> Result<ast::Expression<'s>, Error> {
+	fn parse_procedure_call(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
 		let operator = Box::new(self.parse_expression()?);
 
 		let mut operands = vec![];
@@ -224,17 +373,173 @@ impl<'s> Parser<'s> {
 		let right_paren = self.expect(TokenType::RightParen).unwrap();
 		procedure_span = procedure_span.combine(&right_paren.span);
 
+		// Literals can never evaluate to something callable, so there's no need
+		// to wait for evaluation to report them
+		if let ast::Expression::Literal(literal) = operator.as_ref() {
+			let kind = match literal {
+				ast::Literal::Quotation { .. } => "Quotation",
+				ast::Literal::Boolean { .. } => "Boolean",
+				ast::Literal::Integer { .. } => "Integer",
+				ast::Literal::Rational { .. } => "Rational",
+				ast::Literal::Float { .. } => "Float",
+				ast::Literal::Character { .. } => "Character",
+				ast::Literal::String { .. } => "String",
+				ast::Literal::Atom { .. } => "Atom",
+			};
+
+			return Err(ParseError::NotCallable {
+				loc:    literal.span(),
+				kind:   kind.to_string(),
+				quoted: format!("`{}", self.source_text(procedure_span)),
+			}
+			.into());
+		}
+
 		Ok(ast::Expression::ProcedureCall { span: procedure_span, operator, operands })
 	}
 
-	/// Parse a variable definition of the form `(let <target> <value>)`
-	/// where target is `<identifier>`
-	/// and value is `<expression>`
+	/// Parse a variable definition of the form `(let <target> <value>)`, or
+	/// a let expression of the form `(let (<binding>*) <body>)`
+	///
+	/// The two are told apart by the form right after `let`: a list whose
+	/// elements are all lists (including the empty list) is always read as
+	/// the bindings of a let expression, anything else as the target of a
+	/// definition. A list of lists can still be destructured by a definition
+	/// through a `cons` pattern
 	///
 	/// `(` and `let` already consumed
 	fn parse_variable_definition(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		if self.at_let_bindings() {
+			self.parse_let(initial_span)
+		} else {
+			self.parse_definition_rest(initial_span)
+		}
+	}
+
+	/// Check if the next tokens form a list whose elements are all lists,
+	/// without consuming anything
+	fn at_let_bindings(&mut self) -> bool {
+		let is = |token: Option<&Result<Token<'_>, _>>, t: TokenType<'_>| {
+			matches!(token, Some(Ok(token)) if token.t == t)
+		};
+
+		if !is(self.tokens.peek_n(0), TokenType::LeftParen) {
+			return false;
+		}
+
+		let mut n = 1;
+		loop {
+			if is(self.tokens.peek_n(n), TokenType::RightParen) {
+				return true;
+			}
+			if !is(self.tokens.peek_n(n), TokenType::LeftParen) {
+				return false;
+			}
+
+			// Skip over the element
+			let mut depth = 0;
+			loop {
+				match self.tokens.peek_n(n) {
+					Some(Ok(token)) => {
+						match token.t {
+							TokenType::LeftParen | TokenType::HashParen => depth += 1,
+							TokenType::RightParen => depth -= 1,
+							_ => (),
+						}
+					},
+					_ => return false,
+				}
+
+				n += 1;
+				if depth == 0 {
+					break;
+				}
+			}
+		}
+	}
+
+	/// Parse a timed expression of the form `(time <value>)`
+	/// where value is `<expression>`
+	///
+	/// `(` and `time` already consumed
+	fn parse_time(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let value = Box::new(self.parse_expression()?);
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::Time { span, value })
+	}
+
+	/// Parse the rest of a variable definition of the form
+	/// `(let <target> <value>)`
+	/// where target is `<pattern>`
+	/// and value is `<expression>`
+	///
+	/// `(` and `let` already consumed
+	fn parse_definition_rest(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let target = self.parse_pattern()?;
+		let mut definition_span = initial_span.combine(&self.prev_span);
+
+		let value = self.parse_expression()?;
+		definition_span = definition_span.combine(&self.prev_span);
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		definition_span = definition_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::VariableDefinition {
+			span: definition_span,
+			target,
+			value: Box::new(value),
+		})
+	}
+
+	/// Parse the rest of a let expression of the form
+	/// `(let (<binding>*) <body>)`
+	/// where binding is `(<pattern> <expression>)`
+	/// and body is `<expression>+`
+	///
+	/// `(` and `let` already consumed
+	fn parse_let(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		self.expect(TokenType::LeftParen)?;
+
+		let mut bindings = vec![];
+		while self.peek()?.t != TokenType::RightParen {
+			let left_paren = self.expect(TokenType::LeftParen)?;
+			let target = self.parse_pattern()?;
+			let value = self.parse_expression()?;
+			let right_paren = self.expect(TokenType::RightParen)?;
+			let span = left_paren.span.combine(&right_paren.span);
+
+			bindings.push(ast::LetBinding { span, target, value });
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		self.expect(TokenType::RightParen).unwrap();
+
+		let mut body = vec![self.parse_expression()?];
+		while self.peek()?.t != TokenType::RightParen {
+			body.push(self.parse_expression()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::Let { span, bindings, body })
+	}
+
+	/// Parse a constant definition of the form `(const <target> <value>)`
+	/// where target is `<identifier>`
+	/// and value is `<expression>`
+	///
+	/// `(` and `const` already consumed
+	fn parse_constant_definition(
+		&mut self,
+		initial_span: Span,
 	) -> Result<ast::Expression<'s>, Error> {
 		let target_token = self.expect(TokenType::Identifier(""))?;
 		let mut definition_span = initial_span.combine(&target_token.span);
@@ -245,7 +550,7 @@ impl<'s> Parser<'s> {
 		let right_paren = self.expect(TokenType::RightParen)?;
 		definition_span = definition_span.combine(&right_paren.span);
 
-		Ok(ast::Expression::VariableDefinition {
+		Ok(ast::Expression::ConstantDefinition {
 			span:   definition_span,
 			target: target_token.into(),
 			value:  Box::new(value),
@@ -254,14 +559,20 @@ impl<'s> Parser<'s> {
 
 	/// Parse a variable definition of the form `(fn <target> <formals> <body>)`
 	/// where target is `<identifier>`
-	/// and formals is `<identifier>` or `(<identifier>*)`
+	/// and formals is `<identifier>` or `(<pattern>*)`
 	/// and body is `<expression>+`
 	///
+	/// or a function clause of the form `(fn (<target> <pattern>*) <body>)`
+	///
 	/// `(` and `fn` already consumed
 	fn parse_function_definition(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
 	) -> Result<ast::Expression<'s>, Error> {
+		if self.peek()?.t == TokenType::LeftParen {
+			return self.parse_function_clause(initial_span);
+		}
+
 		let target_token = self.expect(TokenType::Identifier(""))?;
 		let mut function_span = initial_span.combine(&target_token.span);
 
@@ -274,9 +585,9 @@ impl<'s> Parser<'s> {
 			TokenType::Identifier(_) => formals.push(next_token.into()),
 			TokenType::LeftParen => {
 				while self.peek()?.t != TokenType::RightParen {
-					let formal = self.expect(TokenType::Identifier(""))?;
-					function_span = function_span.combine(&formal.span);
-					formals.push(formal.into());
+					let formal = self.parse_pattern()?;
+					function_span = function_span.combine(&self.prev_span);
+					formals.push(formal);
 				}
 
 				// Unwrap is safe as RightParen is selected for in the loop
@@ -311,14 +622,44 @@ impl<'s> Parser<'s> {
 		})
 	}
 
+	/// Parse a function clause of the form `(fn (<target> <pattern>*) <body>)`
+	/// where target is `<identifier>`
+	/// and body is `<expression>+`
+	///
+	/// `(` and `fn` already consumed, the next token is the `(` starting the
+	/// clause head
+	fn parse_function_clause(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		self.expect(TokenType::LeftParen)?;
+		let target_token = self.expect(TokenType::Identifier(""))?;
+
+		let mut patterns = vec![];
+		while self.peek()?.t != TokenType::RightParen {
+			patterns.push(self.parse_pattern()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		self.expect(TokenType::RightParen).unwrap();
+
+		let mut body = vec![self.parse_expression()?];
+		while self.peek()?.t != TokenType::RightParen {
+			body.push(self.parse_expression()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::FunctionClause { span, target: target_token.into(), patterns, body })
+	}
+
 	/// Parse a closure definition of the form `(lambda <formals> <body>)`
-	/// where formals is `<identifier>` or `(<identifier>*)`
+	/// where formals is `<identifier>` or `(<pattern>*)`
 	/// and body is `<expression>+`
 	///
 	/// `(` and `lambda` already consumed
 	fn parse_closure_definition(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
 	) -> Result<ast::Expression<'s>, Error> {
 		let next_token = self.next()?;
 		let mut lambda_span = initial_span.combine(&next_token.span);
@@ -329,9 +670,9 @@ impl<'s> Parser<'s> {
 			TokenType::Identifier(_) => formals.push(next_token.into()),
 			TokenType::LeftParen => {
 				while self.peek()?.t != TokenType::RightParen {
-					let formal = self.expect(TokenType::Identifier(""))?;
-					lambda_span = lambda_span.combine(&formal.span);
-					formals.push(formal.into());
+					let formal = self.parse_pattern()?;
+					lambda_span = lambda_span.combine(&self.prev_span);
+					formals.push(formal);
 				}
 
 				// Unwrap is safe as RightParen is selected for in the loop
@@ -366,7 +707,7 @@ impl<'s> Parser<'s> {
 	/// where sequence is `<expression>+`
 	///
 	/// `(` and `seq` already consumed
-	fn parse_sequence(&mut self, initial_span: SourceSpan) -> Result<ast::Expression<'s>, Error> {
+	fn parse_sequence(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
 		let mut exprs = vec![self.parse_expression()?];
 		let mut sequence_span = initial_span.combine(&self.prev_span);
 
@@ -383,16 +724,33 @@ impl<'s> Parser<'s> {
 		Ok(ast::Expression::Sequence { span: sequence_span, seq: exprs })
 	}
 
+	/// Parse a vector of the form `#(<expression>*)`
+	///
+	/// `#(` already consumed
+	fn parse_vector(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let mut elements = vec![];
+		let mut vector_span = initial_span;
+
+		while self.peek()?.t != TokenType::RightParen {
+			let element = self.parse_expression()?;
+			elements.push(element);
+			vector_span = vector_span.combine(&self.prev_span);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		vector_span = vector_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::Vector { span: vector_span, elements })
+	}
+
 	/// Parse a conditional of the form `(if <test> <consequent> [<alternate>])`
 	/// where test is `<expression>`
 	/// consequent is `<expression>`
 	/// and alternate is `<expression>`
 	///
 	/// `(` and `if` already consumed
-	fn parse_conditional(
-		&mut self,
-		initial_span: SourceSpan,
-	) -> Result<ast::Expression<'s>, Error> {
+	fn parse_conditional(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
 		let test = self.parse_expression()?;
 		let mut conditional_span = initial_span.combine(&self.prev_span);
 
@@ -423,10 +781,86 @@ impl<'s> Parser<'s> {
 		})
 	}
 
+	/// Parse a multi-branch conditional of the form `(cond <clause>+)`
+	/// where clause is `(<test> <expression>+)`
+	/// and the last clause may be `(else <expression>+)`
+	///
+	/// The clauses become nested conditionals, each one the alternate of the
+	/// one before it, with the body of the `else` clause as the innermost
+	/// alternate. Bodies of several expressions become a sequence
+	///
+	/// `(` and `cond` already consumed
+	fn parse_cond(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let mut clauses = vec![];
+		let mut alternate = None;
+
+		loop {
+			let left_paren = self.expect(TokenType::LeftParen)?;
+
+			if self.peek()?.t == TokenType::Identifier("else") {
+				self.next().unwrap();
+				alternate = Some(self.parse_cond_body(left_paren.span)?);
+
+				break;
+			}
+
+			let test = self.parse_expression()?;
+			let consequent = self.parse_cond_body(left_paren.span)?;
+			clauses.push((left_paren.span, test, consequent));
+
+			if self.peek()?.t == TokenType::RightParen {
+				break;
+			}
+		}
+
+		// Only the `else` clause can be followed by something other than a
+		// clause, which has to be the end of the `cond`
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let cond_span = initial_span.combine(&right_paren.span);
+
+		let conditional = clauses.into_iter().enumerate().rev().fold(
+			alternate,
+			|alternate, (i, (clause_span, test, consequent))| {
+				// The outermost conditional covers the entire `cond`, the others
+				// start at their clause
+				let span = if i == 0 { cond_span } else { clause_span.combine(&cond_span) };
+
+				Some(ast::Expression::Conditional {
+					span,
+					test: Box::new(test),
+					consequent: Box::new(consequent),
+					alternate: alternate.map(Box::new),
+				})
+			},
+		);
+
+		// Unwrap is safe as there is at least one clause, a `cond` with only an
+		// `else` clause is just its body
+		Ok(conditional.unwrap())
+	}
+
+	/// Parse the body of a `cond` clause up to and including its closing `)`,
+	/// wrapping it in a sequence if it has several expressions
+	fn parse_cond_body(&mut self, clause_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let mut body = vec![self.parse_expression()?];
+		while self.peek()?.t != TokenType::RightParen {
+			body.push(self.parse_expression()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+
+		if body.len() == 1 {
+			return Ok(body.pop().unwrap());
+		}
+
+		Ok(ast::Expression::Sequence { span: clause_span.combine(&right_paren.span), seq: body })
+	}
+
 	/// Parse an inclusion of the form `(include <string>+)`
 	///
 	/// `(` and `include` already consumed
-	fn parse_inclusion(&mut self, initial_span: SourceSpan) -> Result<ast::Expression<'s>, Error> {
+	fn parse_inclusion(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
 		let first_file_token = self.expect(TokenType::String(""))?;
 		let TokenType::String(first_file) = first_file_token.t else { unreachable!() };
 		let mut inclusion_span = initial_span.combine(&first_file_token.span);
@@ -448,3 +882,74 @@ impl<'s> Parser<'s> {
 		Ok(ast::Expression::Inclusion { span: inclusion_span, files })
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ast::SyntacticEq;
+	use crate::parse_str;
+
+	fn code(error: &Error) -> String { error.code().map(|c| c.to_string()).unwrap_or_default() }
+
+	#[test]
+	fn parse_str_lexes_and_parses_a_whole_file() {
+		let source = "(let x 1)\n(+ x 2)\n";
+		let program = parse_str(source).unwrap();
+
+		let expected = Parser::new(source, Lexer::new(source).into()).parse().unwrap();
+
+		assert_eq!(program.0.len(), 2);
+		assert!(program.syntactic_eq(&expected));
+	}
+
+	#[test]
+	fn parse_str_reports_lex_and_parse_errors() {
+		let lex_error = parse_str("(print \"oops)").unwrap_err();
+		let parse_error = parse_str("(+ 1 2\n").unwrap_err();
+
+		assert_eq!(code(&lex_error), "ream::lex_error::unterminated_string");
+		assert_eq!(code(&parse_error), "ream::parse_error::unclosed_paren");
+	}
+
+	#[test]
+	fn rewinding_undoes_speculative_parsing() {
+		let source = "(f (g 1 2)\n";
+		let mut parser = Parser::new(source, Lexer::new(source).into());
+
+		parser.next().unwrap();
+		let checkpoint = parser.checkpoint();
+
+		// Consumes `f (g 1 2)`, leaving the outer `(` open
+		parser.parse_expression().unwrap();
+		parser.parse_expression().unwrap();
+		assert_eq!(parser.open_parens.len(), 1);
+		assert!(parser.peek().is_ok_and(|t| t.t == TokenType::EndOfFile));
+
+		parser.rewind(checkpoint);
+		assert_eq!(parser.prev_span.range(), 0..1);
+		assert_eq!(parser.open_parens.len(), 1);
+		assert_eq!(parser.peek().unwrap().t, TokenType::Identifier("f"));
+
+		// Parsing again from the checkpoint sees the same tokens
+		let Ok(ast::Expression::Identifier(f)) = parser.parse_expression() else { unreachable!() };
+		assert_eq!(f.id, "f");
+		parser.parse_expression().unwrap();
+
+		let error = parser.expect(TokenType::RightParen).unwrap_err();
+		assert_eq!(code(&error), "ream::parse_error::unclosed_paren");
+	}
+
+	#[test]
+	fn rewinding_forgets_parentheses_opened_since() {
+		let source = "(f x)\n";
+		let mut parser = Parser::new(source, Lexer::new(source).into());
+
+		let checkpoint = parser.checkpoint();
+		parser.next().unwrap();
+		assert_eq!(parser.open_parens.len(), 1);
+
+		parser.rewind(checkpoint);
+		assert!(parser.open_parens.is_empty());
+		assert_eq!(parser.parse().unwrap().0.len(), 1);
+	}
+}
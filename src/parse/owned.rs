@@ -0,0 +1,80 @@
+//! Programs that own the source code they were parsed from
+
+use miette::Error;
+use self_cell::self_cell;
+use static_assertions::assert_impl_all;
+
+use crate::{ast, parse_file, EvalError, FileId, Value};
+
+self_cell!(
+	/// A parsed program stored together with its source code
+	///
+	/// Unlike [`ast::Program`], which borrows the source it was parsed from,
+	/// an owned program can outlive the buffer its source was read into, so
+	/// it can be cached, stored or sent to another thread
+	pub struct OwnedProgram {
+		owner: Box<str>,
+
+		#[covariant]
+		dependent: Program,
+	}
+
+	impl {Debug}
+);
+
+assert_impl_all!(OwnedProgram: Send, Sync);
+
+// `self_cell!` only accepts a plain identifier as the dependent type
+type Program<'s> = ast::Program<'s>;
+
+impl OwnedProgram {
+	/// Lex and parse an entire source file, taking ownership of its source
+	pub fn parse(source: impl Into<String>) -> Result<Self, Error> {
+		Self::parse_file(source, FileId::default())
+	}
+
+	/// Lex and parse an entire source file, taking ownership of its source
+	/// and pointing every span into the given file of a
+	/// [`SourceMap`](crate::SourceMap)
+	pub fn parse_file(source: impl Into<String>, file: FileId) -> Result<Self, Error> {
+		Self::try_new(source.into().into_boxed_str(), |source| parse_file(source, file))
+	}
+
+	/// Get the source code the program was parsed from
+	pub fn source(&self) -> &str { self.borrow_owner() }
+
+	/// Get the parsed program, borrowing from the source it owns
+	pub fn program(&self) -> &ast::Program<'_> { self.borrow_dependent() }
+
+	/// Run a copy of the program with every capability available, returning
+	/// the value of its last expression
+	pub fn run(&self) -> Result<Value, EvalError> { self.program().clone().run() }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+
+	use super::*;
+
+	#[test]
+	fn outlives_the_buffer_it_was_read_from() {
+		let program = {
+			let buffer = String::from("(let x 20) (+ x 22)");
+			OwnedProgram::parse(buffer.as_str()).unwrap()
+		};
+
+		assert_eq!(program.source(), "(let x 20) (+ x 22)");
+		assert_eq!(program.program().0.len(), 2);
+		assert_eq!(program.run().unwrap(), Value::Integer(42));
+	}
+
+	#[test]
+	fn can_be_sent_to_another_thread() {
+		let program = OwnedProgram::parse("(+ 1 2)").unwrap();
+
+		let program = thread::spawn(move || program).join().unwrap();
+
+		assert_eq!(program.run().unwrap(), Value::Integer(3));
+	}
+}
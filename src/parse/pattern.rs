@@ -0,0 +1,109 @@
+use miette::Error;
+
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
+
+impl<'s> Parser<'s> {
+	/// Parse a pattern of the form `<identifier>`, `<literal>`, `<atom>`,
+	/// `(<atom> <pattern>)`, `(cons <pattern> <pattern>)` or `(<pattern>*)`
+	/// where the identifier `_` matches anything without binding it, and
+	/// literal is a boolean, number, character or string
+	pub(super) fn parse_pattern(&mut self) -> Result<ast::Pattern<'s>, Error> {
+		let token = self.next()?;
+
+		match token.t {
+			TokenType::Identifier(_) => Ok(token.into()),
+			TokenType::Boolean(_)
+			| TokenType::Integer(_)
+			| TokenType::Rational(_)
+			| TokenType::Float(_)
+			| TokenType::Character(_)
+			| TokenType::String(_) => Ok(ast::Pattern::Literal(token.into())),
+			TokenType::Atom(a) => {
+				Ok(ast::Pattern::Variant { span: token.span, variant: &a[1..], payload: None })
+			},
+			TokenType::LeftParen => {
+				if let TokenType::Atom(a) = self.peek()?.t {
+					self.next().unwrap();
+
+					let payload = match self.peek()?.t {
+						TokenType::RightParen => None,
+						_ => Some(Box::new(self.parse_pattern()?)),
+					};
+
+					let right_paren = self.expect(TokenType::RightParen)?;
+					let span = token.span.combine(&right_paren.span);
+
+					return Ok(ast::Pattern::Variant { span, variant: &a[1..], payload });
+				}
+
+				if self.peek()?.t == TokenType::Identifier("cons") {
+					self.next().unwrap();
+
+					let head = Box::new(self.parse_pattern()?);
+					let tail = Box::new(self.parse_pattern()?);
+
+					let right_paren = self.expect(TokenType::RightParen)?;
+					let span = token.span.combine(&right_paren.span);
+
+					return Ok(ast::Pattern::Cons { span, head, tail });
+				}
+
+				let mut span = token.span;
+				let mut patterns = vec![];
+
+				while self.peek()?.t != TokenType::RightParen {
+					let pattern = self.parse_pattern()?;
+					span = span.combine(&self.prev_span);
+					patterns.push(pattern);
+				}
+
+				// Unwrap is safe as RightParen is selected for in the loop
+				let right_paren = self.expect(TokenType::RightParen).unwrap();
+				span = span.combine(&right_paren.span);
+
+				Ok(ast::Pattern::List { span, patterns })
+			},
+			tt => Err(ParseError::InvalidPattern { loc: token.span, found: tt.to_string() }.into()),
+		}
+	}
+
+	/// Parse a match expression of the form `(match <value> <clause>*)`
+	/// where value is `<expression>`
+	/// and clause is `(<pattern> [:when <expression>] <expression>+)`
+	///
+	/// `(` and `match` already consumed
+	pub(super) fn parse_match(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let value = Box::new(self.parse_expression()?);
+
+		let mut clauses = vec![];
+		while self.peek()?.t != TokenType::RightParen {
+			let left_paren = self.expect(TokenType::LeftParen)?;
+			let pattern = self.parse_pattern()?;
+
+			let guard = match self.peek()?.t {
+				TokenType::Atom(":when") => {
+					self.next().unwrap();
+					Some(self.parse_expression()?)
+				},
+				_ => None,
+			};
+
+			let mut body = vec![self.parse_expression()?];
+			while self.peek()?.t != TokenType::RightParen {
+				body.push(self.parse_expression()?);
+			}
+
+			// Unwrap is safe as RightParen is selected for in the loop
+			let right_paren = self.expect(TokenType::RightParen).unwrap();
+			let span = left_paren.span.combine(&right_paren.span);
+
+			clauses.push(ast::MatchClause { span, pattern, guard, body });
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::Match { span, value, clauses })
+	}
+}
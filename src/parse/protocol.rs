@@ -0,0 +1,100 @@
+use miette::Error;
+
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
+
+impl<'s> Parser<'s> {
+	/// Parse a protocol definition of the form
+	/// `(defprotocol <target> <method>+)`
+	/// where target is `<identifier>`
+	/// and method is `(<identifier> <identifier>+)`
+	///
+	/// `(` and `defprotocol` already consumed
+	pub(super) fn parse_protocol_definition(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let target = self.expect(TokenType::Identifier(""))?;
+
+		let mut methods = vec![self.parse_protocol_method()?];
+		while self.peek()?.t != TokenType::RightParen {
+			methods.push(self.parse_protocol_method()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::ProtocolDefinition { span, target: target.into(), methods })
+	}
+
+	/// Parse the signature of a protocol method of the form
+	/// `(<identifier> <identifier>+)`
+	///
+	/// At least one formal is required as methods are dispatched on the type
+	/// of their first argument
+	fn parse_protocol_method(&mut self) -> Result<ast::ProtocolMethod<'s>, Error> {
+		let left_paren = self.expect(TokenType::LeftParen)?;
+		let target = self.expect(TokenType::Identifier(""))?;
+
+		let mut formals = vec![self.expect(TokenType::Identifier(""))?.into()];
+		while self.peek()?.t != TokenType::RightParen {
+			formals.push(self.expect(TokenType::Identifier(""))?.into());
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = left_paren.span.combine(&right_paren.span);
+
+		Ok(ast::ProtocolMethod { span, target: target.into(), formals })
+	}
+
+	/// Parse a protocol implementation of the form
+	/// `(impl <protocol> <target> <expression>+)`
+	/// where protocol is `<identifier>`
+	/// and target is the name of a type
+	///
+	/// The expressions implement the methods of the protocol in the order
+	/// they were defined in
+	///
+	/// `(` and `impl` already consumed
+	pub(super) fn parse_protocol_implementation(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let protocol = self.expect(TokenType::Identifier(""))?;
+
+		let target_token = self.next()?;
+		let target = match target_token.t {
+			TokenType::Identifier(_) => target_token.into(),
+			tt => {
+				match tt.type_keyword_name() {
+					Some(id) => ast::Identifier { span: target_token.span, id },
+					None => {
+						return Err(ParseError::UnexpectedToken {
+							loc:      target_token.span,
+							found:    tt.to_string(),
+							expected: vec!["Identifier".to_string()],
+						}
+						.into());
+					},
+				}
+			},
+		};
+
+		let mut methods = vec![self.parse_expression()?];
+		while self.peek()?.t != TokenType::RightParen {
+			methods.push(self.parse_expression()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::ProtocolImplementation {
+			span,
+			protocol: protocol.into(),
+			target,
+			methods,
+		})
+	}
+}
@@ -1,16 +1,22 @@
-use miette::{Error, SourceSpan};
+use miette::Error;
 
-use crate::{ast, Combine, ParseError, Parser, TokenType};
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
 
 impl<'s> Parser<'s> {
-	/// Parse a shorthand quote of the form '`<datum>'
+	/// Parse a quasiquote of the form '`<datum>', in which `,<expression>`
+	/// and `,@<expression>` can be used to insert values into the datum
 	///
 	/// '`' already consumed
 	pub(super) fn parse_shorthand_quote(
 		&mut self,
-		initial_span: SourceSpan,
+		initial_span: Span,
 	) -> Result<ast::Literal<'s>, Error> {
-		let (datum, datum_span) = self.parse_datum()?;
+		let (datum, datum_span) = self.parse_datum(true)?;
+
+		// There is no surrounding list to splice the values into
+		if let ast::Datum::UnquoteSplicing { span, .. } = datum {
+			return Err(ParseError::InvalidDatum { loc: span, found: ",@".to_string() }.into());
+		}
 
 		let quote_span = initial_span.combine(&datum_span);
 
@@ -20,11 +26,8 @@ impl<'s> Parser<'s> {
 	/// Parse a quote of the form `(quote <datum>)`
 	///
 	/// `(` and `quote` already consumed
-	pub(super) fn parse_quote(
-		&mut self,
-		initial_span: SourceSpan,
-	) -> Result<ast::Literal<'s>, Error> {
-		let (datum, datum_span) = self.parse_datum()?;
+	pub(super) fn parse_quote(&mut self, initial_span: Span) -> Result<ast::Literal<'s>, Error> {
+		let (datum, datum_span) = self.parse_datum(false)?;
 
 		let right_paren = self.expect(TokenType::RightParen)?;
 		let quote_span = initial_span.combine(&datum_span).combine(&right_paren.span);
@@ -33,7 +36,9 @@ impl<'s> Parser<'s> {
 	}
 
 	/// Parse a datum and return it alongside its span
-	fn parse_datum(&mut self) -> Result<(ast::Datum<'s>, SourceSpan), Error> {
+	///
+	/// Unquotes are only allowed if the datum is quasiquoted
+	pub(super) fn parse_datum(&mut self, quasi: bool) -> Result<(ast::Datum<'s>, Span), Error> {
 		let token = self.next()?;
 
 		let span = token.span;
@@ -42,30 +47,132 @@ impl<'s> Parser<'s> {
 			TokenType::Identifier(_) => Ok((token.into(), token.span)),
 			TokenType::Boolean(_) => Ok((token.into(), token.span)),
 			TokenType::Integer(_) => Ok((token.into(), token.span)),
+			TokenType::Rational(_) => Ok((token.into(), token.span)),
 			TokenType::Float(_) => Ok((token.into(), token.span)),
 			TokenType::Character(_) => Ok((token.into(), token.span)),
 			TokenType::String(_) => Ok((token.into(), token.span)),
 			TokenType::Atom(_) => Ok((token.into(), token.span)),
 
+			// Quoted code is just data, so keywords are ordinary identifiers
+			// in here
+			tt if tt.is_keyword() => {
+				let id = self.source_text(span);
+
+				Ok((ast::Datum::Identifier { span, id }, span))
+			},
+
+			TokenType::Comma if quasi => {
+				let e = self.parse_expression()?;
+				let unquote_span = span.combine(&self.prev_span);
+
+				Ok((ast::Datum::Unquote { span: unquote_span, e: Box::new(e) }, unquote_span))
+			},
+			TokenType::CommaAt if quasi => {
+				let e = self.parse_expression()?;
+				let unquote_span = span.combine(&self.prev_span);
+
+				Ok((
+					ast::Datum::UnquoteSplicing { span: unquote_span, e: Box::new(e) },
+					unquote_span,
+				))
+			},
+
 			TokenType::LeftParen => {
-				let (data, data_span) = self.parse_datum_list(span)?;
+				let (data, data_span) = self.parse_datum_list(span, quasi)?;
 
 				let list: ast::ConsList = data.into();
 
 				Ok((ast::Datum::List { span: data_span, l: list }, data_span))
 			},
+			TokenType::HashParen => {
+				let mut data = vec![];
+				let mut data_span = span;
+
+				while self.peek()?.t != TokenType::RightParen {
+					let (datum, datum_span) = self.parse_datum(quasi)?;
+					data.push(datum);
+					data_span = data_span.combine(&datum_span);
+				}
+
+				// Unwrap is safe as RightParen is selected for in the loop
+				let right_paren = self.expect(TokenType::RightParen).unwrap();
+				data_span = data_span.combine(&right_paren.span);
+
+				Ok((ast::Datum::Vector { span: data_span, data }, data_span))
+			},
 
 			tt => Err(ParseError::InvalidDatum { loc: token.span, found: tt.to_string() }.into()),
 		}
 	}
 
+	/// Parse a case expression of the form `(case <value> <clause>* [<else>])`
+	/// where value is `<expression>`
+	/// and clause is `((<datum>*) <expression>+)`
+	/// and else is `(else <expression>+)`
+	///
+	/// The data are quoted, so they can't be unquoted into
+	///
+	/// `(` and `case` already consumed
+	pub(super) fn parse_case(&mut self, initial_span: Span) -> Result<ast::Expression<'s>, Error> {
+		let value = Box::new(self.parse_expression()?);
+
+		let mut clauses = vec![];
+		let mut default = None;
+		while self.peek()?.t != TokenType::RightParen {
+			let left_paren = self.expect(TokenType::LeftParen)?;
+
+			if self.peek()?.t == TokenType::Identifier("else") {
+				self.next().unwrap();
+				default = Some(self.parse_clause_body()?.0);
+
+				break;
+			}
+
+			self.expect(TokenType::LeftParen)?;
+			let mut data = vec![];
+			while self.peek()?.t != TokenType::RightParen {
+				data.push(self.parse_datum(false)?.0);
+			}
+
+			// Unwrap is safe as RightParen is selected for in the loop
+			self.expect(TokenType::RightParen).unwrap();
+
+			let (body, right_paren_span) = self.parse_clause_body()?;
+			let span = left_paren.span.combine(&right_paren_span);
+
+			clauses.push(ast::CaseClause { span, data, body });
+		}
+
+		// Only the `else` clause can be followed by something other than a
+		// clause, which has to be the end of the `case`
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::Case { span, value, clauses, default })
+	}
+
+	/// Parse the body of a clause up to and including its closing `)`,
+	/// returning it alongside the span of that `)`
+	fn parse_clause_body(&mut self) -> Result<(Vec<ast::Expression<'s>>, Span), Error> {
+		let mut body = vec![self.parse_expression()?];
+		while self.peek()?.t != TokenType::RightParen {
+			body.push(self.parse_expression()?);
+		}
+
+		// Unwrap is safe as RightParen is selected for in the loop
+		let right_paren = self.expect(TokenType::RightParen).unwrap();
+
+		Ok((body, right_paren.span))
+	}
+
 	/// Parse a datum list of the form `(<datum>*)` or `(<datum> . <list>)`
 	///
 	/// `(` already consumed
 	fn parse_datum_list(
 		&mut self,
-		initial_span: SourceSpan,
-	) -> Result<(Vec<ast::Datum<'s>>, SourceSpan), Error> {
+		initial_span: Span,
+		quasi: bool,
+	) -> Result<(Vec<ast::Datum<'s>>, Span), Error> {
 		let mut data = vec![];
 		let mut span = initial_span;
 
@@ -80,7 +187,7 @@ impl<'s> Parser<'s> {
 		}
 
 		loop {
-			let (datum, next_span) = self.parse_datum()?;
+			let (datum, next_span) = self.parse_datum(quasi)?;
 			span = span.combine(&next_span);
 			data.push(datum);
 
@@ -100,7 +207,7 @@ impl<'s> Parser<'s> {
 					let left_paren = self.expect(TokenType::LeftParen)?;
 					// span.combine(&left_paren.span);
 
-					let (rec_data, rec_span) = self.parse_datum_list(left_paren.span)?;
+					let (rec_data, rec_span) = self.parse_datum_list(left_paren.span, quasi)?;
 
 					data.extend(rec_data);
 					span.combine(&rec_span);
@@ -0,0 +1,247 @@
+use miette::Error;
+
+use crate::{ast, Combine, ParseError, Parser, Span, TokenType};
+
+impl<'s> Parser<'s> {
+	/// Parse a type alias of the form `(type-alias <target> <typespec>)`
+	/// where target is `<identifier>`
+	///
+	/// `(` and `type-alias` already consumed
+	pub(super) fn parse_type_alias(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let target = self.expect(TokenType::Identifier(""))?;
+
+		let spec = self.parse_typespec()?;
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::TypeAlias { span, target: target.into(), spec })
+	}
+
+	/// Parse an algebraic type definition of the form
+	/// `(define-type <target> <typespec>)`
+	/// where target is `<identifier>`
+	/// and typespec is a `Sum` or `Product` type
+	///
+	/// `(` and `define-type` already consumed
+	pub(super) fn parse_algebraic_type_definition(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let target = self.expect(TokenType::Identifier(""))?;
+
+		let spec_span = self.peek()?.span;
+		let spec = self.parse_typespec()?;
+
+		let found = match &spec {
+			ast::TypeSpec::Constructor(
+				ast::TypeConstructor::Sum { .. } | ast::TypeConstructor::Product { .. },
+			) => None,
+			ast::TypeSpec::Identifier(id) => Some(id.id),
+			ast::TypeSpec::Constructor(ast::TypeConstructor::Bottom { .. }) => Some("Bottom"),
+			ast::TypeSpec::Constructor(ast::TypeConstructor::Tuple { .. }) => Some("Tuple"),
+			ast::TypeSpec::Constructor(ast::TypeConstructor::List { .. }) => Some("List"),
+			ast::TypeSpec::Constructor(ast::TypeConstructor::Vector { .. }) => Some("Vector"),
+			ast::TypeSpec::Constructor(ast::TypeConstructor::Function { .. }) => Some("Function"),
+		};
+
+		if let Some(found) = found {
+			return Err(ParseError::InvalidAlgebraicType {
+				loc:   spec_span.combine(&self.prev_span),
+				found: found.to_string(),
+			}
+			.into());
+		}
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::AlgebraicTypeDefintion { span, target: target.into(), spec })
+	}
+
+	/// Parse a type assertion of the form `(the <typespec> <value>)`
+	/// where value is `<expression>`
+	///
+	/// `(` and `the` already consumed
+	pub(super) fn parse_type_assertion(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::Expression<'s>, Error> {
+		let spec = self.parse_typespec()?;
+		let value = Box::new(self.parse_expression()?);
+
+		let right_paren = self.expect(TokenType::RightParen)?;
+		let span = initial_span.combine(&right_paren.span);
+
+		Ok(ast::Expression::TypeAssertion { span, spec, value })
+	}
+
+	/// Parse a type specification of the form `<identifier>` or
+	/// `(<type constructor>)`
+	pub(super) fn parse_typespec(&mut self) -> Result<ast::TypeSpec<'s>, Error> {
+		let token = self.next()?;
+
+		match token.t {
+			TokenType::Identifier(_) => Ok(ast::Identifier::from(token).into()),
+			TokenType::LeftParen => Ok(self.parse_type_constructor(token.span)?.into()),
+			tt => {
+				Err(ParseError::InvalidTypeSpec { loc: token.span, found: tt.to_string() }.into())
+			},
+		}
+	}
+
+	/// Parse a type constructor of the form `Bottom`, `Tuple <typespec>*`,
+	/// `List <typespec>`, `Function <typespecs> <typespecs>`,
+	/// `Sum <named typespec>+` or `Product <named typespec>+`
+	///
+	/// `(` already consumed
+	fn parse_type_constructor(
+		&mut self,
+		initial_span: Span,
+	) -> Result<ast::TypeConstructor<'s>, Error> {
+		let token = self.next()?;
+
+		match token.t {
+			TokenType::TypeKwBottom => {
+				Ok(ast::TypeConstructor::Bottom {
+					span: self.finish_type_constructor(initial_span)?,
+				})
+			},
+			TokenType::TypeKwTuple => {
+				let mut fields = vec![];
+				while self.peek()?.t != TokenType::RightParen {
+					fields.push(self.parse_typespec()?);
+				}
+
+				Ok(ast::TypeConstructor::Tuple {
+					span: self.finish_type_constructor(initial_span)?,
+					fields,
+				})
+			},
+			TokenType::TypeKwList => {
+				let t = Box::new(self.parse_typespec()?);
+
+				Ok(ast::TypeConstructor::List {
+					span: self.finish_type_constructor(initial_span)?,
+					t,
+				})
+			},
+			TokenType::TypeKwFunction => {
+				let arguments = self.parse_typespec_group()?;
+				let values = self.parse_typespec_group()?;
+
+				Ok(ast::TypeConstructor::Function {
+					span: self.finish_type_constructor(initial_span)?,
+					arguments,
+					values,
+				})
+			},
+			TokenType::TypeKwSum => {
+				let fields = self.parse_named_typespecs()?;
+
+				Ok(ast::TypeConstructor::Sum {
+					span: self.finish_type_constructor(initial_span)?,
+					fields,
+				})
+			},
+			TokenType::TypeKwProduct => {
+				let fields = self.parse_named_typespecs()?;
+
+				Ok(ast::TypeConstructor::Product {
+					span: self.finish_type_constructor(initial_span)?,
+					fields,
+				})
+			},
+			tt => {
+				Err(ParseError::InvalidTypeSpec { loc: token.span, found: tt.to_string() }.into())
+			},
+		}
+	}
+
+	/// Consume the `)` closing a type constructor and return the span of the
+	/// entire constructor
+	fn finish_type_constructor(&mut self, initial_span: Span) -> Result<Span, Error> {
+		let right_paren = self.expect(TokenType::RightParen)?;
+
+		Ok(initial_span.combine(&right_paren.span))
+	}
+
+	/// Parse either a single type specification or a parenthesized group of
+	/// them, as used for the arguments and values of a `Function` type
+	fn parse_typespec_group(&mut self) -> Result<Vec<ast::TypeSpec<'s>>, Error> {
+		if self.peek()?.t != TokenType::LeftParen {
+			return Ok(vec![self.parse_typespec()?]);
+		}
+
+		let left_paren = self.next()?;
+
+		match self.peek()?.t {
+			TokenType::TypeKwBottom
+			| TokenType::TypeKwTuple
+			| TokenType::TypeKwList
+			| TokenType::TypeKwFunction
+			| TokenType::TypeKwSum
+			| TokenType::TypeKwProduct => {
+				Ok(vec![self.parse_type_constructor(left_paren.span)?.into()])
+			},
+			_ => {
+				let mut specs = vec![self.parse_typespec()?];
+				while self.peek()?.t != TokenType::RightParen {
+					specs.push(self.parse_typespec()?);
+				}
+
+				// Unwrap is safe as RightParen is selected for in the loop
+				self.expect(TokenType::RightParen).unwrap();
+
+				Ok(specs)
+			},
+		}
+	}
+
+	/// Parse named type specifications of the form `<atom>` or
+	/// `(<atom> <typespec>?)` until a `)` is found
+	fn parse_named_typespecs(&mut self) -> Result<Vec<ast::NamedTypeSpec<'s>>, Error> {
+		let mut fields = vec![];
+
+		while self.peek()?.t != TokenType::RightParen {
+			let token = self.next()?;
+
+			let field = match token.t {
+				TokenType::Atom(_) => {
+					ast::NamedTypeSpec { span: token.span, name: token.into(), spec: None }
+				},
+				TokenType::LeftParen => {
+					let name = self.expect(TokenType::Atom(""))?;
+
+					let spec = match self.peek()?.t {
+						TokenType::RightParen => None,
+						_ => Some(self.parse_typespec()?),
+					};
+
+					let right_paren = self.expect(TokenType::RightParen)?;
+
+					ast::NamedTypeSpec {
+						span: token.span.combine(&right_paren.span),
+						name: name.into(),
+						spec,
+					}
+				},
+				tt => {
+					return Err(ParseError::UnexpectedToken {
+						loc:      token.span,
+						found:    tt.to_string(),
+						expected: vec!["Atom".to_string(), "(".to_string()],
+					}
+					.into());
+				},
+			};
+
+			fields.push(field);
+		}
+
+		Ok(fields)
+	}
+}
@@ -0,0 +1,38 @@
+//! Rendering of diagnostics
+
+use miette::{GraphicalTheme, InstallError, MietteHandlerOpts};
+
+/// How diagnostics are rendered when a [`miette::Report`] is displayed
+/// using `{:?}`
+#[derive(Clone, Debug, Default)]
+pub enum DiagnosticStyle {
+	/// Colors and unicode box drawing, as far as the terminal supports them
+	#[default]
+	Fancy,
+	/// ASCII only and no colors or links, for CI logs and piped output
+	Plain,
+	/// A custom theme, used regardless of what the terminal supports
+	Theme(Box<GraphicalTheme>),
+}
+
+/// Render every diagnostic in the given [`DiagnosticStyle`]
+///
+/// This installs a global [`miette`] hook, so it can only be done once and
+/// has to happen before the first diagnostic is created
+pub fn set_diagnostic_style(style: DiagnosticStyle) -> Result<(), InstallError> {
+	let options = match style {
+		DiagnosticStyle::Fancy => MietteHandlerOpts::new(),
+		DiagnosticStyle::Plain => {
+			MietteHandlerOpts::new()
+				.color(false)
+				.unicode(false)
+				.terminal_links(false)
+				.force_graphical(true)
+		},
+		DiagnosticStyle::Theme(theme) => {
+			MietteHandlerOpts::new().graphical_theme(*theme).force_graphical(true)
+		},
+	};
+
+	miette::set_hook(Box::new(move |_| Box::new(options.clone().build())))
+}
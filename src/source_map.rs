@@ -0,0 +1,104 @@
+//! Registry of every source file a program was loaded from
+
+use std::sync::Arc;
+
+use miette::{MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents};
+use static_assertions::assert_impl_all;
+
+use crate::{FileId, Span};
+
+/// The source files of a program, each identified by a [`FileId`]
+///
+/// Used as the [`SourceCode`] of diagnostics, every snippet is read from and
+/// named after the file its [`Span`] points into
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+	files: Vec<SourceFile>,
+}
+
+assert_impl_all!(SourceMap: Send, Sync);
+
+/// A single file in a [`SourceMap`]
+#[derive(Clone, Debug)]
+struct SourceFile {
+	name:   Arc<str>,
+	source: Arc<str>,
+}
+
+impl SourceMap {
+	/// Create a new, empty [`SourceMap`]
+	pub fn new() -> Self { Self::default() }
+
+	/// Add a file, returning the id its spans should be created with
+	///
+	/// The first file gets the default [`FileId`], so spans of a program
+	/// parsed on its own already point into it
+	pub fn add(&mut self, name: impl Into<Arc<str>>, source: impl Into<Arc<str>>) -> FileId {
+		// Unwrap is safe as a map can't hold more files than there are file
+		// ids before running out of memory
+		let id = FileId::new(u32::try_from(self.files.len()).unwrap());
+		self.files.push(SourceFile { name: name.into(), source: source.into() });
+
+		id
+	}
+
+	/// Get the name of a file
+	pub fn name(&self, file: FileId) -> Option<&str> {
+		self.files.get(file.index()).map(|file| &*file.name)
+	}
+
+	/// Get the source of a file
+	pub fn source(&self, file: FileId) -> Option<&str> {
+		self.files.get(file.index()).map(|file| &*file.source)
+	}
+}
+
+impl SourceCode for SourceMap {
+	fn read_span<'a>(
+		&'a self,
+		span: &SourceSpan,
+		context_lines_before: usize,
+		context_lines_after: usize,
+	) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+		let span = Span::from(*span);
+		let file = self.files.get(span.file().index()).ok_or(MietteError::OutOfBounds)?;
+
+		let local = SourceSpan::from((span.offset(), span.len()));
+		let contents = file.source.read_span(&local, context_lines_before, context_lines_after)?;
+
+		let start = contents.span().offset();
+		let global = Span::new(span.file(), start..start + contents.span().len());
+
+		Ok(Box::new(MietteSpanContents::new_named(
+			file.name.to_string(),
+			contents.data(),
+			global.into(),
+			contents.line(),
+			contents.column(),
+			contents.line_count(),
+		)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn spans_are_read_from_their_own_file() {
+		let mut map = SourceMap::new();
+		let main = map.add("main.rm", "(include \"lib.rm\")\n");
+		let lib = map.add("lib.rm", "(fn f () x)\n");
+		assert_eq!(main, FileId::default());
+
+		let span = SourceSpan::from(Span::new(lib, 9..10));
+		let contents = map.read_span(&span, 0, 0).unwrap();
+		assert_eq!(contents.name(), Some("lib.rm"));
+		assert_eq!(contents.data(), b"x");
+		assert_eq!(contents.span(), &span);
+		assert_eq!((contents.line(), contents.column()), (0, 9));
+
+		assert_eq!(map.name(main), Some("main.rm"));
+		assert!(map.read_span(&Span::new(FileId::new(2), 0..1).into(), 0, 0).is_err());
+	}
+}
@@ -0,0 +1,89 @@
+//! Locations in the source files of a program
+
+use std::ops::Range;
+
+use miette::SourceSpan;
+
+/// The number of bits of a [`SourceSpan`] offset used for the position in a
+/// file, the bits above them hold its [`FileId`]
+const FILE_SHIFT: u32 = usize::BITS / 2;
+
+/// Identifies a file in a [`SourceMap`](crate::SourceMap)
+///
+/// The default id refers to the first file of a map, which is also the file
+/// spans of sources parsed on their own belong to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+impl FileId {
+	/// Create the id of the file at the given index in its
+	/// [`SourceMap`](crate::SourceMap)
+	pub const fn new(index: u32) -> Self { Self(index) }
+
+	/// Get the index of this file in its [`SourceMap`](crate::SourceMap)
+	pub fn index(self) -> usize { self.0 as usize }
+}
+
+/// A range of bytes in a single source file
+///
+/// Converts to and from a [`SourceSpan`] with the file stored in the upper
+/// half of its offset, so diagnostics can be rendered against a
+/// [`SourceMap`](crate::SourceMap) holding every file of a program
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+	file:   FileId,
+	offset: usize,
+	len:    usize,
+}
+
+impl Span {
+	/// Create a new [`Span`] covering the given range of a file
+	pub fn new(file: FileId, range: Range<usize>) -> Self {
+		Self { file, offset: range.start, len: range.len() }
+	}
+
+	/// Get the file this span points into
+	pub fn file(&self) -> FileId { self.file }
+
+	/// Get the offset of the first byte of this span in its file
+	pub fn offset(&self) -> usize { self.offset }
+
+	/// Get the length of this span in bytes
+	pub fn len(&self) -> usize { self.len }
+
+	/// Check if this span is empty
+	pub fn is_empty(&self) -> bool { self.len == 0 }
+
+	/// Get the range of bytes of its file covered by this span
+	pub fn range(&self) -> Range<usize> { self.offset..self.offset + self.len }
+}
+
+impl From<Span> for SourceSpan {
+	fn from(span: Span) -> Self {
+		((span.file.index() << FILE_SHIFT) | span.offset, span.len).into()
+	}
+}
+
+impl From<SourceSpan> for Span {
+	fn from(span: SourceSpan) -> Self {
+		let file = FileId((span.offset() >> FILE_SHIFT) as u32);
+		let offset = span.offset() & ((1 << FILE_SHIFT) - 1);
+
+		Self { file, offset, len: span.len() }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips_through_source_span() {
+		let span = Span::new(FileId::new(3), 7..12);
+		let source_span = SourceSpan::from(span);
+
+		assert_eq!(Span::from(source_span), span);
+		assert_eq!(source_span.len(), 5);
+		assert_eq!(Span::from(SourceSpan::from((4, 2))), Span::new(FileId::default(), 4..6));
+	}
+}
@@ -0,0 +1,241 @@
+//! Statistics about the structure of a program
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::ast::{Datum, Expression, Literal, Program};
+use crate::Span;
+
+/// Statistics about the structure of a [`Program`], as returned by
+/// [`Program::stats`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+	/// The amount of expressions of every kind, keyed by the name of the kind
+	pub expressions: BTreeMap<&'static str, usize>,
+	/// The deepest nesting of expressions, top-level expressions are at
+	/// depth 1
+	pub max_depth:   usize,
+	/// The amount of variables, functions, types, protocols, protocol
+	/// implementations and macros defined
+	pub definitions: usize,
+	/// The amount of anonymous functions
+	pub lambdas:     usize,
+	/// Every named function, in the order they are defined in
+	pub functions:   Vec<FunctionStats>,
+	/// The name of every included file, in the order they are included in
+	pub includes:    Vec<String>,
+}
+
+/// Statistics about a single function definition
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionStats {
+	/// The name of the function
+	pub name:      String,
+	/// The region of source code wrapped by the definition
+	pub span:      Span,
+	/// The deepest nesting of expressions in the body of the function,
+	/// relative to the definition itself
+	pub max_depth: usize,
+}
+
+impl<'s> Program<'s> {
+	/// Collect statistics about the structure of the program
+	pub fn stats(&self) -> Stats {
+		let mut stats = Stats::default();
+		for expr in &self.0 {
+			stats.max_depth = stats.max_depth.max(stats.visit(expr, 1));
+		}
+
+		stats
+	}
+}
+
+impl Stats {
+	/// Record an expression at the given depth and everything nested in it,
+	/// returning the deepest depth reached
+	fn visit(&mut self, expr: &Expression<'_>, depth: usize) -> usize {
+		*self.expressions.entry(kind(expr)).or_default() += 1;
+
+		let children: Vec<&Expression<'_>> = match expr {
+			Expression::TypeAlias { .. }
+			| Expression::AlgebraicTypeDefintion { .. }
+			| Expression::ProtocolDefinition { .. } => {
+				self.definitions += 1;
+				vec![]
+			},
+			Expression::Inclusion { files, .. } => {
+				self.includes.extend(files.iter().map(|file| file.to_string()));
+				vec![]
+			},
+			Expression::Annotation(_) | Expression::Identifier(_) => vec![],
+			Expression::Literal(Literal::Quotation { q, .. }) => unquoted(q),
+			Expression::Literal(_) => vec![],
+			Expression::VariableDefinition { value, .. }
+			| Expression::ConstantDefinition { value, .. } => {
+				self.definitions += 1;
+				vec![value]
+			},
+			Expression::FunctionDefinition { span, target, body, .. }
+			| Expression::FunctionClause { span, target, body, .. } => {
+				self.definitions += 1;
+
+				let index = self.functions.len();
+				self.functions.push(FunctionStats {
+					name:      target.id.to_string(),
+					span:      *span,
+					max_depth: 0,
+				});
+
+				let deepest = self.visit_all(body, depth + 1);
+				self.functions[index].max_depth = deepest - depth;
+
+				return deepest;
+			},
+			Expression::ClosureDefintion { body, .. } => {
+				self.lambdas += 1;
+				body.iter().collect()
+			},
+			Expression::Sequence { seq, .. } => seq.iter().collect(),
+			Expression::Let { bindings, body, .. } => {
+				bindings.iter().map(|b| &b.value).chain(body).collect()
+			},
+			Expression::Vector { elements, .. } => elements.iter().collect(),
+			Expression::ProcedureCall { operator, operands, .. } => {
+				std::iter::once(&**operator).chain(operands).collect()
+			},
+			Expression::Conditional { test, consequent, alternate, .. } => {
+				[&**test, &**consequent].into_iter().chain(alternate.as_deref()).collect()
+			},
+			Expression::Match { value, clauses, .. } => {
+				std::iter::once(&**value)
+					.chain(clauses.iter().flat_map(|c| c.guard.iter().chain(&c.body)))
+					.collect()
+			},
+			Expression::Case { value, clauses, default, .. } => {
+				std::iter::once(&**value)
+					.chain(clauses.iter().flat_map(|c| &c.body))
+					.chain(default.iter().flatten())
+					.collect()
+			},
+			Expression::ProtocolImplementation { methods, .. } => {
+				self.definitions += 1;
+				methods.iter().collect()
+			},
+			Expression::TypeAssertion { value, .. } | Expression::Time { value, .. } => vec![value],
+			Expression::MacroDefinition { body, .. } => {
+				self.definitions += 1;
+				vec![body]
+			},
+		};
+
+		self.visit_all(children, depth + 1).max(depth)
+	}
+
+	/// Record several expressions at the same depth, returning the deepest
+	/// depth reached by any of them
+	fn visit_all<'e, 's: 'e>(
+		&mut self,
+		exprs: impl IntoIterator<Item = &'e Expression<'s>>,
+		depth: usize,
+	) -> usize {
+		exprs.into_iter().map(|expr| self.visit(expr, depth)).max().unwrap_or(depth - 1)
+	}
+}
+
+/// Get the name of the kind of an expression
+fn kind(expr: &Expression<'_>) -> &'static str {
+	match expr {
+		Expression::TypeAlias { .. } => "type-alias",
+		Expression::AlgebraicTypeDefintion { .. } => "define-type",
+		Expression::Annotation(_) => "annotation",
+		Expression::Literal(_) => "literal",
+		Expression::Identifier(_) => "identifier",
+		Expression::VariableDefinition { .. } => "let",
+		Expression::ConstantDefinition { .. } => "const",
+		Expression::FunctionDefinition { .. } => "fn",
+		Expression::FunctionClause { .. } => "fn-clause",
+		Expression::ClosureDefintion { .. } => "lambda",
+		Expression::Sequence { .. } => "seq",
+		Expression::Let { .. } => "let-bindings",
+		Expression::Vector { .. } => "vector",
+		Expression::ProcedureCall { .. } => "call",
+		Expression::Conditional { .. } => "if",
+		Expression::Inclusion { .. } => "include",
+		Expression::Match { .. } => "match",
+		Expression::Case { .. } => "case",
+		Expression::ProtocolDefinition { .. } => "define-protocol",
+		Expression::ProtocolImplementation { .. } => "implement",
+		Expression::TypeAssertion { .. } => "the",
+		Expression::Time { .. } => "time",
+		Expression::MacroDefinition { .. } => "define-macro",
+	}
+}
+
+/// Get the expressions unquoted anywhere in a quasiquoted datum
+fn unquoted<'d, 's>(datum: &'d Datum<'s>) -> Vec<&'d Expression<'s>> {
+	match datum {
+		Datum::Unquote { e, .. } | Datum::UnquoteSplicing { e, .. } => vec![e],
+		Datum::List { l, .. } => l.iter().flat_map(unquoted).collect(),
+		Datum::Vector { data, .. } => data.iter().flat_map(unquoted).collect(),
+		_ => vec![],
+	}
+}
+
+impl fmt::Display for Stats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "expressions:")?;
+		for (kind, count) in &self.expressions {
+			writeln!(f, "  {kind}: {count}")?;
+		}
+
+		writeln!(f, "max depth: {}", self.max_depth)?;
+		writeln!(f, "definitions: {}", self.definitions)?;
+		write!(f, "lambdas: {}", self.lambdas)?;
+
+		for function in &self.functions {
+			write!(f, "\nfunction `{}`: max depth {}", function.name, function.max_depth)?;
+		}
+
+		for include in &self.includes {
+			write!(f, "\ninclude `{include}`")?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::parse_str;
+
+	#[test]
+	fn counts_expressions_and_definitions() {
+		let program = parse_str("(let x 1) (fn f (y) (+ x y)) (f (lambda (z) z))").unwrap();
+		let stats = program.stats();
+
+		assert_eq!(stats.expressions["let"], 1);
+		assert_eq!(stats.expressions["fn"], 1);
+		assert_eq!(stats.expressions["call"], 2);
+		assert_eq!(stats.expressions["identifier"], 5);
+		assert_eq!(stats.definitions, 2);
+		assert_eq!(stats.lambdas, 1);
+	}
+
+	#[test]
+	fn measures_nesting_depth() {
+		let program = parse_str("1 (fn f (x) (if x (g (h x)) 0))").unwrap();
+		let stats = program.stats();
+
+		assert_eq!(stats.max_depth, 5);
+		assert_eq!(stats.functions.len(), 1);
+		assert_eq!(stats.functions[0].name, "f");
+		assert_eq!(stats.functions[0].max_depth, 4);
+	}
+
+	#[test]
+	fn collects_nested_includes() {
+		let program = parse_str("(include \"a.rm\") (seq (include \"b.rm\" \"c.rm\") 1)").unwrap();
+
+		assert_eq!(program.stats().includes, ["a.rm", "b.rm", "c.rm"]);
+	}
+}
@@ -1,23 +1,24 @@
 use std::fmt;
-use std::sync::OnceLock;
 
-use miette::SourceSpan;
+use num_rational::Rational64;
+use static_assertions::assert_impl_all;
 
-/// Placeholder EndOfFile token that can be inserted by the parser
-pub static EOF_TOKEN: OnceLock<Token> = OnceLock::new();
+use crate::Span;
 
 /// A single source code token
 #[derive(Clone, Copy, Debug)]
 pub struct Token<'t> {
 	/// The region of source code wrapped by this token
-	pub span: SourceSpan,
+	pub span: Span,
 	/// The type of the token
 	pub t:    TokenType<'t>,
 }
 
+assert_impl_all!(Token<'static>: Send, Sync);
+
 impl<'t> Token<'t> {
 	/// Change the span of self
-	pub fn with_span(mut self, span: SourceSpan) -> Self {
+	pub fn with_span(mut self, span: Span) -> Self {
 		self.span = span;
 		self
 	}
@@ -36,24 +37,44 @@ pub enum TokenType<'t> {
 
 	KwQuote,
 	KwLet,
+	KwConst,
 	KwFn,
 	KwLambda,
 	KwSeq,
 	KwIf,
+	KwCond,
 	KwInclude,
+	KwMatch,
+	KwCase,
+	KwDefProtocol,
+	KwImpl,
+	KwTypeAlias,
+	KwDefineType,
+	KwDefineMacro,
+	KwThe,
+	KwTime,
 
 	Identifier(&'t str),
 	Boolean(bool),
-	Integer(u64),
+	Integer(i64),
+	Rational(Rational64),
 	Float(f64),
 	Character(char),
 	String(&'t str),
 	Atom(&'t str),
 
 	LeftParen,
+	/// The `#(` opening a vector
+	HashParen,
 	RightParen,
 	Period,
 	Backtick,
+	Comma,
+	CommaAt,
+
+	/// Source code the lexer couldn't make sense of, only produced in
+	/// recovery mode
+	Error(&'t str),
 
 	EndOfFile,
 }
@@ -69,28 +90,87 @@ impl<'t> fmt::Display for TokenType<'t> {
 			Self::TypeKwProduct => write!(f, "Product"),
 			Self::KwQuote => write!(f, "quote"),
 			Self::KwLet => write!(f, "let"),
+			Self::KwConst => write!(f, "const"),
 			Self::KwFn => write!(f, "fn"),
 			Self::KwLambda => write!(f, "lambda"),
 			Self::KwSeq => write!(f, "seq"),
 			Self::KwIf => write!(f, "if"),
+			Self::KwCond => write!(f, "cond"),
 			Self::KwInclude => write!(f, "include"),
+			Self::KwMatch => write!(f, "match"),
+			Self::KwCase => write!(f, "case"),
+			Self::KwDefProtocol => write!(f, "defprotocol"),
+			Self::KwImpl => write!(f, "impl"),
+			Self::KwTypeAlias => write!(f, "type-alias"),
+			Self::KwDefineType => write!(f, "define-type"),
+			Self::KwDefineMacro => write!(f, "define-macro"),
+			Self::KwThe => write!(f, "the"),
+			Self::KwTime => write!(f, "time"),
 			Self::Identifier(id) => write!(f, "{id}"),
 			Self::Boolean(b) => write!(f, "{b}"),
 			Self::Integer(i) => write!(f, "{i}"),
+			Self::Rational(r) => write!(f, "{r}"),
 			Self::Float(fl) => write!(f, "{fl}"),
 			Self::Character(c) => write!(f, "{c}"),
 			Self::String(s) => write!(f, "{s}"),
 			Self::Atom(a) => write!(f, "{a}"),
 			Self::LeftParen => write!(f, "("),
+			Self::HashParen => write!(f, "#("),
 			Self::RightParen => write!(f, ")"),
 			Self::Period => write!(f, "."),
 			Self::Backtick => write!(f, "`"),
+			Self::Comma => write!(f, ","),
+			Self::CommaAt => write!(f, ",@"),
+			Self::Error(e) => write!(f, "{e}"),
 			Self::EndOfFile => write!(f, "EOF"),
 		}
 	}
 }
 
 impl<'t> TokenType<'t> {
+	/// Get the name of the type this [`TokenType`] is a keyword for, if any
+	pub fn type_keyword_name(&self) -> Option<&'static str> {
+		match self {
+			Self::TypeKwBottom => Some("Bottom"),
+			Self::TypeKwTuple => Some("Tuple"),
+			Self::TypeKwList => Some("List"),
+			Self::TypeKwFunction => Some("Function"),
+			Self::TypeKwSum => Some("Sum"),
+			Self::TypeKwProduct => Some("Product"),
+			_ => None,
+		}
+	}
+
+	/// Check if this [`TokenType`] is a keyword, which would be an ordinary
+	/// identifier if the language didn't reserve it
+	pub fn is_keyword(&self) -> bool {
+		matches!(
+			self,
+			Self::TypeKwBottom
+				| Self::TypeKwTuple
+				| Self::TypeKwList
+				| Self::TypeKwFunction
+				| Self::TypeKwSum
+				| Self::TypeKwProduct
+				| Self::KwQuote
+				| Self::KwLet
+				| Self::KwConst
+				| Self::KwFn | Self::KwLambda
+				| Self::KwSeq
+				| Self::KwIf | Self::KwCond
+				| Self::KwInclude
+				| Self::KwMatch
+				| Self::KwCase
+				| Self::KwDefProtocol
+				| Self::KwImpl
+				| Self::KwTypeAlias
+				| Self::KwDefineType
+				| Self::KwDefineMacro
+				| Self::KwThe
+				| Self::KwTime
+		)
+	}
+
 	/// Get the name of this [`TokenType`]
 	pub fn name(&self) -> String {
 		match self {
@@ -102,22 +182,38 @@ impl<'t> TokenType<'t> {
 			Self::TypeKwProduct => "Product".to_string(),
 			Self::KwQuote => "quote".to_string(),
 			Self::KwLet => "let".to_string(),
+			Self::KwConst => "const".to_string(),
 			Self::KwFn => "fn".to_string(),
 			Self::KwLambda => "lambda".to_string(),
 			Self::KwSeq => "begin".to_string(),
 			Self::KwIf => "if".to_string(),
+			Self::KwCond => "cond".to_string(),
 			Self::KwInclude => "include".to_string(),
+			Self::KwMatch => "match".to_string(),
+			Self::KwCase => "case".to_string(),
+			Self::KwDefProtocol => "defprotocol".to_string(),
+			Self::KwImpl => "impl".to_string(),
+			Self::KwTypeAlias => "type-alias".to_string(),
+			Self::KwDefineType => "define-type".to_string(),
+			Self::KwDefineMacro => "define-macro".to_string(),
+			Self::KwThe => "the".to_string(),
+			Self::KwTime => "time".to_string(),
 			Self::Identifier(_) => "Identifier".to_string(),
 			Self::Boolean(_) => "Boolean".to_string(),
 			Self::Integer(_) => "Integer".to_string(),
+			Self::Rational(_) => "Rational".to_string(),
 			Self::Float(_) => "Float".to_string(),
 			Self::Character(_) => "Character".to_string(),
 			Self::String(_) => "String".to_string(),
 			Self::Atom(_) => "Atom".to_string(),
 			Self::LeftParen => "(".to_string(),
+			Self::HashParen => "#(".to_string(),
 			Self::RightParen => ")".to_string(),
 			Self::Period => ".".to_string(),
 			Self::Backtick => "`".to_string(),
+			Self::Comma => ",".to_string(),
+			Self::CommaAt => ",@".to_string(),
+			Self::Error(_) => "Error".to_string(),
 			Self::EndOfFile => "EndOfFile".to_string(),
 		}
 	}
@@ -0,0 +1,114 @@
+//! Buffered token stream with arbitrary lookahead
+
+use crate::{LexError, Lexer, Token};
+
+/// A stream of [`Token`]s lexed on demand, allowing any amount of lookahead
+/// and rewinding to earlier positions
+///
+/// Every token that has been lexed is kept around so the stream can be
+/// rewound to any [`Checkpoint`] taken from it
+#[derive(Clone)]
+pub struct TokenStream<'s> {
+	lexer:  Lexer<'s>,
+	buffer: Vec<Result<Token<'s>, LexError>>,
+	/// The index of the next token in the buffer
+	pos:    usize,
+}
+
+/// A position in a [`TokenStream`] that can be returned to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+impl<'s> TokenStream<'s> {
+	/// Create a new [`TokenStream`] reading from a lexer
+	pub fn new(lexer: Lexer<'s>) -> Self { Self { lexer, buffer: vec![], pos: 0 } }
+
+	/// Peek at the next token without consuming it
+	///
+	/// Returns [`None`] if no tokens are left
+	pub fn peek(&mut self) -> Option<&Result<Token<'s>, LexError>> { self.peek_n(0) }
+
+	/// Peek at the token `n` positions after the next one without consuming
+	/// anything, `peek_n(0)` is the next token
+	///
+	/// Returns [`None`] if the stream ends before then
+	pub fn peek_n(&mut self, n: usize) -> Option<&Result<Token<'s>, LexError>> {
+		while self.buffer.len() <= self.pos + n {
+			let token = self.lexer.next()?;
+			self.buffer.push(token);
+		}
+
+		self.buffer.get(self.pos + n)
+	}
+
+	/// Remember the current position in the stream
+	pub fn checkpoint(&self) -> Checkpoint { Checkpoint(self.pos) }
+
+	/// Return to a position remembered with [`checkpoint`](Self::checkpoint),
+	/// every token consumed since will be produced again
+	pub fn rewind(&mut self, checkpoint: Checkpoint) { self.pos = checkpoint.0; }
+}
+
+impl<'s> From<Lexer<'s>> for TokenStream<'s> {
+	fn from(value: Lexer<'s>) -> Self { Self::new(value) }
+}
+
+impl<'s> Iterator for TokenStream<'s> {
+	type Item = Result<Token<'s>, LexError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let token = self.peek()?.clone();
+		self.pos += 1;
+
+		Some(token)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::TokenType;
+
+	fn stream(source: &str) -> TokenStream<'_> { Lexer::new(source).into() }
+
+	fn peek_type<'s>(tokens: &mut TokenStream<'s>, n: usize) -> Option<TokenType<'s>> {
+		tokens.peek_n(n).map(|token| token.as_ref().unwrap().t)
+	}
+
+	#[test]
+	fn peeking_does_not_consume() {
+		let mut tokens = stream("(f x)\n");
+
+		assert_eq!(peek_type(&mut tokens, 2), Some(TokenType::Identifier("x")));
+		assert_eq!(peek_type(&mut tokens, 0), Some(TokenType::LeftParen));
+		assert_eq!(peek_type(&mut tokens, 4), None);
+		assert_eq!(tokens.count(), 4);
+	}
+
+	#[test]
+	fn rewinding_produces_tokens_again() {
+		let mut tokens = stream("(f x)\n");
+		tokens.next();
+
+		let checkpoint = tokens.checkpoint();
+		let consumed = tokens.by_ref().take(2).map(|t| t.unwrap().t).collect::<Vec<_>>();
+		assert_eq!(consumed, [TokenType::Identifier("f"), TokenType::Identifier("x")]);
+
+		tokens.rewind(checkpoint);
+		assert_eq!(peek_type(&mut tokens, 0), Some(TokenType::Identifier("f")));
+		assert_eq!(tokens.count(), 3);
+	}
+
+	#[test]
+	fn errors_are_kept_in_the_stream() {
+		let mut tokens = stream("(f \"x)\n");
+
+		assert!(tokens.peek_n(2).is_some_and(Result::is_err));
+
+		let checkpoint = tokens.checkpoint();
+		assert!(tokens.nth(2).is_some_and(|t| t.is_err()));
+
+		tokens.rewind(checkpoint);
+		assert!(tokens.nth(2).is_some_and(|t| t.is_err()));
+	}
+}
@@ -0,0 +1,743 @@
+//! Static checking of programs against their `:type` annotations
+//!
+//! Only the types known without running the program are checked: those of
+//! literals, quotations, `the` assertions, functions, and names that are
+//! annotated or bound to an expression of a known type. Anything else is
+//! assumed to have whatever type it needs, so a program is never rejected
+//! just because too little is known about it
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{
+	Annotation,
+	Datum,
+	Expression,
+	Identifier,
+	Literal,
+	Pattern,
+	Program,
+	TypeConstructor,
+	TypeSpec,
+};
+use crate::expand::pattern_names;
+use crate::{Span, TypeError, TypeErrors};
+
+/// The names of the types built into the language, as values of those types
+/// name them at runtime
+const BUILTIN_TYPES: [&str; 21] = [
+	"Unit",
+	"Boolean",
+	"Integer",
+	"Rational",
+	"Float",
+	"Character",
+	"String",
+	"StringBuilder",
+	"Regex",
+	"DateTime",
+	"Duration",
+	"Socket",
+	"Listener",
+	"Identifier",
+	"Atom",
+	"Map",
+	"Set",
+	"Range",
+	"Iterator",
+	"Result",
+	"Option",
+];
+
+/// A type known without running the program
+#[derive(Clone, Debug)]
+enum Type {
+	/// A type that isn't known, which is accepted wherever any other type
+	/// is expected
+	Unknown,
+	/// A type referred to by its name, like `Integer` or a user defined type
+	Named(String),
+	List(Box<Type>),
+	Vector(Box<Type>),
+	Tuple(Vec<Type>),
+	Function {
+		arguments: Vec<Type>,
+		values:    Vec<Type>,
+	},
+}
+
+impl Type {
+	/// Check if a value of the `found` type can be used where a value of this
+	/// type is expected
+	///
+	/// Numbers aren't converted implicitly at runtime, so an integer isn't
+	/// accepted as a float, it has to be converted with `the` first
+	fn accepts(&self, found: &Self) -> bool {
+		match (self, found) {
+			(Self::Unknown, _) | (_, Self::Unknown) => true,
+			(Self::Named(expected), Self::Named(found)) => expected == found,
+			(Self::List(expected), Self::List(found))
+			| (Self::Vector(expected), Self::Vector(found)) => expected.accepts(found),
+			(Self::Tuple(expected), Self::Tuple(found)) => all_accept(expected, found),
+			(
+				Self::Function { arguments: expected_arguments, values: expected_values },
+				Self::Function { arguments: found_arguments, values: found_values },
+			) => {
+				// The function found is passed the arguments of the one expected
+				all_accept(found_arguments, expected_arguments)
+					&& all_accept(expected_values, found_values)
+			},
+			_ => false,
+		}
+	}
+
+	/// Get the type every one of a number of types is accepted as, if they
+	/// all accept each other
+	fn common(types: impl IntoIterator<Item = Self>) -> Self {
+		let mut types = types.into_iter();
+		let Some(first) = types.next() else { return Self::Unknown };
+
+		types.fold(first, |common, t| {
+			if common.accepts(&t) && t.accepts(&common) {
+				common
+			} else {
+				Self::Unknown
+			}
+		})
+	}
+}
+
+/// Check if every type in a list accepts the type at the same position in
+/// another list of the same length
+fn all_accept(expected: &[Type], found: &[Type]) -> bool {
+	expected.len() == found.len() && expected.iter().zip(found).all(|(e, f)| e.accepts(f))
+}
+
+impl fmt::Display for Type {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		/// Write a group of types the way a `Function` type lists them
+		fn group(f: &mut fmt::Formatter<'_>, types: &[Type]) -> fmt::Result {
+			match types {
+				[t] => write!(f, "{t}"),
+				types => write!(f, "({})", join(types)),
+			}
+		}
+
+		match self {
+			Self::Unknown => write!(f, "_"),
+			Self::Named(name) => write!(f, "{name}"),
+			Self::List(t) => write!(f, "(List {t})"),
+			Self::Vector(t) => write!(f, "(Vector {t})"),
+			Self::Tuple(fields) => write!(f, "(Tuple {})", join(fields)),
+			Self::Function { arguments, values } => {
+				write!(f, "(Function ")?;
+				group(f, arguments)?;
+				write!(f, " ")?;
+				group(f, values)?;
+				write!(f, ")")
+			},
+		}
+	}
+}
+
+/// Join the representations of some types with spaces
+fn join(types: &[Type]) -> String {
+	types.iter().map(Type::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// A name in scope, with its type and the span of the annotation that gave
+/// it that type if there is one
+#[derive(Clone, Debug)]
+struct Binding {
+	t:          Type,
+	annotation: Option<Span>,
+}
+
+/// Every name in scope at some point of the program
+type Scope<'s> = HashMap<&'s str, Binding>;
+
+/// Walks a program, collecting every way it contradicts its annotations
+#[derive(Default)]
+struct Checker<'a, 's> {
+	/// The type aliases defined by the program, keyed by name
+	aliases: HashMap<&'s str, &'a TypeSpec<'s>>,
+	/// The names of the algebraic types defined by the program
+	types:   Vec<&'s str>,
+	errors:  Vec<TypeError>,
+}
+
+impl<'s> Program<'s> {
+	/// Check the program against its `:type` annotations, without running it
+	///
+	/// Definitions of annotated names are checked against their annotation,
+	/// and calls to names annotated with a `Function` type against the types
+	/// of its arguments. Every contradiction found is reported, as a
+	/// [`TypeErrors`] if there is more than one
+	pub fn typecheck(&self) -> Result<(), miette::Error> {
+		let mut checker = Checker::default();
+
+		for expr in &self.0 {
+			match expr {
+				Expression::TypeAlias { target, spec, .. } => {
+					checker.aliases.insert(target.id, spec);
+				},
+				Expression::AlgebraicTypeDefintion { target, .. } => checker.types.push(target.id),
+				_ => (),
+			}
+		}
+
+		checker.body(&self.0, &Scope::new());
+
+		let mut errors = checker.errors;
+		match errors.len() {
+			0 => Ok(()),
+			// Unwrap is safe as there is exactly one error
+			1 => Err(errors.pop().unwrap().into()),
+			_ => Err(TypeErrors { errors }.into()),
+		}
+	}
+}
+
+impl<'a, 's> Checker<'a, 's> {
+	/// Get the type described by a type specification
+	///
+	/// Names that aren't types known to the checker, and the anonymous types
+	/// built by `Sum`, `Product` and `Bottom`, are unknown
+	fn resolve(&self, spec: &TypeSpec<'s>, depth: usize) -> Type {
+		match spec {
+			TypeSpec::Identifier(Identifier { id: "Vector", .. }) => {
+				Type::Vector(Box::new(Type::Unknown))
+			},
+			TypeSpec::Identifier(Identifier { id, .. })
+				if BUILTIN_TYPES.contains(id) || self.types.contains(id) =>
+			{
+				Type::Named(id.to_string())
+			},
+			// Aliases can only refer to each other so many times without going
+			// around in circles
+			TypeSpec::Identifier(Identifier { id, .. }) => {
+				match self.aliases.get(id) {
+					Some(spec) if depth < self.aliases.len() => self.resolve(spec, depth + 1),
+					_ => Type::Unknown,
+				}
+			},
+			TypeSpec::Constructor(constructor) => {
+				match constructor {
+					TypeConstructor::Tuple { fields, .. } => {
+						Type::Tuple(fields.iter().map(|f| self.resolve(f, depth)).collect())
+					},
+					TypeConstructor::List { t, .. } => Type::List(Box::new(self.resolve(t, depth))),
+					TypeConstructor::Vector { t, .. } => {
+						Type::Vector(Box::new(self.resolve(t, depth)))
+					},
+					TypeConstructor::Function { arguments, values, .. } => {
+						Type::Function {
+							arguments: arguments.iter().map(|a| self.resolve(a, depth)).collect(),
+							values:    values.iter().map(|v| self.resolve(v, depth)).collect(),
+						}
+					},
+					TypeConstructor::Bottom { .. }
+					| TypeConstructor::Sum { .. }
+					| TypeConstructor::Product { .. } => Type::Unknown,
+				}
+			},
+		}
+	}
+
+	/// Check a body, whose definitions are only visible inside of it,
+	/// returning the type of its last expression
+	///
+	/// Annotations apply to the definitions in the body they're in, and the
+	/// names they annotate have their annotated type throughout the body, so
+	/// functions can be called before they're defined
+	fn body(&mut self, body: &'a [Expression<'s>], scope: &Scope<'s>) -> Type {
+		let mut scope = scope.clone();
+		let mut annotated = Scope::new();
+
+		for expr in body {
+			if let Expression::Annotation(Annotation::TypeAnnotation { span, target, spec }) = expr
+			{
+				let binding =
+					Binding { t: self.resolve(spec, 0), annotation: Some(*span) };
+				annotated.insert(target.id, binding);
+			}
+		}
+		scope.extend(annotated.clone());
+
+		let mut t = Type::Named("Unit".to_string());
+		for expr in body {
+			t = self.expression(expr, &mut scope, &annotated);
+		}
+
+		t
+	}
+
+	/// Check an expression, returning its type
+	///
+	/// Definitions made by the expression are added to the scope, those of
+	/// names in `annotated` are checked against their annotation
+	fn expression(
+		&mut self,
+		expr: &'a Expression<'s>,
+		scope: &mut Scope<'s>,
+		annotated: &Scope<'s>,
+	) -> Type {
+		match expr {
+			Expression::Literal(literal) => self.literal(literal, scope),
+			Expression::Identifier(id) => scope.get(id.id).map_or(Type::Unknown, |b| b.t.clone()),
+			Expression::VariableDefinition {
+				target: Pattern::Identifier(target), value, ..
+			}
+			| Expression::ConstantDefinition { target, value, .. } => {
+				self.definition(*target, value, scope, annotated);
+				Type::Named("Unit".to_string())
+			},
+			Expression::VariableDefinition { target, value, .. } => {
+				self.expression(value, scope, annotated);
+				bind_unknown(target, scope);
+				Type::Named("Unit".to_string())
+			},
+			Expression::FunctionDefinition { span, target, formals, body }
+			| Expression::FunctionClause { span, target, patterns: formals, body } => {
+				let annotation = annotated.get(target.id);
+				let t = self.function(*span, Some(*target), formals, body, scope, annotation);
+
+				let binding = annotation.cloned().unwrap_or(Binding { t, annotation: None });
+				scope.insert(target.id, binding);
+
+				Type::Named("Unit".to_string())
+			},
+			Expression::ClosureDefintion { span, formals, body } => {
+				self.function(*span, None, formals, body, scope, None)
+			},
+			Expression::Sequence { seq, .. } => self.body(seq, scope),
+			Expression::Let { bindings, body, .. } => {
+				let mut inner = scope.clone();
+				for binding in bindings {
+					// Every value is evaluated before any binding is made
+					let t = self.expression(&binding.value, &mut scope.clone(), annotated);
+
+					match &binding.target {
+						Pattern::Identifier(id) => {
+							inner.insert(id.id, Binding { t, annotation: None });
+						},
+						target => bind_unknown(target, &mut inner),
+					}
+				}
+
+				self.body(body, &inner)
+			},
+			Expression::Vector { elements, .. } => {
+				let types: Vec<_> =
+					elements.iter().map(|e| self.expression(e, scope, annotated)).collect();
+
+				Type::Vector(Box::new(Type::common(types)))
+			},
+			Expression::ProcedureCall { span, operator, operands } => {
+				self.call(*span, operator, operands, scope, annotated)
+			},
+			Expression::Conditional { test, consequent, alternate, .. } => {
+				self.expression(test, scope, annotated);
+				let consequent = self.expression(consequent, scope, annotated);
+
+				match alternate {
+					Some(alternate) => {
+						let alternate = self.expression(alternate, scope, annotated);
+						Type::common([consequent, alternate])
+					},
+					None => Type::Unknown,
+				}
+			},
+			Expression::Match { value, clauses, .. } => {
+				self.expression(value, scope, annotated);
+
+				for clause in clauses {
+					let mut inner = scope.clone();
+					bind_unknown(&clause.pattern, &mut inner);
+
+					if let Some(guard) = &clause.guard {
+						self.expression(guard, &mut inner, &Scope::new());
+					}
+					self.body(&clause.body, &inner);
+				}
+
+				Type::Unknown
+			},
+			Expression::Case { value, clauses, default, .. } => {
+				self.expression(value, scope, annotated);
+
+				for body in clauses.iter().map(|c| &c.body).chain(default) {
+					self.body(body, scope);
+				}
+
+				Type::Unknown
+			},
+			Expression::ProtocolImplementation { methods, .. } => {
+				self.body(methods, scope);
+				Type::Named("Unit".to_string())
+			},
+			Expression::TypeAssertion { spec, value, .. } => {
+				self.expression(value, scope, annotated);
+				self.resolve(spec, 0)
+			},
+			Expression::Time { value, .. } => {
+				let t = self.expression(value, scope, annotated);
+
+				Type::Tuple(vec![
+					t,
+					Type::Named("Float".to_string()),
+					Type::Named("Integer".to_string()),
+				])
+			},
+			Expression::TypeAlias { .. }
+			| Expression::AlgebraicTypeDefintion { .. }
+			| Expression::Annotation(_)
+			| Expression::Inclusion { .. }
+			| Expression::ProtocolDefinition { .. }
+			| Expression::MacroDefinition { .. } => Type::Named("Unit".to_string()),
+		}
+	}
+
+	/// Check the definition of a single name, against its annotation if it
+	/// has one
+	fn definition(
+		&mut self,
+		target: Identifier<'s>,
+		value: &'a Expression<'s>,
+		scope: &mut Scope<'s>,
+		annotated: &Scope<'s>,
+	) {
+		let annotation = annotated.get(target.id);
+
+		let t = match value {
+			// Closures bound to an annotated name are checked like functions
+			Expression::ClosureDefintion { span, formals, body } => {
+				self.function(*span, Some(target), formals, body, scope, annotation)
+			},
+			value => {
+				let t = self.expression(value, scope, annotated);
+
+				if let Some(Binding { t: expected, annotation: Some(annotation) }) = annotation {
+					if !expected.accepts(&t) {
+						self.errors.push(TypeError::Mismatch {
+							loc:        value.span(),
+							annotation: *annotation,
+							name:       target.id.to_string(),
+							expected:   expected.to_string(),
+							found:      t.to_string(),
+						});
+					}
+				}
+
+				t
+			},
+		};
+
+		scope.insert(target.id, annotation.cloned().unwrap_or(Binding { t, annotation: None }));
+	}
+
+	/// Check a function, and its body against the annotation of its name if
+	/// it has one, returning its type
+	///
+	/// The parameters of an annotated function have the types of its
+	/// arguments while checking its body
+	fn function(
+		&mut self,
+		span: Span,
+		name: Option<Identifier<'s>>,
+		formals: &'a [Pattern<'s>],
+		body: &'a [Expression<'s>],
+		scope: &Scope<'s>,
+		annotation: Option<&Binding>,
+	) -> Type {
+		let defined = Type::Function {
+			arguments: vec![Type::Unknown; formals.len()],
+			values:    vec![Type::Unknown],
+		};
+
+		let (t, annotation) = match (name, annotation) {
+			(Some(name), Some(Binding { t: expected, annotation: Some(annotation) })) => {
+				if !expected.accepts(&defined) {
+					self.errors.push(TypeError::Mismatch {
+						loc:        span,
+						annotation: *annotation,
+						name:       name.id.to_string(),
+						expected:   expected.to_string(),
+						found:      defined.to_string(),
+					});
+				}
+
+				match expected {
+					Type::Function { arguments, .. } if arguments.len() == formals.len() => {
+						(expected.clone(), Some((name, *annotation)))
+					},
+					_ => (defined, None),
+				}
+			},
+			_ => (defined, None),
+		};
+
+		let Type::Function { arguments, values } = &t else { unreachable!() };
+
+		let mut inner = scope.clone();
+		for (formal, argument) in formals.iter().zip(arguments) {
+			match formal {
+				Pattern::Identifier(id) => {
+					inner.insert(id.id, Binding { t: argument.clone(), annotation: None });
+				},
+				formal => bind_unknown(formal, &mut inner),
+			}
+		}
+
+		let found = self.body(body, &inner);
+
+		if let (Some((name, annotation)), [expected]) = (annotation, values.as_slice()) {
+			if !expected.accepts(&found) {
+				self.errors.push(TypeError::ReturnMismatch {
+					// Unwrap is safe as function bodies are never empty
+					loc: body.last().unwrap().span(),
+					annotation,
+					name: name.id.to_string(),
+					expected: expected.to_string(),
+					found: found.to_string(),
+				});
+			}
+		}
+
+		t
+	}
+
+	/// Check a procedure call, against the annotation of the name it calls if
+	/// it has one, returning the type of its result
+	fn call(
+		&mut self,
+		span: Span,
+		operator: &'a Expression<'s>,
+		operands: &'a [Expression<'s>],
+		scope: &mut Scope<'s>,
+		annotated: &Scope<'s>,
+	) -> Type {
+		let t = self.expression(operator, scope, annotated);
+		let found =
+			operands.iter().map(|o| self.expression(o, scope, annotated)).collect::<Vec<_>>();
+
+		let annotation = match operator {
+			Expression::Identifier(id) => {
+				scope.get(id.id).and_then(|b| b.annotation).map(|annotation| (id.id, annotation))
+			},
+			_ => None,
+		};
+
+		match (t, annotation) {
+			(Type::Function { arguments, values }, Some((name, annotation))) => {
+				if arguments.len() != operands.len() {
+					self.errors.push(TypeError::WrongArgumentCount {
+						loc: span,
+						annotation,
+						name: name.to_string(),
+						expected: arguments.len(),
+						found: operands.len(),
+					});
+				}
+
+				for (position, ((expected, found), operand)) in
+					arguments.iter().zip(&found).zip(operands).enumerate()
+				{
+					if !expected.accepts(found) {
+						self.errors.push(TypeError::ArgumentMismatch {
+							loc: operand.span(),
+							annotation,
+							name: name.to_string(),
+							position: position + 1,
+							expected: expected.to_string(),
+							found: found.to_string(),
+						});
+					}
+				}
+
+				result(values)
+			},
+			(Type::Function { values, .. }, None) => result(values),
+			(Type::Unknown, _) | (_, None) => Type::Unknown,
+			(t, Some((name, annotation))) => {
+				self.errors.push(TypeError::NotCallable {
+					loc: operator.span(),
+					annotation,
+					name: name.to_string(),
+					spec: t.to_string(),
+				});
+
+				Type::Unknown
+			},
+		}
+	}
+
+	/// Get the type of a literal
+	fn literal(&mut self, literal: &'a Literal<'s>, scope: &mut Scope<'s>) -> Type {
+		let name = match literal {
+			Literal::Quotation { q, .. } => return self.datum(q, scope),
+			Literal::Boolean { .. } => "Boolean",
+			Literal::Integer { .. } => "Integer",
+			Literal::Rational { .. } => "Rational",
+			Literal::Float { .. } => "Float",
+			Literal::Character { .. } => "Character",
+			Literal::String { .. } => "String",
+			Literal::Atom { .. } => "Atom",
+		};
+
+		Type::Named(name.to_string())
+	}
+
+	/// Get the type of a quoted datum, checking the expressions unquoted in it
+	fn datum(&mut self, datum: &'a Datum<'s>, scope: &mut Scope<'s>) -> Type {
+		let name = match datum {
+			Datum::Identifier { .. } => "Identifier",
+			Datum::Boolean { .. } => "Boolean",
+			Datum::Integer { .. } => "Integer",
+			Datum::Rational { .. } => "Rational",
+			Datum::Float { .. } => "Float",
+			Datum::Character { .. } => "Character",
+			Datum::String { .. } => "String",
+			Datum::Atom { .. } => "Atom",
+			Datum::List { l, .. } => {
+				for datum in l.iter() {
+					self.datum(datum, scope);
+				}
+
+				return Type::List(Box::new(Type::Unknown));
+			},
+			Datum::Vector { data, .. } => {
+				for datum in data {
+					self.datum(datum, scope);
+				}
+
+				return Type::Vector(Box::new(Type::Unknown));
+			},
+			Datum::Unquote { e, .. } => return self.expression(e, scope, &Scope::new()),
+			Datum::UnquoteSplicing { e, .. } => {
+				self.expression(e, scope, &Scope::new());
+				return Type::Unknown;
+			},
+		};
+
+		Type::Named(name.to_string())
+	}
+}
+
+/// Bind every name in a pattern to an unknown type
+fn bind_unknown<'s>(pattern: &Pattern<'s>, scope: &mut Scope<'s>) {
+	let mut names = vec![];
+	pattern_names(pattern, &mut names);
+
+	for name in names {
+		scope.insert(name, Binding { t: Type::Unknown, annotation: None });
+	}
+}
+
+/// Get the type of the result of a function returning the given values
+fn result(values: Vec<Type>) -> Type {
+	match <[Type; 1]>::try_from(values) {
+		Ok([value]) => value,
+		Err(_) => Type::Unknown,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{parse_str, Capabilities, Interpreter, Value};
+
+	/// Check a program, returning every error found
+	fn check(source: &str) -> Vec<TypeError> {
+		let Err(report) = parse_str(source).unwrap().typecheck() else { return vec![] };
+
+		match report.downcast::<TypeErrors>() {
+			Ok(errors) => errors.errors,
+			Err(report) => vec![report.downcast::<TypeError>().unwrap()],
+		}
+	}
+
+	#[test]
+	fn annotated_definitions() {
+		assert!(check("(:type x Integer) (let x 1)").is_empty());
+		assert!(check("(:type x Float) (const x 1.5)").is_empty());
+		assert!(matches!(
+			check("(:type x Integer) (let x \"one\")")[..],
+			[TypeError::Mismatch { .. }]
+		));
+	}
+
+	#[test]
+	fn numbers_are_not_widened() {
+		let half = "(:type half (Function Float Float)) (fn half (x) (* x 0.5))";
+
+		assert!(matches!(
+			check("(:type x Float) (let x 1)")[..],
+			[TypeError::Mismatch { .. }]
+		));
+		assert!(matches!(
+			check(&format!("{half} (half 3)"))[..],
+			[TypeError::ArgumentMismatch { position: 1, .. }]
+		));
+
+		// Converting the argument first makes the call check and evaluate
+		let source = format!("{half} (half (the Float 3))\n");
+		let program = parse_str(&source).unwrap();
+		assert!(program.clone().typecheck().is_ok());
+		assert_eq!(
+			Interpreter::new(Capabilities::PURE).run(program).unwrap(),
+			Value::Float(1.5)
+		);
+	}
+
+	#[test]
+	fn aliases_are_resolved() {
+		assert!(matches!(
+			check("(type-alias Id Integer) (:type x Id) (let x 1.5)")[..],
+			[TypeError::Mismatch { .. }]
+		));
+	}
+
+	#[test]
+	fn calls() {
+		let add = "(:type add (Function (Integer Integer) Integer)) (fn add (a b) (+ a b))";
+
+		assert!(check(&format!("{add} (add 1 2)")).is_empty());
+		assert!(matches!(
+			check(&format!("{add} (add 1)"))[..],
+			[TypeError::WrongArgumentCount { expected: 2, found: 1, .. }]
+		));
+		assert!(matches!(
+			check(&format!("{add} (add 1 'f')"))[..],
+			[TypeError::ArgumentMismatch { position: 2, .. }]
+		));
+		assert!(matches!(
+			check("(:type x Integer) (let x 1) (x)")[..],
+			[TypeError::NotCallable { .. }]
+		));
+	}
+
+	#[test]
+	fn results() {
+		assert!(matches!(
+			check("(:type f (Function String Integer)) (fn f (s) s)")[..],
+			[TypeError::ReturnMismatch { .. }]
+		));
+		assert!(matches!(
+			check("(:type f (Function Integer Integer)) (fn f (a b) a)")[..],
+			[TypeError::Mismatch { .. }]
+		));
+	}
+
+	#[test]
+	fn shadowed_names_are_not_checked() {
+		assert!(check("(:type x Integer) (let x 1) (fn f (x) (x))").is_empty());
+		assert!(check("(:type x Integer) (let x 1) (fn f () (let x \"one\") x)").is_empty());
+	}
+
+	#[test]
+	fn unknown_types_are_accepted() {
+		assert!(check("(fn f (a) a) (:type x Integer) (let x (f \"one\"))").is_empty());
+		assert!(check("(:type x Shape) (let x 1)").is_empty());
+	}
+}
@@ -0,0 +1,224 @@
+//! Values produced by running programs
+
+use std::fmt;
+
+use num_rational::Rational64;
+use static_assertions::assert_impl_all;
+
+/// A value produced by running a program
+///
+/// Values are detached from the interpreter that produced them, anything
+/// that can't exist outside of it (functions, sockets, iterators, ...) is
+/// represented as [`Opaque`](Value::Opaque)
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+	Unit,
+	Boolean(bool),
+	Integer(i64),
+	/// An integer that doesn't fit in an `i64`
+	#[cfg(feature = "bigint")]
+	BigInt(num_bigint::BigInt),
+	/// A fraction in lowest terms that isn't a whole number
+	Rational(Rational64),
+	Float(f64),
+	Character(char),
+	String(String),
+	Identifier(String),
+	Atom(String),
+	List(Vec<Value>),
+	Tuple(Vec<Value>),
+	Vector(Vec<Value>),
+	/// A map as its `(key, value)` pairs, ordered by key
+	Map(Vec<(Value, Value)>),
+	/// A set as its elements, in order
+	Set(Vec<Value>),
+	Ok(Box<Value>),
+	Err(Box<Value>),
+	Some(Box<Value>),
+	None,
+	/// A value of a product type, field names are stored without their
+	/// leading `:`
+	Product {
+		name:   String,
+		fields: Vec<(String, Value)>,
+	},
+	/// A value of a sum type
+	Variant {
+		name:    String,
+		variant: String,
+		payload: Option<Box<Value>>,
+	},
+	/// A value only meaningful inside the interpreter
+	Opaque {
+		type_name: String,
+		repr:      String,
+	},
+	/// A value of a type with a printer registered by `define-printer`,
+	/// alongside the way the printer displays it
+	Printed {
+		value: Box<Value>,
+		repr:  String,
+	},
+}
+
+assert_impl_all!(Value: Send, Sync);
+
+impl Value {
+	/// Get the name of the type of this value
+	pub fn type_name(&self) -> &str {
+		match self {
+			Self::Unit => "Unit",
+			Self::Boolean(_) => "Boolean",
+			Self::Integer(_) => "Integer",
+			#[cfg(feature = "bigint")]
+			Self::BigInt(_) => "Integer",
+			Self::Rational(_) => "Rational",
+			Self::Float(_) => "Float",
+			Self::Character(_) => "Character",
+			Self::String(_) => "String",
+			Self::Identifier(_) => "Identifier",
+			Self::Atom(_) => "Atom",
+			Self::List(_) => "List",
+			Self::Tuple(_) => "Tuple",
+			Self::Vector(_) => "Vector",
+			Self::Map(_) => "Map",
+			Self::Set(_) => "Set",
+			Self::Ok(_) | Self::Err(_) => "Result",
+			Self::Some(_) | Self::None => "Option",
+			Self::Product { name, .. } | Self::Variant { name, .. } => name,
+			Self::Opaque { type_name, .. } => type_name,
+			Self::Printed { value, .. } => value.type_name(),
+		}
+	}
+
+	/// Get this value without the way a printer displays it
+	pub fn unprinted(&self) -> &Value {
+		match self {
+			Self::Printed { value, .. } => value.unprinted(),
+			value => value,
+		}
+	}
+
+	/// Check if this value is unit
+	pub fn is_unit(&self) -> bool { matches!(self.unprinted(), Self::Unit) }
+
+	/// Get the boolean held by this value, if it is one
+	pub fn as_bool(&self) -> Option<bool> {
+		match self.unprinted() {
+			Self::Boolean(b) => Some(*b),
+			_ => None,
+		}
+	}
+
+	/// Get the integer held by this value, if it is one
+	pub fn as_integer(&self) -> Option<i64> {
+		match self.unprinted() {
+			Self::Integer(i) => Some(*i),
+			_ => None,
+		}
+	}
+
+	/// Get the float held by this value, if it is one
+	pub fn as_float(&self) -> Option<f64> {
+		match self.unprinted() {
+			Self::Float(f) => Some(*f),
+			_ => None,
+		}
+	}
+
+	/// Get the string held by this value, if it is one
+	pub fn as_str(&self) -> Option<&str> {
+		match self.unprinted() {
+			Self::String(s) => Some(s),
+			_ => None,
+		}
+	}
+
+	/// Get the elements of this value, if it is a list, tuple or vector
+	pub fn as_slice(&self) -> Option<&[Value]> {
+		match self.unprinted() {
+			Self::List(values) | Self::Tuple(values) | Self::Vector(values) => Some(values),
+			_ => None,
+		}
+	}
+}
+
+/// Format a float so it can't be mistaken for an integer, `1.0` rather
+/// than `1`, with `nan`, `inf` and `-inf` for the special values
+///
+/// Floats are never written with an exponent, which the lexer doesn't
+/// accept, so the result always reads back as the same float
+pub(crate) fn format_float(f: f64) -> String {
+	if f.is_nan() {
+		"nan".to_string()
+	} else if f.is_infinite() {
+		if f > 0.0 { "inf" } else { "-inf" }.to_string()
+	} else {
+		// Display formatting is the shortest representation that reads back
+		// as the same float, without an exponent
+		let mut repr = format!("{f}");
+		if !repr.contains('.') {
+			repr.push_str(".0");
+		}
+
+		repr
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unit => write!(f, "()"),
+			Self::Boolean(b) => write!(f, "{b}"),
+			Self::Integer(i) => write!(f, "{i}"),
+			#[cfg(feature = "bigint")]
+			Self::BigInt(i) => write!(f, "{i}"),
+			Self::Rational(r) => write!(f, "{r}"),
+			Self::Float(fl) => write!(f, "{}", format_float(*fl)),
+			Self::Character(c) => write!(f, "{c}"),
+			Self::String(s) => write!(f, "{s}"),
+			Self::Identifier(i) => write!(f, "{i}"),
+			Self::Atom(a) => write!(f, "{a}"),
+			Self::List(l) => {
+				let repr = l.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+
+				write!(f, "({repr})")
+			},
+			Self::Tuple(t) => {
+				let repr: String = t.iter().map(|v| format!(" {v}")).collect();
+
+				write!(f, "(tuple{repr})")
+			},
+			Self::Vector(v) => {
+				let repr = v.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ");
+
+				write!(f, "#({repr})")
+			},
+			Self::Map(m) => {
+				let repr = m.iter().map(|(k, v)| format!("{k} {v}")).collect::<Vec<_>>().join(", ");
+
+				write!(f, "{{{repr}}}")
+			},
+			Self::Set(s) => {
+				let repr = s.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+
+				write!(f, "#{{{repr}}}")
+			},
+			Self::Ok(v) => write!(f, "(ok {v})"),
+			Self::Err(e) => write!(f, "(err {e})"),
+			Self::Some(v) => write!(f, "(some {v})"),
+			Self::None => write!(f, "none"),
+			Self::Product { name, fields } => {
+				let repr: String = fields.iter().map(|(f, v)| format!(" :{f} {v}")).collect();
+
+				write!(f, "({name}{repr})")
+			},
+			Self::Variant { variant, payload: Some(payload), .. } => {
+				write!(f, "({variant} {payload})")
+			},
+			Self::Variant { variant, payload: None, .. } => write!(f, "({variant})"),
+			Self::Opaque { repr, .. } | Self::Printed { repr, .. } => write!(f, "{repr}"),
+		}
+	}
+}
@@ -0,0 +1,76 @@
+//! Tests for the `build` command, compiling and running the Rust program it
+//! generates
+//!
+//! The generated program is compiled offline against this crate, with the
+//! dependency versions locked by its `Cargo.lock`
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const MAIN: &str = r#"(include "lib/greet.rm")
+(greet (car (args)))
+(run-command "true" (list))
+"#;
+
+const GREET: &str = r#"(include "punctuation.rm")
+(fn greet (name) (print (string-append "hello " name bang)))
+"#;
+
+const PUNCTUATION: &str = r#"(let bang "!")"#;
+
+#[test]
+fn generated_programs_embed_includes_and_keep_capabilities() {
+	let tmp = Path::new(env!("CARGO_TARGET_TMPDIR"));
+	let root = tmp.join("build-test");
+	let _ = fs::remove_dir_all(&root);
+	fs::create_dir_all(root.join("script/lib")).unwrap();
+	fs::create_dir_all(root.join("src")).unwrap();
+
+	fs::write(root.join("script/main.rm"), MAIN).unwrap();
+	fs::write(root.join("script/lib/greet.rm"), GREET).unwrap();
+	fs::write(root.join("script/lib/punctuation.rm"), PUNCTUATION).unwrap();
+
+	let build = Command::new(env!("CARGO_BIN_EXE_ream"))
+		.current_dir(root.join("script"))
+		.args(["build", "main.rm", "--allow", "pure,io,env", "-o"])
+		.arg(root.join("src/main.rs"))
+		.output()
+		.unwrap();
+	assert!(build.status.success(), "{}", String::from_utf8_lossy(&build.stderr));
+
+	// The sources are embedded, the program must not read them at runtime
+	fs::remove_dir_all(root.join("script")).unwrap();
+
+	let manifest = format!(
+		r#"[package]
+name = "build-test"
+version = "0.1.0"
+edition = "2021"
+
+[workspace]
+
+[dependencies]
+miette = "5.10.0"
+ream = {{ path = {:?} }}
+"#,
+		env!("CARGO_MANIFEST_DIR"),
+	);
+	fs::write(root.join("Cargo.toml"), manifest).unwrap();
+	fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock"), root.join("Cargo.lock"))
+		.unwrap();
+
+	let run = Command::new(env!("CARGO"))
+		.current_dir(&root)
+		// Kept apart from the project so later runs reuse the compiled dependencies
+		.env("CARGO_TARGET_DIR", tmp.join("build-test-target"))
+		.args(["run", "--offline", "--quiet", "--", "world"])
+		.output()
+		.unwrap();
+	let stdout = String::from_utf8_lossy(&run.stdout);
+	let stderr = String::from_utf8_lossy(&run.stderr);
+
+	assert_eq!(stdout, "hello world!\n", "{stderr}");
+	assert!(!run.status.success());
+	assert!(stderr.contains("capability_denied"), "{stderr}");
+}
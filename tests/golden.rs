@@ -0,0 +1,164 @@
+//! Golden tests running every `testdata/*.rm` file through the pipeline
+//!
+//! Each stage is compared against a file next to the source with the same
+//! name and a different extension:
+//!  - `.tokens` holds the tokens produced by the lexer
+//!  - `.ast` holds the program produced by the parser
+//!  - `.eval` holds everything the program printed, followed by the outcome
+//!    of checking its types and running it, and the value of its last
+//!    expression if that isn't unit
+//!
+//! A stage whose expected file is missing is skipped, a stage that fails
+//! records its diagnostic code and message instead of its output
+//!
+//! Programs are run with every capability, unless their first line is a
+//! comment like `; allow: pure, io` listing the capabilities to grant
+//!
+//! Run with `REAM_BLESS=1` to (re)write the expected files from the current
+//! output
+
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+
+use miette::Report;
+use ream::{lex_str, parse_str, Capabilities, Capability, Interpreter, Value};
+
+/// Render the output of a stage, or the diagnostic code and message of the
+/// error it failed with
+fn describe<T>(result: Result<T, Report>, f: impl FnOnce(T) -> String) -> String {
+	match result {
+		Ok(t) => f(t),
+		Err(e) => {
+			let code = e.code().map(|c| c.to_string()).unwrap_or_default();
+
+			format!("error: {code}\n{e}\n")
+		},
+	}
+}
+
+/// Output sink that can still be read after handing it to an interpreter
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.borrow_mut().write(buf) }
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Get the capabilities to run a source file with, from its `; allow:`
+/// header if it has one
+fn capabilities(source: &str) -> Capabilities {
+	let Some(allowed) = source.lines().next().and_then(|line| line.strip_prefix("; allow:")) else {
+		return Capabilities::ALL;
+	};
+
+	allowed
+		.split(',')
+		.map(|capability| {
+			capability.trim().parse::<Capability>().expect("capabilities should be valid")
+		})
+		.collect()
+}
+
+/// Render every stage of the pipeline for a source file, tagged with the
+/// extension of its expected file
+fn run_stages(source: &str) -> Vec<(&'static str, String)> {
+	let tokens =
+		describe(lex_str(source), |tokens| tokens.iter().map(|t| format!("{t:?}\n")).collect());
+
+	let ast = describe(parse_str(source), |program| format!("{program:#?}\n"));
+
+	let eval = describe(parse_str(source), |program| {
+		let output = SharedBuffer::default();
+		let result = program.typecheck().and_then(|()| {
+			Interpreter::new(capabilities(source))
+				.with_output(output.clone())
+				.run(program)
+				.map_err(Report::from)
+		});
+
+		let outcome = describe(result, |value| {
+			match value {
+				Value::Unit => "ok\n".to_string(),
+				value => format!("ok\n{value}\n"),
+			}
+		});
+
+		format!("{}{outcome}", String::from_utf8_lossy(&output.0.borrow()))
+	});
+
+	vec![("tokens", tokens), ("ast", ast), ("eval", eval)]
+}
+
+/// Run [`run_stages`] on a thread with as much stack as the main thread of
+/// `ream` usually gets, rather than the smaller default of test threads
+///
+/// Unoptimized builds use a lot of stack for every nested evaluation, which
+/// deeply recursive programs would otherwise run out of
+fn run_on_large_stack(source: &str) -> Vec<(&'static str, String)> {
+	thread::scope(|scope| {
+		thread::Builder::new()
+			.stack_size(8 * 1024 * 1024)
+			.spawn_scoped(scope, || run_stages(source))
+			.expect("test thread should spawn")
+			.join()
+			.expect("stages should not panic")
+	})
+}
+
+/// Get every source file in the testdata directory
+fn sources() -> Vec<PathBuf> {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+
+	let mut sources = fs::read_dir(dir)
+		.expect("testdata directory should exist")
+		.map(|entry| entry.expect("testdata entries should be readable").path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "rm"))
+		.collect::<Vec<_>>();
+	sources.sort();
+
+	sources
+}
+
+#[test]
+fn golden() {
+	let bless = std::env::var_os("REAM_BLESS").is_some();
+	let mut failures = vec![];
+
+	for source_path in sources() {
+		let source = fs::read_to_string(&source_path).expect("sources should be readable");
+		let mut checked = 0;
+
+		for (extension, actual) in run_on_large_stack(&source) {
+			let expected_path = source_path.with_extension(extension);
+
+			if bless {
+				fs::write(&expected_path, &actual).expect("expected files should be writable");
+				continue;
+			}
+
+			let Ok(expected) = fs::read_to_string(&expected_path) else {
+				continue;
+			};
+			checked += 1;
+
+			if expected != actual {
+				failures.push(format!(
+					"{} differs\n--- expected\n{expected}--- actual\n{actual}",
+					expected_path.display()
+				));
+			}
+		}
+
+		if !bless && checked == 0 {
+			failures.push(format!("{} has no expected output", source_path.display()));
+		}
+	}
+
+	assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,104 @@
+//! Tests for functions registered by an embedder through
+//! `Interpreter::with_function`
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use ream::{parse_str, Capabilities, Capability, EvalError, Interpreter, Value};
+
+fn run<'s>(mut interpreter: Interpreter<'s>, source: &'s str) -> Result<Value, EvalError> {
+	interpreter.run(parse_str(source).unwrap())
+}
+
+/// A host function upcasing every string it is passed
+fn shout(args: Vec<Value>) -> Result<Value, Value> {
+	match &args[..] {
+		[Value::String(s)] => Ok(Value::String(format!("{}!", s.to_uppercase()))),
+		_ => Err(Value::String("shout takes a single string".to_string())),
+	}
+}
+
+#[test]
+fn host_functions_are_called_with_converted_values() {
+	let interpreter = Interpreter::new(Capabilities::PURE)
+		.with_function("shout", Capability::Pure, shout)
+		.with_function("describe", Capability::Pure, |args| {
+			Ok(Value::List(vec![
+				Value::Integer(args.len() as i64),
+				Value::Atom(":described".to_string()),
+				Value::Some(Box::new(args.into_iter().next().unwrap_or(Value::Unit))),
+			]))
+		});
+
+	let value = run(interpreter, "(list (shout \"hi\") (describe `(1 2)))").unwrap();
+
+	assert_eq!(
+		value,
+		Value::List(vec![
+			Value::String("HI!".to_string()),
+			Value::List(vec![
+				Value::Integer(1),
+				Value::Atom(":described".to_string()),
+				Value::Some(Box::new(Value::List(vec![Value::Integer(1), Value::Integer(2)]))),
+			]),
+		])
+	);
+}
+
+#[test]
+fn host_results_are_usable_by_the_program() {
+	let interpreter = Interpreter::new(Capabilities::PURE)
+		.with_function("pair", Capability::Pure, |_| {
+			Ok(Value::Tuple(vec![Value::Atom(":answer".to_string()), Value::Integer(42)]))
+		});
+
+	let value = run(
+		interpreter,
+		"(let p (pair)) (list (== (tuple-ref p 0) :answer) (+ (tuple-ref p 1) 1))\n",
+	)
+	.unwrap();
+
+	assert_eq!(value, Value::List(vec![Value::Boolean(true), Value::Integer(43)]));
+}
+
+#[test]
+fn host_functions_require_their_capability() {
+	let called = Rc::new(Cell::new(false));
+	let flag = called.clone();
+	let interpreter = Interpreter::new(Capabilities::PURE)
+		.with_function("fetch", Capability::Net, move |_| {
+			flag.set(true);
+			Ok(Value::Unit)
+		});
+
+	let result = run(interpreter, "(fetch)");
+
+	assert!(
+		matches!(&result, Err(EvalError::CapabilityDenied { callee, .. }) if callee == "fetch"),
+		"{result:?}"
+	);
+	assert!(!called.get(), "the function ran without its capability");
+}
+
+#[test]
+fn host_errors_are_raised() {
+	let interpreter =
+		Interpreter::new(Capabilities::PURE).with_function("shout", Capability::Pure, shout);
+
+	let err = run(interpreter, "(shout 1)").unwrap_err();
+
+	assert!(matches!(err, EvalError::Raised { .. }), "{err:?}");
+	assert_eq!(err.payload(), Some(&Value::String("shout takes a single string".to_string())));
+}
+
+#[test]
+fn opaque_host_results_are_rejected() {
+	let interpreter = Interpreter::new(Capabilities::PURE)
+		.with_function("socket", Capability::Pure, |_| {
+			Ok(Value::Opaque { type_name: "Socket".to_string(), repr: "socket".to_string() })
+		});
+
+	let result = run(interpreter, "(socket)");
+
+	assert!(matches!(result, Err(EvalError::WrongType { .. })), "{result:?}");
+}
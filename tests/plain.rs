@@ -0,0 +1,37 @@
+//! Tests for the `--plain` diagnostics mode of the command line interface
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Run `ream` on a failing program written to a file with the given name,
+/// with the given flags, returning what it printed to stderr
+///
+/// Every call should use its own file, as tests run in parallel
+fn diagnostics(name: &str, flags: &[&str]) -> String {
+	let source = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+	fs::write(&source, "(let x 1)\n(+ x \"y\")\n").unwrap();
+
+	let run = Command::new(env!("CARGO_BIN_EXE_ream")).args(flags).arg(&source).output().unwrap();
+	assert!(!run.status.success());
+
+	String::from_utf8(run.stderr).unwrap()
+}
+
+#[test]
+fn plain_diagnostics_are_ascii_without_colors() {
+	for flag in ["--plain", "--no-color"] {
+		let stderr = diagnostics(&format!("plain{flag}.rm"), &[flag]);
+
+		assert!(stderr.contains("ream::eval_error::wrong_type"), "{stderr}");
+		assert!(stderr.is_ascii(), "{flag} printed non-ASCII output:\n{stderr}");
+		assert!(!stderr.contains('\x1b'), "{flag} printed escape codes:\n{stderr}");
+	}
+}
+
+#[test]
+fn plain_diagnostics_still_point_at_the_source() {
+	let stderr = diagnostics("plain-source.rm", &["--plain"]);
+
+	assert!(stderr.contains("(+ x \"y\")"), "{stderr}");
+}